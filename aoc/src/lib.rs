@@ -0,0 +1,61 @@
+//! Every day's puzzle in one place, for listing and running them from a single binary
+
+use common::registry::AocDay;
+
+/// Every day, in order
+pub const DAYS: &[AocDay] = &[
+    day1::DAY,
+    day2::DAY,
+    day3::DAY,
+    day4::DAY,
+    day5::DAY,
+    day6::DAY,
+    day7::DAY,
+    day8::DAY,
+    day9::DAY,
+    day10::DAY,
+    day11::DAY,
+    day12::DAY,
+    day13::DAY,
+    day14::DAY,
+    day15::DAY,
+    day16::DAY,
+    day17::DAY,
+    day18::DAY,
+    day19::DAY,
+    day20::DAY,
+    day21::DAY,
+    day22::DAY,
+    day23::DAY,
+    day24::DAY,
+    day25::DAY,
+];
+
+/// One line per day: number, title and puzzle link
+pub fn listing() -> String {
+    let width = DAYS.iter().map(|d| d.title.len()).max().unwrap_or(0);
+    DAYS.iter()
+        .map(|d| format!("{:>2}  {:<width$}  {}\n", d.day, d.title, d.url()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_in_order() {
+        let numbers: Vec<_> = DAYS.iter().map(|d| d.day).collect();
+        assert_eq!(numbers, (1..=25).collect::<Vec<_>>());
+        assert!(DAYS.iter().all(|d| !d.title.is_empty()));
+    }
+
+    #[test]
+    fn listing_lines() {
+        let listing = listing();
+        let lines: Vec<_> = listing.lines().collect();
+        assert_eq!(lines.len(), 25);
+        assert!(lines[0].starts_with(" 1  Historian Hysteria "));
+        assert!(lines[24].ends_with("https://adventofcode.com/2024/day/25"));
+    }
+}
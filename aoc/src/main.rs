@@ -0,0 +1,41 @@
+use anyhow::{bail, Context};
+use common::{
+    error::AocContext,
+    registry,
+    timing::{Timed, TimingReport},
+};
+
+use aoc::{listing, DAYS};
+
+const USAGE: &str = "usage: aoc list | aoc run DAY [--input FILE | --example] [--json]";
+
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("list") => print!("{}", listing()),
+        // `run DAY` solves the day's `dayN/input1.txt`, or `--input FILE`, or with
+        // `--example` the example from the puzzle text, checking its answers
+        Some("run") => {
+            let day: u8 = args.get(1).context(USAGE)?.parse()?;
+            let day = registry::find(DAYS, day).with_context(|| format!("no day {day}"))?;
+            let timing = TimingReport::for_day(day).with_example_answers(day.example_answers);
+            if common::example_requested() {
+                let timed = Timed::run(|| (day.solve_example)(day.example));
+                let (part1, part2) = timed.value.input_context(day.day, "example")?;
+                timing.report("1", &part1, timed.elapsed);
+                if let Some(part2) = part2 {
+                    timing.report("2", &part2, timed.elapsed);
+                }
+            } else {
+                let file = common::arg_value("--input")
+                    .unwrap_or_else(|| format!("day{}/input1.txt", day.day));
+                let text = common::read_file(&file).input_context(day.day, &file)?;
+                timing
+                    .solve(|| (day.solve)(&text))
+                    .input_context(day.day, &file)?;
+            }
+        }
+        _ => bail!(USAGE),
+    }
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use std::process::Command;
+
+/// `--json` output is only report lines, so a stray debug print in a day's `solve`
+/// breaks it
+#[test]
+fn example_json_is_only_reports() {
+    for day in 1..=25 {
+        let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+            .args(["run", &day.to_string(), "--example", "--json"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "day {day} failed");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(!stdout.is_empty(), "day {day} reported nothing");
+        for line in stdout.lines() {
+            let prefix = format!(r#"{{"day":{day},"#);
+            assert!(line.starts_with(&prefix), "day {day} printed {line:?}");
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::{collections::BTreeMap, fmt::Display, io::ErrorKind};
+
+use anyhow::{bail, Result};
+
+use crate::OptionAnyhow;
+
+/// Answers known to be correct (accepted on the site), keyed by `(day, part)`.
+/// Stored as text, one `day part answer` per line; blank lines and `#` comments are
+/// ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnswerLog {
+    answers: BTreeMap<(u8, u8), String>,
+}
+impl AnswerLog {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut log = AnswerLog::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.splitn(3, ' ');
+            let day = fields.next().ok_anyhow()?.parse()?;
+            let part = fields.next().ok_anyhow()?.parse()?;
+            let answer = fields.next().expect_anyhow("missing answer")?.trim();
+            if log.answers.contains_key(&(day, part)) {
+                bail!("more than one answer for day {day} part {part}");
+            }
+            log.insert(day, part, answer);
+        }
+        Ok(log)
+    }
+
+    /// Read the log from `file_name`; a missing file is an empty log
+    pub fn load(file_name: &str) -> Result<Self> {
+        match std::fs::read_to_string(file_name) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.answers.get(&(day, part)).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u8, part: u8, answer: impl Display) {
+        self.answers.insert((day, part), answer.to_string());
+    }
+
+    /// The parts in `required` with no recorded answer, in order
+    pub fn missing(&self, required: impl IntoIterator<Item = (u8, u8)>) -> Vec<(u8, u8)> {
+        required
+            .into_iter()
+            .filter(|(day, part)| self.get(*day, *part).is_none())
+            .collect()
+    }
+}
+impl Display for AnswerLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for ((day, part), answer) in &self.answers {
+            writeln!(f, "{day} {part} {answer}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_roundtrip() -> Result<()> {
+        let log = AnswerLog::parse("# comment\n1 1 1234\n\n1 2 6,36\n24 2 a,b c\n")?;
+        assert_eq!(log.get(1, 1), Some("1234"));
+        assert_eq!(log.get(1, 2), Some("6,36"));
+        assert_eq!(log.get(24, 2), Some("a,b c"));
+        assert_eq!(log.get(2, 1), None);
+        assert_eq!(AnswerLog::parse(&log.to_string())?, log);
+        assert_eq!(
+            log.missing([(1, 1), (2, 1), (1, 2), (3, 2)]),
+            [(2, 1), (3, 2)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(AnswerLog::parse("1 1 5\n1 1 6\n").is_err());
+        assert!(AnswerLog::parse("1 1\n").is_err());
+        assert!(AnswerLog::parse("x 1 5\n").is_err());
+    }
+
+    #[test]
+    fn load_missing_file_is_empty() -> Result<()> {
+        assert_eq!(
+            AnswerLog::load("no/such/answers.txt")?,
+            AnswerLog::default()
+        );
+        Ok(())
+    }
+}
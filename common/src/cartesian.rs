@@ -1,7 +1,7 @@
 use std::{
     fmt::Display,
     num::TryFromIntError,
-    ops::{Add, Mul, Sub},
+    ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub},
 };
 
 use nalgebra::{
@@ -10,6 +10,8 @@ use nalgebra::{
 };
 use strum_macros::EnumIter;
 
+use crate::OptionAnyhow;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
 pub enum ScreenDir {
     R,
@@ -59,6 +61,27 @@ impl ScreenDir {
         }
     }
 
+    /// rotate by quarter turns; positive is clockwise (to the right)
+    pub fn rotate(&self, turns: i32) -> Self {
+        const CLOCKWISE: [ScreenDir; 4] = [ScreenDir::R, ScreenDir::D, ScreenDir::L, ScreenDir::U];
+        CLOCKWISE[(*self as i32 + turns).rem_euclid(4) as usize]
+    }
+
+    /// Fewest quarter turns from this direction to `other`: 0, 1, or 2 for the
+    /// opposite direction
+    pub fn turns_to(&self, other: ScreenDir) -> u8 {
+        match (other as i32 - *self as i32).rem_euclid(4) {
+            0 => 0,
+            2 => 2,
+            _ => 1,
+        }
+    }
+
+    /// Clockwise angle on screen from `R`, in `0..360`
+    pub fn degrees(&self) -> u16 {
+        *self as u16 * 90
+    }
+
     // returns row and column
     fn delta(&self) -> (i64, i64) {
         match self {
@@ -70,21 +93,240 @@ impl ScreenDir {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, EnumIter)]
+/// A value for each `ScreenDir`, held inline rather than in a map keyed on the
+/// direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DirMap<T>([T; 4]);
+impl<T> DirMap<T> {
+    /// In the order of the `ScreenDir` variants
+    const DIRS: [ScreenDir; 4] = [ScreenDir::R, ScreenDir::D, ScreenDir::L, ScreenDir::U];
+
+    pub fn from_fn(mut f: impl FnMut(ScreenDir) -> T) -> Self {
+        Self(Self::DIRS.map(&mut f))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ScreenDir, &T)> {
+        Self::DIRS.into_iter().zip(&self.0)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (ScreenDir, &mut T)> {
+        Self::DIRS.into_iter().zip(&mut self.0)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.0.iter()
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> DirMap<U> {
+        DirMap(self.0.map(f))
+    }
+}
+impl<T> Index<ScreenDir> for DirMap<T> {
+    type Output = T;
+
+    fn index(&self, dir: ScreenDir) -> &T {
+        &self.0[dir as usize]
+    }
+}
+impl<T> IndexMut<ScreenDir> for DirMap<T> {
+    fn index_mut(&mut self, dir: ScreenDir) -> &mut T {
+        &mut self.0[dir as usize]
+    }
+}
+impl<T> IntoIterator for DirMap<T> {
+    type Item = (ScreenDir, T);
+    type IntoIter = std::iter::Zip<std::array::IntoIter<ScreenDir, 4>, std::array::IntoIter<T, 4>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Self::DIRS.into_iter().zip(self.0)
+    }
+}
+
+/// The four `ScreenDir`s and the diagonals between them, in clockwise order on
+/// screen starting from `R`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum Dir8 {
+    R,
+    DR,
+    D,
+    DL,
+    L,
+    UL,
+    U,
+    UR,
+}
+impl Dir8 {
+    const CLOCKWISE: [Dir8; 8] = [
+        Dir8::R,
+        Dir8::DR,
+        Dir8::D,
+        Dir8::DL,
+        Dir8::L,
+        Dir8::UL,
+        Dir8::U,
+        Dir8::UR,
+    ];
+
+    /// rotate by eighth turns; positive is clockwise
+    pub fn rotate(&self, eighths: i32) -> Self {
+        Self::CLOCKWISE[(*self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        *self as u8 % 2 == 1
+    }
+
+    /// Fewest eighth turns from this direction to `other`, in `0..=4`
+    pub fn turns_to(&self, other: Dir8) -> u8 {
+        let eighths = (other as i32 - *self as i32).rem_euclid(8) as u8;
+        eighths.min(8 - eighths)
+    }
+
+    /// Clockwise angle on screen from `R`, in `0..360`
+    pub fn degrees(&self) -> u16 {
+        *self as u16 * 45
+    }
+
+    /// Direction at a multiple of 45 degrees clockwise from `R`; any other angle is
+    /// `None`
+    pub fn from_degrees(degrees: i32) -> Option<Self> {
+        (degrees % 45 == 0).then(|| Dir8::R.rotate(degrees / 45))
+    }
+
+    /// step on the screen, with y down
+    pub fn delta(&self) -> Point {
+        match self {
+            Dir8::R => Point::new(1, 0),
+            Dir8::DR => Point::new(1, 1),
+            Dir8::D => Point::new(0, 1),
+            Dir8::DL => Point::new(-1, 1),
+            Dir8::L => Point::new(-1, 0),
+            Dir8::UL => Point::new(-1, -1),
+            Dir8::U => Point::new(0, -1),
+            Dir8::UR => Point::new(1, -1),
+        }
+    }
+}
+impl Display for Dir8 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl From<ScreenDir> for Dir8 {
+    fn from(value: ScreenDir) -> Self {
+        match value {
+            ScreenDir::R => Dir8::R,
+            ScreenDir::D => Dir8::D,
+            ScreenDir::L => Dir8::L,
+            ScreenDir::U => Dir8::U,
+        }
+    }
+}
+
+/// fails for the diagonals
+impl TryFrom<Dir8> for ScreenDir {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Dir8) -> Result<Self, Self::Error> {
+        match value {
+            Dir8::R => Ok(ScreenDir::R),
+            Dir8::D => Ok(ScreenDir::D),
+            Dir8::L => Ok(ScreenDir::L),
+            Dir8::U => Ok(ScreenDir::U),
+            diagonal => Err(anyhow::anyhow!("{diagonal} is not a ScreenDir")),
+        }
+    }
+}
+
+impl From<Dir8> for Point {
+    fn from(value: Dir8) -> Self {
+        value.delta()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
 pub enum CompassDir {
     N,
     S,
     W,
     E,
 }
+impl CompassDir {
+    /// step on the screen, with north up
+    pub fn delta(&self) -> Point {
+        Point::from(ScreenDir::from(*self))
+    }
+
+    /// rotate by quarter turns; positive is clockwise (N -> E)
+    pub fn rotate(&self, turns: i32) -> Self {
+        Self::from(ScreenDir::from(*self).rotate(turns))
+    }
+
+    /// screen direction when north is drawn facing `north` rather than up
+    pub fn to_screen(self, north: ScreenDir) -> ScreenDir {
+        // quarter turns from up to north carry across to every direction
+        let turns = north as i32 - ScreenDir::U as i32;
+        ScreenDir::from(self).rotate(turns)
+    }
+}
 
-#[derive(Debug, Clone)]
+/// north-up mapping; use `CompassDir::to_screen` for other orientations
+impl From<CompassDir> for ScreenDir {
+    fn from(value: CompassDir) -> Self {
+        match value {
+            CompassDir::N => ScreenDir::U,
+            CompassDir::S => ScreenDir::D,
+            CompassDir::W => ScreenDir::L,
+            CompassDir::E => ScreenDir::R,
+        }
+    }
+}
+
+/// north-up mapping
+impl From<ScreenDir> for CompassDir {
+    fn from(value: ScreenDir) -> Self {
+        match value {
+            ScreenDir::U => CompassDir::N,
+            ScreenDir::D => CompassDir::S,
+            ScreenDir::L => CompassDir::W,
+            ScreenDir::R => CompassDir::E,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Instruction {
     Move(u32),
     TurnLeft,
     TurnRight,
 }
 
+/// Follow the instructions from `start`, returning every point visited in order,
+/// including the start
+pub fn walk(start: Point, facing: ScreenDir, instructions: &[Instruction]) -> Vec<Point> {
+    let mut path = vec![start];
+    let mut pos = start;
+    let mut facing = facing;
+    for instruction in instructions {
+        match instruction {
+            Instruction::Move(n) => {
+                for _ in 0..*n {
+                    pos = pos + facing.into();
+                    path.push(pos);
+                }
+            }
+            Instruction::TurnLeft => facing = facing.left(),
+            Instruction::TurnRight => facing = facing.right(),
+        }
+    }
+    path
+}
+
 #[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Point {
     pub x: i64,
@@ -111,6 +353,23 @@ impl Point {
     pub fn within_bounds<T>(self, matrix: &DMatrix<T>) -> bool {
         self.to_coord_matrix(matrix).is_some()
     }
+
+    /// Point for matrix coordinates `(r, c)`, or `None` if they're outside the matrix
+    pub fn try_from_coord<T>(coord: (usize, usize), matrix: &DMatrix<T>) -> Option<Point> {
+        let (r, c) = coord;
+        (r < matrix.nrows() && c < matrix.ncols()).then(|| Point::from(coord))
+    }
+
+    /// Add `delta`, or `None` if the result is outside the matrix
+    pub fn try_add_within<T>(self, delta: Point, matrix: &DMatrix<T>) -> Option<Point> {
+        let next = self + delta;
+        next.within_bounds(matrix).then_some(next)
+    }
+
+    /// Move one step in `dir`, or `None` if that leaves the matrix
+    pub fn step_within<T>(self, dir: ScreenDir, matrix: &DMatrix<T>) -> Option<Point> {
+        self.try_add_within(dir.into(), matrix)
+    }
 }
 impl Add for Point {
     type Output = Point;
@@ -147,12 +406,7 @@ impl From<(usize, usize)> for Point {
 
 impl From<CompassDir> for Point {
     fn from(value: CompassDir) -> Self {
-        match value {
-            CompassDir::N => Point::new(0, -1),
-            CompassDir::S => Point::new(0, 1),
-            CompassDir::W => Point::new(-1, 0),
-            CompassDir::E => Point::new(1, 0),
-        }
+        value.delta()
     }
 }
 
@@ -175,6 +429,92 @@ impl From<ScreenDir> for Point {
     }
 }
 
+/// Floating point counterpart of `Point`, in the same axes (y down on screen), for
+/// geometry like centroids and spreads that doesn't stay on the grid
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f64,
+    pub y: f64,
+}
+impl Vec2 {
+    pub const fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn dot(self, rhs: Vec2) -> f64 {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    /// z component of the 3D cross product; positive when `rhs` is clockwise from
+    /// `self` on screen
+    pub fn cross(self, rhs: Vec2) -> f64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
+    pub fn norm_squared(self) -> f64 {
+        self.dot(self)
+    }
+
+    pub fn norm(self) -> f64 {
+        self.norm_squared().sqrt()
+    }
+
+    /// Unit vector in the same direction, or `None` for the zero vector
+    pub fn normalized(self) -> Option<Vec2> {
+        let norm = self.norm();
+        (norm > 0.0).then(|| self / norm)
+    }
+
+    /// Mean of `points`, or `None` if there aren't any
+    pub fn centroid(points: impl IntoIterator<Item = Vec2>) -> Option<Vec2> {
+        let (sum, n) = points
+            .into_iter()
+            .fold((Vec2::default(), 0), |(sum, n), p| (sum + p, n + 1));
+        (n > 0).then(|| sum / n as f64)
+    }
+}
+impl Add for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vec2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+impl Sub for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+impl Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Self::Output {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+impl Mul<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+impl Div<f64> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Vec2::new(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl From<Point> for Vec2 {
+    fn from(value: Point) -> Self {
+        Vec2::new(value.x as f64, value.y as f64)
+    }
+}
+
 // permit `Point` to be used as a matrix index
 impl<'a, T: 'a, R, C, S> MatrixIndex<'a, T, R, C, S> for Point
 where
@@ -238,7 +578,11 @@ where
     T: Default + Scalar,
 {
     let rows = lines.len();
-    let cols = lines.iter().map(|l| l.chars().count()).max().unwrap();
+    let cols = lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .expect_anyhow("no lines in matrix")?;
 
     let mut map = DMatrix::from_element(rows, cols, T::default());
     for row in 0..rows {
@@ -250,3 +594,300 @@ where
 
     Ok(map)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use strum::IntoEnumIterator;
+
+    fn point() -> impl Strategy<Value = Point> {
+        (-1_000_000_i64..1_000_000, -1_000_000_i64..1_000_000).prop_map(|(x, y)| Point::new(x, y))
+    }
+
+    /// Small enough that products of coordinates are exact in f64
+    fn small_point() -> impl Strategy<Value = Point> {
+        (-1000_i64..1000, -1000_i64..1000).prop_map(|(x, y)| Point::new(x, y))
+    }
+
+    fn screen_dir() -> impl Strategy<Value = ScreenDir> {
+        proptest::sample::select(ScreenDir::iter().collect::<Vec<_>>())
+    }
+
+    fn compass_dir() -> impl Strategy<Value = CompassDir> {
+        proptest::sample::select(CompassDir::iter().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn dir_map() {
+        let mut costs = DirMap::from_fn(|d| Point::from(d).x);
+        assert_eq!(costs[ScreenDir::R], 1);
+        assert_eq!(costs[ScreenDir::L], -1);
+        costs[ScreenDir::U] = 7;
+        assert_eq!(costs.values().sum::<i64>(), 7);
+        for (dir, cost) in costs.iter_mut() {
+            *cost += dir as i64;
+        }
+        let dirs: Vec<_> = costs.map(|c| c * 2).into_iter().collect();
+        assert_eq!(
+            dirs,
+            [
+                (ScreenDir::R, 2),
+                (ScreenDir::D, 2),
+                (ScreenDir::L, 2),
+                (ScreenDir::U, 20)
+            ]
+        );
+        // in the same order as the variants
+        let order: Vec<_> = DirMap::<()>::default().iter().map(|(d, _)| d).collect();
+        assert_eq!(order, ScreenDir::iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn screen_dir_turns_to() {
+        for a in ScreenDir::iter() {
+            for b in ScreenDir::iter() {
+                let expected = match b {
+                    _ if b == a => 0,
+                    _ if b == a.opposite() => 2,
+                    _ => 1,
+                };
+                assert_eq!(a.turns_to(b), expected, "{a} -> {b}");
+                assert_eq!(b.turns_to(a), expected);
+                assert_eq!(Dir8::from(a).turns_to(Dir8::from(b)), 2 * expected);
+            }
+        }
+        assert_eq!(ScreenDir::U.degrees(), 270);
+    }
+
+    #[test]
+    fn dir8_all_pairs() {
+        for a in Dir8::iter() {
+            // diagonals are the sum of the neighbouring straight directions
+            let delta = a.delta();
+            assert_eq!(a.is_diagonal(), delta.x != 0 && delta.y != 0);
+            if a.is_diagonal() {
+                let sum = Point::from(a.rotate(-1)) + a.rotate(1).into();
+                assert_eq!(sum, delta);
+            }
+            assert_eq!(a.opposite().delta(), Point::default() - delta);
+            assert_eq!(Dir8::from_degrees(a.degrees() as i32), Some(a));
+            assert_eq!(Dir8::from_degrees(a.degrees() as i32 - 720), Some(a));
+
+            for b in Dir8::iter() {
+                // the turns either way round add up to a full turn
+                let clockwise = (b.degrees() + 360 - a.degrees()) % 360 / 45;
+                let expected = clockwise.min(8 - clockwise) as u8;
+                assert_eq!(a.turns_to(b), expected, "{a} -> {b}");
+                assert_eq!(a.rotate(clockwise as i32), b);
+                assert_eq!(a.turns_to(b) == 0, a == b);
+            }
+        }
+        assert_eq!(Dir8::from_degrees(30), None);
+        assert_eq!(Dir8::from_degrees(-45), Some(Dir8::UR));
+    }
+
+    #[test]
+    fn dir8_screen_dir_conversions() {
+        for d in ScreenDir::iter() {
+            let d8 = Dir8::from(d);
+            assert!(!d8.is_diagonal());
+            assert_eq!(Point::from(d8), Point::from(d));
+            assert_eq!(d8.degrees(), d.degrees());
+            assert_eq!(ScreenDir::try_from(d8).unwrap(), d);
+            assert_eq!(d8.rotate(2), Dir8::from(d.right()));
+        }
+        let diagonals: Vec<_> = Dir8::iter().filter(Dir8::is_diagonal).collect();
+        assert_eq!(diagonals, [Dir8::DR, Dir8::DL, Dir8::UL, Dir8::UR]);
+        for d in diagonals {
+            assert!(ScreenDir::try_from(d).is_err());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn point_add_sub_roundtrip(a in point(), b in point()) {
+            prop_assert_eq!((a + b) - b, a);
+            prop_assert_eq!((a - b) + b, a);
+            prop_assert_eq!(a + b, b + a);
+            prop_assert_eq!(a - a, Point::default());
+        }
+
+        #[test]
+        fn point_coord_roundtrip(r in 0_usize..10_000, c in 0_usize..10_000) {
+            let p = Point::from((r, c));
+            prop_assert_eq!(p.to_coord(), Some((r, c)));
+        }
+
+        #[test]
+        fn to_coord_matrix_within_bounds(
+            p in (-20_i64..20, -20_i64..20).prop_map(|(x, y)| Point::new(x, y)),
+            rows in 1_usize..10,
+            cols in 1_usize..10,
+        ) {
+            let matrix = DMatrix::from_element(rows, cols, 0_u8);
+            let inside = p.x >= 0 && p.y >= 0 && (p.x as usize) < cols && (p.y as usize) < rows;
+            match p.to_coord_matrix(&matrix) {
+                Some((r, c)) => {
+                    prop_assert!(r < rows && c < cols);
+                    prop_assert_eq!(Point::from((r, c)), p);
+                }
+                None => prop_assert!(!inside),
+            }
+            prop_assert_eq!(p.within_bounds(&matrix), inside);
+            prop_assert_eq!(matrix.get(p).is_some(), inside);
+        }
+
+        #[test]
+        fn step_within_matches_bounds(
+            p in (-2_i64..12, -2_i64..12).prop_map(|(x, y)| Point::new(x, y)),
+            d in screen_dir(),
+            rows in 1_usize..10,
+            cols in 1_usize..10,
+        ) {
+            let matrix = DMatrix::from_element(rows, cols, 0_u8);
+            let next = p + d.into();
+            let expected = next.within_bounds(&matrix).then_some(next);
+            prop_assert_eq!(p.step_within(d, &matrix), expected);
+            prop_assert_eq!(p.try_add_within(d.into(), &matrix), expected);
+        }
+
+        #[test]
+        fn try_from_coord_bounds(r in 0_usize..12, c in 0_usize..12, rows in 1_usize..10, cols in 1_usize..10) {
+            let matrix = DMatrix::from_element(rows, cols, 0_u8);
+            let p = Point::try_from_coord((r, c), &matrix);
+            prop_assert_eq!(p.is_some(), r < rows && c < cols);
+            if let Some(p) = p {
+                prop_assert_eq!(p.to_coord_matrix(&matrix), Some((r, c)));
+            }
+        }
+
+        #[test]
+        fn vec2_matches_point(a in small_point(), b in small_point()) {
+            let (va, vb) = (Vec2::from(a), Vec2::from(b));
+            prop_assert_eq!(va + vb, Vec2::from(a + b));
+            prop_assert_eq!(va - vb, Vec2::from(a - b));
+            prop_assert_eq!(va.dot(vb), (a.x * b.x + a.y * b.y) as f64);
+            prop_assert_eq!(va.cross(vb), -vb.cross(va));
+            prop_assert_eq!(va.cross(va), 0.0);
+
+            // Lagrange's identity, exact at these sizes
+            prop_assert_eq!(
+                va.dot(vb).powi(2) + va.cross(vb).powi(2),
+                va.norm_squared() * vb.norm_squared()
+            );
+        }
+
+        #[test]
+        fn vec2_normalized(a in small_point()) {
+            match Vec2::from(a).normalized() {
+                Some(u) => {
+                    prop_assert!((u.norm() - 1.0).abs() < 1e-12);
+                    prop_assert!(u.cross(Vec2::from(a)).abs() < 1e-9);
+                    prop_assert!(u.dot(Vec2::from(a)) > 0.0);
+                }
+                None => prop_assert_eq!(a, Point::default()),
+            }
+        }
+
+        #[test]
+        fn screen_dir_turn_laws(d in screen_dir()) {
+            prop_assert_eq!(d.left().right(), d);
+            prop_assert_eq!(d.right().left(), d);
+            prop_assert_eq!(d.opposite().opposite(), d);
+            prop_assert_eq!(d.left().left(), d.opposite());
+            prop_assert_eq!(d.right().right(), d.opposite());
+            prop_assert_eq!(d.right().right().right().right(), d);
+        }
+
+        #[test]
+        fn screen_dir_deltas(d in screen_dir(), p in point()) {
+            let delta = Point::from(d);
+            prop_assert_eq!(delta.x.abs() + delta.y.abs(), 1);
+            prop_assert_eq!(p + delta + d.opposite().into(), p);
+
+            // turning right on screen (y down) is a clockwise rotation
+            let right = Point::from(d.right());
+            prop_assert_eq!(right, Point::new(-delta.y, delta.x));
+        }
+
+        #[test]
+        fn screen_dir_rotate(d in screen_dir(), turns in -20_i32..20) {
+            prop_assert_eq!(d.rotate(1), d.right());
+            prop_assert_eq!(d.rotate(-1), d.left());
+            prop_assert_eq!(d.rotate(2), d.opposite());
+            prop_assert_eq!(d.rotate(turns + 4), d.rotate(turns));
+            prop_assert_eq!(d.rotate(turns).rotate(-turns), d);
+        }
+
+        #[test]
+        fn compass_dir_rotate(d in compass_dir(), turns in -20_i32..20) {
+            prop_assert_eq!(d.rotate(turns).rotate(-turns), d);
+            prop_assert_eq!(d.rotate(2).delta(), Point::default() - d.delta());
+            prop_assert_eq!(CompassDir::from(ScreenDir::from(d)), d);
+            prop_assert_eq!(d.to_screen(ScreenDir::U), ScreenDir::from(d));
+        }
+
+        #[test]
+        fn compass_to_screen_keeps_handedness(d in compass_dir(), north in screen_dir()) {
+            prop_assert_eq!(d.rotate(1).to_screen(north), d.to_screen(north).right());
+        }
+    }
+
+    #[test]
+    fn vec2_cross_is_clockwise_on_screen() {
+        let right = Vec2::from(Point::from(ScreenDir::R));
+        let down = Vec2::from(Point::from(ScreenDir::D));
+        assert!(right.cross(down) > 0.0);
+        assert_eq!(right.cross(-down), -1.0);
+    }
+
+    #[test]
+    fn vec2_centroid() {
+        let square = [(0, 0), (2, 0), (2, 2), (0, 2)].map(|(x, y)| Point::new(x, y).into());
+        assert_eq!(Vec2::centroid(square), Some(Vec2::new(1.0, 1.0)));
+        assert_eq!(Vec2::centroid([]), None);
+    }
+
+    #[test]
+    fn compass_rotation_is_clockwise() {
+        assert_eq!(CompassDir::N.rotate(1), CompassDir::E);
+        assert_eq!(CompassDir::N.rotate(-1), CompassDir::W);
+        assert_eq!(CompassDir::E.delta(), Point::new(1, 0));
+        assert_eq!(CompassDir::N.to_screen(ScreenDir::R), ScreenDir::R);
+        assert_eq!(CompassDir::E.to_screen(ScreenDir::R), ScreenDir::D);
+    }
+
+    #[test]
+    fn walk_square() {
+        use Instruction::*;
+        let path = walk(
+            Point::new(0, 0),
+            ScreenDir::R,
+            &[
+                Move(2),
+                TurnRight,
+                Move(1),
+                TurnRight,
+                Move(2),
+                TurnLeft,
+                TurnLeft,
+            ],
+        );
+        assert_eq!(
+            path,
+            [
+                Point::new(0, 0),
+                Point::new(1, 0),
+                Point::new(2, 0),
+                Point::new(2, 1),
+                Point::new(1, 1),
+                Point::new(0, 1),
+            ]
+        );
+        assert_eq!(
+            walk(Point::new(3, 4), ScreenDir::U, &[]),
+            [Point::new(3, 4)]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+//! Errors that say which day, part and input file they came from, with the
+//! underlying anyhow chain kept as the source
+
+use std::fmt::Display;
+
+/// An error from a day, with as much of the part and input file as is known.
+/// Converts into `anyhow::Error` like any other, and wrapping one that's already an
+/// `AocError` fills in the missing details rather than nesting another.
+#[derive(Debug)]
+pub struct AocError {
+    day: u8,
+    part: Option<String>,
+    input: Option<String>,
+    source: anyhow::Error,
+}
+
+pub type AocResult<T> = Result<T, AocError>;
+
+impl AocError {
+    pub fn new(day: u8, error: impl Into<anyhow::Error>) -> Self {
+        let error = error.into();
+        match error.downcast::<AocError>() {
+            Ok(aoc) if aoc.day == day => aoc,
+            Ok(aoc) => AocError::wrap(day, aoc.into()),
+            Err(error) => AocError::wrap(day, error),
+        }
+    }
+
+    fn wrap(day: u8, source: anyhow::Error) -> Self {
+        AocError {
+            day,
+            part: None,
+            input: None,
+            source,
+        }
+    }
+
+    /// Name the part, such as "1" or "2 (brute)", unless one already is
+    pub fn in_part(mut self, part: &str) -> Self {
+        self.part.get_or_insert_with(|| part.to_string());
+        self
+    }
+
+    /// Name the input file, unless one already is
+    pub fn with_input(mut self, file: &str) -> Self {
+        self.input.get_or_insert_with(|| file.to_string());
+        self
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn part(&self) -> Option<&str> {
+        self.part.as_deref()
+    }
+
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+
+    /// The error this wraps
+    pub fn inner(&self) -> &anyhow::Error {
+        &self.source
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}", self.day)?;
+        if let Some(part) = &self.part {
+            write!(f, ", part {part}")?;
+        }
+        if let Some(input) = &self.input {
+            write!(f, ", input {input}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Tag errors with where they came from, as an `AocError`
+pub trait AocContext<T> {
+    fn day_context(self, day: u8) -> AocResult<T>;
+    fn part_context(self, day: u8, part: &str) -> AocResult<T>;
+    fn input_context(self, day: u8, file: &str) -> AocResult<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> AocContext<T> for Result<T, E> {
+    fn day_context(self, day: u8) -> AocResult<T> {
+        self.map_err(|e| AocError::new(day, e))
+    }
+
+    fn part_context(self, day: u8, part: &str) -> AocResult<T> {
+        self.map_err(|e| AocError::new(day, e).in_part(part))
+    }
+
+    fn input_context(self, day: u8, file: &str) -> AocResult<T> {
+        self.map_err(|e| AocError::new(day, e).with_input(file))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Context};
+
+    use super::*;
+
+    #[test]
+    fn context_fills_in() {
+        let failed: anyhow::Result<()> = Err(anyhow!("bad digit")).context("parsing line 3");
+        let err = failed.part_context(7, "2").unwrap_err();
+        assert_eq!(err.to_string(), "day 7, part 2");
+        assert_eq!(
+            format!("{:#}", anyhow::Error::from(err)),
+            "day 7, part 2: parsing line 3: bad digit"
+        );
+
+        // through anyhow and back, the part is kept and the input added
+        let failed: anyhow::Result<()> = Err(anyhow!("bad digit"))
+            .part_context(7, "2")
+            .map_err(Into::into);
+        let err = failed.input_context(7, "day7/input1.txt").unwrap_err();
+        assert_eq!(err.to_string(), "day 7, part 2, input day7/input1.txt");
+        assert_eq!(
+            (err.day(), err.part(), err.input()),
+            (7, Some("2"), Some("day7/input1.txt"))
+        );
+        assert_eq!(err.inner().to_string(), "bad digit");
+
+        // the first part named sticks
+        let err = AocError::new(3, anyhow!("x")).in_part("1").in_part("2");
+        assert_eq!(err.part(), Some("1"));
+    }
+
+    #[test]
+    fn other_days_nest() {
+        let err = AocError::new(4, AocError::new(5, anyhow!("x")));
+        assert_eq!(format!("{:#}", anyhow::Error::from(err)), "day 4: day 5: x");
+    }
+}
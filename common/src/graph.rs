@@ -0,0 +1,692 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use arrayvec::ArrayVec;
+use nalgebra::DMatrix;
+use rustc_hash::{FxHashMap, FxHashSet};
+use strum::IntoEnumIterator;
+
+use crate::cartesian::{Point, ScreenDir};
+
+/// How `best_paths` orders its search
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolverStrategy {
+    /// Plain Dijkstra; the heuristic is ignored
+    #[default]
+    Dijkstra,
+    /// A*, expanding states in order of cost plus the heuristic's estimate of the
+    /// remaining cost. The heuristic must be consistent (never drop by more than the
+    /// cost of a move) for the best paths to be complete.
+    AStar,
+}
+
+/// Best cost found to reach a state, and every state it's reached from at that cost.
+/// The origins are kept inline, since a search can reach millions of states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reached<S, const N: usize> {
+    pub cost: i64,
+    pub origins: ArrayVec<S, N>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BestPaths<S, const N: usize> {
+    pub reached: FxHashMap<S, Reached<S, N>>,
+    /// Lowest cost of any goal state, if one was reached
+    pub goal_cost: Option<i64>,
+    /// Number of states expanded
+    pub expanded: usize,
+}
+
+/// A `best_paths` search that can be paused, inspected, and resumed. Each step expands
+/// one state, which is then settled: its cost is final, as are its origins once every
+/// state of that cost has been expanded.
+pub struct Search<S, I, F, H, G, const N: usize> {
+    strategy: SolverStrategy,
+    successors: F,
+    heuristic: H,
+    is_goal: G,
+    reached: FxHashMap<S, Reached<S, N>>,
+    settled: FxHashSet<S>,
+    q: BinaryHeap<Reverse<(i64, i64, S)>>,
+    goal_cost: Option<i64>,
+    finished: bool,
+    _successors: PhantomData<fn() -> I>,
+}
+
+impl<S, I, F, H, G, const N: usize> Search<S, I, F, H, G, N>
+where
+    S: Copy + Eq + Hash + Ord,
+    I: IntoIterator<Item = (S, i64)>,
+    F: FnMut(S) -> I,
+    H: Fn(S) -> i64,
+    G: Fn(S) -> bool,
+{
+    /// A search from `start`, with nothing expanded yet; arguments as for `best_paths`
+    pub fn new(
+        start: S,
+        strategy: SolverStrategy,
+        successors: F,
+        heuristic: H,
+        is_goal: G,
+    ) -> Self {
+        let mut search = Search {
+            strategy,
+            successors,
+            heuristic,
+            is_goal,
+            reached: FxHashMap::default(),
+            settled: FxHashSet::default(),
+            q: BinaryHeap::new(),
+            goal_cost: None,
+            finished: false,
+            _successors: PhantomData,
+        };
+        search.reached.insert(
+            start,
+            Reached {
+                cost: 0,
+                origins: ArrayVec::new(),
+            },
+        );
+        search.q.push(Reverse((search.estimate(start), 0, start)));
+        search
+    }
+
+    fn estimate(&self, s: S) -> i64 {
+        match self.strategy {
+            SolverStrategy::Dijkstra => 0,
+            SolverStrategy::AStar => (self.heuristic)(s),
+        }
+    }
+
+    /// Expand the next state, returning it; `None` once the search is finished
+    pub fn step(&mut self) -> Option<S> {
+        while let Some(Reverse((priority, cost, state))) = self.q.pop() {
+            if self.goal_cost.is_some_and(|goal| priority > goal) {
+                break;
+            }
+            // stale entry, since improved on
+            if cost > self.reached[&state].cost {
+                continue;
+            }
+            self.settled.insert(state);
+            if (self.is_goal)(state) {
+                self.goal_cost = Some(self.goal_cost.unwrap_or(cost).min(cost));
+            }
+
+            for (next, step_cost) in (self.successors)(state) {
+                let alt = cost + step_cost;
+                let current = self.reached.get_mut(&next);
+                match current
+                    .as_ref()
+                    .map_or(Ordering::Less, |r| alt.cmp(&r.cost))
+                {
+                    Ordering::Less => {
+                        self.reached.insert(
+                            next,
+                            Reached {
+                                cost: alt,
+                                origins: [state].into_iter().collect(),
+                            },
+                        );
+                        self.q.push(Reverse((alt + self.estimate(next), alt, next)));
+                    }
+                    Ordering::Equal => current
+                        .unwrap()
+                        .origins
+                        .try_push(state)
+                        .expect("more equal-cost origins than the capacity"),
+                    Ordering::Greater => {}
+                }
+            }
+            return Some(state);
+        }
+        self.finished = true;
+        None
+    }
+
+    /// Expand up to `k` states, returning how many were
+    pub fn step_n(&mut self, k: usize) -> usize {
+        (0..k).take_while(|_| self.step().is_some()).count()
+    }
+
+    /// Expand states until the search is finished
+    pub fn run(&mut self) {
+        while self.step().is_some() {}
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Best cost found so far to each state reached, and the origins at that cost
+    pub fn distances(&self) -> &FxHashMap<S, Reached<S, N>> {
+        &self.reached
+    }
+
+    /// Whether `state` has been expanded, so its cost won't change
+    pub fn is_settled(&self, state: S) -> bool {
+        self.settled.contains(&state)
+    }
+
+    /// Lowest cost of any goal state expanded so far
+    pub fn goal_cost(&self) -> Option<i64> {
+        self.goal_cost
+    }
+
+    /// Number of states expanded so far
+    pub fn expanded(&self) -> usize {
+        self.settled.len()
+    }
+
+    pub fn into_best_paths(self) -> BestPaths<S, N> {
+        BestPaths {
+            expanded: self.expanded(),
+            reached: self.reached,
+            goal_cost: self.goal_cost,
+        }
+    }
+}
+
+/// Shortest paths from `start` to the goal states, keeping every equal-cost origin so
+/// all the best paths can be walked back. The search stops once nothing left could
+/// reach a goal as cheaply as the best found; states beyond that may be missing or
+/// have worse than their best cost.
+///
+/// No state may be reached at its best cost from more than `N` others, such as the
+/// at most four neighbours on a grid; panics if one is.
+pub fn best_paths<S, I, const N: usize>(
+    start: S,
+    strategy: SolverStrategy,
+    successors: impl FnMut(S) -> I,
+    heuristic: impl Fn(S) -> i64,
+    is_goal: impl Fn(S) -> bool,
+) -> BestPaths<S, N>
+where
+    S: Copy + Eq + Hash + Ord,
+    I: IntoIterator<Item = (S, i64)>,
+{
+    let mut search = Search::new(start, strategy, successors, heuristic, is_goal);
+    search.run();
+    search.into_best_paths()
+}
+
+/// Priority queue for Dijkstra with small integer step costs (Dial's algorithm): one
+/// bucket per priority, in a ring covering the current priority up to `max_step`
+/// beyond it. Everything pushed must be within that window, which is so when the
+/// priority is the popped one plus a step cost; panics otherwise.
+#[derive(Debug, Clone)]
+pub struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    current: i64,
+    len: usize,
+}
+impl<T> BucketQueue<T> {
+    /// A queue starting from priority 0, for step costs up to `max_step`
+    pub fn new(max_step: usize) -> Self {
+        BucketQueue {
+            buckets: (0..=max_step).map(|_| Vec::new()).collect(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T, priority: i64) {
+        let ahead = priority - self.current;
+        assert!(
+            (0..self.buckets.len() as i64).contains(&ahead),
+            "priority {priority} outside {}..{}",
+            self.current,
+            self.current + self.buckets.len() as i64
+        );
+        let slot = priority.rem_euclid(self.buckets.len() as i64) as usize;
+        self.buckets[slot].push(item);
+        self.len += 1;
+    }
+
+    /// An item with the lowest priority, and the priority
+    pub fn pop(&mut self) -> Option<(T, i64)> {
+        if self.len == 0 {
+            return None;
+        }
+        loop {
+            let slot = self.current.rem_euclid(self.buckets.len() as i64) as usize;
+            if let Some(item) = self.buckets[slot].pop() {
+                self.len -= 1;
+                return Some((item, self.current));
+            }
+            self.current += 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Distances across a grid from one start, from `bfs_grid` or `bucket_grid`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GridDistances {
+    /// Steps to each cell, or `i64::MAX` where it can't be reached
+    pub dist: DMatrix<i64>,
+    /// The cell each was reached from on a shortest path; `None` for the start and
+    /// anywhere unreached
+    pub prev: DMatrix<Option<Point>>,
+}
+impl GridDistances {
+    /// Nothing reached yet
+    fn new(shape: (usize, usize)) -> Self {
+        GridDistances {
+            dist: DMatrix::from_element(shape.0, shape.1, i64::MAX),
+            prev: DMatrix::from_element(shape.0, shape.1, None),
+        }
+    }
+
+    fn reach_start(&mut self, start: Point) {
+        *self.dist.get_mut(start).expect("start outside the grid") = 0;
+    }
+
+    /// Steps to `p`, if it's on the grid and was reached
+    pub fn get(&self, p: Point) -> Option<i64> {
+        self.dist.get(p).copied().filter(|&d| d != i64::MAX)
+    }
+
+    /// A shortest path from the start to `end`, including both
+    pub fn path_to(&self, end: Point) -> Option<Vec<Point>> {
+        self.get(end)?;
+        let mut path = vec![end];
+        while let Some(&Some(p)) = self.prev.get(path[path.len() - 1]) {
+            path.push(p);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Breadth-first search over a grid of `shape` (rows, columns), stepping between
+/// orthogonal neighbours for one each. `passable(p, steps)` says whether `p` can be
+/// entered after `steps` steps, including the start at step 0; if it can't, nothing
+/// is reached. For unit costs this visits cells in the same order as Dijkstra,
+/// without the heap.
+pub fn bfs_grid(
+    start: Point,
+    shape: (usize, usize),
+    mut passable: impl FnMut(Point, i64) -> bool,
+) -> GridDistances {
+    let mut found = GridDistances::new(shape);
+    if !passable(start, 0) {
+        return found;
+    }
+    found.reach_start(start);
+    let mut q = VecDeque::from([start]);
+    while let Some(p) = q.pop_front() {
+        let alt = found.dist.get(p).unwrap() + 1;
+        for dir in ScreenDir::iter() {
+            let next = p + dir.into();
+            if found.dist.get(next).is_some_and(|&d| d == i64::MAX) && passable(next, alt) {
+                *found.dist.get_mut(next).unwrap() = alt;
+                *found.prev.get_mut(next).unwrap() = Some(p);
+                q.push_back(next);
+            }
+        }
+    }
+    found
+}
+
+/// As `bfs_grid` with a cost for each step, `None` where it can't be taken: Dijkstra
+/// over a `BucketQueue`, so costs must be at most `max_step`
+pub fn bucket_grid(
+    start: Point,
+    shape: (usize, usize),
+    max_step: usize,
+    mut step_cost: impl FnMut(Point, Point) -> Option<i64>,
+) -> GridDistances {
+    let mut found = GridDistances::new(shape);
+    found.reach_start(start);
+    let mut q = BucketQueue::new(max_step);
+    q.push(start, 0);
+    while let Some((p, cost)) = q.pop() {
+        // stale entry, since improved on
+        if cost > *found.dist.get(p).unwrap() {
+            continue;
+        }
+        for dir in ScreenDir::iter() {
+            let next = p + dir.into();
+            let Some(&current) = found.dist.get(next) else {
+                continue;
+            };
+            if let Some(step) = step_cost(p, next) {
+                let alt = cost + step;
+                if alt < current {
+                    *found.dist.get_mut(next).unwrap() = alt;
+                    *found.prev.get_mut(next).unwrap() = Some(p);
+                    q.push(next, alt);
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Disjoint sets over indices `0..len`, each named by its lowest member
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+}
+impl UnionFind {
+    pub fn new(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+        }
+    }
+
+    pub fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            // path halving
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+    use proptest::prelude::*;
+    use strum::IntoEnumIterator;
+
+    use super::*;
+    use crate::cartesian::{Point, ScreenDir};
+
+    /// Unit steps along a line of `n` nodes, plus a costly shortcut from 0 to the end
+    fn line(n: i64) -> impl FnMut(i64) -> Vec<(i64, i64)> {
+        move |s| {
+            let mut next = vec![];
+            if s + 1 < n {
+                next.push((s + 1, 1));
+            }
+            if s == 0 {
+                next.push((n - 1, n - 1));
+            }
+            next
+        }
+    }
+
+    #[test]
+    fn strategies_agree() {
+        for strategy in [SolverStrategy::Dijkstra, SolverStrategy::AStar] {
+            let found = best_paths::<_, _, 2>(0, strategy, line(10), |s| 9 - s, |s| s == 9);
+            assert_eq!(found.goal_cost, Some(9));
+            // reached both along the line and by the shortcut
+            let mut origins = found.reached[&9].origins.clone();
+            origins.sort();
+            assert!(found.reached[&9].origins.is_full());
+            assert_eq!(origins.as_slice(), [0, 8]);
+        }
+    }
+
+    #[test]
+    fn astar_expands_fewer() {
+        // a grid where only moving right gets closer to the goal
+        let successors = |(x, y): (i64, i64)| {
+            [(x + 1, y), (x, y + 1), (x, y - 1)]
+                .into_iter()
+                .filter(|(x, y)| (0..20).contains(x) && (0..20).contains(y))
+                .map(|s| (s, 1))
+                .collect::<Vec<_>>()
+        };
+        let heuristic = |(x, _): (i64, i64)| 19 - x;
+        let is_goal = |(x, y)| (x, y) == (19, 0);
+
+        let dijkstra = best_paths::<_, _, 3>(
+            (0, 0),
+            SolverStrategy::Dijkstra,
+            successors,
+            heuristic,
+            is_goal,
+        );
+        let astar = best_paths::<_, _, 3>(
+            (0, 0),
+            SolverStrategy::AStar,
+            successors,
+            heuristic,
+            is_goal,
+        );
+        assert_eq!(dijkstra.goal_cost, Some(19));
+        assert_eq!(astar.goal_cost, Some(19));
+        assert!(astar.expanded < dijkstra.expanded / 4);
+    }
+
+    #[test]
+    fn paused_search_resumes() {
+        let mut full =
+            best_paths::<_, _, 2>(0, SolverStrategy::Dijkstra, line(10), |_| 0, |s| s == 9);
+        let mut search: Search<_, _, _, _, _, 2> =
+            Search::new(0, SolverStrategy::Dijkstra, line(10), |_| 0, |s| s == 9);
+        assert_eq!(search.step_n(3), 3);
+        assert_eq!(search.expanded(), 3);
+        // 0, 1 and 2 are settled; 3 and the end are on the frontier
+        assert!((0..3).all(|s| search.is_settled(s)));
+        assert!(!search.is_settled(3) && !search.is_settled(9));
+        assert_eq!(search.distances()[&3].cost, 3);
+        assert_eq!(search.goal_cost(), None);
+        assert!(!search.is_finished());
+
+        // nothing more once finished
+        assert_eq!(search.step_n(100), 7);
+        assert!(search.is_finished());
+        assert_eq!(search.step(), None);
+        let resumed = search.into_best_paths();
+        assert_eq!(resumed.goal_cost, Some(9));
+        assert_eq!(resumed.expanded, full.expanded);
+        for (state, reached) in resumed.reached {
+            let mut origins = reached.origins;
+            origins.sort();
+            let expected = full.reached.get_mut(&state).unwrap();
+            expected.origins.sort();
+            assert_eq!(
+                (reached.cost, origins),
+                (expected.cost, expected.origins.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn unreachable_goal() {
+        let found = best_paths::<_, _, 2>(0, SolverStrategy::AStar, line(5), |_| 0, |s| s == 7);
+        assert_eq!(found.goal_cost, None);
+        assert_eq!(found.reached.len(), 5);
+    }
+
+    #[test]
+    fn bucket_queue_pops_in_order() {
+        let mut q = BucketQueue::new(3);
+        q.push('a', 2);
+        q.push('b', 0);
+        q.push('c', 3);
+        assert_eq!(q.pop(), Some(('b', 0)));
+        q.push('d', 1);
+        assert_eq!(q.len(), 3);
+        // wraps around the ring
+        let popped: Vec<_> = std::iter::from_fn(|| {
+            let next = q.pop();
+            if let Some((_, 2)) = next {
+                q.push('e', 5);
+            }
+            next
+        })
+        .collect();
+        assert_eq!(popped, [('d', 1), ('a', 2), ('c', 3), ('e', 5)]);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "priority 4 outside 0..4")]
+    fn bucket_queue_window() {
+        BucketQueue::new(3).push((), 4);
+    }
+
+    /// A 5x4 grid with a wall down column 2, except at the bottom
+    fn walled(p: Point) -> bool {
+        p.x != 2 || p.y == 3
+    }
+
+    #[test]
+    fn grid_searches_agree() {
+        let start = Point::new(0, 0);
+        let bfs = bfs_grid(start, (4, 5), |p, _| walled(p));
+        assert_eq!(bfs.get(Point::new(4, 0)), Some(10));
+        assert_eq!(bfs.get(Point::new(2, 0)), None);
+        assert_eq!(bfs.get(Point::new(9, 9)), None);
+        let path = bfs.path_to(Point::new(3, 3)).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!((path[0], path[6]), (start, Point::new(3, 3)));
+        let step = |w: &[Point]| (w[1].x - w[0].x).abs() + (w[1].y - w[0].y).abs();
+        assert!(path.windows(2).all(|w| step(w) == 1));
+
+        // unit costs give the same distances as the bfs
+        let bucket = bucket_grid(start, (4, 5), 1, |_, to| walled(to).then_some(1));
+        assert_eq!(bucket.dist, bfs.dist);
+
+        // and weighted ones what best_paths finds
+        let cost = |p: Point| (p.x * 3 + p.y * 5) % 4;
+        let bucket = bucket_grid(start, (4, 5), 3, |_, to| walled(to).then(|| cost(to)));
+        let neighbours = |p: Point| {
+            ScreenDir::iter()
+                .map(move |d| p + d.into())
+                .filter(|n| (0..5).contains(&n.x) && (0..4).contains(&n.y) && walled(*n))
+                .map(|n| (n, cost(n)))
+                .collect::<Vec<_>>()
+        };
+        let found = best_paths::<_, _, 4>(
+            start,
+            SolverStrategy::Dijkstra,
+            neighbours,
+            |_| 0,
+            |_| false,
+        );
+        for (p, reached) in found.reached {
+            assert_eq!(bucket.get(p), Some(reached.cost), "{p:?}");
+        }
+    }
+
+    #[test]
+    fn bfs_with_timed_cells() {
+        // the cell right of the start closes after step 0, forcing a detour
+        let found = bfs_grid(Point::new(0, 0), (2, 3), |p, steps| {
+            p != Point::new(1, 0) || steps < 1
+        });
+        assert_eq!(found.get(Point::new(1, 0)), None);
+        assert_eq!(found.get(Point::new(2, 0)), Some(4));
+
+        // nothing is reached from a start that's already closed
+        let found = bfs_grid(Point::new(0, 0), (2, 3), |p, steps| {
+            p != Point::new(0, 0) || steps < 0
+        });
+        assert_eq!(found.get(Point::new(0, 0)), None);
+        assert_eq!(found.path_to(Point::new(2, 1)), None);
+    }
+
+    #[test]
+    fn union_find() {
+        let mut sets = UnionFind::new(6);
+        sets.union(4, 2);
+        sets.union(5, 4);
+        sets.union(1, 3);
+        assert!(sets.connected(2, 5));
+        assert!(!sets.connected(1, 2));
+        assert_eq!(sets.find(5), 2);
+        sets.union(3, 5);
+        assert_eq!(
+            (0..6).map(|i| sets.find(i)).collect::<Vec<_>>(),
+            [0, 1, 1, 1, 1, 1]
+        );
+    }
+
+    /// Walls on a small grid, with the corners kept open
+    fn any_walls() -> impl Strategy<Value = DMatrix<bool>> {
+        (2_usize..8, 2_usize..8).prop_flat_map(|(rows, cols)| {
+            proptest::collection::vec(prop::bool::weighted(0.3), rows * cols).prop_map(move |v| {
+                let mut walls = DMatrix::from_vec(rows, cols, v);
+                walls[(0, 0)] = false;
+                walls[(rows - 1, cols - 1)] = false;
+                walls
+            })
+        })
+    }
+
+    /// Cheapest cost from the top left to the bottom right of the open cells
+    fn grid_cost(walls: &DMatrix<bool>) -> Option<i64> {
+        let goal = Point::new(walls.ncols() as i64 - 1, walls.nrows() as i64 - 1);
+        let successors = |p: Point| {
+            ScreenDir::iter()
+                .map(move |d| p + d.into())
+                .filter(|n| walls.get(*n) == Some(&false))
+                .map(|n| (n, 1))
+                .collect::<Vec<_>>()
+        };
+        let heuristic = |p: Point| (goal.x - p.x) + (goal.y - p.y);
+        let found = best_paths::<_, _, 4>(
+            Point::new(0, 0),
+            SolverStrategy::AStar,
+            successors,
+            heuristic,
+            |p| p == goal,
+        );
+        found.goal_cost
+    }
+
+    proptest! {
+        #[test]
+        fn walls_never_shorten(walls in any_walls(), wall in any::<prop::sample::Index>()) {
+            let before = grid_cost(&walls);
+            let mut walled = walls.clone();
+            let cell = wall.index(walls.len());
+            // the corners stay open
+            if cell != 0 && cell != walls.len() - 1 {
+                walled[cell] = true;
+            }
+            // a blocked grid costs more than any open one
+            let after = grid_cost(&walled);
+            prop_assert!(after.unwrap_or(i64::MAX) >= before.unwrap_or(i64::MAX));
+        }
+
+        #[test]
+        fn grid_searches_agree_on_walls(walls in any_walls()) {
+            let open = |p: Point| walls.get(p) == Some(&false);
+            let start = Point::new(0, 0);
+            let bfs = bfs_grid(start, walls.shape(), |p, _| open(p));
+            let bucket = bucket_grid(start, walls.shape(), 1, |_, to| open(to).then_some(1));
+            prop_assert_eq!(&bfs.dist, &bucket.dist);
+            let goal = Point::new(walls.ncols() as i64 - 1, walls.nrows() as i64 - 1);
+            prop_assert_eq!(bfs.get(goal), grid_cost(&walls));
+        }
+
+        #[test]
+        fn cost_at_least_manhattan(walls in any_walls()) {
+            if let Some(cost) = grid_cost(&walls) {
+                prop_assert!(cost >= (walls.nrows() + walls.ncols() - 2) as i64);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Parsing grids into matrices, and rotating and flipping them; `DMatrix::transpose`
+//! covers transposing.
+
+use std::io::BufRead;
+
+use anyhow::{bail, Result};
+use nalgebra::{DMatrix, Scalar};
+
+use crate::{OptionAnyhow, ResultAnyhow};
+
+/// Parse a grid straight from `reader`, one row per line, without holding the whole
+/// text. The first row sets the width, and a row of any other width is an error.
+/// Reading stops at the end of input or the first blank line, leaving anything after
+/// that unread, such as the moves after a warehouse map.
+pub fn grid_from_reader<T: Scalar>(
+    mut reader: impl BufRead,
+    mut mapping: impl FnMut(char) -> Result<T>,
+) -> Result<DMatrix<T>> {
+    let mut cells = vec![];
+    let mut cols = None;
+    let mut rows = 0;
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 0 {
+        let row = line.trim_end_matches(['\n', '\r']);
+        if row.is_empty() {
+            break;
+        }
+        let row_start = cells.len();
+        for (col, ch) in row.chars().enumerate() {
+            cells.push(mapping(ch).at(rows + 1, col + 1)?);
+        }
+        let width = cells.len() - row_start;
+        match cols {
+            None => cols = Some(width),
+            Some(cols) if cols != width => {
+                bail!("line {}: expected {cols} columns, found {width}", rows + 1)
+            }
+            Some(_) => {}
+        }
+        rows += 1;
+        line.clear();
+    }
+
+    let cols = cols.expect_anyhow("no rows in grid")?;
+    Ok(DMatrix::from_row_iterator(rows, cols, cells))
+}
+
+/// As `grid_from_reader`, for input already in memory
+pub fn grid_from_str<T: Scalar>(
+    input: &str,
+    mapping: impl FnMut(char) -> Result<T>,
+) -> Result<DMatrix<T>> {
+    grid_from_reader(input.as_bytes(), mapping)
+}
+
+/// One line of text per row, for drawing a grid of characters
+pub fn render(grid: &DMatrix<char>) -> String {
+    let mut s = String::with_capacity(grid.nrows() * (grid.ncols() + 1));
+    for row in grid.row_iter() {
+        s.extend(row.iter());
+        s.push('\n');
+    }
+    s
+}
+
+/// Rotate a quarter turn clockwise, so the first column becomes the first row
+pub fn rotate_cw<T: Scalar>(grid: &DMatrix<T>) -> DMatrix<T> {
+    let rows = grid.nrows();
+    DMatrix::from_fn(grid.ncols(), rows, |r, c| grid[(rows - 1 - c, r)].clone())
+}
+
+/// Rotate a quarter turn anticlockwise, so the last column becomes the first row
+pub fn rotate_ccw<T: Scalar>(grid: &DMatrix<T>) -> DMatrix<T> {
+    let cols = grid.ncols();
+    DMatrix::from_fn(cols, grid.nrows(), |r, c| grid[(c, cols - 1 - r)].clone())
+}
+
+/// Mirror left to right
+pub fn flip_h<T: Scalar>(grid: &DMatrix<T>) -> DMatrix<T> {
+    let cols = grid.ncols();
+    DMatrix::from_fn(grid.nrows(), cols, |r, c| grid[(r, cols - 1 - c)].clone())
+}
+
+/// Mirror top to bottom
+pub fn flip_v<T: Scalar>(grid: &DMatrix<T>) -> DMatrix<T> {
+    let rows = grid.nrows();
+    DMatrix::from_fn(rows, grid.ncols(), |r, c| grid[(rows - 1 - r, c)].clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartesian::matrix_from_lines;
+    use proptest::prelude::*;
+
+    fn grid(lines: &[&str]) -> DMatrix<char> {
+        matrix_from_lines(lines, Ok).unwrap()
+    }
+
+    #[test]
+    fn parse_from_reader() -> Result<()> {
+        let g = grid_from_str("ab\r\ncd\n", Ok)?;
+        assert_eq!(g, grid(&["ab", "cd"]));
+        assert_eq!(grid_from_str("abc", Ok)?, grid(&["abc"]));
+
+        // stops at a blank line, leaving the rest to read
+        let mut reader = "12\n34\n\n<>^v\n".as_bytes();
+        let digits = grid_from_reader(&mut reader, |ch| {
+            ch.to_digit(10).expect_anyhow("not a digit")
+        })?;
+        assert_eq!(digits.shape(), (2, 2));
+        assert_eq!(digits[(1, 0)], 3);
+        assert_eq!(reader, b"<>^v\n");
+
+        let err = grid_from_str("abc\nab\n", Ok).unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected 3 columns, found 2");
+        let err = grid_from_str("12\n3x\n", |ch| {
+            ch.to_digit(10).expect_anyhow("not a digit")
+        })
+        .unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 2, column 2: not a digit");
+        assert!(grid_from_str("", Ok).is_err());
+        assert!(grid_from_str("\nab\n", Ok).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rotate_and_flip_small_grid() {
+        let g = grid(&["abc", "def"]);
+        assert_eq!(rotate_cw(&g), grid(&["da", "eb", "fc"]));
+        assert_eq!(rotate_ccw(&g), grid(&["cf", "be", "ad"]));
+        assert_eq!(flip_h(&g), grid(&["cba", "fed"]));
+        assert_eq!(flip_v(&g), grid(&["def", "abc"]));
+        assert_eq!(render(&g), "abc\ndef\n");
+    }
+
+    fn any_grid() -> impl Strategy<Value = DMatrix<u8>> {
+        (1_usize..6, 1_usize..6).prop_flat_map(|(rows, cols)| {
+            proptest::collection::vec(any::<u8>(), rows * cols)
+                .prop_map(move |v| DMatrix::from_vec(rows, cols, v))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn rotation_laws(g in any_grid()) {
+            prop_assert_eq!(rotate_ccw(&rotate_cw(&g)), g.clone());
+            prop_assert_eq!(rotate_cw(&rotate_cw(&rotate_cw(&rotate_cw(&g)))), g.clone());
+            prop_assert_eq!(rotate_cw(&rotate_cw(&g)), flip_h(&flip_v(&g)));
+            prop_assert_eq!(rotate_cw(&g), flip_h(&g.transpose()));
+        }
+
+        #[test]
+        fn flips_are_involutions(g in any_grid()) {
+            prop_assert_eq!(flip_h(&flip_h(&g)), g.clone());
+            prop_assert_eq!(flip_v(&flip_v(&g)), g.clone());
+        }
+    }
+}
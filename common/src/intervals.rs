@@ -0,0 +1,201 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
+
+/// A set of integers stored as disjoint half-open intervals, merging any that touch.
+/// Intervals are also indexed by length, so `first_fit` only looks at one candidate
+/// per distinct length.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    /// start -> end of each interval
+    by_start: BTreeMap<i64, i64>,
+    /// starts of the intervals of each length
+    by_len: BTreeMap<i64, BTreeSet<i64>>,
+    covered: i64,
+}
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an interval known not to touch any other
+    fn add(&mut self, start: i64, end: i64) {
+        self.by_start.insert(start, end);
+        self.by_len.entry(end - start).or_default().insert(start);
+        self.covered += end - start;
+    }
+
+    /// Forget the interval starting at `start`, returning its end
+    fn take(&mut self, start: i64) -> i64 {
+        let end = self.by_start.remove(&start).unwrap();
+        let len = end - start;
+        let starts = self.by_len.get_mut(&len).unwrap();
+        starts.remove(&start);
+        if starts.is_empty() {
+            self.by_len.remove(&len);
+        }
+        self.covered -= len;
+        end
+    }
+
+    pub fn insert(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+
+        // join an interval that starts before and reaches this one
+        if let Some((&s, &e)) = self.by_start.range(..start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(self.take(s));
+            }
+        }
+        // and swallow any starting within it, or right at its end
+        while let Some((&s, _)) = self.by_start.range(start..=end).next() {
+            end = end.max(self.take(s));
+        }
+        self.add(start, end);
+    }
+
+    pub fn remove(&mut self, range: Range<i64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mut overlapping = vec![];
+        if let Some((&s, &e)) = self.by_start.range(..range.start).next_back() {
+            if e > range.start {
+                overlapping.push(s);
+            }
+        }
+        overlapping.extend(self.by_start.range(range.clone()).map(|(s, _)| *s));
+
+        for s in overlapping {
+            let e = self.take(s);
+            if s < range.start {
+                self.add(s, range.start);
+            }
+            if e > range.end {
+                self.add(range.end, e);
+            }
+        }
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.by_start
+            .range(..=value)
+            .next_back()
+            .is_some_and(|(_, e)| value < *e)
+    }
+
+    /// Total number of values in the set
+    pub fn covered(&self) -> i64 {
+        self.covered
+    }
+
+    /// Number of separate intervals
+    pub fn len(&self) -> usize {
+        self.by_start.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_start.is_empty()
+    }
+
+    /// The intervals in order
+    pub fn iter(&self) -> impl Iterator<Item = Range<i64>> + '_ {
+        self.by_start.iter().map(|(s, e)| *s..*e)
+    }
+
+    /// Leftmost interval at least `len` long, such as the first gap that fits a file
+    /// when the set holds free space
+    pub fn first_fit(&self, len: i64) -> Option<Range<i64>> {
+        let start = self
+            .by_len
+            .range(len.max(1)..)
+            .filter_map(|(_, starts)| starts.first())
+            .min()?;
+        Some(*start..self.by_start[start])
+    }
+}
+impl FromIterator<Range<i64>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn op() -> impl Strategy<Value = (bool, Range<i64>)> {
+        (any::<bool>(), 0_i64..64, 0_i64..12)
+            .prop_map(|(insert, start, len)| (insert, start..start + len))
+    }
+
+    proptest! {
+        #[test]
+        fn matches_model(ops in proptest::collection::vec(op(), 0..40)) {
+            let mut set = IntervalSet::new();
+            let mut model = BTreeSet::new();
+            for (insert, range) in ops {
+                if insert {
+                    set.insert(range.clone());
+                    model.extend(range);
+                } else {
+                    set.remove(range.clone());
+                    for v in range {
+                        model.remove(&v);
+                    }
+                }
+            }
+
+            for v in -2..80 {
+                prop_assert_eq!(set.contains(v), model.contains(&v));
+            }
+            prop_assert_eq!(set.covered(), model.len() as i64);
+            prop_assert_eq!(set.iter().flatten().collect::<BTreeSet<_>>(), model);
+
+            // sorted, non-empty, and never touching
+            let intervals: Vec<_> = set.iter().collect();
+            prop_assert_eq!(intervals.len(), set.len());
+            for r in &intervals {
+                prop_assert!(!r.is_empty());
+            }
+            for pair in intervals.windows(2) {
+                prop_assert!(pair[0].end < pair[1].start);
+            }
+
+            for len in 0..16 {
+                let expected = intervals.iter().find(|r| r.end - r.start >= len.max(1)).cloned();
+                prop_assert_eq!(set.first_fit(len), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_and_split() {
+        let mut set: IntervalSet = [0..3, 5..8, 3..5].into_iter().collect();
+        assert_eq!((set.len(), set.iter().next()), (1, Some(0..8)));
+
+        set.remove(2..4);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [0..2, 4..8]);
+        assert_eq!(set.covered(), 6);
+        assert_eq!(set.first_fit(3), Some(4..8));
+        assert_eq!(set.first_fit(5), None);
+
+        set.insert(10..12);
+        set.remove(0..11);
+        assert_eq!((set.len(), set.iter().next()), (1, Some(11..12)));
+        set.remove(11..12);
+        assert!(set.is_empty());
+        assert_eq!(set.covered(), 0);
+    }
+}
@@ -0,0 +1,71 @@
+use std::ops::Sub;
+
+/// Iterator from `windowed_deltas`
+#[derive(Debug, Clone)]
+pub struct WindowedDeltas<I: Iterator, const K: usize> {
+    iter: I,
+    prev: Option<I::Item>,
+    deltas: [I::Item; K],
+    filled: usize,
+}
+
+impl<I, T, const K: usize> Iterator for WindowedDeltas<I, K>
+where
+    I: Iterator<Item = T>,
+    T: Copy + Sub<Output = T>,
+{
+    type Item = (T, [T; K]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let value = self.iter.next()?;
+            if let (Some(prev), true) = (self.prev, K > 0) {
+                self.deltas.rotate_left(1);
+                self.deltas[K - 1] = value - prev;
+                self.filled = (self.filled + 1).min(K);
+            }
+            self.prev = Some(value);
+            if self.filled == K {
+                return Some((value, self.deltas));
+            }
+        }
+    }
+}
+
+/// Each value along with the `K` changes leading up to it, oldest first, so
+/// `[1, 4, 2, 7]` with `K = 2` gives `(2, [3, -2])` and `(7, [-2, 5])`. The first `K`
+/// values don't have enough history, so are skipped.
+pub fn windowed_deltas<const K: usize, I>(iter: I) -> WindowedDeltas<I::IntoIter, K>
+where
+    I: IntoIterator,
+    I::Item: Copy + Default + Sub<Output = I::Item>,
+{
+    WindowedDeltas {
+        iter: iter.into_iter(),
+        prev: None,
+        deltas: [I::Item::default(); K],
+        filled: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas_oldest_first() {
+        let found: Vec<_> = windowed_deltas::<2, _>([1, 4, 2, 7]).collect();
+        assert_eq!(found, [(2, [3, -2]), (7, [-2, 5])]);
+
+        let found: Vec<_> = windowed_deltas::<4, _>([3_i8, 0, 6, 5, 4, 4]).collect();
+        assert_eq!(found, [(4, [-3, 6, -1, -1]), (4, [6, -1, -1, 0])]);
+    }
+
+    #[test]
+    fn short_inputs_and_empty_windows() {
+        assert_eq!(windowed_deltas::<3, _>([1, 2, 3]).count(), 0);
+        assert_eq!(windowed_deltas::<1, _>(Vec::<i64>::new()).count(), 0);
+        let found: Vec<_> = windowed_deltas::<0, _>([5.0, 1.5]).collect();
+        assert_eq!(found, [(5.0, []), (1.5, [])]);
+    }
+}
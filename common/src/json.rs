@@ -0,0 +1,347 @@
+//! Just enough JSON reading to load back what the days write with
+//! `timing::json_string`. Numbers are kept as their text, and strings borrow from the
+//! input unless they contain escapes.
+
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+
+use crate::OptionAnyhow;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json<'a> {
+    Null,
+    Bool(bool),
+    /// The number as written, to be parsed as whatever type is wanted
+    Number(&'a str),
+    String(Cow<'a, str>),
+    Array(Vec<Json<'a>>),
+    /// Members in the order written
+    Object(Vec<(Cow<'a, str>, Json<'a>)>),
+}
+
+impl<'a> Json<'a> {
+    /// Member `key` of an object; `None` for anything else
+    pub fn get(&self, key: &str) -> Option<&Json<'a>> {
+        self.as_object()?
+            .iter()
+            .find_map(|(k, v)| (k == key).then_some(v))
+    }
+
+    pub fn as_object(&self) -> Option<&[(Cow<'a, str>, Json<'a>)]> {
+        match self {
+            Json::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json<'a>]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// The number, if it's a non-negative integer that fits
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a whole JSON document; errors give the byte offset they were found at
+pub fn parse(text: &str) -> Result<Json<'_>> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        return parser.fail("trailing characters");
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn fail<T>(&self, message: &str) -> Result<T> {
+        bail!("byte {}: {message}", self.pos)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, ch: u8) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() != Some(ch) {
+            return self.fail(&format!("expected `{}`", ch as char));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json<'a>> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => {
+                let rest = &self.text[self.pos..];
+                for (word, value) in [
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ] {
+                    if rest.starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value);
+                    }
+                }
+                self.fail("expected a value")
+            }
+            None => self.fail("unexpected end of input"),
+        }
+    }
+
+    /// Comma-separated items up to `close`, the opening bracket already taken
+    fn items(&mut self, close: u8, mut item: impl FnMut(&mut Self) -> Result<()>) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return self.fail(&format!("expected `,` or `{}`", close as char)),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json<'a>> {
+        self.expect(b'{')?;
+        let mut members = vec![];
+        self.items(b'}', |p| {
+            p.skip_whitespace();
+            if p.peek() != Some(b'"') {
+                return p.fail("expected a member name");
+            }
+            let key = p.string()?;
+            p.expect(b':')?;
+            members.push((key, p.value()?));
+            Ok(())
+        })?;
+        Ok(Json::Object(members))
+    }
+
+    fn array(&mut self) -> Result<Json<'a>> {
+        self.expect(b'[')?;
+        let mut items = vec![];
+        self.items(b']', |p| {
+            items.push(p.value()?);
+            Ok(())
+        })?;
+        Ok(Json::Array(items))
+    }
+
+    fn number(&mut self) -> Result<Json<'a>> {
+        let start = self.pos;
+        let digits = |p: &mut Self| {
+            let from = p.pos;
+            while matches!(p.peek(), Some(b'0'..=b'9')) {
+                p.pos += 1;
+            }
+            p.pos > from
+        };
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        let mut valid = digits(self);
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            valid &= digits(self);
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            valid &= digits(self);
+        }
+        if !valid {
+            return self.fail("malformed number");
+        }
+        Ok(Json::Number(&self.text[start..self.pos]))
+    }
+
+    /// A string, starting at its opening quote
+    fn string(&mut self) -> Result<Cow<'a, str>> {
+        self.pos += 1;
+        let start = self.pos;
+        let rest = &self.text[start..];
+        // anything but a plain closing quote is handled below
+        let end = rest
+            .find(|c: char| c == '"' || c == '\\' || (c as u32) < 0x20)
+            .with_context_anyhow(|| format!("byte {start}: unterminated string"))?;
+        if rest.as_bytes()[end] == b'"' {
+            self.pos += end + 1;
+            return Ok(Cow::Borrowed(&rest[..end]));
+        }
+
+        let mut decoded = rest[..end].to_string();
+        self.pos += end;
+        loop {
+            let ch = self.text[self.pos..]
+                .chars()
+                .next()
+                .with_context_anyhow(|| format!("byte {start}: unterminated string"))?;
+            self.pos += ch.len_utf8();
+            match ch {
+                '"' => return Ok(Cow::Owned(decoded)),
+                '\\' => {
+                    let escape = self.peek().map(|b| b as char);
+                    self.pos += 1;
+                    match escape {
+                        Some('"') => decoded.push('"'),
+                        Some('\\') => decoded.push('\\'),
+                        Some('/') => decoded.push('/'),
+                        Some('b') => decoded.push('\u{8}'),
+                        Some('f') => decoded.push('\u{c}'),
+                        Some('n') => decoded.push('\n'),
+                        Some('r') => decoded.push('\r'),
+                        Some('t') => decoded.push('\t'),
+                        Some('u') => decoded.push(self.unicode_escape()?),
+                        _ => return self.fail("unknown escape"),
+                    }
+                }
+                c if (c as u32) < 0x20 => return self.fail("control character in string"),
+                c => decoded.push(c),
+            }
+        }
+    }
+
+    /// The character for `\uXXXX`, after the `u`, taking a second escape for the low
+    /// half of a surrogate pair
+    fn unicode_escape(&mut self) -> Result<char> {
+        let hex = |p: &mut Self| -> Result<u32> {
+            let digits = p.text.get(p.pos..p.pos + 4);
+            match digits.and_then(|d| u32::from_str_radix(d, 16).ok()) {
+                Some(n) => {
+                    p.pos += 4;
+                    Ok(n)
+                }
+                None => p.fail("expected four hex digits"),
+            }
+        };
+        let high = hex(self)?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if !self.text[self.pos..].starts_with("\\u") {
+                    return self.fail("unpaired surrogate");
+                }
+                self.pos += 2;
+                let low = hex(self)?;
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return self.fail("unpaired surrogate");
+                }
+                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+            }
+            code => code,
+        };
+        match char::from_u32(code) {
+            Some(c) => Ok(c),
+            None => self.fail("unpaired surrogate"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::json_string;
+
+    #[test]
+    fn parse_values() -> Result<()> {
+        let doc = parse(r#" {"a": [1, -2.5e3, true, null], "b": {"c": "d"}, "e": []} "#)?;
+        let a = doc.get("a").ok_anyhow()?.as_array().ok_anyhow()?;
+        assert_eq!(a[0].as_u64(), Some(1));
+        assert_eq!(a[1], Json::Number("-2.5e3"));
+        assert_eq!(a[1].as_u64(), None);
+        assert_eq!(a[2].as_bool(), Some(true));
+        assert_eq!(a[3], Json::Null);
+        let c = doc.get("b").and_then(|b| b.get("c"));
+        assert_eq!(c.and_then(Json::as_str), Some("d"));
+        assert_eq!(doc.get("e"), Some(&Json::Array(vec![])));
+        assert_eq!(doc.get("f"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn strings_round_trip() -> Result<()> {
+        for s in ["plain", "a\"b\\c\nd\u{1}", "caf\u{e9} \u{1f600}", ""] {
+            let written = json_string(s);
+            assert_eq!(parse(&written)?.as_str(), Some(s));
+        }
+        // escapes are decoded, anything else borrowed
+        assert!(matches!(
+            parse(r#""x00""#)?,
+            Json::String(Cow::Borrowed("x00"))
+        ));
+        assert_eq!(parse(r#""\ud83d\ude00\/""#)?.as_str(), Some("\u{1f600}/"));
+        Ok(())
+    }
+
+    #[test]
+    fn malformed() {
+        for (text, message) in [
+            ("", "byte 0: unexpected end of input"),
+            ("[1 2]", "byte 3: expected `,` or `]`"),
+            ("{\"a\" 1}", "byte 5: expected `:`"),
+            ("{1: 2}", "byte 1: expected a member name"),
+            ("\"abc", "byte 1: unterminated string"),
+            ("\"\\q\"", "byte 3: unknown escape"),
+            ("\"\\ud800\"", "byte 7: unpaired surrogate"),
+            ("\"a\nb\"", "byte 3: control character in string"),
+            ("-", "byte 1: malformed number"),
+            ("1.", "byte 2: malformed number"),
+            ("nul", "byte 0: expected a value"),
+            ("{} x", "byte 3: trailing characters"),
+        ] {
+            let err = parse(text).unwrap_err();
+            assert_eq!(err.to_string(), message, "{text:?}");
+        }
+    }
+}
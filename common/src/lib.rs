@@ -1,6 +1,23 @@
-use std::{fs::File, io::Read};
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+};
 
+pub mod answers;
 pub mod cartesian;
+pub mod error;
+pub mod graph;
+pub mod grid;
+pub mod intervals;
+pub mod iterutil;
+pub mod json;
+pub mod math;
+pub mod maze;
+pub mod memo;
+pub mod registry;
+pub mod rng;
+pub mod timing;
 
 use anyhow::anyhow;
 
@@ -10,9 +27,40 @@ pub fn read_file(file_name: &str) -> anyhow::Result<String> {
     Ok(contents)
 }
 
+/// Whether `--example` is on the command line, to run a day on the example from the
+/// puzzle text rather than its input file
+pub fn example_requested() -> bool {
+    std::env::args().any(|a| a == "--example")
+}
+
+/// The puzzle input from `file_name`, or `example` with `--example`
+pub fn read_input(file_name: &str, example: &str) -> anyhow::Result<String> {
+    match example_requested() {
+        true => Ok(example.to_string()),
+        false => read_file(file_name),
+    }
+}
+
+/// As `read_input`, for days that stream their input
+pub fn open_input(file_name: &str, example: &'static str) -> anyhow::Result<Box<dyn BufRead>> {
+    match example_requested() {
+        true => Ok(Box::new(example.as_bytes())),
+        false => Ok(Box::new(BufReader::new(File::open(file_name)?))),
+    }
+}
+
+/// Value following `flag` on the command line, e.g. `--map other.txt`
+pub fn arg_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|a| a != flag);
+    args.next()?;
+    args.next()
+}
+
 pub trait OptionAnyhow<T> {
     fn ok_anyhow(self) -> anyhow::Result<T>;
     fn expect_anyhow(self, message: &str) -> anyhow::Result<T>;
+    /// As `expect_anyhow`, only building the message if it's needed
+    fn with_context_anyhow<C: Display>(self, f: impl FnOnce() -> C) -> anyhow::Result<T>;
 }
 
 impl<T> OptionAnyhow<T> for Option<T> {
@@ -23,6 +71,53 @@ impl<T> OptionAnyhow<T> for Option<T> {
     fn expect_anyhow(self, message: &str) -> anyhow::Result<T> {
         self.ok_or_else(|| anyhow!(message.to_string()))
     }
+
+    fn with_context_anyhow<C: Display>(self, f: impl FnOnce() -> C) -> anyhow::Result<T> {
+        self.ok_or_else(|| anyhow!("{}", f()))
+    }
+}
+
+/// Add where in the input an error happened as context, `line N` or
+/// `line N, column M` (both 1-based), keeping the original error as its source
+pub trait ResultAnyhow<T> {
+    fn at_line(self, line: usize) -> anyhow::Result<T>;
+    fn at(self, line: usize, column: usize) -> anyhow::Result<T>;
+}
+
+impl<T, E: Into<anyhow::Error>> ResultAnyhow<T> for Result<T, E> {
+    fn at_line(self, line: usize) -> anyhow::Result<T> {
+        self.map_err(|e| e.into().context(format!("line {line}")))
+    }
+
+    fn at(self, line: usize, column: usize) -> anyhow::Result<T> {
+        self.map_err(|e| e.into().context(format!("line {line}, column {column}")))
+    }
 }
 
 pub type AnyResult<T> = anyhow::Result<T>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positional_context() {
+        let missing: Option<u8> = None;
+        let err = missing
+            .with_context_anyhow(|| "expected a digit")
+            .at(3, 7)
+            .unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 3, column 7: expected a digit");
+        let chain: Vec<String> = err.chain().map(|e| e.to_string()).collect();
+        assert_eq!(chain, ["line 3, column 7", "expected a digit"]);
+
+        let err = "x".parse::<u8>().at_line(2).unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 2: invalid digit found in string");
+        assert!(err.root_cause().is::<std::num::ParseIntError>());
+
+        assert_eq!(
+            Some(1).with_context_anyhow(|| "unused").at(1, 1).unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use std::fmt::Display;
+
+use anyhow::{anyhow, Result};
+
+/// Integers with checked arithmetic, for the helpers below
+pub trait CheckedInt: Copy + Display {
+    const ZERO: Self;
+    const ONE: Self;
+    const NAME: &'static str;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_int {
+    ($($t:ty),*) => {
+        $(
+            impl CheckedInt for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const NAME: &'static str = stringify!($t);
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+            }
+        )*
+    };
+}
+impl_checked_int!(i32, i64, i128, u32, u64, u128, usize);
+
+/// A running total that fails with an error naming the operands, rather than
+/// overflowing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckedAccumulator<T> {
+    value: T,
+}
+impl<T: CheckedInt> CheckedAccumulator<T> {
+    pub fn new(initial: T) -> Self {
+        Self { value: initial }
+    }
+
+    /// Starting from zero, for sums
+    pub fn sum() -> Self {
+        Self::new(T::ZERO)
+    }
+
+    /// Starting from one, for products
+    pub fn product() -> Self {
+        Self::new(T::ONE)
+    }
+
+    pub fn add(&mut self, rhs: T) -> Result<()> {
+        self.value = self
+            .value
+            .checked_add(rhs)
+            .ok_or_else(|| anyhow!("{} + {rhs} overflows {}", self.value, T::NAME))?;
+        Ok(())
+    }
+
+    pub fn mul(&mut self, rhs: T) -> Result<()> {
+        self.value = self
+            .value
+            .checked_mul(rhs)
+            .ok_or_else(|| anyhow!("{} * {rhs} overflows {}", self.value, T::NAME))?;
+        Ok(())
+    }
+
+    /// Add `a * b`
+    pub fn add_product(&mut self, a: T, b: T) -> Result<()> {
+        let product = a
+            .checked_mul(b)
+            .ok_or_else(|| anyhow!("{a} * {b} overflows {}", T::NAME))?;
+        self.add(product)
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+}
+impl<T: CheckedInt> Default for CheckedAccumulator<T> {
+    fn default() -> Self {
+        Self::sum()
+    }
+}
+
+pub fn checked_sum<T: CheckedInt>(values: impl IntoIterator<Item = T>) -> Result<T> {
+    let mut sum = CheckedAccumulator::sum();
+    for v in values {
+        sum.add(v)?;
+    }
+    Ok(sum.value())
+}
+
+pub fn checked_product<T: CheckedInt>(values: impl IntoIterator<Item = T>) -> Result<T> {
+    let mut product = CheckedAccumulator::product();
+    for v in values {
+        product.mul(v)?;
+    }
+    Ok(product.value())
+}
+
+/// Compensated sum, using Neumaier's variant of Kahan summation so the error doesn't
+/// grow with the number of values
+pub fn kahan_sum(values: impl IntoIterator<Item = f64>) -> f64 {
+    let mut sum = 0.0_f64;
+    let mut compensation = 0.0;
+    for v in values {
+        let t = sum + v;
+        if sum.abs() >= v.abs() {
+            compensation += (sum - t) + v;
+        } else {
+            compensation += (v - t) + sum;
+        }
+        sum = t;
+    }
+    sum + compensation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_sums_and_products() -> Result<()> {
+        assert_eq!(checked_sum([1_u64, 2, 3])?, 6);
+        assert_eq!(checked_sum(Vec::<i64>::new())?, 0);
+        assert_eq!(checked_product([2_i32, 3, 7])?, 42);
+        assert_eq!(checked_product(Vec::<u128>::new())?, 1);
+
+        let err = checked_sum([u64::MAX - 1, 1, 1]).unwrap_err();
+        assert_eq!(err.to_string(), "18446744073709551615 + 1 overflows u64");
+        let err = checked_product([i64::MAX, 2]).unwrap_err();
+        assert_eq!(err.to_string(), "9223372036854775807 * 2 overflows i64");
+        Ok(())
+    }
+
+    #[test]
+    fn accumulator() -> Result<()> {
+        let mut acc = CheckedAccumulator::sum();
+        acc.add_product(3_i64, 4)?;
+        acc.add(5)?;
+        assert_eq!(acc.value(), 17);
+        assert!(acc.add_product(i64::MAX, 2).is_err());
+        assert_eq!(acc.value(), 17);
+        Ok(())
+    }
+
+    #[test]
+    fn kahan_sum_is_stable() {
+        let values = std::iter::once(1.0).chain(std::iter::repeat_n(1e-16, 10_000));
+        assert_eq!(values.clone().sum::<f64>(), 1.0);
+        assert!((kahan_sum(values) - (1.0 + 1e-12)).abs() < 1e-24);
+        assert_eq!(kahan_sum([1e100, 1.0, -1e100]), 1.0);
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{bail, Result};
+use nalgebra::DMatrix;
+use rustc_hash::FxHashMap;
+use strum::IntoEnumIterator;
+
+use crate::{
+    cartesian::{Point, ScreenDir},
+    grid::grid_from_str,
+    OptionAnyhow,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Block {
+    #[default]
+    Open,
+    Wall,
+    Start,
+    End,
+}
+
+pub type MazeMap = DMatrix<Block>;
+
+/// A grid maze made up of `#`, `.`, `S` and `E` blocks, with the start and end located
+#[derive(Debug, Clone)]
+pub struct Maze {
+    pub map: MazeMap,
+    pub start: Point,
+    pub end: Point,
+}
+
+pub fn parse_maze(input: &str) -> Result<Maze> {
+    let map = grid_from_str(input, |ch| match ch {
+        '.' => Ok(Block::Open),
+        '#' => Ok(Block::Wall),
+        'S' => Ok(Block::Start),
+        'E' => Ok(Block::End),
+        _ => bail!("Unexpected block type {ch}"),
+    })?;
+
+    let mut start = None;
+    let mut end = None;
+    for r in 0..map.nrows() {
+        for c in 0..map.ncols() {
+            let p = Point::from((r, c));
+            if map.get(p).copied() == Some(Block::Start) {
+                start = Some(p);
+            }
+            if map.get(p).copied() == Some(Block::End) {
+                end = Some(p);
+            }
+        }
+    }
+
+    let start = start.expect_anyhow("maze has no start")?;
+    let end = end.expect_anyhow("maze has no end")?;
+    Ok(Maze { map, start, end })
+}
+
+/// A corridor between two nodes of a contracted maze, in one direction
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// direction of the first step out of `from`
+    pub start_dir: ScreenDir,
+    /// direction of the last step into `to`
+    pub end_dir: ScreenDir,
+    pub steps: i64,
+    /// turns made along the corridor, not counting any turn at either end
+    pub turns: i64,
+    /// corridor cells between the two nodes
+    pub cells: Vec<Point>,
+}
+
+/// Maze with the corridors contracted into weighted edges. Nodes are junctions,
+/// dead ends, and the start and end; every other open cell has exactly two open
+/// neighbours and lies on an edge.
+#[derive(Debug, Clone)]
+pub struct WeightedGraph {
+    pub nodes: Vec<Point>,
+    pub edges: Vec<Edge>,
+    node_index: FxHashMap<Point, usize>,
+    adjacency: Vec<Vec<usize>>,
+}
+impl WeightedGraph {
+    pub fn node(&self, p: Point) -> Option<usize> {
+        self.node_index.get(&p).copied()
+    }
+
+    /// Edges leaving `node`, with their indices into `edges`
+    pub fn edges_from(&self, node: usize) -> impl Iterator<Item = (usize, &Edge)> {
+        self.adjacency[node].iter().map(|&e| (e, &self.edges[e]))
+    }
+}
+
+impl Maze {
+    pub fn is_open(&self, p: Point) -> bool {
+        matches!(
+            self.map.get(p),
+            Some(Block::Open) | Some(Block::Start) | Some(Block::End)
+        )
+    }
+
+    fn open_dirs(&self, p: Point) -> impl Iterator<Item = ScreenDir> + '_ {
+        ScreenDir::iter().filter(move |d| self.is_open(p + (*d).into()))
+    }
+
+    fn is_node(&self, p: Point) -> bool {
+        p == self.start || p == self.end || self.open_dirs(p).count() != 2
+    }
+
+    /// Contract corridors into weighted edges between nodes
+    pub fn contract(&self) -> WeightedGraph {
+        let mut nodes = vec![];
+        let mut node_index = FxHashMap::default();
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                let p = Point::from((r, c));
+                if self.is_open(p) && self.is_node(p) {
+                    node_index.insert(p, nodes.len());
+                    nodes.push(p);
+                }
+            }
+        }
+
+        let mut edges = vec![];
+        let mut adjacency = vec![vec![]; nodes.len()];
+        for (from, &node) in nodes.iter().enumerate() {
+            for start_dir in self.open_dirs(node) {
+                let mut p = node + start_dir.into();
+                let mut dir = start_dir;
+                let mut steps = 1;
+                let mut turns = 0;
+                let mut cells = vec![];
+                while !self.is_node(p) {
+                    // corridor cell, so exactly one way on that isn't back
+                    let next_dir = self
+                        .open_dirs(p)
+                        .find(|d| *d != dir.opposite())
+                        .expect("corridor continues");
+                    if next_dir != dir {
+                        turns += 1;
+                    }
+                    cells.push(p);
+                    p = p + next_dir.into();
+                    dir = next_dir;
+                    steps += 1;
+                }
+                adjacency[from].push(edges.len());
+                edges.push(Edge {
+                    from,
+                    to: node_index[&p],
+                    start_dir,
+                    end_dir: dir,
+                    steps,
+                    turns,
+                    cells,
+                });
+            }
+        }
+
+        WeightedGraph {
+            nodes,
+            edges,
+            node_index,
+            adjacency,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn parse_maze_finds_start_and_end() -> Result<()> {
+        let maze = parse_maze(indoc! {"
+            #####
+            #S.E#
+            #####
+        "})?;
+        assert_eq!(maze.start, Point::new(1, 1));
+        assert_eq!(maze.end, Point::new(3, 1));
+        assert_eq!(maze.map[(1, 2)], Block::Open);
+        assert_eq!(maze.map[(0, 0)], Block::Wall);
+        Ok(())
+    }
+
+    #[test]
+    fn contract_corridors() -> Result<()> {
+        // a loop from S, with a dead-end spur to E
+        let maze = parse_maze(indoc! {"
+            #######
+            #S....#
+            #.###.#
+            #.....#
+            #.#####
+            #E#####
+        "})?;
+        let graph = maze.contract();
+
+        // S, E, and the T-junction below S
+        assert_eq!(graph.nodes.len(), 3);
+        let start = graph.node(maze.start).unwrap();
+        let junction = graph.node(Point::new(1, 3)).unwrap();
+        let end = graph.node(maze.end).unwrap();
+
+        // each corridor in both directions
+        assert_eq!(graph.edges.len(), 6);
+
+        let mut from_start: Vec<_> = graph.edges_from(start).map(|(_, e)| e).collect();
+        from_start.sort_by_key(|e| e.steps);
+        assert_eq!(from_start[0].to, junction);
+        assert_eq!((from_start[0].steps, from_start[0].turns), (2, 0));
+        assert_eq!(from_start[0].cells, [Point::new(1, 2)]);
+        assert_eq!(from_start[1].to, junction);
+        assert_eq!(from_start[1].start_dir, ScreenDir::R);
+        assert_eq!(from_start[1].end_dir, ScreenDir::L);
+        assert_eq!((from_start[1].steps, from_start[1].turns), (10, 2));
+        assert_eq!(from_start[1].cells.len(), 9);
+
+        let (_, to_end) = graph
+            .edges_from(junction)
+            .find(|(_, e)| e.to == end)
+            .unwrap();
+        assert_eq!(
+            (to_end.steps, to_end.start_dir, to_end.end_dir),
+            (2, ScreenDir::D, ScreenDir::D)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_maze_rejects_invalid() {
+        assert!(parse_maze("#S.E#x").is_err());
+        assert!(parse_maze("#..E#").is_err());
+        assert!(parse_maze("#S..#").is_err());
+    }
+}
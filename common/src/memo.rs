@@ -0,0 +1,105 @@
+use std::{borrow::Borrow, hash::Hash};
+
+use rustc_hash::FxHashMap;
+
+/// Cache for memoized recursive functions. The cache is passed explicitly to the
+/// recursive function, which checks it before doing any work:
+///
+/// ```
+/// use common::memo::Memoized;
+///
+/// fn fib(n: u64, memo: &mut Memoized<u64, u64>) -> u64 {
+///     if n < 2 {
+///         return n;
+///     }
+///     memo.get_or_compute(n, |memo| fib(n - 1, memo) + fib(n - 2, memo))
+/// }
+///
+/// assert_eq!(fib(90, &mut Memoized::new()), 2880067194370816120);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Memoized<K, V> {
+    cache: FxHashMap<K, V>,
+}
+
+impl<K, V> Default for Memoized<K, V> {
+    fn default() -> Self {
+        Self {
+            cache: FxHashMap::default(),
+        }
+    }
+}
+
+impl<K, V> Memoized<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Previously computed value, if any; the key may be a borrowed form, e.g. `&[u8]` for `Vec<u8>`
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.cache.get(key).cloned()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.cache.insert(key, value);
+    }
+
+    /// Return the cached value for `key`, or compute and cache it. The cache is passed
+    /// to `compute` so it can be used for recursive calls.
+    pub fn get_or_compute(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.get(&key) {
+            return value;
+        }
+        let value = compute(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_each_key_once() {
+        let mut calls = 0;
+        let mut memo = Memoized::new();
+        for _ in 0..3 {
+            let v = memo.get_or_compute(5, |_| {
+                calls += 1;
+                25
+            });
+            assert_eq!(v, 25);
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(memo.len(), 1);
+    }
+
+    #[test]
+    fn borrowed_lookup() {
+        let mut memo: Memoized<Vec<u8>, usize> = Memoized::new();
+        memo.insert(vec![1, 2, 3], 6);
+        assert_eq!(memo.get([1_u8, 2, 3].as_slice()), Some(6));
+        assert_eq!(memo.get([1_u8].as_slice()), None);
+    }
+}
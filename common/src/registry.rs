@@ -0,0 +1,92 @@
+//! Puzzle metadata for each day. Every day declares its own `DAY` with `aoc_day!`,
+//! so runners only list the constants and the titles live next to the solutions.
+
+use std::fmt::Display;
+
+use anyhow::Result;
+
+/// Answers for an example, with no part 2 for some days
+pub type ExampleSolver = fn(&str) -> Result<(String, Option<String>)>;
+
+/// A day's puzzle: its number, title, the day's `solve`, and the example from the
+/// puzzle text with its answers
+#[derive(Debug, Clone, Copy)]
+pub struct AocDay {
+    pub day: u8,
+    pub title: &'static str,
+    /// Both answers for a puzzle input
+    pub solve: fn(&str) -> Result<(String, String)>,
+    pub example: &'static str,
+    /// The puzzle text's answers for the example, where it gives one
+    pub example_answers: [Option<&'static str>; 2],
+    /// Answers for the example; usually from `solve`, unless the example needs a
+    /// smaller grid or has no part 2, which is then `None`
+    pub solve_example: ExampleSolver,
+}
+impl AocDay {
+    pub fn url(&self) -> String {
+        format!("https://adventofcode.com/2024/day/{}", self.day)
+    }
+}
+impl Display for AocDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Day {}: {}", self.day, self.title)
+    }
+}
+
+/// The entry for `day` in `days`
+pub fn find(days: &[AocDay], day: u8) -> Option<&AocDay> {
+    days.iter().find(|d| d.day == day)
+}
+
+/// Declare a day's `DAY` constant, with the `solve`, `EXAMPLE` and `EXAMPLE_ANSWERS`
+/// in scope: `common::aoc_day!(7, "Bridge Repair");`, or with a separate solver for
+/// the example, `common::aoc_day!(18, "RAM Run", example: solve_example);`
+#[macro_export]
+macro_rules! aoc_day {
+    ($day:literal, $title:literal) => {
+        $crate::aoc_day!($day, $title, example: |input| {
+            solve(input).map(|(part1, part2)| (part1, Some(part2)))
+        });
+    };
+    ($day:literal, $title:literal, example: $solve_example:expr) => {
+        /// This day's number and puzzle title, its `solve`, and the example
+        pub const DAY: $crate::registry::AocDay = $crate::registry::AocDay {
+            day: $day,
+            title: $title,
+            solve,
+            example: EXAMPLE,
+            example_answers: EXAMPLE_ANSWERS,
+            solve_example: $solve_example,
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve(input: &str) -> Result<(String, String)> {
+        Ok((input.len().to_string(), input.to_uppercase()))
+    }
+
+    const EXAMPLE: &str = "mul";
+    const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("3"), None];
+
+    aoc_day!(3, "Mull It Over");
+
+    #[test]
+    fn declared_day() -> Result<()> {
+        assert_eq!(DAY.to_string(), "Day 3: Mull It Over");
+        assert_eq!(DAY.url(), "https://adventofcode.com/2024/day/3");
+        assert_eq!((DAY.solve)("ab")?, ("2".to_string(), "AB".to_string()));
+        let example = (DAY.solve_example)(DAY.example)?;
+        assert_eq!(example, ("3".to_string(), Some("MUL".to_string())));
+        assert_eq!(DAY.example_answers, [Some("3"), None]);
+
+        let days = [DAY];
+        assert_eq!(find(&days, 3).map(|d| d.title), Some("Mull It Over"));
+        assert!(find(&days, 4).is_none());
+        Ok(())
+    }
+}
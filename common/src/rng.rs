@@ -0,0 +1,123 @@
+//! Seeded random numbers for input generators and generated tests. The generator is
+//! splitmix64, implemented here so sequences are the same on every platform and
+//! don't change with a dependency's version.
+
+use nalgebra::DMatrix;
+
+use crate::cartesian::Point;
+
+/// Splitmix64 generator; cheap to create, and any seed (including zero) is fine
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+pub fn seeded(seed: u64) -> Rng {
+    Rng { state: seed }
+}
+
+impl Rng {
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..bound`, which must not be zero. Plain modulo, so very slightly
+    /// biased for bounds that don't divide 2^64; fine for generating inputs.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "empty range");
+        self.next_u64() % bound
+    }
+
+    /// Index into something `len` long
+    pub fn index(&mut self, len: usize) -> usize {
+        self.below(len as u64) as usize
+    }
+
+    /// Value in `lo..=hi`
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi, "empty range {lo}..={hi}");
+        // the span only wraps to zero for the whole of i64
+        let span = hi.abs_diff(lo).wrapping_add(1);
+        let offset = match span {
+            0 => self.next_u64(),
+            _ => self.below(span),
+        };
+        lo.wrapping_add(offset as i64)
+    }
+
+    /// True about one time in `n`; never if `n` is zero
+    pub fn one_in(&mut self, n: u64) -> bool {
+        n > 0 && self.below(n) == 0
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    /// Fisher-Yates shuffle
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+
+    /// Point with `min.x <= x < max.x`, and likewise for y
+    pub fn point_in(&mut self, min: Point, max: Point) -> Point {
+        Point::new(self.range(min.x, max.x - 1), self.range(min.y, max.y - 1))
+    }
+
+    /// `rows` x `cols` grid with each cell picked from `alphabet`
+    pub fn grid(&mut self, rows: usize, cols: usize, alphabet: &[char]) -> DMatrix<char> {
+        DMatrix::from_fn(rows, cols, |_, _| *self.choose(alphabet))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproducible() {
+        // reference splitmix64 output for seed 0
+        let mut rng = seeded(0);
+        assert_eq!(rng.next_u64(), 0xe220a8397b1dcdaf);
+        assert_eq!(rng.next_u64(), 0x6e789e6aa1b965f4);
+
+        let draws = |seed| {
+            let mut rng = seeded(seed);
+            (0..20).map(|_| rng.range(-3, 3)).collect::<Vec<_>>()
+        };
+        let (a, b) = (draws(7), draws(7));
+        assert_eq!(a, b);
+        assert!(a.iter().all(|v| (-3..=3).contains(v)));
+    }
+
+    #[test]
+    fn ranges_and_helpers() {
+        let mut rng = seeded(1);
+        for _ in 0..1000 {
+            let p = rng.point_in(Point::new(-2, 5), Point::new(3, 6));
+            assert!((-2..3).contains(&p.x) && p.y == 5);
+        }
+        assert_eq!(rng.range(4, 4), 4);
+        let full = rng.range(i64::MIN, i64::MAX);
+        assert!((i64::MIN..=i64::MAX).contains(&full));
+        assert!(!rng.one_in(0));
+        assert!(rng.one_in(1));
+
+        let mut v: Vec<_> = (0..50).collect();
+        rng.shuffle(&mut v);
+        assert_ne!(v, (0..50).collect::<Vec<_>>());
+        v.sort();
+        assert_eq!(v, (0..50).collect::<Vec<_>>());
+
+        let grid = rng.grid(4, 9, &['X', 'M']);
+        assert_eq!(grid.shape(), (4, 9));
+        assert!(grid.iter().all(|c| *c == 'X' || *c == 'M'));
+        assert!(grid.iter().any(|c| *c == 'X') && grid.iter().any(|c| *c == 'M'));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{AocContext, AocResult},
+    registry::AocDay,
+};
+
+/// A value along with how long it took to compute
+#[derive(Debug, Clone)]
+pub struct Timed<T> {
+    pub value: T,
+    pub elapsed: Duration,
+}
+impl<T> Timed<T> {
+    pub fn run(f: impl FnOnce() -> T) -> Self {
+        let t = Instant::now();
+        let value = f();
+        Timed {
+            value,
+            elapsed: t.elapsed(),
+        }
+    }
+}
+
+/// Times the parse and part stages of a day and reports each as it completes,
+/// either as the usual human-readable lines or, with `--json` on the command line,
+/// as one `{"day", "part", "answer", "micros"}` JSON object per line, with the
+/// puzzle's `"title"` after the day when known. Answers with an expected value also
+/// report it, and whether they match. A stage that fails gives an `AocError` naming
+/// the day, and the part for parts.
+#[derive(Debug, Clone)]
+pub struct TimingReport {
+    day: u8,
+    title: Option<&'static str>,
+    json: bool,
+    example: bool,
+    expected: [Option<String>; 2],
+}
+impl TimingReport {
+    pub fn new(day: u8, json: bool) -> Self {
+        TimingReport {
+            day,
+            title: None,
+            json,
+            example: false,
+            expected: [None, None],
+        }
+    }
+
+    pub fn from_args(day: u8) -> Self {
+        TimingReport {
+            example: crate::example_requested(),
+            ..Self::new(day, std::env::args().any(|a| a == "--json"))
+        }
+    }
+
+    /// As `from_args`, also carrying the puzzle's title
+    pub fn for_day(day: &AocDay) -> Self {
+        TimingReport {
+            title: Some(day.title),
+            ..Self::from_args(day.day)
+        }
+    }
+
+    /// The puzzle text's answers for parts 1 and 2 of the example, checked when
+    /// running it with `--example`
+    pub fn with_example_answers(self, answers: [Option<&str>; 2]) -> Self {
+        match self.example {
+            true => self.expecting(answers),
+            false => self,
+        }
+    }
+
+    /// Expected answers for parts "1" and "2"
+    pub fn expecting(mut self, answers: [Option<&str>; 2]) -> Self {
+        self.expected = answers.map(|a| a.map(str::to_string));
+        self
+    }
+
+    fn expected(&self, part: &str) -> Option<&str> {
+        match part {
+            "1" => self.expected[0].as_deref(),
+            "2" => self.expected[1].as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn is_json(&self) -> bool {
+        self.json
+    }
+
+    /// Time parsing the input; reported without an answer
+    pub fn parse<T>(&self, f: impl FnOnce() -> anyhow::Result<T>) -> AocResult<T> {
+        let timed = Timed::run(f);
+        let value = timed.value.day_context(self.day)?;
+        if self.json {
+            println!("{}", self.json_line("parse", None, timed.elapsed));
+        }
+        Ok(value)
+    }
+
+    /// Time a part; `part` is a label such as "1", "2" or "2 (brute)"
+    pub fn part<T: Display>(
+        &self,
+        part: &str,
+        f: impl FnOnce() -> anyhow::Result<T>,
+    ) -> AocResult<T> {
+        let timed = Timed::run(f);
+        let value = timed.value.part_context(self.day, part)?;
+        self.report(part, &value, timed.elapsed);
+        Ok(value)
+    }
+
+    /// Time a whole solve, reporting both answers with the shared elapsed time
+    pub fn solve(
+        &self,
+        f: impl FnOnce() -> anyhow::Result<(String, String)>,
+    ) -> AocResult<(String, String)> {
+        let timed = Timed::run(f);
+        let (part1, part2) = timed.value.day_context(self.day)?;
+        self.report("1", &part1, timed.elapsed);
+        self.report("2", &part2, timed.elapsed);
+        Ok((part1, part2))
+    }
+
+    /// Report an answer that was timed elsewhere
+    pub fn report(&self, part: &str, answer: &impl Display, elapsed: Duration) {
+        let answer = answer.to_string();
+        let expected = self.expected(part);
+        if self.json {
+            println!("{}", self.json_line(part, Some(&answer), elapsed));
+        } else {
+            let check = match expected {
+                Some(e) if e == answer => format!(", expected {e}: ok"),
+                Some(e) => format!(", expected {e}: MISMATCH"),
+                None => String::new(),
+            };
+            println!("Part {part} result is {answer} (took {elapsed:?}){check}");
+        }
+    }
+
+    fn json_line(&self, part: &str, answer: Option<&str>, elapsed: Duration) -> String {
+        let expected = match (self.expected(part), answer) {
+            (Some(e), Some(a)) => format!(r#","expected":{},"matches":{}"#, json_string(e), e == a),
+            _ => String::new(),
+        };
+        let answer = match answer {
+            Some(a) => json_string(a),
+            None => "null".to_string(),
+        };
+        let title = match self.title {
+            Some(t) => format!(r#","title":{}"#, json_string(t)),
+            None => String::new(),
+        };
+        format!(
+            r#"{{"day":{}{},"part":{},"answer":{}{},"micros":{}}}"#,
+            self.day,
+            title,
+            json_string(part),
+            answer,
+            expected,
+            elapsed.as_micros()
+        )
+    }
+}
+
+/// `s` as a quoted JSON string
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_line_format() {
+        let report = TimingReport::new(7, true);
+        let line = report.json_line("2 (brute)", Some("a\"b"), Duration::from_micros(1500));
+        assert_eq!(
+            line,
+            r#"{"day":7,"part":"2 (brute)","answer":"a\"b","micros":1500}"#
+        );
+        let line = report.json_line("parse", None, Duration::from_micros(3));
+        assert_eq!(line, r#"{"day":7,"part":"parse","answer":null,"micros":3}"#);
+
+        let report = report.expecting([Some("12"), None]);
+        let line = report.json_line("1", Some("12"), Duration::from_micros(4));
+        assert_eq!(
+            line,
+            r#"{"day":7,"part":"1","answer":"12","expected":"12","matches":true,"micros":4}"#
+        );
+        let line = report.json_line("1", Some("13"), Duration::from_micros(4));
+        assert_eq!(
+            line,
+            r#"{"day":7,"part":"1","answer":"13","expected":"12","matches":false,"micros":4}"#
+        );
+        let line = report.json_line("2", Some("5"), Duration::from_micros(4));
+        assert_eq!(line, r#"{"day":7,"part":"2","answer":"5","micros":4}"#);
+
+        let report = TimingReport {
+            title: Some("Bridge Repair"),
+            ..report
+        };
+        let line = report.json_line("parse", None, Duration::from_micros(3));
+        assert_eq!(
+            line,
+            r#"{"day":7,"title":"Bridge Repair","part":"parse","answer":null,"micros":3}"#
+        );
+    }
+
+    #[test]
+    fn failures_name_the_part() {
+        let report = TimingReport::new(9, false);
+        let err = report
+            .part("2 (smart)", || -> anyhow::Result<u8> {
+                anyhow::bail!("no gap")
+            })
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", anyhow::Error::from(err)),
+            "day 9, part 2 (smart): no gap"
+        );
+        let err = report
+            .parse(|| "x".parse::<u8>().map_err(Into::into))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "day 9");
+    }
+
+    #[test]
+    fn json_string_escapes() {
+        assert_eq!(json_string("a\\b\nc\u{1}"), r#""a\\b\nc\u0001""#);
+    }
+}
@@ -0,0 +1,57 @@
+use anyhow::Result;
+
+#[derive(Debug, Clone)]
+pub struct Problem {}
+
+pub fn parse_input(_input: &str) -> Result<Problem> {
+    Ok(Problem {})
+}
+
+pub fn part1(_problem: &Problem) -> Result<usize> {
+    Ok(1)
+}
+
+pub fn part2(_problem: &Problem) -> Result<usize> {
+    Ok(2)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(0, "Template");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [None, None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 2);
+        Ok(())
+    }
+}
@@ -1,65 +1,14 @@
-use std::time::Instant;
+use common::timing::TimingReport;
 
-use anyhow::Result;
-
-#[derive(Debug, Clone)]
-pub struct Problem {}
-
-fn parse_input(input: &str) -> Result<Problem> {
-    Ok(Problem {})
-}
-
-fn part1(problem: &Problem) -> Result<usize> {
-    Ok(1)
-}
-
-fn part2(problem: &Problem) -> Result<usize> {
-    Ok(2)
-}
+use day_template::{parse_input, part1, part2, DAY, EXAMPLE, EXAMPLE_ANSWERS};
 
 fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
-    let problem = parse_input(&text)?;
+    let text = common::read_input("input1.txt", EXAMPLE)?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
 
-    let t1 = Instant::now();
-    let count_part1 = part1(&problem)?;
-    println!("Part 1 result is {count_part1} (took {:?})", t1.elapsed());
-
-    let t2 = Instant::now();
-    let count_part2 = part2(&problem)?;
-    println!("Part 2 result is {count_part2} (took {:?})", t2.elapsed());
+    let problem = timing.parse(|| parse_input(&text))?;
+    timing.part("1", || part1(&problem))?;
+    timing.part("2", || part2(&problem))?;
 
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use indoc::indoc;
-
-    const EXAMPLE: &str = indoc! {"
-    "};
-
-    #[test]
-    fn test_parse_input() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        println!("{:?}", problem);
-        Ok(())
-    }
-
-    #[test]
-    fn part1_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part1(&problem)?;
-        assert_eq!(count, 1);
-        Ok(())
-    }
-
-    #[test]
-    fn part2_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part2(&problem)?;
-        assert_eq!(count, 2);
-        Ok(())
-    }
-}
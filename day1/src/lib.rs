@@ -0,0 +1,181 @@
+use std::{collections::HashMap, iter};
+
+use anyhow::{bail, Context};
+use common::{error::AocContext, OptionAnyhow};
+
+/// Location lists read as whitespace-separated columns, all the same length
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationLists {
+    columns: Vec<Vec<i64>>,
+}
+impl LocationLists {
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn column(&self, index: usize) -> anyhow::Result<&[i64]> {
+        self.columns
+            .get(index)
+            .map(|c| c.as_slice())
+            .expect_anyhow("column index out of range")
+    }
+
+    /// Distance from the `reference` column to each of the others, scaled by that
+    /// column's weight; the reference column's own weight is ignored
+    pub fn weighted_distance(&self, reference: usize, weights: &[i64]) -> anyhow::Result<i64> {
+        if weights.len() != self.column_count() {
+            bail!(
+                "{} weights given for {} columns",
+                weights.len(),
+                self.column_count()
+            );
+        }
+        let reference_column = self.column(reference)?;
+        let mut total = 0;
+        for (i, (column, weight)) in iter::zip(&self.columns, weights).enumerate() {
+            if i != reference {
+                total += weight * total_distance(reference_column, column)?;
+            }
+        }
+        Ok(total)
+    }
+}
+
+pub fn parse_input(text: &str) -> anyhow::Result<LocationLists> {
+    let mut columns: Vec<Vec<i64>> = vec![];
+
+    for (line_no, l) in text.lines().enumerate() {
+        let fields = l
+            .split_whitespace()
+            .map(|f| f.parse())
+            .collect::<Result<Vec<i64>, _>>()
+            .with_context(|| format!("line {}", line_no + 1))?;
+        if line_no == 0 {
+            columns = vec![vec![]; fields.len()];
+        }
+        if fields.len() != columns.len() {
+            bail!(
+                "line {} has {} columns, expected {}",
+                line_no + 1,
+                fields.len(),
+                columns.len()
+            );
+        }
+        for (column, value) in iter::zip(&mut columns, fields) {
+            column.push(value);
+        }
+    }
+
+    Ok(LocationLists { columns })
+}
+
+/// Sum of differences between the two lists after sorting both (part 1)
+pub fn total_distance(a: &[i64], b: &[i64]) -> anyhow::Result<i64> {
+    if a.len() != b.len() {
+        bail!("lists have different lengths: {} and {}", a.len(), b.len());
+    }
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
+
+    let mut total_difference = 0;
+    for (l, r) in iter::zip(&a, &b) {
+        total_difference += (r - l).abs();
+    }
+    Ok(total_difference)
+}
+
+/// Each value in `a` multiplied by the number of times it appears in `b` (part 2)
+pub fn similarity(a: &[i64], b: &[i64]) -> i64 {
+    // get counts/freq of numbers in b first...
+    let mut b_counts = HashMap::new();
+    for r in b {
+        let entry = b_counts.entry(*r).or_insert(0);
+        *entry += 1;
+    }
+
+    let mut similarity_score = 0;
+    for l in a {
+        let b_count = b_counts.get(l).copied().unwrap_or_default();
+        similarity_score += l * b_count;
+    }
+    similarity_score
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    let lists = parse_input(input).day_context(DAY.day)?;
+    let (a, b) = (lists.column(0)?, lists.column(1)?);
+    Ok((
+        total_distance(a, b).part_context(DAY.day, "1")?.to_string(),
+        similarity(a, b).to_string(),
+    ))
+}
+
+common::aoc_day!(1, "Historian Hysteria");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    3   4
+    4   3
+    2   5
+    1   3
+    3   9
+    3   3
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("11"), Some("31")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_correct() {
+        let lists = parse_input(EXAMPLE).unwrap();
+        let distance = total_distance(lists.column(0).unwrap(), lists.column(1).unwrap());
+        assert_eq!(distance.unwrap(), 11);
+    }
+
+    #[test]
+    fn part2_correct() {
+        let lists = parse_input(EXAMPLE).unwrap();
+        let score = similarity(lists.column(0).unwrap(), lists.column(1).unwrap());
+        assert_eq!(score, 31);
+    }
+
+    #[test]
+    fn three_columns() {
+        let lists = parse_input("1 2 1\n3 4 3\n").unwrap();
+        assert_eq!(lists.column_count(), 3);
+        assert_eq!(lists.column(2).unwrap(), [1, 3]);
+        assert!(lists.column(3).is_err());
+
+        assert_eq!(
+            total_distance(lists.column(0).unwrap(), lists.column(1).unwrap()).unwrap(),
+            2
+        );
+        assert_eq!(
+            total_distance(lists.column(0).unwrap(), lists.column(2).unwrap()).unwrap(),
+            0
+        );
+        assert_eq!(
+            similarity(lists.column(2).unwrap(), lists.column(0).unwrap()),
+            4
+        );
+
+        assert_eq!(
+            lists.weighted_distance(1, &[3, 0, 5]).unwrap(),
+            3 * 2 + 5 * 2
+        );
+        assert!(lists.weighted_distance(1, &[1, 1]).is_err());
+    }
+
+    #[test]
+    fn ragged_rows_rejected() {
+        let err = parse_input("1 2\n3\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2 has 1 columns, expected 2");
+        assert!(parse_input("1 2\n3 x\n").is_err());
+        assert!(total_distance(&[1, 2], &[1]).is_err());
+    }
+}
@@ -1,54 +1,14 @@
-use std::{collections::HashMap, iter};
+use common::{error::AocContext, timing::TimingReport};
 
-use common::OptionAnyhow;
+use day1::{parse_input, similarity, total_distance, DAY, EXAMPLE, EXAMPLE_ANSWERS};
 
 fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
+    let text = common::read_input("input1.txt", EXAMPLE).input_context(DAY.day, "input1.txt")?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
 
-    let mut left_list = vec![];
-    let mut right_list = vec![];
-
-    for l in text.lines() {
-        let mut fields = l.split_whitespace();
-        let left: i32 = fields
-            .next()
-            .expect_anyhow("left number missing")?
-            .parse()?;
-        let right: i32 = fields
-            .next()
-            .expect_anyhow("right number missing")?
-            .parse()?;
-        left_list.push(left);
-        right_list.push(right);
-    }
-
-    // part 1 - could just calculate the difference without the sort
-
-    left_list.sort();
-    right_list.sort();
-
-    let mut total_difference = 0;
-    for (l, r) in iter::zip(&left_list, &right_list) {
-        total_difference += (r - l).abs();
-    }
-
-    println!("part 1: total difference {}", total_difference);
-
-    // part 2 - get counts/freq of numbers on right side first...
-
-    let mut right_counts = HashMap::new();
-    for r in right_list {
-        let entry = right_counts.entry(r).or_insert(0);
-        *entry += 1;
-    }
-
-    let mut similarity_score = 0;
-    for l in left_list {
-        let right_count = right_counts.get(&l).copied().unwrap_or_default();
-        similarity_score += l * right_count;
-    }
-
-    println!("part 2: similarity_score {}", similarity_score);
+    let lists = timing.parse(|| parse_input(&text))?;
+    timing.part("1", || total_distance(lists.column(0)?, lists.column(1)?))?;
+    timing.part("2", || Ok(similarity(lists.column(0)?, lists.column(1)?)))?;
 
     Ok(())
 }
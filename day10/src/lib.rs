@@ -0,0 +1,356 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Result};
+use common::{
+    cartesian::{Point, ScreenDir},
+    grid::{self, grid_from_str},
+};
+use nalgebra::DMatrix;
+
+/// Heights, with `None` for impassable cells (`.` in the input)
+type Map = DMatrix<Option<i32>>;
+
+/// What counts as a hiking trail: the heights it starts and ends at, and which steps
+/// from one height to the next are allowed
+#[derive(Debug, Clone, Copy)]
+pub struct TrailRule<F> {
+    pub head: i32,
+    pub summit: i32,
+    pub step: F,
+}
+pub type StepFn = fn(i32, i32) -> bool;
+impl TrailRule<StepFn> {
+    /// The puzzle's rule: from 0 up to 9, climbing exactly one each step
+    pub fn standard() -> Self {
+        TrailRule {
+            head: 0,
+            summit: 9,
+            step: |from_h, to_h| to_h - from_h == 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    map: Map,
+    trail_heads: Vec<Point>,
+}
+
+impl Problem {
+    pub fn trail_heads(&self) -> &[Point] {
+        &self.trail_heads
+    }
+
+    /// Every distinct hiking trail from `head`, each running from the head to a summit
+    pub fn trails_from(&self, head: Point) -> Vec<Vec<Point>> {
+        let rule = TrailRule::standard();
+        fold_trails(
+            &self.map,
+            &rule,
+            &mut vec![head],
+            vec![],
+            |mut acc, trail| {
+                acc.push(trail.to_vec());
+                acc
+            },
+        )
+    }
+
+    /// Every cell at height `height`, such as the heads of trails under some rule
+    pub fn cells_at(&self, height: i32) -> Vec<Point> {
+        let mut cells = vec![];
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                if self.map[(r, c)] == Some(height) {
+                    cells.push(Point::from((r, c)));
+                }
+            }
+        }
+        cells
+    }
+
+    /// The height map with only the cells on `trail` showing their heights
+    pub fn render_trail(&self, trail: &[Point]) -> String {
+        let mut grid = DMatrix::from_element(self.map.nrows(), self.map.ncols(), '.');
+        for p in trail {
+            if let (Some(cell), Some(Some(height))) = (grid.get_mut(*p), self.map.get(*p)) {
+                *cell = char::from_digit(*height as u32, 10).unwrap_or('?');
+            }
+        }
+
+        grid::render(&grid)
+    }
+}
+
+/// Digits are heights and `.` is impassable
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let map = grid_from_str(input, |ch| match ch {
+        '.' => Ok(None),
+        _ => match ch.to_digit(10) {
+            Some(h) => Ok(Some(h as i32)),
+            None => bail!("unexpected character {ch:?} in map"),
+        },
+    })?;
+
+    let mut problem = Problem {
+        map,
+        trail_heads: vec![],
+    };
+    problem.trail_heads = problem.cells_at(TrailRule::standard().head);
+    Ok(problem)
+}
+
+const DIRS: &[ScreenDir] = &[ScreenDir::R, ScreenDir::L, ScreenDir::U, ScreenDir::D];
+
+/// Depth-first over every trail continuing `path`, folding each complete trail
+/// (head to summit) into `acc`. Trails never visit a cell twice, so rules allowing
+/// level or downhill steps still finish.
+fn fold_trails<A, F, S>(
+    map: &Map,
+    rule: &TrailRule<S>,
+    path: &mut Vec<Point>,
+    mut acc: A,
+    acc_fn: F,
+) -> A
+where
+    F: Copy + Fn(A, &[Point]) -> A,
+    S: Fn(i32, i32) -> bool,
+{
+    let cur = *path.last().unwrap();
+    let Some(&Some(cur_height)) = map.get(cur) else {
+        return acc;
+    };
+
+    // termination
+    if cur_height == rule.summit {
+        return acc_fn(acc, path);
+    }
+
+    // explore
+    for dir in DIRS {
+        if let Some(next) = cur.step_within(*dir, map) {
+            let Some(&Some(next_height)) = map.get(next) else {
+                continue;
+            };
+            if !(rule.step)(cur_height, next_height) || path.contains(&next) {
+                continue;
+            }
+
+            path.push(next);
+            acc = fold_trails(map, rule, path, acc, acc_fn);
+            path.pop();
+        }
+    }
+    acc
+}
+
+/// As `fold_trails`, but only passing on the summit each trail reaches
+fn find_trail_from<A, F, S>(map: &Map, rule: &TrailRule<S>, cur: Point, acc: A, acc_fn: F) -> A
+where
+    F: Copy + Fn(A, Point) -> A,
+    S: Fn(i32, i32) -> bool,
+{
+    fold_trails(map, rule, &mut vec![cur], acc, move |acc, trail| {
+        acc_fn(acc, *trail.last().unwrap())
+    })
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    part1_with(problem, &TrailRule::standard())
+}
+
+pub fn part2(problem: &Problem) -> Result<usize> {
+    part2_with(problem, &TrailRule::standard())
+}
+
+/// Sum over the trail heads of the number of summits reachable under `rule`
+pub fn part1_with(problem: &Problem, rule: &TrailRule<impl Fn(i32, i32) -> bool>) -> Result<usize> {
+    let mut total = 0;
+    for head in problem.cells_at(rule.head) {
+        let found = find_trail_from(&problem.map, rule, head, HashSet::new(), |mut acc, p| {
+            acc.insert(p);
+            acc
+        });
+        let count = found.len();
+        total += count;
+    }
+    Ok(total)
+}
+
+/// Sum over the trail heads of the number of distinct trails under `rule`
+pub fn part2_with(problem: &Problem, rule: &TrailRule<impl Fn(i32, i32) -> bool>) -> Result<usize> {
+    let mut total = 0;
+    for head in problem.cells_at(rule.head) {
+        let trails_found = find_trail_from(&problem.map, rule, head, 0, |acc, _| acc + 1);
+        total += trails_found;
+    }
+    Ok(total)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(10, "Hoof It");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    89010123
+    78121874
+    87430965
+    96549874
+    45678903
+    32019012
+    01329801
+    10456732
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("36"), Some("81")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn count_from_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let points = find_trail_from(
+            &problem.map,
+            &TrailRule::standard(),
+            Point::new(4, 2),
+            HashSet::new(),
+            |mut acc, p| {
+                acc.insert(p);
+                acc
+            },
+        );
+        assert_eq!(5, points.len());
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 36);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 81);
+        Ok(())
+    }
+
+    #[test]
+    fn trails_match_rating() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let mut total = 0;
+        for head in problem.trail_heads().iter().copied() {
+            let trails = problem.trails_from(head);
+            total += trails.len();
+
+            let distinct: HashSet<_> = trails.iter().collect();
+            assert_eq!(distinct.len(), trails.len());
+            for trail in &trails {
+                assert_eq!(trail.len(), 10);
+                assert_eq!(trail[0], head);
+                for (i, (a, b)) in trail.iter().zip(&trail[1..]).enumerate() {
+                    let d = *b - *a;
+                    assert_eq!(d.x.abs() + d.y.abs(), 1);
+                    assert_eq!(problem.map.get(*b), Some(&Some(i as i32 + 1)));
+                }
+            }
+        }
+        assert_eq!(total, 81);
+        Ok(())
+    }
+
+    #[test]
+    fn render_trail_overlay() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let trail = &problem.trails_from(Point::new(2, 0))[0];
+        let grid = problem.render_trail(trail);
+        assert_eq!(grid.lines().count(), 8);
+        let digits: Vec<char> = grid.chars().filter(|c| c.is_ascii_digit()).collect();
+        assert_eq!(digits.len(), 10);
+        for h in '0'..='9' {
+            assert!(digits.contains(&h));
+        }
+        assert_eq!(grid.lines().next().unwrap().chars().nth(2), Some('0'));
+        Ok(())
+    }
+
+    #[test]
+    fn impassable_cells() -> Result<()> {
+        let problem = parse_input(indoc! {"
+            ...0...
+            ...1...
+            ...2...
+            6543456
+            7.....7
+            8.....8
+            9.....9
+        "})?;
+        assert_eq!(part1(&problem)?, 2);
+        assert_eq!(problem.render_trail(&[]).lines().count(), 7);
+
+        let err = parse_input("012\n3x4\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 2, column 2: unexpected character 'x' in map"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn custom_rules() -> Result<()> {
+        let problem = parse_input(indoc! {"
+            01123
+            ....4
+            98765
+        "})?;
+        assert_eq!(part2(&problem)?, 0);
+
+        // level steps allowed, without going round in circles
+        let gentle: TrailRule<StepFn> = TrailRule {
+            step: |from_h, to_h| (0..=1).contains(&(to_h - from_h)),
+            ..TrailRule::standard()
+        };
+        assert_eq!(part1_with(&problem, &gentle)?, 1);
+        assert_eq!(part2_with(&problem, &gentle)?, 1);
+
+        // walking back down, which stops at the level step
+        let descend = TrailRule {
+            head: 9,
+            summit: 2,
+            step: |from_h: i32, to_h: i32| from_h - to_h == 1,
+        };
+        assert_eq!(part2_with(&problem, &descend)?, 1);
+        let descend = TrailRule {
+            summit: 0,
+            ..descend
+        };
+        assert_eq!(part2_with(&problem, &descend)?, 0);
+
+        let by_two = TrailRule {
+            head: 8,
+            summit: 0,
+            step: |from_h: i32, to_h: i32| from_h - to_h == 2,
+        };
+        assert_eq!(part2_with(&parse_input("86420\n")?, &by_two)?, 1);
+        assert_eq!(part2_with(&parse_input("8640\n")?, &by_two)?, 0);
+        Ok(())
+    }
+}
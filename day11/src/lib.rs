@@ -0,0 +1,576 @@
+use std::{
+    iter,
+    ops::{Div, Rem},
+};
+
+use anyhow::{bail, Context, Result};
+use arrayvec::ArrayVec;
+use common::{
+    math::{checked_sum, CheckedAccumulator},
+    memo::Memoized,
+    OptionAnyhow,
+};
+use dlv_list::VecList;
+use rustc_hash::FxHashMap;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    stones: VecList<i64>,
+}
+impl Problem {
+    pub fn stones(&self) -> Vec<i64> {
+        self.stones.iter().copied().collect()
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut stones = VecList::new();
+    for n in input.split_whitespace() {
+        let n: i64 = n.parse()?;
+        if n < 0 {
+            bail!("stone {n} is negative");
+        }
+        stones.push_back(n);
+    }
+    Ok(Problem { stones })
+}
+
+fn try_split(n: i64) -> Option<(i64, i64)> {
+    let order = n.ilog10() + 1;
+    if order.is_multiple_of(2) {
+        let factor = iter::successors(Some(1), |a| Some(a * 10))
+            .nth(order as usize / 2)
+            .expect("factor");
+        Some((n.div(factor), n.rem(factor)))
+    } else {
+        None
+    }
+}
+
+/// Result of a stone changing on a blink
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Blink {
+    One(i64),
+    Two(i64, i64),
+}
+
+/// How a single stone changes each time we blink
+pub trait Rule {
+    /// `None` if the new value overflows
+    fn blink(&self, n: i64) -> Option<Blink>;
+}
+
+/// The puzzle's rules, applied in order: `0 -> 1`, split even digits, `* 2024`
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Day11Rule;
+impl Rule for Day11Rule {
+    fn blink(&self, n: i64) -> Option<Blink> {
+        if n == 0 {
+            Some(Blink::One(1))
+        } else if let Some((a, b)) = try_split(n) {
+            Some(Blink::Two(a, b))
+        } else {
+            n.checked_mul(2024).map(Blink::One)
+        }
+    }
+}
+
+fn iterate(rule: &impl Rule, stones: &VecList<i64>, iterations: usize) -> Result<usize> {
+    let mut stones = stones.clone();
+    for _ in 0..iterations {
+        let mut ix = stones.front_index().ok_anyhow()?;
+        loop {
+            let n = stones.get(ix).copied().ok_anyhow()?;
+            match rule.blink(n).ok_anyhow()? {
+                Blink::One(a) => *stones.get_mut(ix).ok_anyhow()? = a,
+                Blink::Two(a, b) => {
+                    stones.insert_before(ix, a);
+                    *stones.get_mut(ix).ok_anyhow()? = b;
+                }
+            }
+            if let Some(next) = stones.get_next_index(ix) {
+                ix = next;
+            } else {
+                break;
+            }
+        }
+    }
+    let num_stones = stones.len();
+    Ok(num_stones)
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    iterate(&Day11Rule, &problem.stones, 25)
+}
+
+/// Simple recursion that only really works for part 1
+pub fn iterate_recurse_count(rule: &impl Rule, n: i64, remaining_depth: usize) -> Result<usize> {
+    if remaining_depth == 0 {
+        return Ok(1);
+    }
+    match rule.blink(n).expect_anyhow("stone value overflows")? {
+        Blink::One(a) => iterate_recurse_count(rule, a, remaining_depth - 1),
+        Blink::Two(a, b) => {
+            let num_a = iterate_recurse_count(rule, a, remaining_depth - 1)?;
+            let num_b = iterate_recurse_count(rule, b, remaining_depth - 1)?;
+            checked_sum([num_a, num_b])
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Key(i64, usize);
+pub type Cache = Memoized<Key, usize>;
+
+/// Recursive with memoization. Large values eventually split to smaller values, so
+/// we don't need to try to memoize everything - just storing the small values is enough.
+pub fn iterate_recurse_count_mem(
+    rule: &impl Rule,
+    n: i64,
+    remaining_depth: usize,
+    memory: &mut Cache,
+) -> Result<usize> {
+    // termination
+    if remaining_depth == 0 {
+        return Ok(1);
+    }
+
+    // already-computed value
+    if let Some(mem) = memory.get(&Key(n, remaining_depth)) {
+        return Ok(mem);
+    }
+
+    // otherwise iterate
+    let count = match rule.blink(n).expect_anyhow("stone value overflows")? {
+        Blink::One(a) => iterate_recurse_count_mem(rule, a, remaining_depth - 1, memory)?,
+        Blink::Two(a, b) => {
+            let num_a = iterate_recurse_count_mem(rule, a, remaining_depth - 1, memory)?;
+            let num_b = iterate_recurse_count_mem(rule, b, remaining_depth - 1, memory)?;
+            checked_sum([num_a, num_b])?
+        }
+    };
+
+    // store smaller values of n in the cache
+    if n <= 1024 {
+        memory.insert(Key(n, remaining_depth), count);
+    }
+
+    Ok(count)
+}
+
+fn count_stones(rule: &impl Rule, stones: &VecList<i64>, iterations: usize) -> Result<usize> {
+    // memory can be used across multiple calls
+    let mut mem = Cache::default();
+    let mut total = CheckedAccumulator::sum();
+    for n in stones {
+        total.add(iterate_recurse_count_mem(rule, *n, iterations, &mut mem)?)?;
+    }
+    Ok(total.value())
+}
+
+/// Stone count before any blinks and after each of `iterations` blinks, keeping only
+/// how many stones have each value; order doesn't affect the count
+pub fn counts_per_iteration_with(
+    rule: &impl Rule,
+    stones: &[i64],
+    iterations: usize,
+) -> Result<Vec<usize>> {
+    let mut counts: FxHashMap<i64, usize> = FxHashMap::default();
+    for n in stones {
+        *counts.entry(*n).or_default() += 1;
+    }
+
+    let mut totals = Vec::with_capacity(iterations + 1);
+    totals.push(stones.len());
+    for _ in 0..iterations {
+        let mut next: FxHashMap<i64, usize> = FxHashMap::default();
+        let mut add = |n: i64, count: usize| {
+            *next.entry(n).or_default() += count;
+        };
+        for (n, count) in counts {
+            match rule.blink(n).expect_anyhow("stone value overflows")? {
+                Blink::One(a) => add(a, count),
+                Blink::Two(a, b) => {
+                    add(a, count);
+                    add(b, count);
+                }
+            }
+        }
+        counts = next;
+
+        let total = checked_sum(counts.values().copied()).context("stone count")?;
+        totals.push(total);
+    }
+    Ok(totals)
+}
+
+/// `counts_per_iteration_with` for the puzzle's rule
+pub fn counts_per_iteration(stones: &[i64], iterations: usize) -> Result<Vec<usize>> {
+    counts_per_iteration_with(&Day11Rule, stones, iterations)
+}
+
+/// Ratio of each count to the one before it
+pub fn growth_rates(counts: &[usize]) -> Vec<f64> {
+    counts
+        .windows(2)
+        .map(|w| w[1] as f64 / w[0] as f64)
+        .collect()
+}
+
+/// Mersenne prime 2^61 - 1, which `count_after` counts modulo; exact for up to about
+/// 100 blinks of the puzzle input
+pub const COUNT_MODULUS: u64 = (1 << 61) - 1;
+
+fn add_mod(a: u64, b: u64) -> u64 {
+    let sum = a + b;
+    if sum >= COUNT_MODULUS {
+        sum - COUNT_MODULUS
+    } else {
+        sum
+    }
+}
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    // 2^61 is 1 modulo the prime, so the high bits fold back onto the low ones
+    let product = a as u128 * b as u128;
+    add_mod(product as u64 & COUNT_MODULUS, (product >> 61) as u64)
+}
+
+fn pow_mod(mut base: u64, mut exp: u64) -> u64 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Most values stones can take, which is far more than the puzzle input reaches
+const MAX_REACHABLE: usize = 1 << 20;
+
+/// The linear recurrence the stone count follows, modulo `COUNT_MODULUS`. The values
+/// reachable from the starting stones form a finite set, and one blink is a linear
+/// map on the counts of each value, so the total count satisfies a recurrence no
+/// longer than the set is big. Counts for twice that many blinks are enough for
+/// Berlekamp-Massey to find the shortest one.
+#[derive(Debug, Clone)]
+pub struct CountRecurrence {
+    /// Counts from no blinks on, enough to start the recurrence
+    counts: Vec<u64>,
+    /// `count[n] = sum of coefficients[i] * count[n - 1 - i]`
+    coefficients: Vec<u64>,
+    reachable: usize,
+}
+impl CountRecurrence {
+    pub fn discover(rule: &impl Rule, stones: &[i64]) -> Result<Self> {
+        let mut index: FxHashMap<i64, usize> = FxHashMap::default();
+        let mut successors: Vec<ArrayVec<usize, 2>> = vec![];
+        let mut values = vec![];
+        let mut intern = |n: i64, values: &mut Vec<i64>| {
+            let next = index.len();
+            *index.entry(n).or_insert_with(|| {
+                values.push(n);
+                next
+            })
+        };
+        let mut counts = vec![];
+        for n in stones {
+            counts.push(intern(*n, &mut values));
+        }
+        while successors.len() < values.len() {
+            if values.len() > MAX_REACHABLE {
+                bail!("stones reach more than {MAX_REACHABLE} values");
+            }
+            let n = values[successors.len()];
+            let next: ArrayVec<i64, 2> =
+                match rule.blink(n).expect_anyhow("stone value overflows")? {
+                    Blink::One(a) => [a].into_iter().collect(),
+                    Blink::Two(a, b) => [a, b].into_iter().collect(),
+                };
+            let next = next.into_iter().map(|m| intern(m, &mut values)).collect();
+            successors.push(next);
+        }
+
+        let mut by_value = vec![0; values.len()];
+        for i in counts {
+            by_value[i] += 1;
+        }
+        let mut totals = Vec::with_capacity(2 * values.len());
+        for _ in 0..2 * values.len().max(1) {
+            totals.push(by_value.iter().fold(0, |sum, c| add_mod(sum, *c)));
+            let mut next = vec![0; values.len()];
+            for (count, succ) in by_value.iter().zip(&successors) {
+                for j in succ {
+                    next[*j] = add_mod(next[*j], *count);
+                }
+            }
+            by_value = next;
+        }
+
+        let coefficients = berlekamp_massey(&totals);
+        Ok(CountRecurrence {
+            counts: totals,
+            coefficients,
+            reachable: values.len(),
+        })
+    }
+
+    /// Length of the recurrence
+    pub fn order(&self) -> usize {
+        self.coefficients.len()
+    }
+
+    /// Number of different values the stones can take
+    pub fn reachable(&self) -> usize {
+        self.reachable
+    }
+
+    /// Stone count after `depth` blinks, modulo `COUNT_MODULUS`. Stepping the
+    /// recurrence is multiplying by its companion matrix, and the matrix power is
+    /// worked out as `x^depth` modulo the characteristic polynomial, which takes
+    /// `order^2` rather than `order^3` per squaring.
+    pub fn count_after(&self, depth: u64) -> u64 {
+        if let Some(count) = usize::try_from(depth).ok().and_then(|d| self.counts.get(d)) {
+            return *count;
+        }
+        if self.order() == 0 {
+            return 0;
+        }
+
+        let mut power = vec![1];
+        for bit in (0..u64::BITS - depth.leading_zeros()).rev() {
+            let mut square = vec![0; 2 * power.len() - 1];
+            for (i, a) in power.iter().enumerate() {
+                for (j, b) in power.iter().enumerate() {
+                    square[i + j] = add_mod(square[i + j], mul_mod(*a, *b));
+                }
+            }
+            if depth >> bit & 1 == 1 {
+                square.insert(0, 0);
+            }
+            power = self.reduce(square);
+        }
+        power
+            .iter()
+            .zip(&self.counts)
+            .fold(0, |sum, (p, c)| add_mod(sum, mul_mod(*p, *c)))
+    }
+
+    /// `poly` modulo the characteristic polynomial, so with at most `order` terms
+    fn reduce(&self, mut poly: Vec<u64>) -> Vec<u64> {
+        let k = self.order();
+        for top in (k..poly.len()).rev() {
+            // x^top is the recurrence applied to the k powers below it
+            let lead = std::mem::take(&mut poly[top]);
+            for (i, c) in self.coefficients.iter().enumerate() {
+                poly[top - 1 - i] = add_mod(poly[top - 1 - i], mul_mod(lead, *c));
+            }
+        }
+        poly.truncate(k);
+        poly
+    }
+}
+
+/// Shortest linear recurrence generating `seq`, modulo `COUNT_MODULUS`, as the
+/// coefficients of the preceding terms, nearest first
+fn berlekamp_massey(seq: &[u64]) -> Vec<u64> {
+    let neg = |a: u64| (COUNT_MODULUS - a) % COUNT_MODULUS;
+    // connection polynomials, with `current[0]` and `previous[0]` always 1
+    let mut current = vec![1];
+    let mut previous = vec![1];
+    let (mut order, mut shift, mut previous_discrepancy) = (0, 1, 1);
+    for n in 0..seq.len() {
+        let discrepancy =
+            (1..=order).fold(seq[n], |d, i| add_mod(d, mul_mod(current[i], seq[n - i])));
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = mul_mod(
+            discrepancy,
+            pow_mod(previous_discrepancy, COUNT_MODULUS - 2),
+        );
+        let before = current.clone();
+        current.resize(current.len().max(previous.len() + shift), 0);
+        for (i, p) in previous.iter().enumerate() {
+            current[i + shift] = add_mod(current[i + shift], neg(mul_mod(scale, *p)));
+        }
+        if 2 * order <= n {
+            order = n + 1 - order;
+            previous = before;
+            previous_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+    current.resize(order + 1, 0);
+    current[1..].iter().map(|c| neg(*c)).collect()
+}
+
+/// Stone count after `depth` blinks of the puzzle's rule, modulo `COUNT_MODULUS`, in
+/// time logarithmic in the depth once the recurrence is found
+pub fn count_after(stones: &[i64], depth: u64) -> Result<u64> {
+    Ok(CountRecurrence::discover(&Day11Rule, stones)?.count_after(depth))
+}
+
+pub fn part2(problem: &Problem, iterations: usize) -> Result<usize> {
+    count_stones(&Day11Rule, &problem.stones, iterations)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((
+        part1(&problem)?.to_string(),
+        part2(&problem, 75)?.to_string(),
+    ))
+}
+
+common::aoc_day!(11, "Plutonian Pebbles");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    125 17
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("55312"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_stones_rejected() {
+        let err = parse_input("-5 3").unwrap_err();
+        assert_eq!(err.to_string(), "stone -5 is negative");
+    }
+
+    #[test]
+    fn split_correct() {
+        assert_eq!(try_split(1000).unwrap(), (10, 0));
+        assert_eq!(try_split(10).unwrap(), (1, 0));
+        assert_eq!(try_split(111222).unwrap(), (111, 222));
+    }
+
+    #[test]
+    fn day11_rule_cases() {
+        let rule = Day11Rule;
+        assert_eq!(rule.blink(0), Some(Blink::One(1)));
+        assert_eq!(rule.blink(1), Some(Blink::One(2024)));
+        assert_eq!(rule.blink(99), Some(Blink::Two(9, 9)));
+        assert_eq!(rule.blink(1000), Some(Blink::Two(10, 0)));
+        assert_eq!(rule.blink(999), Some(Blink::One(2021976)));
+        assert_eq!(rule.blink(i64::MAX / 100), None);
+    }
+
+    /// Every stone splits into two smaller ones until it reaches zero
+    struct HalvingRule;
+    impl Rule for HalvingRule {
+        fn blink(&self, n: i64) -> Option<Blink> {
+            match n {
+                0 => Some(Blink::One(0)),
+                n => Some(Blink::Two(n / 2, n - n / 2 - 1)),
+            }
+        }
+    }
+
+    #[test]
+    fn solvers_agree_for_alternate_rule() -> Result<()> {
+        let stones: VecList<i64> = [0, 1, 7, 100].into_iter().collect();
+        for iterations in 0..10 {
+            let expected = iterate(&HalvingRule, &stones, iterations)?;
+            let recursive: usize = stones
+                .iter()
+                .map(|n| iterate_recurse_count(&HalvingRule, *n, iterations))
+                .sum::<Result<_>>()?;
+            assert_eq!(recursive, expected);
+            assert_eq!(count_stones(&HalvingRule, &stones, iterations)?, expected);
+        }
+        let counts = counts_per_iteration_with(&HalvingRule, &[0, 1, 7, 100], 9)?;
+        for (iterations, count) in counts.iter().enumerate() {
+            assert_eq!(*count, count_stones(&HalvingRule, &stones, iterations)?);
+        }
+        // zero stays as one stone, everything else splits
+        assert_eq!(count_stones(&HalvingRule, &stones, 1)?, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn recurrence_matches_counting() -> Result<()> {
+        let stones = parse_input(EXAMPLE)?.stones();
+        let recurrence = CountRecurrence::discover(&Day11Rule, &stones)?;
+        assert_eq!((recurrence.reachable(), recurrence.order()), (76, 49));
+        let counts = counts_per_iteration(&stones, 90)?;
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(recurrence.count_after(depth as u64), *count as u64);
+        }
+        assert_eq!(count_after(&stones, 75)?, 65601038650482);
+
+        let stones = [0, 1, 7, 100];
+        let recurrence = CountRecurrence::discover(&HalvingRule, &stones)?;
+        let counts = counts_per_iteration_with(&HalvingRule, &stones, 300)?;
+        for depth in [0, 5, 20, 299, 300] {
+            assert_eq!(recurrence.count_after(depth as u64), counts[depth] as u64);
+        }
+        assert_eq!(count_after(&[], 1_000)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn deep_counts_are_consistent() -> Result<()> {
+        // a 0 becomes a 1 on the first blink
+        let depth = 1_000_000_000;
+        assert_eq!(count_after(&[0], depth + 1)?, count_after(&[1], depth)?);
+        assert_ne!(count_after(&[0], depth)?, count_after(&[1], depth)?);
+        // and 2024 splits into 20 and 24
+        assert_eq!(
+            count_after(&[2024], depth)?,
+            add_mod(
+                count_after(&[20], depth - 1)?,
+                count_after(&[24], depth - 1)?
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 55312);
+        Ok(())
+    }
+
+    #[test]
+    fn counts_per_iteration_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let counts = counts_per_iteration(&problem.stones(), 25)?;
+        assert_eq!(counts.len(), 26);
+        assert_eq!(counts[..7], [2, 3, 4, 5, 9, 13, 22]);
+        assert_eq!(counts[25], 55312);
+
+        let rates = growth_rates(&counts);
+        assert_eq!(rates.len(), 25);
+        assert_eq!(rates[0], 1.5);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem, 25)?;
+        assert_eq!(count, 55312);
+        Ok(())
+    }
+}
@@ -0,0 +1,579 @@
+use anyhow::Result;
+use common::{
+    cartesian::{Dir8, Point, ScreenDir},
+    graph::UnionFind,
+    grid::grid_from_str,
+};
+use nalgebra::DMatrix;
+use rayon::prelude::*;
+use strum::IntoEnumIterator;
+
+type PlantMap = DMatrix<char>;
+type RegionMap = DMatrix<i32>;
+
+/// Which plots count as touching when growing a region. Either way, perimeter and
+/// sides are measured on the edges between plots: the perimeter counts unit edges
+/// between a region's plots and anything outside it, and sides count straight runs of
+/// those edges. With `Eight`, a region pinched to a diagonal touch turns a corner there,
+/// so the runs on either side are separate sides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    plant: char,
+    area: usize,
+    perimeter: usize,
+    sides: usize,
+}
+impl Measurement {
+    pub fn plant(&self) -> char {
+        self.plant
+    }
+
+    pub fn area(&self) -> usize {
+        self.area
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.perimeter
+    }
+
+    /// Number of straight sections of fence
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+}
+
+/// Part 1 pricing: area times perimeter
+pub fn perimeter_pricer(m: &Measurement) -> usize {
+    m.area * m.perimeter
+}
+
+/// Part 2 pricing, with the bulk discount: area times number of sides
+pub fn sides_pricer(m: &Measurement) -> usize {
+    m.area * m.sides
+}
+
+/// Area times perimeter plus sides, charging for both the fence and its corners
+pub fn hybrid_pricer(m: &Measurement) -> usize {
+    m.area * (m.perimeter + m.sides)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionPrice {
+    pub plant: char,
+    pub area: usize,
+    pub perimeter: usize,
+    pub sides: usize,
+    pub price_p1: usize,
+    pub price_p2: usize,
+}
+impl From<&Measurement> for RegionPrice {
+    fn from(m: &Measurement) -> Self {
+        RegionPrice {
+            plant: m.plant,
+            area: m.area,
+            perimeter: m.perimeter,
+            sides: m.sides,
+            price_p1: perimeter_pricer(m),
+            price_p2: sides_pricer(m),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    plants: PlantMap,
+}
+impl Problem {
+    fn perimeter(&self, loc: Point) -> usize {
+        let mut perim = 0;
+        let ch = self.plants.get(loc).unwrap();
+        for n in neighbours(loc) {
+            perim += match self.plants.get(n) {
+                None => 1,
+                Some(nch) if nch == ch => 0,
+                Some(_) => 1,
+            }
+        }
+        perim
+    }
+
+    fn border(&self, loc: Point, d: ScreenDir) -> bool {
+        let next = loc + d.into();
+        let ch = self.plants.get(loc).unwrap();
+        match self.plants.get(next) {
+            Some(nch) if nch == ch => false,
+            Some(_) => true, // different plant
+            None => true,    // edge of
+        }
+    }
+
+    fn corners(&self, loc: Point) -> usize {
+        let mut inside_corners = 0;
+        let mut outside_corners = 0;
+
+        let ch = self.plants.get(loc).unwrap();
+        let diag_different_plant = |d1: ScreenDir, d2: ScreenDir| {
+            let next = loc + d1.into() + d2.into();
+            if let Some(nch) = self.plants.get(next) {
+                nch != ch
+            } else {
+                false // not on map
+            }
+        };
+
+        for d in ScreenDir::iter() {
+            let adjacent = d.right();
+
+            let border = self.border(loc, d);
+            let adjacent_border = self.border(loc, adjacent);
+            let diag = diag_different_plant(d, adjacent);
+
+            if border && adjacent_border {
+                outside_corners += 1;
+            } else if !border && !adjacent_border && diag {
+                inside_corners += 1;
+            }
+        }
+        inside_corners + outside_corners
+    }
+
+    fn explore_region(
+        &self,
+        loc: Point,
+        regions: &mut RegionMap,
+        label: i32,
+        connectivity: Connectivity,
+    ) -> Measurement {
+        let mut area = 0;
+        let mut perimeter = 0;
+        let mut corners = 0;
+
+        let mut queue = Vec::new();
+        queue.push(loc);
+
+        let plant = self.plants.get(loc).unwrap();
+        loop {
+            // explore next location
+            let current = match queue.pop() {
+                None => break,
+                Some(n) => n,
+            };
+
+            // only if not visited
+            if *regions.get(current).unwrap() != -1 {
+                continue;
+            }
+
+            // add area & record visited
+            area += 1;
+            perimeter += self.perimeter(current);
+            corners += self.corners(current);
+            *regions.get_mut(current).unwrap() = label;
+            //println!("{current:?} {area}");
+
+            // find possible neighbours
+            for next in connected(current, connectivity) {
+                // only unexplored
+                if let Some(r) = regions.get(next) {
+                    if *r != -1 {
+                        continue;
+                    }
+                }
+                // and only if it matches our plant type
+                if let Some(ch) = self.plants.get(next) {
+                    if ch == plant {
+                        queue.push(next);
+                    }
+                }
+            }
+        }
+
+        Measurement {
+            plant: *plant,
+            area,
+            perimeter,
+            sides: corners,
+        }
+    }
+}
+
+fn neighbours(loc: Point) -> impl Iterator<Item = Point> {
+    ScreenDir::iter().map(move |d| loc + d.into())
+}
+
+/// Plots touching `loc`: the four neighbours, plus the diagonals for `Eight`
+fn connected(loc: Point, connectivity: Connectivity) -> impl Iterator<Item = Point> {
+    Dir8::iter()
+        .filter(move |d| connectivity == Connectivity::Eight || !d.is_diagonal())
+        .map(move |d| loc + d.into())
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let plants = grid_from_str(input, Ok)?;
+    Ok(Problem { plants })
+}
+
+/// Measure every region on the map, in the order they are discovered
+fn measure_regions(problem: &Problem, connectivity: Connectivity) -> Vec<Measurement> {
+    explore_regions(problem, connectivity).1
+}
+
+/// Label and measure every region, scanning down each column in turn; labels count
+/// up from 0 in the order regions are discovered
+fn explore_regions(problem: &Problem, connectivity: Connectivity) -> (RegionMap, Vec<Measurement>) {
+    let mut measurements = Vec::new();
+    let mut region_map =
+        RegionMap::from_element(problem.plants.nrows(), problem.plants.ncols(), -1);
+
+    let mut label = 0;
+    for x in 0..problem.plants.ncols() {
+        for y in 0..problem.plants.nrows() {
+            let loc = Point::new(x as i64, y as i64);
+            if *region_map.get(loc).unwrap() == -1 {
+                // unexplored -- map this region
+                let measurement = problem.explore_region(loc, &mut region_map, label, connectivity);
+                // println!("{loc:?} {measurement:?}");
+                // println!("{region_map}");
+                label += 1;
+                measurements.push(measurement);
+            }
+        }
+    }
+
+    (region_map, measurements)
+}
+
+/// Region label of every plot, numbered in the order regions are discovered when
+/// scanning down each column in turn
+pub fn segment(problem: &Problem, connectivity: Connectivity) -> RegionMap {
+    explore_regions(problem, connectivity).0
+}
+
+/// Label the plots of the `tile` by `tile` square at `(row, col)` on their own, each
+/// plot getting the (column-major) index of the first plot found in its region
+fn label_tile(
+    plants: &PlantMap,
+    (row, col): (usize, usize),
+    tile: usize,
+    connectivity: Connectivity,
+) -> Vec<((usize, usize), usize)> {
+    let rows = row..(row + tile).min(plants.nrows());
+    let cols = col..(col + tile).min(plants.ncols());
+    let in_tile = |p: Point| match p.to_coord() {
+        Some((r, c)) => rows.contains(&r) && cols.contains(&c),
+        None => false,
+    };
+
+    let mut labels = DMatrix::from_element(rows.len(), cols.len(), None);
+    let mut out = Vec::with_capacity(rows.len() * cols.len());
+    for c in cols.clone() {
+        for r in rows.clone() {
+            if labels[(r - row, c - col)].is_some() {
+                continue;
+            }
+            let label = c * plants.nrows() + r;
+            let plant = plants[(r, c)];
+            let mut queue = vec![Point::from((r, c))];
+            while let Some(current) = queue.pop() {
+                let (cr, cc) = current.to_coord().unwrap();
+                let slot = &mut labels[(cr - row, cc - col)];
+                if slot.is_some() {
+                    continue;
+                }
+                *slot = Some(label);
+                out.push(((cr, cc), label));
+                for next in connected(current, connectivity) {
+                    if in_tile(next) && plants.get(next) == Some(&plant) {
+                        queue.push(next);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// As `segment`, but labelling `tile` by `tile` squares of the map in parallel and
+/// then joining up regions that cross tile boundaries. Gives exactly the same labels.
+pub fn segment_parallel(problem: &Problem, connectivity: Connectivity, tile: usize) -> RegionMap {
+    let plants = &problem.plants;
+    let (nrows, ncols) = plants.shape();
+    let tile = tile.max(1);
+    let index = |(r, c): (usize, usize)| c * nrows + r;
+
+    let origins: Vec<_> = (0..nrows)
+        .step_by(tile)
+        .flat_map(|r| (0..ncols).step_by(tile).map(move |c| (r, c)))
+        .collect();
+    let tiles: Vec<_> = origins
+        .into_par_iter()
+        .map(|origin| label_tile(plants, origin, tile, connectivity))
+        .collect();
+
+    let mut sets = UnionFind::new(nrows * ncols);
+    for (rc, label) in tiles.into_iter().flatten() {
+        sets.union(index(rc), label);
+    }
+
+    // any pair of touching plots in different tiles includes one on the last row or
+    // column of its tile, so those are the only plots to check
+    for c in 0..ncols {
+        for r in 0..nrows {
+            if (r + 1) % tile != 0 && (c + 1) % tile != 0 {
+                continue;
+            }
+            let p = Point::from((r, c));
+            for next in connected(p, connectivity) {
+                let Some(next_rc) = next.to_coord_matrix(plants) else {
+                    continue;
+                };
+                let other_tile = next_rc.0 / tile != r / tile || next_rc.1 / tile != c / tile;
+                if other_tile && plants[next_rc] == plants[(r, c)] {
+                    sets.union(index((r, c)), index(next_rc));
+                }
+            }
+        }
+    }
+
+    // relabel in discovery order, matching `segment`
+    let mut labels = vec![-1; nrows * ncols];
+    let mut regions = RegionMap::from_element(nrows, ncols, -1);
+    let mut next_label = 0;
+    for c in 0..ncols {
+        for r in 0..nrows {
+            let root = sets.find(index((r, c)));
+            if labels[root] == -1 {
+                labels[root] = next_label;
+                next_label += 1;
+            }
+            regions[(r, c)] = labels[root];
+        }
+    }
+    regions
+}
+
+/// Total fencing price of the (four-connected) regions, pricing each with `pricer`
+pub fn total_price(problem: &Problem, pricer: impl Fn(&Measurement) -> usize) -> usize {
+    measure_regions(problem, Connectivity::Four)
+        .iter()
+        .map(pricer)
+        .sum()
+}
+
+/// Per-region prices for both parts, sorted by plant type and then descending part 1 price
+pub fn price_report(problem: &Problem, connectivity: Connectivity) -> Vec<RegionPrice> {
+    let mut report: Vec<RegionPrice> = measure_regions(problem, connectivity)
+        .iter()
+        .map(RegionPrice::from)
+        .collect();
+    report.sort_by(|a, b| a.plant.cmp(&b.plant).then(b.price_p1.cmp(&a.price_p1)));
+    report
+}
+
+pub fn print_report(report: &[RegionPrice]) {
+    println!(
+        "{:>5} {:>6} {:>9} {:>6} {:>9} {:>9}",
+        "plant", "area", "perimeter", "sides", "price_p1", "price_p2"
+    );
+    for r in report {
+        println!(
+            "{:>5} {:>6} {:>9} {:>6} {:>9} {:>9}",
+            r.plant, r.area, r.perimeter, r.sides, r.price_p1, r.price_p2
+        );
+    }
+    let total_p1: usize = report.iter().map(|r| r.price_p1).sum();
+    let total_p2: usize = report.iter().map(|r| r.price_p2).sum();
+    println!(
+        "{:>5} {:>6} {:>9} {:>6} {total_p1:>9} {total_p2:>9}",
+        "total", "", "", ""
+    );
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    Ok(total_price(problem, perimeter_pricer))
+}
+
+pub fn part2(problem: &Problem) -> Result<usize> {
+    Ok(total_price(problem, sides_pricer))
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(12, "Garden Groups");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    RRRRIICCFF
+    RRRRIICCCF
+    VVRRRCCFFF
+    VVRCCCJFFF
+    VVVVCJJCFE
+    VVIVCCJJEE
+    VVIIICJJEE
+    MIIIIIJJEE
+    MIIISIJEEE
+    MMMISSJEEE
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("1930"), Some("1206")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 1930);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 1206);
+        Ok(())
+    }
+
+    #[test]
+    fn price_report_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let report = price_report(&problem, Connectivity::Four);
+        assert_eq!(report.len(), 11);
+
+        // the example enumerates the region prices; spot check a few
+        let r = report.iter().find(|r| r.plant == 'R').unwrap();
+        assert_eq!((r.area, r.perimeter, r.sides), (12, 18, 10));
+        assert_eq!((r.price_p1, r.price_p2), (216, 120));
+        let s = report.iter().find(|r| r.plant == 'S').unwrap();
+        assert_eq!((s.price_p1, s.price_p2), (24, 18));
+
+        // two separate C regions, larger one first
+        let c: Vec<_> = report.iter().filter(|r| r.plant == 'C').collect();
+        assert_eq!(c.len(), 2);
+        assert_eq!((c[0].price_p1, c[1].price_p1), (392, 4));
+
+        assert_eq!(report.iter().map(|r| r.price_p1).sum::<usize>(), 1930);
+        assert_eq!(report.iter().map(|r| r.price_p2).sum::<usize>(), 1206);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_pricers() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(total_price(&problem, hybrid_pricer), 1930 + 1206);
+        assert_eq!(total_price(&problem, |m| m.area()), 100);
+        // a flat fee per region
+        assert_eq!(total_price(&problem, |_| 1), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn eight_connected_merges_diagonals() -> Result<()> {
+        let problem = parse_input(indoc! {"
+            AB
+            BA
+        "})?;
+        let four = price_report(&problem, Connectivity::Four);
+        assert_eq!(four.len(), 4);
+
+        // each pair is one region now, but the edges are unchanged; the pinch at the
+        // centre is a corner, so none of the sides join up
+        let eight = price_report(&problem, Connectivity::Eight);
+        assert_eq!(eight.len(), 2);
+        for r in &eight {
+            assert_eq!((r.area, r.perimeter, r.sides), (2, 8, 8));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn eight_connected_examples() -> Result<()> {
+        // no region in the main example touches another of its plant diagonally
+        let problem = parse_input(EXAMPLE)?;
+        let four = price_report(&problem, Connectivity::Four);
+        assert_eq!(price_report(&problem, Connectivity::Eight), four);
+
+        // the two B regions touch at a corner
+        let problem = parse_input(indoc! {"
+            AAAAAA
+            AAABBA
+            AAABBA
+            ABBAAA
+            ABBAAA
+            AAAAAA
+        "})?;
+        let four = price_report(&problem, Connectivity::Four);
+        assert_eq!(four.iter().map(|r| r.price_p2).sum::<usize>(), 368);
+
+        let eight = price_report(&problem, Connectivity::Eight);
+        let b: Vec<_> = eight.iter().filter(|r| r.plant == 'B').collect();
+        assert_eq!(b.len(), 1);
+        assert_eq!((b[0].area, b[0].perimeter, b[0].sides), (8, 16, 8));
+        assert_eq!(eight.iter().map(|r| r.price_p2).sum::<usize>(), 400);
+        Ok(())
+    }
+
+    /// Random map of `letters` plant types
+    fn random_map(rows: usize, cols: usize, letters: u8, seed: u64) -> Problem {
+        let alphabet: Vec<_> = (b'A'..b'A' + letters).map(char::from).collect();
+        let plants = common::rng::seeded(seed).grid(rows, cols, &alphabet);
+        Problem { plants }
+    }
+
+    #[test]
+    fn parallel_segmentation_matches_sequential() -> Result<()> {
+        let mut problems = vec![parse_input(EXAMPLE)?];
+        for seed in 1..=6 {
+            problems.push(random_map(37, 53, 2 + seed as u8 % 3, seed));
+        }
+        for problem in &problems {
+            for connectivity in [Connectivity::Four, Connectivity::Eight] {
+                let expected = segment(problem, connectivity);
+                for tile in [1, 2, 3, 7, 16, 64] {
+                    assert_eq!(
+                        segment_parallel(problem, connectivity, tile),
+                        expected,
+                        "{connectivity:?}, tile {tile}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_segmentation_joins_snake() -> Result<()> {
+        // one region winding back and forth across every tile boundary
+        let problem = parse_input(indoc! {"
+            AAAAAAA
+            BBBBBBA
+            AAAAAAA
+            ABBBBBB
+            AAAAAAA
+        "})?;
+        let regions = segment_parallel(&problem, Connectivity::Four, 2);
+        assert_eq!(regions, segment(&problem, Connectivity::Four));
+        assert_eq!(regions.max(), 2);
+        Ok(())
+    }
+}
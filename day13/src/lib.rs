@@ -0,0 +1,746 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Context, Result};
+use common::{
+    cartesian::Point, math::CheckedAccumulator, timing::json_string, OptionAnyhow, ResultAnyhow,
+};
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    machines: Vec<Machine>,
+}
+const A_COST: i64 = 3;
+const B_COST: i64 = 1;
+const PART2_OFFSET: i64 = 10000000000000;
+
+#[derive(Debug, Clone)]
+pub struct Machine {
+    a: Point,
+    b: Point,
+    prize: Point,
+}
+/// In the puzzle's input format, without the blank line between machines
+impl Display for Machine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Button A: X+{}, Y+{}", self.a.x, self.a.y)?;
+        writeln!(f, "Button B: X+{}, Y+{}", self.b.x, self.b.y)?;
+        writeln!(f, "Prize: X={}, Y={}", self.prize.x, self.prize.y)
+    }
+}
+
+/// Presses of each button that win a machine's prize, and what they cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    pub a: i64,
+    pub b: i64,
+    pub cost: i64,
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let re_button = Regex::new(r#"Button [AB]: X\+(\d+), Y\+(\d+)"#).unwrap();
+    let re_prize = Regex::new(r#"Prize: X=(\d+), Y=(\d+)"#).unwrap();
+
+    // both coordinates from `line`, which is `line_no` in the input
+    let parse_point = |re: &Regex, line: &str, line_no: usize, expected: &str| -> Result<Point> {
+        let cap = re
+            .captures(line)
+            .with_context_anyhow(|| format!("expected `{expected}`"))
+            .at_line(line_no)?;
+        let x = cap[1]
+            .parse()
+            .at(line_no, cap.get(1).unwrap().start() + 1)?;
+        let y = cap[2]
+            .parse()
+            .at(line_no, cap.get(2).unwrap().start() + 1)?;
+        Ok(Point::new(x, y))
+    };
+
+    let lines: Vec<_> = input.lines().collect();
+    let mut machines = Vec::new();
+    let mut line_no = 1;
+    for sp in lines.split(|l| l.is_empty()) {
+        let [a, b, prize] = sp else {
+            bail!("line {line_no}: expected 3 lines per machine, got {sp:?}");
+        };
+        let a = parse_point(&re_button, a, line_no, "Button A: X+N, Y+N")?;
+        let b = parse_point(&re_button, b, line_no + 1, "Button B: X+N, Y+N")?;
+        let prize = parse_point(&re_prize, prize, line_no + 2, "Prize: X=N, Y=N")?;
+        machines.push(Machine { a, b, prize });
+        line_no += sp.len() + 1;
+    }
+
+    Ok(Problem { machines })
+}
+
+// we should only really have one solution, so this is probably missing
+// the mark
+pub fn solve_brute(machine: &Machine) -> Result<Option<i64>> {
+    let mut best_cost: Option<i64> = None;
+    for a in 0..=100 {
+        for b in 0..=100 {
+            // widened so that huge coefficients can't overflow
+            let x = a as i128 * machine.a.x as i128 + b as i128 * machine.b.x as i128;
+            let y = a as i128 * machine.a.y as i128 + b as i128 * machine.b.y as i128;
+            let cost = a * A_COST + b * B_COST;
+            if x == machine.prize.x as i128 && y == machine.prize.y as i128 {
+                best_cost = best_cost.map(|bc| bc.min(cost)).or(Some(cost));
+            }
+        }
+    }
+    Ok(best_cost)
+}
+
+// is just a simultaneous equation - provided we can find an
+// integer solution, we're good. Worked in i128, since with the part 2
+// offset the cross products like `y * c` overflow i64 for large
+// coefficients.
+pub fn solve_equation(machine: &Machine) -> Result<Option<i64>> {
+    Ok(solve_presses(machine)?.map(|win| win.cost))
+}
+
+/// As `solve_equation`, keeping the presses
+pub fn solve_presses(machine: &Machine) -> Result<Option<Win>> {
+    let x = machine.prize.x as i128;
+    let y = machine.prize.y as i128;
+
+    // x coeffs
+    let c = machine.a.x as i128;
+    let d = machine.b.x as i128;
+
+    // y coeffs
+    let e = machine.a.y as i128;
+    let f = machine.b.y as i128;
+
+    // solve for b; collinear buttons don't occur in the puzzle input
+    let num_b = y * c - x * e;
+    let den_b = c * f - d * e;
+    if den_b == 0 || num_b % den_b != 0 {
+        return Ok(None);
+    }
+    let b = num_b / den_b;
+
+    // solve for a from whichever axis A actually moves along; one of them
+    // must, or the determinant would be zero
+    let (num_a, den_a) = if c != 0 {
+        (x - b * d, c)
+    } else {
+        (y - b * f, e)
+    };
+    if num_a % den_a != 0 {
+        return Ok(None);
+    }
+    let a = num_a / den_a;
+
+    // can't press a button a negative number of times
+    if a < 0 || b < 0 {
+        return Ok(None);
+    }
+
+    let cost = a * A_COST as i128 + b * B_COST as i128;
+    match i64::try_from(cost) {
+        // both presses are at most the cost, so fit too
+        Ok(cost) => Ok(Some(Win {
+            a: a as i64,
+            b: b as i64,
+            cost,
+        })),
+        Err(_) => bail!("cost {cost} for {machine:?} overflows i64"),
+    }
+}
+
+/// An exact fraction in lowest terms, with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rational {
+    pub num: i128,
+    pub den: i128,
+}
+impl Rational {
+    /// `num / den`, reduced; `den` must not be zero
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "zero denominator");
+        let mut g = gcd(num.unsigned_abs(), den.unsigned_abs()) as i128;
+        if den < 0 {
+            g = -g;
+        }
+        Rational {
+            num: num / g,
+            den: den / g,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    /// The nearest integer, rounding halves up
+    pub fn round(&self) -> i128 {
+        (2 * self.num + self.den).div_euclid(2 * self.den)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.den {
+            1 => write!(f, "{}", self.num),
+            den => write!(f, "{}/{den}", self.num),
+        }
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    match b {
+        0 => a.max(1),
+        _ => gcd(b, a % b),
+    }
+}
+
+/// A machine solved over the rationals: the presses that would reach the prize if
+/// fractions of a press were allowed, and the whole presses nearest them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MachineAnalysis {
+    pub a: Rational,
+    pub b: Rational,
+    /// Whole presses of A and B nearest to `a` and `b`, which may be negative
+    pub nearest: (i64, i64),
+    /// Straight-line distance from (`a`, `b`) to `nearest`, in presses
+    pub distance: f64,
+    /// Where the claw ends up after the `nearest` presses, relative to the prize
+    pub miss: Point,
+    /// As found by `solve_presses`
+    pub win: Option<Win>,
+}
+impl MachineAnalysis {
+    /// Which of `solve_presses`' checks turns the machine down, in the order it makes
+    /// them; `None` if it can be won
+    pub fn rejection(&self) -> Option<&'static str> {
+        if !self.b.is_integer() {
+            Some("fractional b")
+        } else if !self.a.is_integer() {
+            Some("fractional a")
+        } else if self.a.num < 0 || self.b.num < 0 {
+            Some("negative presses")
+        } else {
+            None
+        }
+    }
+}
+impl Display for MachineAnalysis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a = {}, b = {}", self.a, self.b)?;
+        match (self.win, self.rejection()) {
+            (Some(win), _) => write!(f, ": won for {}", win.cost),
+            (None, reason) => write!(
+                f,
+                ": {}; nearest a = {}, b = {} is {:.4} presses off and misses by ({}, {})",
+                reason.unwrap_or("not won"),
+                self.nearest.0,
+                self.nearest.1,
+                self.distance,
+                self.miss.x,
+                self.miss.y
+            ),
+        }
+    }
+}
+
+/// How close `machine` comes to being won, for picking out near misses. An error if
+/// the buttons are collinear, so that there's no single solution to be near.
+pub fn analyze(machine: &Machine) -> Result<MachineAnalysis> {
+    let (x, y) = (machine.prize.x as i128, machine.prize.y as i128);
+    let (c, d) = (machine.a.x as i128, machine.b.x as i128);
+    let (e, f) = (machine.a.y as i128, machine.b.y as i128);
+
+    // Cramer's rule, as in `solve_presses` but without dividing out
+    let det = c * f - d * e;
+    if det == 0 {
+        bail!("buttons of {machine:?} are collinear");
+    }
+    let a = Rational::new(x * f - y * d, det);
+    let b = Rational::new(y * c - x * e, det);
+
+    let (near_a, near_b) = (a.round(), b.round());
+    let offset = |r: Rational, n: i128| Rational::new(r.num - n * r.den, r.den).to_f64();
+    let distance = offset(a, near_a).hypot(offset(b, near_b));
+    let miss = |button_a: i128, button_b: i128, prize: i128| {
+        i64::try_from(near_a * button_a + near_b * button_b - prize)
+            .with_context(|| format!("miss for {machine:?} overflows i64"))
+    };
+    let miss = Point::new(miss(c, d, x)?, miss(e, f, y)?);
+    let nearest = match (i64::try_from(near_a), i64::try_from(near_b)) {
+        (Ok(near_a), Ok(near_b)) => (near_a, near_b),
+        _ => bail!("presses for {machine:?} overflow i64"),
+    };
+
+    Ok(MachineAnalysis {
+        a,
+        b,
+        nearest,
+        distance,
+        miss,
+        win: solve_presses(machine)?,
+    })
+}
+
+/// `analyze` for every machine in `problem`, with the part 2 offset if `offset`
+pub fn analyze_all(problem: &Problem, offset: bool) -> Result<Vec<MachineAnalysis>> {
+    problem
+        .machines
+        .iter()
+        .map(|machine| match offset {
+            true => analyze(&offset_machine(machine)?),
+            false => analyze(machine),
+        })
+        .collect()
+}
+
+fn total_cost(costs: impl IntoIterator<Item = Result<Option<i64>>>) -> Result<i64> {
+    let mut total_cost = CheckedAccumulator::sum();
+    for cost in costs {
+        if let Some(cost) = cost? {
+            total_cost.add(cost).context("total cost")?;
+        }
+    }
+    Ok(total_cost.value())
+}
+
+pub fn part1(problem: &Problem, solver: impl Fn(&Machine) -> Result<Option<i64>>) -> Result<i64> {
+    total_cost(problem.machines.iter().map(solver))
+}
+
+/// The machine with its prize moved out by `PART2_OFFSET`, or an error if the
+/// new prize doesn't fit in i64
+fn offset_machine(machine: &Machine) -> Result<Machine> {
+    let prize = Point::new(
+        machine.prize.x.checked_add(PART2_OFFSET).ok_anyhow()?,
+        machine.prize.y.checked_add(PART2_OFFSET).ok_anyhow()?,
+    );
+    Ok(Machine { prize, ..*machine })
+}
+
+pub fn part2(problem: &Problem) -> Result<i64> {
+    total_cost(
+        problem
+            .machines
+            .iter()
+            .map(|p| solve_equation(&offset_machine(p)?)),
+    )
+}
+
+/// How one machine from a batch of inputs does in each part
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineReport {
+    /// Where the machine came from, such as its input file
+    pub source: String,
+    /// Position of the machine in its input, from 0
+    pub index: usize,
+    pub part1: Option<Win>,
+    pub part2: Option<Win>,
+}
+impl MachineReport {
+    pub const CSV_HEADER: &str = "source,machine,\
+        part1_solvable,part1_a,part1_b,part1_cost,\
+        part2_solvable,part2_a,part2_b,part2_cost";
+
+    /// A row to go under `CSV_HEADER`, with the press and cost columns empty for a
+    /// part that can't be won
+    pub fn csv_row(&self) -> String {
+        let win = |win: Option<Win>| match win {
+            Some(Win { a, b, cost }) => format!("true,{a},{b},{cost}"),
+            None => "false,,,".to_string(),
+        };
+        let source = match self.source.contains([',', '"', '\n']) {
+            true => format!("\"{}\"", self.source.replace('"', "\"\"")),
+            false => self.source.clone(),
+        };
+        format!(
+            "{source},{},{},{}",
+            self.index,
+            win(self.part1),
+            win(self.part2)
+        )
+    }
+
+    /// One JSON object, with `null` for a part that can't be won
+    pub fn json_line(&self) -> String {
+        let win = |win: Option<Win>| match win {
+            Some(Win { a, b, cost }) => format!(r#"{{"a":{a},"b":{b},"cost":{cost}}}"#),
+            None => "null".to_string(),
+        };
+        format!(
+            r#"{{"source":{},"machine":{},"part1":{},"part2":{}}}"#,
+            json_string(&self.source),
+            self.index,
+            win(self.part1),
+            win(self.part2)
+        )
+    }
+}
+
+/// Every machine in `problem` solved with and without the part 2 offset; part 1's
+/// 100-press limit isn't applied, as with `solve_equation`
+pub fn machine_reports(source: &str, problem: &Problem) -> Result<Vec<MachineReport>> {
+    problem
+        .machines
+        .iter()
+        .enumerate()
+        .map(|(index, machine)| {
+            Ok(MachineReport {
+                source: source.to_string(),
+                index,
+                part1: solve_presses(machine)?,
+                part2: solve_presses(&offset_machine(machine)?)?,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedMachine {
+    pub machine: Machine,
+    /// cost of the (unique) solution, or `None` if there is no integer solution
+    pub expected_cost: Option<i64>,
+    /// presses are within the 100 limit, so `solve_brute` applies
+    pub small: bool,
+}
+
+/// Generate a mix of solvable and unsolvable machines, both within the 100-press
+/// limit and at part 2 scale. Buttons are never collinear, so every solvable machine
+/// has exactly one solution.
+pub fn generate_machines(n: usize, seed: u64) -> Vec<GeneratedMachine> {
+    let mut rng = common::rng::seeded(seed);
+    let mut machines = Vec::with_capacity(n);
+    while machines.len() < n {
+        let a = Point::new(rng.range(1, 99), rng.range(1, 99));
+        let b = Point::new(rng.range(1, 99), rng.range(1, 99));
+        let det = a.x * b.y - b.x * a.y;
+        if det == 0 {
+            continue;
+        }
+
+        let kind = machines.len() % 4;
+        let small = kind < 2;
+        let solvable = kind % 2 == 0;
+        let (presses_a, presses_b) = if small {
+            (rng.range(0, 100), rng.range(0, 100))
+        } else {
+            let max = PART2_OFFSET / 50;
+            (rng.range(max / 4, max), rng.range(max / 4, max))
+        };
+        let target = Point::new(presses_a, presses_a) * a + Point::new(presses_b, presses_b) * b;
+
+        if solvable {
+            machines.push(GeneratedMachine {
+                machine: Machine {
+                    a,
+                    b,
+                    prize: target,
+                },
+                expected_cost: Some(presses_a * A_COST + presses_b * B_COST),
+                small,
+            });
+        } else {
+            // nudging the prize by one in x moves the (unique) solution by
+            // (b.y, -a.y) / det, which is fractional unless det divides both
+            if b.y % det == 0 && a.y % det == 0 {
+                continue;
+            }
+            machines.push(GeneratedMachine {
+                machine: Machine {
+                    a,
+                    b,
+                    prize: target + Point::new(1, 0),
+                },
+                expected_cost: None,
+                small,
+            });
+        }
+    }
+    machines
+}
+
+/// Cross-check `solve_equation` against the generated answers, and against
+/// `solve_brute` where that applies; returns the number of machines checked
+pub fn stress_test(n: usize, seed: u64) -> Result<usize> {
+    for (i, generated) in generate_machines(n, seed).iter().enumerate() {
+        let equation = solve_equation(&generated.machine)?;
+        if equation != generated.expected_cost {
+            bail!("machine {i} {generated:?}: equation gave {equation:?}");
+        }
+        if generated.small {
+            let brute = solve_brute(&generated.machine)?;
+            if brute != equation {
+                bail!("machine {i} {generated:?}: brute gave {brute:?}, equation {equation:?}");
+            }
+        }
+    }
+    Ok(n)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((
+        part1(&problem, solve_equation)?.to_string(),
+        part2(&problem)?.to_string(),
+    ))
+}
+
+common::aoc_day!(13, "Claw Contraption");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    Button A: X+94, Y+34
+    Button B: X+22, Y+67
+    Prize: X=8400, Y=5400
+
+    Button A: X+26, Y+66
+    Button B: X+67, Y+21
+    Prize: X=12748, Y=12176
+
+    Button A: X+17, Y+86
+    Button B: X+84, Y+37
+    Prize: X=7870, Y=6450
+
+    Button A: X+69, Y+23
+    Button B: X+27, Y+71
+    Prize: X=18641, Y=10279
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("480"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors_have_positions() {
+        let message = |input: &str| format!("{:#}", parse_input(input).unwrap_err());
+        let second = EXAMPLE.replace("Y+21", "Y=21");
+        assert_eq!(message(&second), "line 6: expected `Button B: X+N, Y+N`");
+        let huge = EXAMPLE.replace("X=7870", "X=99999999999999999999");
+        assert_eq!(
+            message(&huge),
+            "line 11, column 10: number too large to fit in target type"
+        );
+        let short = EXAMPLE.replace("Prize: X=8400, Y=5400\n", "");
+        assert!(message(&short).starts_with("line 1: expected 3 lines per machine"));
+    }
+
+    #[test]
+    fn part1_correct_brute() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem, solve_brute)?;
+        assert_eq!(count, 480);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct_equation() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem, solve_equation)?;
+        assert_eq!(count, 480);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_has_solution() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert!(count > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn batch_reports() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let reports = machine_reports("a,b.txt", &problem)?;
+        let rows: Vec<_> = reports.iter().map(MachineReport::csv_row).collect();
+        assert_eq!(rows[0], "\"a,b.txt\",0,true,80,40,280,false,,,");
+        assert_eq!(
+            rows[1],
+            "\"a,b.txt\",1,false,,,,true,118679050709,103199174542,459236326669"
+        );
+        assert_eq!(
+            MachineReport::CSV_HEADER.split(',').count(),
+            rows[0].split(',').count() - 1
+        );
+        assert_eq!(
+            reports[0].json_line(),
+            r#"{"source":"a,b.txt","machine":0,"part1":{"a":80,"b":40,"cost":280},"part2":null}"#
+        );
+
+        // generated machines survive a round trip through the input format
+        let generated = generate_machines(20, 3);
+        let text = generated
+            .iter()
+            .map(|g| g.machine.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let reports = machine_reports("generated", &parse_input(&text)?)?;
+        for (g, report) in generated.iter().zip(&reports) {
+            assert_eq!(report.part1.map(|w| w.cost), g.expected_cost);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn equation_matches_generated_machines() -> Result<()> {
+        for seed in 0..4 {
+            assert_eq!(stress_test(400, seed)?, 400);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generated_machines_are_mixed() {
+        let machines = generate_machines(100, 1);
+        assert_eq!(machines.len(), 100);
+        assert_eq!(machines.iter().filter(|m| m.small).count(), 50);
+        assert_eq!(
+            machines
+                .iter()
+                .filter(|m| m.expected_cost.is_some())
+                .count(),
+            50
+        );
+        assert!(machines
+            .iter()
+            .filter(|m| !m.small)
+            .all(|m| m.machine.prize.x > PART2_OFFSET / 100));
+    }
+
+    #[test]
+    fn solver_second_machine_solves() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let machine = &problem.machines[1];
+        let machine = offset_machine(machine).unwrap();
+        let cost = solve_equation(&machine).unwrap();
+        assert!(cost.is_some());
+    }
+
+    const NEAR_MAX: i64 = i64::MAX / 4;
+
+    fn machine(a: (i64, i64), b: (i64, i64), prize: (i64, i64)) -> Machine {
+        Machine {
+            a: Point::new(a.0, a.1),
+            b: Point::new(b.0, b.1),
+            prize: Point::new(prize.0, prize.1),
+        }
+    }
+
+    #[test]
+    fn huge_coefficients_solve() -> Result<()> {
+        // 2 A + 3 B, with cross products far beyond i64
+        let m = machine(
+            (NEAR_MAX, 1),
+            (1, NEAR_MAX),
+            (2 * NEAR_MAX + 3, 2 + 3 * NEAR_MAX),
+        );
+        assert_eq!(solve_equation(&m)?, Some(9));
+        assert_eq!(solve_brute(&m)?, Some(9));
+
+        // one off in x is no longer reachable
+        let m = machine(
+            (NEAR_MAX, 1),
+            (1, NEAR_MAX),
+            (2 * NEAR_MAX + 4, 2 + 3 * NEAR_MAX),
+        );
+        assert_eq!(solve_equation(&m)?, None);
+        assert_eq!(solve_brute(&m)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn a_without_x_movement() -> Result<()> {
+        // A only moves in y, so its presses come from the y equation
+        let m = machine((0, 5), (3, 1), (9, 13));
+        assert_eq!(solve_equation(&m)?, Some(2 * A_COST + 3 * B_COST));
+        assert_eq!(solve_brute(&m)?, solve_equation(&m)?);
+        Ok(())
+    }
+
+    #[test]
+    fn negative_presses_rejected() -> Result<()> {
+        // -1 A + 3 B is the only solution
+        let m = machine((2, 1), (1, 2), (1, 5));
+        assert_eq!(solve_equation(&m)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn analysis_explains_rejections() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let analyses = analyze_all(&problem, false)?;
+        assert_eq!(analyses[0].to_string(), "a = 80, b = 40: won for 280");
+        // fractional presses bring the claw within a button press of the prize
+        let second = &analyses[1];
+        assert_eq!(second.rejection(), Some("fractional b"));
+        assert!(second.distance > 0.0 && second.distance <= 0.5_f64.sqrt());
+        assert!(second.miss.x.abs() <= 67 + 26 && second.miss.y.abs() <= 66 + 21);
+        assert!(analyze_all(&problem, true)?[1].win.is_some());
+
+        let m = machine((2, 1), (1, 2), (1, 5));
+        let negative = analyze(&m)?;
+        assert_eq!(
+            (negative.a, negative.b),
+            (Rational::new(-1, 1), Rational::new(3, 1))
+        );
+        assert_eq!(negative.rejection(), Some("negative presses"));
+        assert_eq!((negative.distance, negative.miss), (0.0, Point::new(0, 0)));
+
+        // halfway presses: 1.5 A reaches the prize
+        let half = analyze(&machine((2, 0), (0, 2), (3, 4)))?;
+        assert_eq!(half.a.to_string(), "3/2");
+        assert_eq!((half.nearest, half.distance), ((2, 2), 0.5));
+        assert_eq!(half.miss, Point::new(1, 0));
+
+        assert!(analyze(&machine((1, 2), (2, 4), (3, 6))).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn analysis_agrees_with_solver() -> Result<()> {
+        for generated in generate_machines(400, 5) {
+            let analysis = analyze(&generated.machine)?;
+            let whole = analysis.a.is_integer() && analysis.b.is_integer();
+            assert_eq!(analysis.rejection().is_none(), analysis.win.is_some());
+            assert_eq!(analysis.win.map(|w| w.cost), generated.expected_cost);
+            assert_eq!(whole, analysis.distance == 0.0);
+            assert_eq!(whole, analysis.miss == Point::new(0, 0));
+            if let Some(win) = analysis.win {
+                assert_eq!(analysis.nearest, (win.a, win.b));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn overflowing_costs_are_errors() {
+        // i64::MAX presses of A can't be paid for in i64
+        let m = machine((1, 0), (0, 1), (i64::MAX, 0));
+        assert!(solve_equation(&m).is_err());
+
+        // two affordable machines whose total isn't
+        let m = machine((1, 0), (0, 1), (NEAR_MAX, 0));
+        let problem = Problem {
+            machines: vec![m.clone(), m],
+        };
+        assert!(part1(&problem, solve_equation).is_err());
+
+        // the offset prize no longer fits
+        let problem = Problem {
+            machines: vec![machine((1, 0), (0, 1), (i64::MAX - 5, 0))],
+        };
+        assert!(part2(&problem).is_err());
+    }
+}
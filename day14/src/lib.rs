@@ -0,0 +1,560 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use anyhow::{bail, Context, Result};
+use common::{
+    cartesian::{Point, Vec2},
+    math::{checked_product, kahan_sum},
+    OptionAnyhow,
+};
+use nalgebra::DMatrix;
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct Robot {
+    p: Point,
+    v: Point,
+}
+
+/// Statistics over one full period of the robots' motion; each is the first step at
+/// which the value is reached, with the value
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodStats {
+    pub period: usize,
+    pub min_safety: (usize, i64),
+    pub max_safety: (usize, i64),
+    pub min_spread: (usize, f64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    robots: Vec<Robot>,
+    rows: i64,
+    cols: i64,
+}
+impl Problem {
+    fn step(&mut self) {
+        for robot in self.robots.iter_mut() {
+            robot.p = robot.p + robot.v;
+            robot.p.x = robot.p.x.rem_euclid(self.cols);
+            robot.p.y = robot.p.y.rem_euclid(self.rows);
+        }
+    }
+
+    /// Product of the robot counts in each cell of the grid, split into `splits_x` by
+    /// `splits_y` cells; robots on the dividing lines don't count
+    pub fn safety_factor(&self, splits_x: i64, splits_y: i64) -> Result<i64> {
+        let split_x = Split::new(self.cols, splits_x)?;
+        let split_y = Split::new(self.rows, splits_y)?;
+
+        let mut cells = DMatrix::from_element(splits_y as usize, splits_x as usize, 0);
+        for robot in self.robots.iter() {
+            if let Some(p) = grid_cell(robot.p, &split_x, &split_y) {
+                *cells.get_mut(p).unwrap() += 1;
+            }
+        }
+        checked_product(cells.iter().copied()).context("safety factor")
+    }
+
+    /// The robots after `steps` steps, jumped to directly rather than stepped through
+    pub fn after(&self, steps: i64) -> Problem {
+        // positions repeat every lcm(rows, cols) steps, which keeps the jump small
+        let steps = steps.rem_euclid(self.rows / gcd(self.rows, self.cols) * self.cols);
+        let mut problem = self.clone();
+        for robot in problem.robots.iter_mut() {
+            robot.p.x = (robot.p.x + robot.v.x * steps).rem_euclid(self.cols);
+            robot.p.y = (robot.p.y + robot.v.y * steps).rem_euclid(self.rows);
+        }
+        problem
+    }
+
+    fn positions_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for robot in &self.robots {
+            robot.p.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn same_positions(&self, other: &Problem) -> bool {
+        self.robots
+            .iter()
+            .zip(&other.robots)
+            .all(|(a, b)| a.p == b.p)
+    }
+
+    /// Steps until the robots' positions repeat. Each step is reversible, so the
+    /// first repeat is always of the starting positions; frames are compared by hash,
+    /// checking the positions only when the hash matches. Never more than
+    /// lcm(rows, cols), but can be less, e.g. if no robot moves vertically.
+    pub fn find_cycle(&self) -> usize {
+        let start_hash = self.positions_hash();
+        let mut problem = self.clone();
+        for steps in 1.. {
+            problem.step();
+            if problem.positions_hash() == start_hash && problem.same_positions(self) {
+                return steps;
+            }
+        }
+        unreachable!()
+    }
+
+    /// Safety factor (for `splits_x` by `splits_y` cells) and spread at every step of
+    /// exactly one period
+    pub fn period_stats(&self, splits_x: i64, splits_y: i64) -> Result<PeriodStats> {
+        let period = self.find_cycle();
+        let mut problem = self.clone();
+        let safety = problem.safety_factor(splits_x, splits_y)?;
+        let mut stats = PeriodStats {
+            period,
+            min_safety: (0, safety),
+            max_safety: (0, safety),
+            min_spread: (0, problem.spread()),
+        };
+        for i in 1..period {
+            problem.step();
+            let safety = problem.safety_factor(splits_x, splits_y)?;
+            if safety < stats.min_safety.1 {
+                stats.min_safety = (i, safety);
+            }
+            if safety > stats.max_safety.1 {
+                stats.max_safety = (i, safety);
+            }
+            let spread = problem.spread();
+            if spread < stats.min_spread.1 {
+                stats.min_spread = (i, spread);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// The most robots ever sharing a tile, and the first step it happens; positions
+    /// repeat after lcm(rows, cols) steps, so that's as far as we need to look
+    pub fn max_stack(&self) -> (i64, u32) {
+        let mut problem = self.clone();
+        let period = self.rows / gcd(self.rows, self.cols) * self.cols;
+
+        let mut best = (0, 0);
+        for i in 0..period {
+            let stack = density_grid(&problem).max();
+            if stack > best.1 {
+                best = (i, stack);
+            }
+            problem.step();
+        }
+        best
+    }
+
+    /// Mean squared distance of the robots from their centroid
+    pub fn spread(&self) -> f64 {
+        let positions = || self.robots.iter().map(|r| Vec2::from(r.p));
+        let Some(centroid) = Vec2::centroid(positions()) else {
+            return 0.0;
+        };
+        kahan_sum(positions().map(|p| (p - centroid).norm_squared())) / self.robots.len() as f64
+    }
+
+    /// First step at which the robots are most tightly clustered, looking over one
+    /// period of lcm(rows, cols) steps
+    pub fn tightest_step(&self) -> i64 {
+        let mut problem = self.clone();
+        let period = self.rows / gcd(self.rows, self.cols) * self.cols;
+
+        let mut best = (0, f64::INFINITY);
+        for i in 0..period {
+            let spread = problem.spread();
+            if spread < best.1 {
+                best = (i, spread);
+            }
+            problem.step();
+        }
+        best.0
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn parse_input(input: &str, rows: i64, cols: i64) -> Result<Problem> {
+    let re = Regex::new(r#"p=(-?\d+),(-?\d+) v=(-?\d+),(-?\d+)"#).unwrap();
+    let mut robots = Vec::new();
+    for l in input.lines() {
+        let cap = re.captures(l).ok_anyhow()?;
+        let p = Point::new(cap[1].parse()?, cap[2].parse()?);
+        let v = Point::new(cap[3].parse()?, cap[4].parse()?);
+        robots.push(Robot { p, v });
+    }
+    Ok(Problem { robots, rows, cols })
+}
+
+/// One axis split into equal bands; any tiles left over must form a one-tile dividing
+/// line between each pair of bands, like the middle row and column for quadrants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Split {
+    width: i64,
+    gap: i64,
+}
+impl Split {
+    fn new(len: i64, splits: i64) -> Result<Split> {
+        if splits < 1 || splits > len {
+            bail!("can't split {len} tiles {splits} ways");
+        }
+        let gaps = splits - 1;
+        if len % splits == 0 {
+            Ok(Split {
+                width: len / splits,
+                gap: 0,
+            })
+        } else if (len - gaps) % splits == 0 {
+            Ok(Split {
+                width: (len - gaps) / splits,
+                gap: 1,
+            })
+        } else {
+            bail!("{len} tiles don't split evenly {splits} ways");
+        }
+    }
+
+    /// Band containing `v`, or `None` on a dividing line
+    fn band(&self, v: i64) -> Option<i64> {
+        let period = self.width + self.gap;
+        (v % period < self.width).then_some(v / period)
+    }
+}
+
+fn grid_cell(p: Point, split_x: &Split, split_y: &Split) -> Option<Point> {
+    Some(Point::new(split_x.band(p.x)?, split_y.band(p.y)?))
+}
+
+/// Number of robots on each tile
+fn density_grid(problem: &Problem) -> DMatrix<u32> {
+    let mut grid = DMatrix::from_element(problem.rows as usize, problem.cols as usize, 0);
+    for robot in problem.robots.iter() {
+        *grid.get_mut(robot.p).unwrap() += 1;
+    }
+    grid
+}
+
+/// Pixels along each side of a tile in `render_frame_to_image`
+pub const FRAME_SCALE: usize = 4;
+
+/// The robots as a binary PPM image, `scale` pixels to a tile side: empty tiles are
+/// black, and tiles get brighter green the more robots are on them
+pub fn render_ppm(problem: &Problem, scale: usize) -> Vec<u8> {
+    let grid = density_grid(problem);
+    let (width, height) = (grid.ncols() * scale, grid.nrows() * scale);
+    let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+    image.reserve(width * height * 3);
+    for row in 0..height {
+        for col in 0..width {
+            let pixel = match grid[(row / scale, col / scale)] {
+                0 => [0, 0, 0],
+                1 => [40, 170, 60],
+                2 => [80, 210, 100],
+                _ => [160, 255, 170],
+            };
+            image.extend(pixel);
+        }
+    }
+    image
+}
+
+/// Write the robots to `path` as a PPM image, as by `render_ppm` at `FRAME_SCALE`
+pub fn render_frame_to_image(problem: &Problem, path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    std::fs::write(path, render_ppm(problem, FRAME_SCALE))
+        .with_context(|| format!("writing {}", path.display()))
+}
+
+fn print_robots(problem: &Problem) {
+    let grid = density_grid(problem).map(|x| if x > 0 { '#' } else { '.' });
+
+    println!("{}", grid);
+}
+
+pub fn part1(problem: &Problem) -> Result<i64> {
+    let mut problem = problem.clone();
+
+    // iterate
+    for _ in 0..100 {
+        problem.step();
+    }
+
+    // count quadrants
+    problem.safety_factor(2, 2)
+}
+
+// This works, but it doesn't work very well. It assumes the tree is
+// centred, and it is definitely not. Good enough to get a result though.
+// A smarter plan would be to scan for continuous lines, preferably
+// horizontal ones, since the tree has lots of those.
+fn row_symmetry_score(mat: &DMatrix<u32>, row: usize) -> usize {
+    let len = mat.ncols();
+    let x_mid = len / 2;
+    let mut diffs = 0;
+    for i in 0..x_mid {
+        let ir = len - i - 1;
+        let l = mat[(row, i)];
+        let r = mat[(row, ir)];
+        if l != r {
+            diffs += 1;
+        }
+    }
+    diffs
+}
+
+pub fn part2(problem: &Problem) -> Result<i64> {
+    let period = problem.find_cycle();
+    let mut problem = problem.clone();
+
+    // iterate over one period; after that it's all repeats
+    let mut printed_count = 0;
+    for i in 1..=period {
+        problem.step();
+
+        let grid = density_grid(&problem);
+
+        // detect left-right symmetry
+        let mut diffs = 0;
+        for r in 0..grid.nrows() {
+            diffs += row_symmetry_score(&grid, r);
+        }
+
+        // played around with the threshold; 350 works
+        if diffs < 350 {
+            print_robots(&problem);
+            println!("iteration number {}", i);
+            printed_count += 1;
+            if printed_count == 5 {
+                break;
+            }
+        }
+    }
+
+    Ok(123)
+}
+
+/// The tree packs most of the robots into one small area, which shows up as a sharp
+/// minimum in their spread; unlike the symmetry check, it doesn't care where the
+/// tree is
+pub fn part2_variance(problem: &Problem) -> Result<i64> {
+    Ok(problem.tightest_step())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input, 103, 101)?;
+    Ok((
+        part1(&problem)?.to_string(),
+        part2_variance(&problem)?.to_string(),
+    ))
+}
+
+/// Part 1 for the example, on its smaller grid; it has no tree for part 2
+pub fn solve_example(input: &str) -> Result<(String, Option<String>)> {
+    let (rows, cols) = EXAMPLE_DIMS;
+    let problem = parse_input(input, rows, cols)?;
+    Ok((part1(&problem)?.to_string(), None))
+}
+
+common::aoc_day!(14, "Restroom Redoubt", example: solve_example);
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    p=0,4 v=3,-3
+    p=6,3 v=-1,-3
+    p=10,3 v=-1,2
+    p=2,0 v=2,-1
+    p=0,0 v=1,3
+    p=3,0 v=-2,-2
+    p=7,6 v=-1,-3
+    p=3,0 v=-1,-2
+    p=9,3 v=2,3
+    p=7,3 v=-1,2
+    p=2,4 v=2,-3
+    p=9,5 v=-3,-3
+"};
+/// The example's floor, which is smaller than the puzzle's 103 rows by 101 columns
+pub const EXAMPLE_DIMS: (i64, i64) = (7, 11);
+// there's no tree to find in the example
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("12"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::dmatrix;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 12);
+        Ok(())
+    }
+
+    #[test]
+    fn splits() -> Result<()> {
+        // 11 wide: halves of 5 with a middle line, or 11 single columns
+        let halves = Split::new(11, 2)?;
+        let bands: Vec<_> = (0..11).map(|x| halves.band(x)).collect();
+        assert_eq!(bands[..5], [Some(0); 5]);
+        assert_eq!(bands[5], None);
+        assert_eq!(bands[6..], [Some(1); 5]);
+        let thirds = Split::new(11, 3)?;
+        assert_eq!(thirds, Split { width: 3, gap: 1 });
+        assert_eq!(
+            (thirds.band(3), thirds.band(4), thirds.band(10)),
+            (None, Some(1), Some(2))
+        );
+        assert_eq!(Split::new(12, 4)?, Split { width: 3, gap: 0 });
+        assert_eq!(Split::new(11, 4)?, Split { width: 2, gap: 1 });
+        assert!(Split::new(11, 5).is_err());
+        assert!(Split::new(11, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn safety_factor_splits() -> Result<()> {
+        let mut problem = parse_input(EXAMPLE, 7, 11)?;
+        for _ in 0..100 {
+            problem.step();
+        }
+        assert_eq!(problem.safety_factor(2, 2)?, 12);
+
+        // everything in one cell is just the robot count
+        assert_eq!(problem.safety_factor(1, 1)?, 12);
+
+        // single tiles: some are always empty
+        assert_eq!(problem.safety_factor(11, 7)?, 0);
+        assert!(problem.safety_factor(2, 5).is_err());
+
+        // 2^16 robots in each quadrant
+        let corners = "p=0,0 v=0,0\np=10,0 v=0,0\np=0,6 v=0,0\np=10,6 v=0,0\n";
+        let crowd = parse_input(&corners.repeat(1 << 16), 7, 11)?;
+        assert_eq!(crowd.safety_factor(1, 2)?, 1 << 34);
+        assert!(crowd.safety_factor(2, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn density_and_stacking() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        let grid = density_grid(&problem);
+        assert_eq!(grid.shape(), (7, 11));
+        assert_eq!(grid.sum(), 12);
+        // two robots start at 3,0
+        assert_eq!(grid[(0, 3)], 2);
+        assert!(problem.max_stack().1 >= 2);
+
+        // two robots that meet after 2 steps and then move together
+        let problem = parse_input("p=0,0 v=1,1\np=2,0 v=0,1\n", 5, 5)?;
+        assert_eq!(problem.max_stack(), (2, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn jumps_match_steps() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        let mut stepped = problem.clone();
+        for steps in 0..100 {
+            assert!(problem.after(steps).same_positions(&stepped), "{steps}");
+            stepped.step();
+        }
+        // a period on, and a period back
+        assert!(problem.after(77 + 5).same_positions(&problem.after(5)));
+        assert!(problem.after(-1).same_positions(&problem.after(76)));
+        Ok(())
+    }
+
+    #[test]
+    fn frames_rendered() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        let image = render_ppm(&problem, 2);
+        let header = b"P6\n22 14\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(image.len(), header.len() + 22 * 14 * 3);
+        // two robots stacked on tile 3,0, which covers pixels 6 and 7 of the top rows
+        let pixel = |x: usize, y: usize| &image[header.len() + (y * 22 + x) * 3..][..3];
+        assert_eq!(pixel(7, 1), [80, 210, 100]);
+        assert_eq!(pixel(0, 0), [40, 170, 60]);
+        assert_eq!(pixel(2, 0), [0, 0, 0]);
+
+        let path = std::env::temp_dir().join(format!("day14_frame_{}.ppm", std::process::id()));
+        render_frame_to_image(&problem, &path)?;
+        let written = std::fs::read(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(written, render_ppm(&problem, FRAME_SCALE));
+        Ok(())
+    }
+
+    #[test]
+    fn spread_finds_cluster() -> Result<()> {
+        // four robots at the corners of a square that gathers in the middle at step 3
+        let robots = "p=0,0 v=1,1\np=6,0 v=-1,1\np=0,6 v=1,-1\np=6,6 v=-1,-1\n";
+        let problem = parse_input(robots, 7, 7)?;
+        assert_eq!(problem.spread(), 18.0);
+        assert_eq!(problem.tightest_step(), 3);
+        assert_eq!(part2_variance(&problem)?, 3);
+
+        let problem = parse_input("p=2,3 v=1,1\n", 7, 7)?;
+        assert_eq!(problem.spread(), 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn cycle_and_period_stats() -> Result<()> {
+        let problem = parse_input(EXAMPLE, 7, 11)?;
+        assert_eq!(problem.find_cycle(), 77);
+
+        // no vertical movement, so only the columns matter
+        let problem = parse_input("p=0,0 v=2,0\np=3,4 v=-1,0\n", 7, 11)?;
+        assert_eq!(problem.find_cycle(), 11);
+        let problem = parse_input("p=1,1 v=0,0\n", 7, 11)?;
+        assert_eq!(problem.find_cycle(), 1);
+
+        // the square from `spread_finds_cluster`, gathering in the middle at step 3
+        let robots = "p=0,0 v=1,1\np=6,0 v=-1,1\np=0,6 v=1,-1\np=6,6 v=-1,-1\n";
+        let problem = parse_input(robots, 7, 7)?;
+        let stats = problem.period_stats(2, 2)?;
+        assert_eq!(stats.period, 7);
+        assert_eq!(stats.min_spread, (3, 0.0));
+        assert_eq!(stats.max_safety, (0, 1));
+        assert_eq!(stats.min_safety, (3, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn symmetry_detect() {
+        let g1 = dmatrix![
+            1, 0, 1;
+            0, 1, 0;
+            0, 0, 1
+        ];
+        assert_eq!(row_symmetry_score(&g1, 0), 0);
+        assert_eq!(row_symmetry_score(&g1, 1), 0);
+        assert_eq!(row_symmetry_score(&g1, 2), 1);
+
+        let g2 = dmatrix![
+            1, 2, 3, 100, 3, 2, 1;
+            0, 1, 0, 100, 0, 1, 0;
+            0, 1, 0, 100, 5, 1, 0;
+        ];
+        assert_eq!(row_symmetry_score(&g2, 0), 0);
+        assert_eq!(row_symmetry_score(&g2, 1), 0);
+        assert_eq!(row_symmetry_score(&g2, 2), 1);
+    }
+}
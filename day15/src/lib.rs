@@ -0,0 +1,654 @@
+use anyhow::{bail, Result};
+use common::{
+    cartesian::{Point, ScreenDir},
+    OptionAnyhow,
+};
+use nalgebra::DMatrix;
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+    iter,
+};
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+enum Block {
+    #[default]
+    Open,
+    // whole box for part 1
+    BoxWhole,
+    // left or right part of box for part 2
+    BoxL,
+    BoxR,
+    Wall,
+}
+
+type Map = DMatrix<Block>;
+type Instructions = Vec<ScreenDir>;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    map: Map,
+    robot: Point,
+    instructions: Instructions,
+    /// GPS score of the boxes, kept up to date as they move
+    score: usize,
+}
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                let p = Point::new(c as i64, r as i64);
+                let b = self.map.get(p).unwrap();
+                if p == self.robot {
+                    write!(f, "@")?;
+                    assert!(*b == Block::Open);
+                } else {
+                    let ch = match *b {
+                        Block::Open => ".",
+                        Block::BoxWhole => "O",
+                        Block::BoxL => "[",
+                        Block::BoxR => "]",
+                        Block::Wall => "#",
+                    };
+                    write!(f, "{}", ch)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_map_lines<'a>(map_lines: impl Iterator<Item = &'a str>) -> Result<(Map, Point)> {
+    let map_lines: Vec<_> = map_lines.collect();
+    let mut robot = Point::default();
+    let rows = map_lines.len();
+    let cols = map_lines
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .expect_anyhow("map missing")?;
+    let mut map = DMatrix::from_element(rows, cols, Block::default());
+    for (r, line) in map_lines.iter().enumerate() {
+        for (c, ch) in line.chars().enumerate() {
+            let block = match ch {
+                '#' => Block::Wall,
+                'O' => Block::BoxWhole,
+                '.' => Block::Open,
+                '@' => {
+                    robot = Point::new(c as i64, r as i64);
+                    Block::Open
+                }
+                _ => bail!("Unknown block type {}", ch),
+            };
+            map[(r, c)] = block;
+        }
+    }
+    Ok((map, robot))
+}
+
+fn parse_instruction(ch: char) -> Result<ScreenDir> {
+    Ok(match ch {
+        '<' => ScreenDir::L,
+        '>' => ScreenDir::R,
+        'v' => ScreenDir::D,
+        '^' => ScreenDir::U,
+        _ => bail!("Unknown instruction {}", ch),
+    })
+}
+
+/// Map and instructions, separated by a blank line
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut lines_iter = input.lines();
+    let (map, robot) = parse_map_lines((&mut lines_iter).take_while(|l| !l.is_empty()))?;
+    let instructions = lines_iter
+        .flat_map(str::chars)
+        .map(parse_instruction)
+        .collect::<Result<_>>()?;
+
+    Ok(Problem::new(map, robot, instructions))
+}
+
+/// Map only, up to the first blank line; instructions are supplied separately
+pub fn parse_map(input: &str) -> Result<Problem> {
+    let (map, robot) = parse_map_lines(input.lines().take_while(|l| !l.is_empty()))?;
+    Ok(Problem::new(map, robot, Vec::new()))
+}
+
+/// Lazily read instructions, ignoring line breaks; the stream may be any length
+pub fn read_instructions(reader: impl BufRead) -> impl Iterator<Item = Result<ScreenDir>> {
+    reader.bytes().filter_map(|b| match b {
+        Ok(b'\n' | b'\r') => None,
+        Ok(b) => Some(parse_instruction(b as char)),
+        Err(e) => Some(Err(e.into())),
+    })
+}
+
+fn dir_iter(loc: Point, dir: ScreenDir) -> impl Iterator<Item = Point> {
+    let dir_pt = dir.into();
+    iter::successors(Some(loc + dir_pt), move |p| Some(*p + dir_pt))
+}
+
+/// GPS coordinate of a box whose (left) edge is at `p`
+fn box_gps(p: Point) -> usize {
+    (100 * p.y + p.x) as usize
+}
+
+impl Problem {
+    fn new(map: Map, robot: Point, instructions: Instructions) -> Self {
+        let mut problem = Problem {
+            map,
+            robot,
+            instructions,
+            score: 0,
+        };
+        problem.score = problem.rescan_score();
+        problem
+    }
+
+    /// Sum of the GPS coordinates of the boxes, as the robot has left them
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    /// The score worked out from scratch, scanning the whole map
+    fn rescan_score(&self) -> usize {
+        let mut score = 0;
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                score += match self.map[(r, c)] {
+                    Block::BoxWhole | Block::BoxL => 100 * r + c,
+                    _ => 0,
+                }
+            }
+        }
+        score
+    }
+
+    /// Move the robot, pushing whole boxes; returns the number of boxes moved,
+    /// or `None` if blocked
+    fn move_robot_part_1(&mut self, dir: ScreenDir) -> Result<Option<usize>> {
+        let p = self.robot;
+
+        let num_boxes = dir_iter(p, dir)
+            .map(|p| self.map.get(p))
+            .take_while(|b| b.copied() == Some(Block::BoxWhole))
+            .count();
+
+        let loc_after_boxes = dir_iter(p, dir).nth(num_boxes).unwrap();
+        let block_after_boxes = match self.map.get(loc_after_boxes).copied() {
+            Some(b) => b,
+            None => bail!("robot at {p:?} pushed off the map at {loc_after_boxes:?}"),
+        };
+        match block_after_boxes {
+            Block::Open => {}
+            Block::Wall => return Ok(None),
+            b => bail!("unexpected block {b:?} at {loc_after_boxes:?} in part 1 map"),
+        }
+
+        // move the whole chain, which is the same as moving its first box to the end
+        let robot_next = dir_iter(p, dir).nth(0).unwrap();
+        if num_boxes > 0 {
+            *self.map.get_mut(loc_after_boxes).unwrap() = Block::BoxWhole;
+            self.score = self.score - box_gps(robot_next) + box_gps(loc_after_boxes);
+        }
+        *self.map.get_mut(robot_next).unwrap() = Block::Open;
+        self.robot = robot_next;
+
+        Ok(Some(num_boxes))
+    }
+
+    /// Move the robot, pushing wide boxes; returns the number of box halves moved,
+    /// or `None` if blocked
+    fn move_robot_part_2(&mut self, dir: ScreenDir) -> Result<Option<usize>> {
+        let p = self.robot;
+        let dp: Point = dir.into();
+
+        let mut move_set: HashMap<Point, Block> = HashMap::new();
+        let mut to_visit = Vec::new();
+        let mut visited = HashSet::new();
+
+        // build set of affected boxes
+        to_visit.push(self.robot + dp);
+        while let Some(p) = to_visit.pop() {
+            if visited.contains(&p) {
+                continue;
+            }
+
+            let b = match self.map.get(p) {
+                Some(b) => *b,
+                None => bail!("robot at {:?} pushed off the map at {p:?}", self.robot),
+            };
+
+            // collision with wall - no move possible
+            if b == Block::Wall {
+                return Ok(None);
+            }
+
+            match dir {
+                // left-right
+                ScreenDir::L | ScreenDir::R => match b {
+                    Block::BoxL | Block::BoxR => {
+                        move_set.insert(p, b);
+                        to_visit.push(p + dp);
+                    }
+                    Block::Open => {}
+                    _ => bail!("unexpected block {b:?} at {p:?} in part 2 map"),
+                },
+                // up-down
+                ScreenDir::U | ScreenDir::D => match b {
+                    Block::BoxL => {
+                        let other_side_box = Point::new(1, 0);
+                        self.expect_block(p + other_side_box, Block::BoxR)?;
+                        move_set.insert(p, b);
+                        move_set.insert(p + other_side_box, Block::BoxR);
+                        to_visit.push(p + dp);
+                        to_visit.push(p + other_side_box + dp);
+                    }
+                    Block::BoxR => {
+                        let other_side_box = Point::new(-1, 0);
+                        self.expect_block(p + other_side_box, Block::BoxL)?;
+                        move_set.insert(p, b);
+                        move_set.insert(p + other_side_box, Block::BoxL);
+                        to_visit.push(p + dp);
+                        to_visit.push(p + other_side_box + dp);
+                    }
+                    Block::Open => {}
+                    _ => bail!("unexpected block {b:?} at {p:?} in part 2 map"),
+                },
+            }
+            visited.insert(p);
+        }
+
+        // at this point, we know we have no collisions with walls; move the whole thing
+        // 1. clear map
+        for (p, _b) in move_set.iter() {
+            *self.map.get_mut(*p).unwrap() = Block::Open;
+        }
+        // 2. place boxes in new location, scoring them by their left halves
+        for (p, b) in move_set.iter() {
+            if *b == Block::BoxL {
+                self.score = self.score - box_gps(*p) + box_gps(*p + dp);
+            }
+            let p = *p + dp;
+            *self.map.get_mut(p).unwrap() = *b;
+        }
+        // 3. update robot position
+        self.robot = p + dp;
+
+        Ok(Some(move_set.len()))
+    }
+
+    fn expect_block(&self, p: Point, expected: Block) -> Result<()> {
+        match self.map.get(p) {
+            Some(b) if *b == expected => Ok(()),
+            b => bail!("expected {expected:?} at {p:?} but found {b:?}"),
+        }
+    }
+
+    /// Check the widened map only contains open space, walls and complete boxes,
+    /// with the robot in open space
+    fn validate_part_2_map(&self) -> Result<()> {
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                let p = Point::from((r, c));
+                match self.map[(r, c)] {
+                    Block::Open | Block::Wall => {}
+                    Block::BoxL => self.expect_block(p + Point::new(1, 0), Block::BoxR)?,
+                    Block::BoxR => self.expect_block(p + Point::new(-1, 0), Block::BoxL)?,
+                    Block::BoxWhole => bail!("part 2 map should not contain BoxWhole at {p:?}"),
+                }
+            }
+        }
+        self.expect_block(self.robot, Block::Open)
+    }
+
+    fn to_part_2_problem(&self) -> Result<Self> {
+        let mut new_map =
+            DMatrix::from_element(self.map.nrows(), self.map.ncols() * 2, Block::Open);
+
+        for r in 0..self.map.nrows() {
+            for c in 0..self.map.ncols() {
+                let (left, right) = match self.map[(r, c)] {
+                    Block::Open => (Block::Open, Block::Open),
+                    Block::BoxWhole => (Block::BoxL, Block::BoxR),
+                    Block::BoxL => bail!("part 1 map should not contain BoxL"),
+                    Block::BoxR => bail!("part 1 map should not contain BoxR"),
+                    Block::Wall => (Block::Wall, Block::Wall),
+                };
+                new_map[(r, 2 * c)] = left;
+                new_map[(r, 2 * c + 1)] = right;
+            }
+        }
+
+        let problem = Problem::new(new_map, self.robot * Point::new(2, 1), Vec::new());
+        problem.validate_part_2_map()?;
+        Ok(problem)
+    }
+}
+
+fn embedded(problem: &Problem) -> impl Iterator<Item = Result<ScreenDir>> + '_ {
+    problem.instructions.iter().copied().map(Ok)
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    part1_with(problem, embedded(problem))
+}
+
+pub fn part2(problem: &Problem) -> Result<usize> {
+    part2_with(problem, embedded(problem))
+}
+
+/// Part 1 on `problem`'s map, taking instructions from `instructions` as they arrive
+pub fn part1_with(
+    problem: &Problem,
+    instructions: impl IntoIterator<Item = Result<ScreenDir>>,
+) -> Result<usize> {
+    let mut problem = Problem::new(problem.map.clone(), problem.robot, Vec::new());
+
+    for inst in instructions {
+        problem.move_robot_part_1(inst?)?;
+    }
+
+    Ok(problem.score())
+}
+
+/// Part 2 on `problem`'s widened map, taking instructions from `instructions` as they arrive
+pub fn part2_with(
+    problem: &Problem,
+    instructions: impl IntoIterator<Item = Result<ScreenDir>>,
+) -> Result<usize> {
+    let mut problem = problem.to_part_2_problem()?;
+
+    for inst in instructions {
+        problem.move_robot_part_2(inst?)?;
+    }
+
+    Ok(problem.score())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(15, "Warehouse Woes");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    ##########
+    #..O..O.O#
+    #......O.#
+    #.OO..O.O#
+    #..O@..O.#
+    #O#..O...#
+    #O..O..O.#
+    #.OO.O.OO#
+    #....O...#
+    ##########
+
+    <vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+    vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+    ><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+    <<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+    ^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+    ^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+    >^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+    <><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+    ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+    v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("10092"), Some("9021")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use proptest::prelude::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE_SMALL)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_small_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE_SMALL)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 2028);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 10092);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_small_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE_SMALL_PART2)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 618);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 9021);
+        Ok(())
+    }
+
+    #[test]
+    fn score_tracks_rescan() -> Result<()> {
+        for example in [EXAMPLE_SMALL, EXAMPLE] {
+            let problem = parse_input(example)?;
+            let mut p1 = Problem::new(problem.map.clone(), problem.robot, Vec::new());
+            let mut p2 = problem.to_part_2_problem()?;
+            assert_eq!(p1.score(), p1.rescan_score());
+            assert_eq!(p2.score(), p2.rescan_score());
+            for (i, &dir) in problem.instructions.iter().enumerate() {
+                p1.move_robot_part_1(dir)?;
+                p2.move_robot_part_2(dir)?;
+                assert_eq!(p1.score(), p1.rescan_score(), "part 1 step {i}");
+                assert_eq!(p2.score(), p2.rescan_score(), "part 2 step {i}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn part2_whole_box_is_error() -> Result<()> {
+        let mut problem = parse_input(EXAMPLE_SMALL_PART2)?.to_part_2_problem()?;
+        // replace the box pair left of the robot with a part 1 box
+        let robot = problem.robot;
+        *problem
+            .map
+            .get_mut(Point::new(robot.x - 1, robot.y))
+            .unwrap() = Block::BoxWhole;
+        assert!(problem.validate_part_2_map().is_err());
+        assert!(problem.move_robot_part_2(ScreenDir::L).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn part2_half_box_is_error() -> Result<()> {
+        let mut problem = parse_input(EXAMPLE_SMALL_PART2)?.to_part_2_problem()?;
+        // remove the right half of a box, then push it vertically
+        problem.robot = Point::new(6, 5);
+        *problem.map.get_mut(Point::new(7, 4)).unwrap() = Block::Open;
+        assert!(problem.validate_part_2_map().is_err());
+        assert!(problem.move_robot_part_2(ScreenDir::U).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unwalled_map_is_error() -> Result<()> {
+        let problem = parse_input(indoc! {"
+            .O@
+
+            >>>
+        "})?;
+        // robot walks off the edge of the map
+        assert!(part1(&problem).is_err());
+        assert!(part2(&problem).is_err());
+
+        // pushing a box off the edge
+        let problem = parse_input(indoc! {"
+            @O
+
+            >
+        "})?;
+        assert!(part1(&problem).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn separate_streamed_instructions() -> Result<()> {
+        let (map, moves) = EXAMPLE.split_once("\n\n").unwrap();
+        let problem = parse_map(map)?;
+        assert!(problem.instructions.is_empty());
+
+        let streamed = || read_instructions(BufReader::new(moves.as_bytes()));
+        assert_eq!(part1_with(&problem, streamed())?, 10092);
+        assert_eq!(part2_with(&problem, streamed())?, 9021);
+
+        let bad = read_instructions(BufReader::new("<<x>".as_bytes()));
+        assert!(part1_with(&problem, bad).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn robot_on_box_is_error() {
+        let mut problem = parse_input(EXAMPLE_SMALL).unwrap();
+        *problem.map.get_mut(problem.robot).unwrap() = Block::BoxWhole;
+        assert!(problem.to_part_2_problem().is_err());
+    }
+
+    /// A walled map up to 8x8 inside, with the robot on one of its open cells, as
+    /// puzzle input text
+    fn walled_map() -> impl Strategy<Value = String> {
+        let cell = prop_oneof![3 => Just('.'), 1 => Just('#'), 2 => Just('O')];
+        (1_usize..=8, 1_usize..=8)
+            .prop_flat_map(move |(rows, cols)| {
+                (
+                    proptest::collection::vec(cell.clone(), rows * cols),
+                    any::<proptest::sample::Index>(),
+                    Just(cols),
+                )
+            })
+            .prop_map(|(mut cells, robot, cols)| {
+                let robot = robot.index(cells.len());
+                cells[robot] = '@';
+                let wall = "#".repeat(cols + 2);
+                let mut map = vec![wall.clone()];
+                map.extend(
+                    cells
+                        .chunks(cols)
+                        .map(|row| format!("#{}#", String::from_iter(row))),
+                );
+                map.push(wall);
+                map.join("\n")
+            })
+    }
+
+    fn moves(dirs: &'static [char]) -> impl Strategy<Value = String> {
+        proptest::collection::vec(proptest::sample::select(dirs), 0..80).prop_map(String::from_iter)
+    }
+
+    fn count(problem: &Problem, block: Block) -> usize {
+        problem.map.iter().filter(|b| **b == block).count()
+    }
+
+    /// Walls never move, boxes are neither lost nor overlapped, the robot stays in open
+    /// space, and the running score stays right
+    fn check_invariants(before: &Problem, after: &Problem, box_halves: &[Block]) {
+        let walls = |p: &Problem| p.map.map(|b| b == Block::Wall);
+        assert_eq!(walls(before), walls(after));
+        for half in box_halves {
+            assert_eq!(
+                count(before, *half),
+                count(after, *half),
+                "{half:?}\n{after}"
+            );
+        }
+        assert_eq!(after.map.get(after.robot), Some(&Block::Open));
+        assert_eq!(after.score(), after.rescan_score());
+    }
+
+    proptest! {
+        #[test]
+        fn moves_keep_invariants(map in walled_map(), moves in moves(&['<', '>', '^', 'v'])) {
+            let problem = parse_input(&format!("{map}\n\n{moves}")).unwrap();
+            let mut p1 = Problem::new(problem.map.clone(), problem.robot, Vec::new());
+            let mut p2 = problem.to_part_2_problem().unwrap();
+            for &dir in &problem.instructions {
+                let (before1, before2) = (p1.clone(), p2.clone());
+                p1.move_robot_part_1(dir).unwrap();
+                p2.move_robot_part_2(dir).unwrap();
+                check_invariants(&before1, &p1, &[Block::BoxWhole]);
+                check_invariants(&before2, &p2, &[Block::BoxL, Block::BoxR]);
+                p2.validate_part_2_map().unwrap();
+            }
+        }
+
+        /// Moving only up and down, the robot stays on the left half of each doubled
+        /// column, so the wide boxes move exactly as their part 1 counterparts do
+        #[test]
+        fn vertical_moves_match_part1(map in walled_map(), moves in moves(&['^', 'v'])) {
+            let problem = parse_input(&format!("{map}\n\n{moves}")).unwrap();
+            let mut p1 = Problem::new(problem.map.clone(), problem.robot, Vec::new());
+            let mut p2 = problem.to_part_2_problem().unwrap();
+            for &dir in &problem.instructions {
+                let moved1 = p1.move_robot_part_1(dir).unwrap();
+                let moved2 = p2.move_robot_part_2(dir).unwrap();
+                prop_assert_eq!(moved1.map(|boxes| boxes * 2), moved2);
+            }
+            let widened = p1.to_part_2_problem().unwrap();
+            prop_assert_eq!(&widened.map, &p2.map);
+            prop_assert_eq!(widened.robot, p2.robot);
+            // each box's GPS gains its part 1 column again
+            let columns: usize = p1
+                .map
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b == Block::BoxWhole)
+                .map(|(i, _)| i / p1.map.nrows())
+                .sum();
+            prop_assert_eq!(p2.score(), p1.score() + columns);
+        }
+    }
+
+    const EXAMPLE_SMALL: &str = indoc! {"
+        ########
+        #..O.O.#
+        ##@.O..#
+        #...O..#
+        #.#.O..#
+        #...O..#
+        #......#
+        ########
+
+        <^^>>>vv<v>>v<<
+    "};
+
+    const EXAMPLE_SMALL_PART2: &str = indoc! {"
+        #######
+        #...#.#
+        #.....#
+        #..OO@#
+        #..O..#
+        #.....#
+        #######
+
+        <vv<<^^<<^^
+    "};
+}
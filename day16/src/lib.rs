@@ -0,0 +1,572 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use arrayvec::ArrayVec;
+use common::cartesian::Point;
+use common::cartesian::{DirMap, ScreenDir};
+use common::graph::{Reached, Search};
+use common::math::checked_sum;
+use common::maze::{parse_maze, Block, Maze, MazeMap};
+use common::OptionAnyhow;
+use priority_queue::PriorityQueue;
+use rustc_hash::FxHashMap;
+use strum::IntoEnumIterator;
+
+pub use common::graph::SolverStrategy;
+
+pub type Problem = Maze;
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    parse_maze(input)
+}
+
+type State = (Point, ScreenDir);
+
+/// Best cost to reach each state, and the states it's reached from at that cost. A state
+/// can only be entered from the tile behind it, facing one of three ways, so the
+/// origins always fit.
+pub type DistMap = FxHashMap<State, Reached<State, 3>>;
+
+/// Moves from a state: forward one tile, or turn and step onto the tile to either side
+fn moves(map: &MazeMap, (cur_p, cur_dir): State) -> ArrayVec<(State, i64), 3> {
+    let moves = [
+        (cur_dir, 1),
+        (cur_dir.left(), 1000 + 1),
+        (cur_dir.right(), 1000 + 1),
+    ];
+    moves
+        .into_iter()
+        .filter_map(|(dir, cost)| {
+            let p = cur_p.step_within(dir, map)?;
+            match map.get(p).copied() {
+                Some(Block::Open) | Some(Block::End) => Some(((p, dir), cost)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Lower bound on the cost from `state` to `end`: the manhattan distance, plus the
+/// fewest turns that could get there. Consistent, since a step forward never needs
+/// fewer turns afterwards, and a turning step saves at most one turn.
+fn min_cost_to(end: Point, (p, dir): State) -> i64 {
+    let delta = end - p;
+    let needed: ArrayVec<ScreenDir, 2> = [
+        (delta.x > 0, ScreenDir::R),
+        (delta.x < 0, ScreenDir::L),
+        (delta.y > 0, ScreenDir::D),
+        (delta.y < 0, ScreenDir::U),
+    ]
+    .into_iter()
+    .filter_map(|(needed, d)| needed.then_some(d))
+    .collect();
+
+    let turns = match needed.len() {
+        0 => 0,
+        _ if needed.contains(&dir.opposite()) => 2,
+        1 if needed[0] == dir => 0,
+        _ => 1,
+    };
+    delta.x.abs() + delta.y.abs() + 1000 * turns
+}
+
+pub fn part1(problem: &Problem) -> Result<(i64, DistMap)> {
+    part1_with(problem, SolverStrategy::Dijkstra)
+}
+
+/// Search over tiles, facing, from the start to the end, ready to be stepped through
+#[allow(clippy::type_complexity)]
+pub fn tile_search(
+    problem: &Problem,
+    strategy: SolverStrategy,
+) -> Search<
+    State,
+    ArrayVec<(State, i64), 3>,
+    impl FnMut(State) -> ArrayVec<(State, i64), 3> + '_,
+    impl Fn(State) -> i64 + '_,
+    impl Fn(State) -> bool + '_,
+    3,
+> {
+    Search::new(
+        (problem.start, ScreenDir::R),
+        strategy,
+        |state| moves(&problem.map, state),
+        |state| min_cost_to(problem.end, state),
+        |(p, _)| p == problem.end,
+    )
+}
+
+/// Lowest score, and the best costs and origins of the states searched; enough for
+/// `part2` to walk back over every best path with either strategy
+pub fn part1_with(problem: &Problem, strategy: SolverStrategy) -> Result<(i64, DistMap)> {
+    let mut search = tile_search(problem, strategy);
+    search.run();
+    let found = search.into_best_paths();
+    Ok((found.goal_cost.ok_anyhow()?, found.reached))
+}
+
+/// The maze after `steps` states of the tile search have been expanded: `o` where the
+/// tile is settled facing some way, `+` where it's only been reached, so is on the
+/// wavefront
+pub fn render_wavefront(problem: &Problem, strategy: SolverStrategy, steps: usize) -> String {
+    let mut search = tile_search(problem, strategy);
+    search.step_n(steps);
+
+    let mut grid = problem.map.map(|b| match b {
+        Block::Wall => '#',
+        Block::Start => 'S',
+        Block::End => 'E',
+        Block::Open => '.',
+    });
+    for &(p, _) in search.distances().keys() {
+        let settled = ScreenDir::iter().any(|d| search.is_settled((p, d)));
+        if let Some(tile @ '.') = grid.get_mut(p) {
+            *tile = if settled { 'o' } else { '+' };
+        }
+    }
+    common::grid::render(&grid)
+}
+
+pub fn part2(problem: &Problem, dist: DistMap) -> Result<i64> {
+    let mut visited: HashSet<Point> = HashSet::new();
+    let mut q = vec![];
+
+    let ends = DirMap::from_fn(|d| dist.get(&(problem.end, d)));
+    let min_cost = ends.values().flatten().map(|d| d.cost).min().ok_anyhow()?;
+
+    visited.insert(problem.end);
+    for end in ends.values().flatten() {
+        // skip ends where the cost was not the minimum
+        if end.cost != min_cost {
+            continue;
+        }
+        // explore all origins - these are all on the best path
+        q.extend(&end.origins);
+    }
+
+    // states can be on many best paths, so only walk back from each once
+    let mut seen: HashSet<State> = HashSet::new();
+    while let Some((p, dir)) = q.pop() {
+        if !seen.insert((p, dir)) {
+            continue;
+        }
+        visited.insert(p);
+
+        q.extend(&dist[&(p, dir)].origins);
+    }
+
+    Ok(visited.len() as i64)
+}
+
+/// Number of distinct minimum-cost paths from start to end. Every origin of a state
+/// costs strictly less than it, so counting forward in order of cost sees each state's
+/// origins complete before the state itself.
+pub fn count_best_paths(problem: &Problem) -> Result<u64> {
+    let (min_cost, dist) = part1(problem)?;
+
+    let mut states: Vec<_> = dist.iter().collect();
+    states.sort_by_key(|(_, d)| d.cost);
+
+    let mut paths: HashMap<State, u64> = HashMap::new();
+    paths.insert((problem.start, ScreenDir::R), 1);
+    for (state, d) in states {
+        let count = d
+            .origins
+            .iter()
+            .map(|origin| paths.get(origin).copied().unwrap_or(0));
+        let count = checked_sum(count).context("path count")?;
+        if count > 0 {
+            paths.insert(*state, count);
+        }
+    }
+
+    let ends = DirMap::from_fn(|d| dist.get(&(problem.end, d)).map(|d| d.cost));
+    let counts = ends
+        .into_iter()
+        .filter(|(_, cost)| *cost == Some(min_cost))
+        .map(|(d, _)| paths.get(&(problem.end, d)).copied().unwrap_or(0));
+    checked_sum(counts).context("path count")
+}
+
+type GraphState = (usize, ScreenDir);
+
+/// Both parts on the corridor-contracted maze. States are (node, facing) as before,
+/// but each move follows a whole corridor, so far fewer states are searched; best
+/// path tiles are recovered from the cells along the edges taken.
+pub fn solve_contracted(problem: &Problem) -> Result<(i64, usize)> {
+    let graph = problem.contract();
+    let start = graph.node(problem.start).ok_anyhow()?;
+    let end = graph.node(problem.end).ok_anyhow()?;
+
+    let mut dist: HashMap<GraphState, i64> = HashMap::new();
+    let mut origins: HashMap<GraphState, Vec<(GraphState, usize)>> = HashMap::new();
+    let mut q = PriorityQueue::new();
+    dist.insert((start, ScreenDir::R), 0);
+    q.push((start, ScreenDir::R), 0);
+
+    while let Some((state, prio)) = q.pop() {
+        let (node, facing) = state;
+        let cost = -prio;
+        for (edge_id, edge) in graph.edges_from(node) {
+            // as with the grid, no turning around on the spot
+            let turn_cost = match facing.turns_to(edge.start_dir) {
+                2 => continue,
+                turns => 1000 * turns as i64,
+            };
+            let alt = cost + turn_cost + edge.steps + 1000 * edge.turns;
+            let next_state = (edge.to, edge.end_dir);
+            let next_state_cost = *dist.get(&next_state).unwrap_or(&i64::MAX);
+            match alt.cmp(&next_state_cost) {
+                std::cmp::Ordering::Less => {
+                    dist.insert(next_state, alt);
+                    origins.insert(next_state, vec![(state, edge_id)]);
+                    q.push(next_state, -alt);
+                }
+                std::cmp::Ordering::Equal => {
+                    origins
+                        .entry(next_state)
+                        .or_default()
+                        .push((state, edge_id));
+                }
+                std::cmp::Ordering::Greater => {}
+            }
+        }
+    }
+
+    let end_costs = DirMap::from_fn(|d| dist.get(&(end, d)).copied());
+    let min_cost = end_costs.values().flatten().min().copied().ok_anyhow()?;
+
+    // walk back over all the best paths, collecting tiles
+    let mut tiles: HashSet<Point> = HashSet::new();
+    let mut seen: HashSet<GraphState> = HashSet::new();
+    let mut q: Vec<_> = end_costs
+        .into_iter()
+        .filter(|(_, c)| *c == Some(min_cost))
+        .map(|(d, _)| (end, d))
+        .collect();
+    tiles.insert(problem.end);
+    while let Some(state) = q.pop() {
+        if !seen.insert(state) {
+            continue;
+        }
+        for &(origin, edge_id) in origins.get(&state).into_iter().flatten() {
+            let edge = &graph.edges[edge_id];
+            tiles.insert(graph.nodes[edge.from]);
+            tiles.extend(edge.cells.iter().copied());
+            q.push(origin);
+        }
+    }
+
+    Ok((min_cost, tiles.len()))
+}
+
+/// Random `width` x `height` maze with `S` at the bottom left and `E` at the top right,
+/// as in the puzzle. Corridors are carved by a depth-first walk between cells at odd
+/// coordinates, then about one wall in `loop_rate` between corridors is knocked out so
+/// there are loops and more than one way through; none if `loop_rate` is zero.
+/// Reproducible from `seed`.
+pub fn generate_maze(width: usize, height: usize, loop_rate: u64, seed: u64) -> String {
+    let mut rng = common::rng::seeded(seed);
+
+    let mut grid = vec![vec![b'#'; width]; height];
+    let (cols, rows) = ((width - 1) / 2, (height - 1) / 2);
+    let mut visited = vec![vec![false; cols]; rows];
+    let mut stack: Vec<(usize, usize)> = vec![(0, rows - 1)];
+    visited[rows - 1][0] = true;
+    grid[2 * rows - 1][1] = b'.';
+    while let Some(&(c, r)) = stack.last() {
+        let unvisited: ArrayVec<(usize, usize), 4> = [
+            (c.wrapping_sub(1), r),
+            (c + 1, r),
+            (c, r.wrapping_sub(1)),
+            (c, r + 1),
+        ]
+        .into_iter()
+        .filter(|&(c, r)| c < cols && r < rows && !visited[r][c])
+        .collect();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let (nc, nr) = *rng.choose(&unvisited);
+        visited[nr][nc] = true;
+        grid[2 * nr + 1][2 * nc + 1] = b'.';
+        grid[r + nr + 1][c + nc + 1] = b'.';
+        stack.push((nc, nr));
+    }
+
+    // walls with corridor on both sides, either across or up and down
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            let across = grid[y][x - 1] == b'.' && grid[y][x + 1] == b'.';
+            let vertical = grid[y - 1][x] == b'.' && grid[y + 1][x] == b'.';
+            let knock_out = rng.one_in(loop_rate);
+            if grid[y][x] == b'#' && across != vertical && knock_out {
+                grid[y][x] = b'.';
+            }
+        }
+    }
+
+    grid[2 * rows - 1][1] = b'S';
+    grid[1][2 * cols - 1] = b'E';
+    let mut text = String::new();
+    for row in grid {
+        text.push_str(std::str::from_utf8(&row).unwrap());
+        text.push('\n');
+    }
+    text
+}
+
+/// Compare Dijkstra and A* for both parts on `input`, printing the time and the
+/// number of states each expands
+fn benchmark_maze(name: &str, input: &str) -> Result<()> {
+    let problem = parse_input(input)?;
+    for strategy in [SolverStrategy::Dijkstra, SolverStrategy::AStar] {
+        let t = Instant::now();
+        let (cost, dist) = part1_with(&problem, strategy)?;
+        let states = dist.len();
+        let tiles = part2(&problem, dist)?;
+        println!(
+            "{name} {strategy:?}: {cost}, {tiles} tiles, {states} states reached (took {:?})",
+            t.elapsed()
+        );
+    }
+    Ok(())
+}
+
+pub fn benchmark_strategies(input: &str) -> Result<()> {
+    benchmark_maze("puzzle input", input)?;
+    for loop_rate in [1, 10, 0] {
+        let maze = generate_maze(1001, 1001, loop_rate, 16);
+        benchmark_maze(&format!("1001x1001 maze, loop rate {loop_rate}"), &maze)?;
+    }
+    Ok(())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let (cost, tiles) = solve_contracted(&parse_input(input)?)?;
+    Ok((cost.to_string(), tiles.to_string()))
+}
+
+common::aoc_day!(16, "Reindeer Maze");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    ###############
+    #.......#....E#
+    #.#.###.#.###.#
+    #.....#.#...#.#
+    #.###.#####.#.#
+    #.#.#.......#.#
+    #.#.#####.###.#
+    #...........#.#
+    ###.#.#####.#.#
+    #...#.....#.#.#
+    #.#.#.###.#.#.#
+    #.....#...#.#.#
+    #.###.#.#.#.#.#
+    #S..#.....#...#
+    ###############
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("7036"), Some("45")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const EXAMPLE_2: &str = indoc! {"
+        #################
+        #...#...#...#..E#
+        #.#.#.#.#.#.#.#.#
+        #.#.#.#...#...#.#
+        #.#.#.#.###.#.#.#
+        #...#.#.#.....#.#
+        #.#.#.#.#.#####.#
+        #.#...#.#.#.....#
+        #.#.#####.#.###.#
+        #.#.#.......#...#
+        #.#.###.#####.###
+        #.#.#...#.....#.#
+        #.#.#.#####.###.#
+        #.#.#.........#.#
+        #.#.#.#########.#
+        #S#.............#
+        #################
+    "};
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let (count, _) = part1(&problem)?;
+        assert_eq!(count, 7036);
+        Ok(())
+    }
+    #[test]
+    fn part1_correct_example_2() -> Result<()> {
+        let problem = parse_input(EXAMPLE_2)?;
+        let (count, _) = part1(&problem)?;
+        assert_eq!(count, 11048);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let (_, dist) = part1(&problem)?;
+        let count = part2(&problem, dist)?;
+        assert_eq!(count, 45);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct_example_2() -> Result<()> {
+        let problem = parse_input(EXAMPLE_2)?;
+        let (_, dist) = part1(&problem)?;
+        let count = part2(&problem, dist)?;
+        assert_eq!(count, 64);
+        Ok(())
+    }
+
+    #[test]
+    fn best_paths_counted() -> Result<()> {
+        // one diamond: over or under the wall
+        let problem = parse_input(indoc! {"
+            #######
+            ##...##
+            #S.#.E#
+            ##...##
+            #######
+        "})?;
+        assert_eq!(count_best_paths(&problem)?, 2);
+
+        // two diamonds in a row
+        let problem = parse_input(indoc! {"
+            ###########
+            ##...#...##
+            #S.#...#.E#
+            ##...#...##
+            ###########
+        "})?;
+        assert_eq!(count_best_paths(&problem)?, 4);
+
+        assert_eq!(count_best_paths(&parse_input(EXAMPLE)?)?, 3);
+        assert_eq!(count_best_paths(&parse_input(EXAMPLE_2)?)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn heuristic_is_consistent() -> Result<()> {
+        let problem = parse_input(&generate_maze(41, 31, 5, 3))?;
+        let (_, dist) = part1(&problem)?;
+        for state in dist.keys() {
+            assert!(min_cost_to(problem.end, *state) >= 0);
+            for (next, cost) in moves(&problem.map, *state) {
+                let h = min_cost_to(problem.end, *state);
+                assert!(h <= cost + min_cost_to(problem.end, next), "{state:?}");
+            }
+        }
+        for ((p, _), reached) in &dist {
+            if *p == problem.end {
+                assert_eq!(min_cost_to(problem.end, (*p, ScreenDir::U)), 0);
+                assert!(reached.cost >= min_cost_to(problem.end, (problem.start, ScreenDir::R)));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn astar_matches_dijkstra() -> Result<()> {
+        let mazes = [
+            EXAMPLE.to_string(),
+            EXAMPLE_2.to_string(),
+            generate_maze(41, 31, 5, 1),
+            generate_maze(61, 61, 3, 2),
+            generate_maze(21, 21, 0, 4),
+        ];
+        for maze in mazes {
+            let problem = parse_input(&maze)?;
+            let (cost, dist) = part1_with(&problem, SolverStrategy::Dijkstra)?;
+            let (astar_cost, astar_dist) = part1_with(&problem, SolverStrategy::AStar)?;
+            assert_eq!(astar_cost, cost);
+            assert!(astar_dist.len() <= dist.len());
+            assert_eq!(part2(&problem, astar_dist)?, part2(&problem, dist)?);
+            assert_eq!(solve_contracted(&problem)?.0, cost);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn search_resumes() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let (cost, dist) = part1(&problem)?;
+
+        let mut search = tile_search(&problem, SolverStrategy::Dijkstra);
+        assert_eq!(search.step_n(10), 10);
+        let start = (problem.start, ScreenDir::R);
+        assert!(search.is_settled(start));
+        assert_eq!(search.distances()[&start].cost, 0);
+        // anything settled already has its final cost
+        for (state, reached) in search.distances() {
+            if search.is_settled(*state) {
+                assert_eq!(reached.cost, dist[state].cost);
+            }
+        }
+        assert_eq!(search.goal_cost(), None);
+
+        while search.step_n(7) > 0 {}
+        assert!(search.is_finished());
+        assert_eq!(search.goal_cost(), Some(cost));
+        assert_eq!(part2(&problem, search.into_best_paths().reached)?, 45);
+        Ok(())
+    }
+
+    #[test]
+    fn wavefront_rendered() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let before = render_wavefront(&problem, SolverStrategy::Dijkstra, 0);
+        assert_eq!(before, EXAMPLE);
+
+        let wavefront = render_wavefront(&problem, SolverStrategy::Dijkstra, 3);
+        let lines: Vec<_> = wavefront.lines().collect();
+        // along the bottom corridor, with the tile above the start reached by turning
+        assert_eq!(&lines[13][..5], "#Soo#");
+        assert_eq!(&lines[12][..3], "#+#");
+        assert_eq!(&lines[11][..3], "#..");
+        Ok(())
+    }
+
+    #[test]
+    fn generated_maze_shape() -> Result<()> {
+        let maze = generate_maze(11, 7, 0, 1);
+        assert_eq!(maze.lines().count(), 7);
+        assert!(maze.lines().all(|l| l.len() == 11));
+        let problem = parse_input(&maze)?;
+        assert_eq!(
+            (problem.start, problem.end),
+            (Point::new(1, 5), Point::new(9, 1))
+        );
+        // a perfect maze with no loops has exactly one best path
+        assert_eq!(count_best_paths(&problem)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn contracted_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(solve_contracted(&problem)?, (7036, 45));
+        let problem = parse_input(EXAMPLE_2)?;
+        assert_eq!(solve_contracted(&problem)?, (11048, 64));
+        Ok(())
+    }
+}
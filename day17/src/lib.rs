@@ -0,0 +1,827 @@
+use std::{fmt::Display, sync::Arc, time::Instant};
+
+use anyhow::{bail, Result};
+use common::OptionAnyhow;
+use indoc::indoc;
+use itertools::Itertools;
+use rayon::prelude::*;
+
+pub const INPUT: &str = indoc! {"
+    Register A: 27575648
+    Register B: 0
+    Register C: 0
+
+    Program: 2,4,1,2,7,5,4,1,1,3,5,5,0,3,3,0
+"};
+
+const DEFAULT_MAX_STEPS: usize = 1_000_000;
+const DEFAULT_MAX_OUTPUT: usize = 1_000;
+
+/// How a program run finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// the instruction pointer moved past the end of the program
+    Completed,
+    /// the maximum number of instructions was executed before completing
+    StepLimit,
+    /// the maximum number of output values was produced before completing
+    OutputLimit,
+}
+
+/// Why a program couldn't carry on running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VmError {
+    /// combo operand 7 is reserved, and can't be used as a value
+    InvalidComboOperand { ip: usize, operand: u8 },
+    /// only opcodes 0-7 exist
+    InvalidOpcode { ip: usize, opcode: u8 },
+    /// a jump landed on the last value of the program, which has no operand after it
+    IpOutOfBounds { ip: usize, len: usize },
+    /// opcodes and operands come in pairs
+    OddProgramLength(usize),
+    /// a division by 2 to a negative power, from a negative register
+    NegativeShift { ip: usize, shift: i64 },
+}
+impl Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::InvalidComboOperand { ip, operand } => {
+                write!(f, "invalid combo operand {operand} at {ip}")
+            }
+            VmError::InvalidOpcode { ip, opcode } => write!(f, "invalid opcode {opcode} at {ip}"),
+            VmError::IpOutOfBounds { ip, len } => {
+                write!(f, "no operand for the opcode at {ip} of {len}")
+            }
+            VmError::OddProgramLength(len) => write!(f, "program has odd length {len}"),
+            VmError::NegativeShift { ip, shift } => {
+                write!(f, "division by 2^{shift} at {ip}")
+            }
+        }
+    }
+}
+impl std::error::Error for VmError {}
+
+/// The program is shared, so cloning a computer only copies its registers and any
+/// output so far
+#[derive(Debug, Clone)]
+pub struct Computer {
+    reg_a: i64,
+    reg_b: i64,
+    reg_c: i64,
+    program: Arc<[u8]>,
+    ip: usize,
+    output: Vec<u8>,
+}
+impl Computer {
+    pub fn new(a: i64, b: i64, c: i64, program: Vec<u8>) -> Computer {
+        Computer {
+            reg_a: a,
+            reg_b: b,
+            reg_c: c,
+            program: program.into(),
+            ip: 0,
+            output: vec![],
+        }
+    }
+
+    pub fn program(&self) -> &[u8] {
+        &self.program
+    }
+
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Ready to run again from the start with the given registers, keeping the output
+    /// buffer's allocation
+    pub fn reset(&mut self, a: i64, b: i64, c: i64) {
+        self.reg_a = a;
+        self.reg_b = b;
+        self.reg_c = c;
+        self.ip = 0;
+        self.output.clear();
+    }
+}
+
+fn right_str(input: &str) -> Result<&str> {
+    let (_, r) = input.split_once(": ").ok_anyhow()?;
+    Ok(r)
+}
+
+pub fn parse_input(input: &str) -> Result<Computer> {
+    let mut it = input.lines();
+
+    // read registers
+    let reg_a = right_str(it.next().ok_anyhow()?)?.parse()?;
+    let reg_b = right_str(it.next().ok_anyhow()?)?.parse()?;
+    let reg_c = right_str(it.next().ok_anyhow()?)?.parse()?;
+
+    // skip blank line
+    it.next();
+
+    // read program
+    let program_str = right_str(it.next().ok_anyhow()?)?;
+    let mut program = vec![];
+    for code_str in program_str.split(",") {
+        let code = code_str.parse()?;
+        program.push(code);
+    }
+
+    Ok(Computer::new(reg_a, reg_b, reg_c, program))
+}
+
+#[allow(clippy::assign_op_pattern)]
+impl Computer {
+    /// Combo operands 0 through 3 represent literal values 0 through 3.
+    /// Combo operand 4 represents the value of register A.
+    /// Combo operand 5 represents the value of register B.
+    /// Combo operand 6 represents the value of register C.
+    /// Combo operand 7 is reserved and will not appear in valid programs.
+    fn combo_operand(&self, operand: i64) -> Result<i64, VmError> {
+        match operand {
+            0..=3 => Ok(operand),
+            4 => Ok(self.reg_a),
+            5 => Ok(self.reg_b),
+            6 => Ok(self.reg_c),
+            _ => Err(VmError::InvalidComboOperand {
+                ip: self.ip,
+                operand: operand as u8,
+            }),
+        }
+    }
+
+    /// A divided by 2 to the power of the combo operand, as for adv, bdv and cdv. Shifts
+    /// of 64 or more leave nothing but the sign.
+    fn divide_a(&self, operand: i64) -> Result<i64, VmError> {
+        let shift = self.combo_operand(operand)?;
+        if shift < 0 {
+            return Err(VmError::NegativeShift { ip: self.ip, shift });
+        }
+        Ok(self.reg_a >> shift.min(63))
+    }
+
+    /// The adv instruction (opcode 0) performs division. The numerator is the value in the A register.
+    /// The denominator is found by raising 2 to the power of the instruction's combo operand.
+    /// (So, an operand of 2 would divide A by 4 (2^2); an operand of 5 would divide A by 2^B.)
+    /// The result of the division operation is truncated to an integer and then written to the A register.
+    fn adv(&mut self, operand: i64) -> Result<(), VmError> {
+        self.reg_a = self.divide_a(operand)?;
+        self.ip += 2;
+        Ok(())
+    }
+
+    /// The bdv instruction (opcode 6) works exactly like the adv instruction except that the
+    /// result is stored in the B register. (The numerator is still read from the A register.)
+    fn bdv(&mut self, operand: i64) -> Result<(), VmError> {
+        self.reg_b = self.divide_a(operand)?;
+        self.ip += 2;
+        Ok(())
+    }
+
+    /// The cdv instruction (opcode 7) works exactly like the adv instruction except that the
+    /// result is stored in the C register. (The numerator is still read from the A register.)
+    fn cdv(&mut self, operand: i64) -> Result<(), VmError> {
+        self.reg_c = self.divide_a(operand)?;
+        self.ip += 2;
+        Ok(())
+    }
+
+    /// The bxl instruction (opcode 1) calculates the bitwise XOR of register B
+    /// and the instruction's literal operand, then stores the result in register B.
+    fn bxl(&mut self, operand: i64) {
+        self.reg_b ^= operand;
+        self.ip += 2;
+    }
+
+    /// The bst instruction (opcode 2) calculates the value of its combo operand modulo 8
+    /// (thereby keeping only its lowest 3 bits), then writes that value to the B register.
+    fn bst(&mut self, operand: i64) -> Result<(), VmError> {
+        let x = self.combo_operand(operand)?;
+        self.reg_b = x & 0x7;
+        self.ip += 2;
+        Ok(())
+    }
+
+    /// The jnz instruction (opcode 3) does nothing if the A register is 0.
+    /// However, if the A register is not zero, it jumps by setting the
+    /// instruction pointer to the value of its literal operand;
+    /// if this instruction jumps, the instruction pointer is not
+    /// increased by 2 after this instruction.
+    fn jnz(&mut self, operand: i64) {
+        if self.reg_a == 0 {
+            self.ip += 2;
+        } else {
+            self.ip = operand as usize;
+        }
+    }
+
+    /// The bxc instruction (opcode 4) calculates the bitwise XOR of register B and register C,
+    /// then stores the result in register B. (For legacy reasons, this instruction
+    /// reads an operand but ignores it.)
+    fn bxc(&mut self, _operand: i64) {
+        self.reg_b = self.reg_b ^ self.reg_c;
+        self.ip += 2;
+    }
+
+    /// The out instruction (opcode 5) calculates the value of its combo operand modulo 8,
+    /// then outputs that value. (If a program outputs multiple values, they are separated by commas.)
+    fn out(&mut self, operand: i64) -> Result<u8, VmError> {
+        // the low three bits of a negative number are still 0-7
+        let x = self.combo_operand(operand)? & 0x7;
+        self.ip += 2;
+        Ok(x as u8)
+    }
+
+    fn halted(&self) -> bool {
+        self.ip >= self.program.len()
+    }
+
+    /// Execute a single instruction, returning the output value if it produced one
+    fn step(&mut self) -> Result<Option<u8>, VmError> {
+        let len = self.program.len();
+        if !len.is_multiple_of(2) {
+            return Err(VmError::OddProgramLength(len));
+        }
+        let Some(&operand) = self.program.get(self.ip + 1) else {
+            return Err(VmError::IpOutOfBounds { ip: self.ip, len });
+        };
+        let operand = operand as i64;
+        match self.program[self.ip] {
+            0 => self.adv(operand)?,
+            1 => self.bxl(operand),
+            2 => self.bst(operand)?,
+            3 => self.jnz(operand),
+            4 => self.bxc(operand),
+            5 => return self.out(operand).map(Some),
+            6 => self.bdv(operand)?,
+            7 => self.cdv(operand)?,
+            opcode => {
+                return Err(VmError::InvalidOpcode {
+                    ip: self.ip,
+                    opcode,
+                })
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run until the program completes or one of the limits is reached,
+    /// collecting output values into `output`
+    pub fn run_with_limits(
+        &mut self,
+        max_steps: usize,
+        max_output: usize,
+    ) -> Result<RunOutcome, VmError> {
+        let mut steps = 0;
+        while !self.halted() {
+            if steps >= max_steps {
+                return Ok(RunOutcome::StepLimit);
+            }
+            if let Some(x) = self.step()? {
+                if self.output.len() >= max_output {
+                    return Ok(RunOutcome::OutputLimit);
+                }
+                self.output.push(x);
+            }
+            steps += 1;
+        }
+        Ok(RunOutcome::Completed)
+    }
+
+    fn run_program(&mut self) -> Result<RunOutcome, VmError> {
+        self.run_with_limits(DEFAULT_MAX_STEPS, DEFAULT_MAX_OUTPUT)
+    }
+
+    /// Stream output values as they are produced, without collecting them. The stream
+    /// ends when the program completes, after `max_steps` instructions, or after an
+    /// error, so callers can stop early (e.g. on the first unwanted value) without
+    /// running the whole program.
+    pub fn outputs(&mut self, max_steps: usize) -> impl Iterator<Item = Result<u8, VmError>> + '_ {
+        let mut steps = 0;
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            while !failed && !self.halted() && steps < max_steps {
+                steps += 1;
+                match self.step() {
+                    Ok(Some(x)) => return Some(Ok(x)),
+                    Ok(None) => {}
+                    Err(e) => {
+                        failed = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            None
+        })
+    }
+
+    fn format_output(&self) -> String {
+        self.output.iter().join(",")
+    }
+}
+
+const MNEMONICS: [&str; 8] = ["adv", "bxl", "bst", "jnz", "bxc", "out", "bdv", "cdv"];
+
+/// Instructions taking a combo operand, written as 0-3 or a register name
+fn takes_combo(opcode: u8) -> bool {
+    matches!(opcode, 0 | 2 | 5 | 6 | 7)
+}
+
+/// Assembler and disassembler, for writing programs as e.g. `bst A; bxl 2; out B`
+pub struct Program;
+impl Program {
+    /// Instructions are separated by `;` or newlines. Combo operands are 0-3 or A, B or
+    /// C; literal operands are 0-7; `bxc` ignores its operand, so it may be left out
+    /// when it's 0.
+    pub fn from_asm(asm: &str) -> Result<Vec<u8>> {
+        let mut program = vec![];
+        for instruction in asm.split([';', '\n']).map(str::trim) {
+            if instruction.is_empty() {
+                continue;
+            }
+            let mut parts = instruction.split_whitespace();
+            let mnemonic = parts.next().ok_anyhow()?;
+            let opcode = MNEMONICS
+                .iter()
+                .position(|m| m.eq_ignore_ascii_case(mnemonic))
+                .expect_anyhow(&format!("unknown instruction '{instruction}'"))?
+                as u8;
+            let operand = match (parts.next(), takes_combo(opcode)) {
+                (None, _) if opcode == 4 => 0,
+                (None, _) => bail!("missing operand in '{instruction}'"),
+                (Some("A" | "a"), true) => 4,
+                (Some("B" | "b"), true) => 5,
+                (Some("C" | "c"), true) => 6,
+                (Some(literal), combo) => match literal.parse() {
+                    Ok(v @ 0..=3) => v,
+                    Ok(v @ 4..=7) if !combo => v,
+                    _ => bail!("bad operand in '{instruction}'"),
+                },
+            };
+            if parts.next().is_some() {
+                bail!("too many operands in '{instruction}'");
+            }
+            program.extend([opcode, operand]);
+        }
+        Ok(program)
+    }
+
+    /// The inverse of `from_asm`, one instruction per line
+    pub fn to_asm(program: &[u8]) -> Result<String> {
+        if !program.len().is_multiple_of(2) {
+            bail!("program has a trailing opcode");
+        }
+        let mut lines = vec![];
+        for pair in program.chunks(2) {
+            let (opcode, operand) = (pair[0], pair[1]);
+            let mnemonic = MNEMONICS
+                .get(opcode as usize)
+                .expect_anyhow(&format!("bad opcode {opcode}"))?;
+            let operand = match (opcode, operand) {
+                (4, 0) => String::new(),
+                (_, 0..=3) => format!(" {operand}"),
+                (op, 4) if takes_combo(op) => " A".to_string(),
+                (op, 5) if takes_combo(op) => " B".to_string(),
+                (op, 6) if takes_combo(op) => " C".to_string(),
+                (op, 4..=7) if !takes_combo(op) => format!(" {operand}"),
+                _ => bail!("bad operand {operand} for {mnemonic}"),
+            };
+            lines.push(format!("{mnemonic}{operand}"));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+pub fn part1(mut computer: Computer) -> Result<String> {
+    match computer.run_program()? {
+        RunOutcome::Completed => Ok(computer.format_output()),
+        outcome => bail!("program did not complete: {outcome:?}"),
+    }
+}
+
+/// Computer operations coded by hand and analysed on paper.
+/// - single output per loop
+/// - loops back to start with updated a, where next a => a >> 3
+/// - lowest 9 bits are used to compute the output
+/// - so we can build up digits from the end, working back
+pub fn part_2_hardcoded(computer: Computer) -> Result<i64> {
+    fn single_loop(a: i64) -> (i64, i64) {
+        let b = a & 0x7;
+        let b = b ^ 2;
+        let c = a >> b;
+        let b = b ^ c;
+        let b = b ^ 3;
+        let res = b & 0x7;
+        let a = a >> 3;
+        (res, a)
+    }
+
+    // scan lowest 10 bits (0 through 1023) from starting number
+    fn filter_for(start: i64, target_out: i64, target_a: i64) -> impl Iterator<Item = i64> {
+        (start..start + 1024).filter(move |i| {
+            let (res, a) = single_loop(*i);
+            res == target_out && a == target_a
+        })
+    }
+
+    // work back from the last output, finding three more bits of A for each
+    let mut targets_a = vec![0];
+    let mut targets_next = vec![];
+    for num in computer.program.iter().rev().copied() {
+        targets_next.clear();
+        let num = num as i64;
+        for target_a in targets_a.iter() {
+            let start = target_a << 3;
+            targets_next.extend(filter_for(start, num, *target_a));
+        }
+        std::mem::swap(&mut targets_a, &mut targets_next);
+    }
+
+    // return the lowest one
+    targets_a.sort();
+    let res = targets_a.first().unwrap();
+    Ok(*res)
+}
+
+/// Reset the computer to run again from the start with a different value in register A,
+/// keeping the program and initial B and C registers of `initial`
+fn reset_with_a(computer: &mut Computer, initial: &Computer, a: i64) {
+    computer.reset(a, initial.reg_b, initial.reg_c);
+}
+
+/// Run `computer`'s program once for each value of register A, in parallel, with B
+/// and C as they are in `computer`. Each thread reuses one computer; runs stopped by
+/// the default limits give the output up to that point. Results are in the order of
+/// `a_values` if it's an indexed iterator, such as a range.
+pub fn run_many(
+    a_values: impl ParallelIterator<Item = i64>,
+    computer: &Computer,
+) -> Vec<(i64, Result<Vec<u8>, VmError>)> {
+    a_values
+        .map_init(
+            || computer.clone(),
+            |candidate, a| {
+                reset_with_a(candidate, computer, a);
+                let output = candidate.run_program().map(|_| candidate.output.clone());
+                (a, output)
+            },
+        )
+        .collect()
+}
+
+/// Candidate A values below this are searched for a quine: a program that consumes
+/// three bits of A per output, as `adv 3` loops do, can't output its whole program
+/// from any larger value
+fn quine_search_bound(computer: &Computer) -> i64 {
+    match 3 * computer.program.len() {
+        bits if bits < 63 => 1 << bits,
+        _ => i64::MAX,
+    }
+}
+
+/// General quine search that makes no assumptions about the structure of the program:
+/// candidate A values are tried in increasing order, and each run is abandoned as soon as
+/// an output value differs from the corresponding value in the program. Only practical
+/// when the answer is small, but useful where the analytic approach doesn't apply.
+/// Gives up with `None` past `8^len` for a program of `len` values.
+pub fn find_quine_prefix_prune(computer: &Computer) -> Option<i64> {
+    let program = &computer.program;
+    let mut candidate = computer.clone();
+    (0..quine_search_bound(computer)).find(|&a| {
+        reset_with_a(&mut candidate, computer, a);
+        let mut matched = 0;
+        for x in candidate.outputs(DEFAULT_MAX_STEPS) {
+            if x.ok().as_ref() != program.get(matched) {
+                return false;
+            }
+            matched += 1;
+        }
+        matched == program.len() && candidate.halted()
+    })
+}
+
+/// Baseline for `find_quine_prefix_prune`: runs every candidate to completion before comparing
+fn find_quine_full_run(computer: &Computer) -> Option<i64> {
+    let mut candidate = computer.clone();
+    (0..quine_search_bound(computer)).find(|&a| {
+        reset_with_a(&mut candidate, computer, a);
+        candidate.run_program() == Ok(RunOutcome::Completed)
+            && candidate.output[..] == computer.program[..]
+    })
+}
+
+/// The analytic solver is specific to the structure of my input program, so the general
+/// searches are compared on the small quine example from the puzzle instead
+pub fn benchmark_quine_search() -> Result<()> {
+    let example = parse_input(indoc! {"
+        Register A: 2024
+        Register B: 0
+        Register C: 0
+
+        Program: 0,3,5,4,3,0
+    "})?;
+
+    let t = Instant::now();
+    let pruned = find_quine_prefix_prune(&example);
+    println!(
+        "Prefix-pruned quine search: {pruned:?} (took {:?})",
+        t.elapsed()
+    );
+
+    let t = Instant::now();
+    let full = find_quine_full_run(&example);
+    println!("Full-run quine search: {full:?} (took {:?})", t.elapsed());
+
+    Ok(())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let computer = parse_input(input)?;
+    Ok((
+        part1(computer.clone())?,
+        part_2_hardcoded(computer)?.to_string(),
+    ))
+}
+
+common::aoc_day!(17, "Chronospatial Computer");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    Register A: 729
+    Register B: 0
+    Register C: 0
+
+    Program: 0,1,5,4,3,0
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("4,6,3,5,6,3,5,2,1,0"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    // If register C contains 9, the program 2,6 would set register B to 1.
+    fn asm(asm: &str) -> Vec<u8> {
+        Program::from_asm(asm).unwrap()
+    }
+
+    #[test]
+    fn case1() {
+        let mut computer = Computer::new(0, 0, 9, asm("bst C"));
+        computer.run_program().unwrap();
+        assert_eq!(computer.reg_b, 1);
+    }
+
+    // If register A contains 10, the program 5,0,5,1,5,4 would output 0,1,2.
+    #[test]
+    fn case2() {
+        let mut computer = Computer::new(10, 0, 0, asm("out 0; out 1; out A"));
+        computer.run_program().unwrap();
+        assert_eq!(computer.format_output(), "0,1,2");
+    }
+
+    // If register A contains 2024, the program 0,1,5,4,3,0 would
+    // output 4,2,5,6,7,7,7,7,3,1,0 and leave 0 in register A.
+    #[test]
+    fn case3() {
+        let mut computer = Computer::new(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        computer.run_program().unwrap();
+        assert_eq!(computer.format_output(), "4,2,5,6,7,7,7,7,3,1,0");
+        assert_eq!(computer.reg_a, 0);
+    }
+
+    // If register B contains 29, the program 1,7 would set register B to 26.
+    #[test]
+    fn case4() {
+        let mut computer = Computer::new(0, 29, 0, asm("bxl 7"));
+        computer.run_program().unwrap();
+        assert_eq!(computer.reg_b, 26);
+    }
+
+    // If register B contains 2024 and register C contains 43690, the program 4,0 would set register B to 44354.
+    #[test]
+    fn case5() {
+        let mut computer = Computer::new(0, 2024, 43690, asm("bxc"));
+        computer.run_program().unwrap();
+        assert_eq!(computer.reg_b, 44354);
+    }
+
+    #[test]
+    fn step_limit_stops_infinite_loop() {
+        // jnz 0 with A != 0 never terminates
+        let mut computer = Computer::new(1, 0, 0, vec![3, 0]);
+        assert_eq!(
+            computer.run_with_limits(1000, 10),
+            Ok(RunOutcome::StepLimit)
+        );
+        assert!(computer.output.is_empty());
+    }
+
+    #[test]
+    fn output_limit_stops_endless_output() {
+        // out A; jnz 0 outputs forever
+        let mut computer = Computer::new(1, 0, 0, vec![5, 4, 3, 0]);
+        assert_eq!(
+            computer.run_with_limits(1000, 10),
+            Ok(RunOutcome::OutputLimit)
+        );
+        assert_eq!(computer.output.len(), 10);
+    }
+
+    #[test]
+    fn completes_within_limits() {
+        let mut computer = Computer::new(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        assert_eq!(
+            computer.run_with_limits(1000, 11),
+            Ok(RunOutcome::Completed)
+        );
+        assert_eq!(computer.format_output(), "4,2,5,6,7,7,7,7,3,1,0");
+    }
+
+    #[test]
+    fn outputs_stream() -> Result<()> {
+        let mut computer = Computer::new(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        let first: Vec<_> = computer.outputs(1000).take(3).collect::<Result<_, _>>()?;
+        assert_eq!(first, vec![4, 2, 5]);
+        // stream resumes where it left off
+        let rest: Vec<_> = computer.outputs(1000).collect::<Result<_, _>>()?;
+        assert_eq!(rest, vec![6, 7, 7, 7, 7, 3, 1, 0]);
+        assert!(computer.output.is_empty());
+
+        // endless output is cut off by the step limit
+        let mut computer = Computer::new(1, 0, 0, vec![5, 4, 3, 0]);
+        assert_eq!(computer.outputs(10).count(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_programs_are_errors() {
+        let run = |a, program: Vec<u8>| Computer::new(a, 0, 0, program).run_program();
+        assert_eq!(
+            run(0, vec![5, 0, 2, 7]),
+            Err(VmError::InvalidComboOperand { ip: 2, operand: 7 })
+        );
+        assert_eq!(
+            run(0, vec![8, 0]),
+            Err(VmError::InvalidOpcode { ip: 0, opcode: 8 })
+        );
+        assert_eq!(run(0, vec![5, 0, 1]), Err(VmError::OddProgramLength(3)));
+        // jnz 3 lands on the last value
+        assert_eq!(
+            run(1, vec![3, 3, 0, 1]),
+            Err(VmError::IpOutOfBounds { ip: 3, len: 4 })
+        );
+        assert_eq!(
+            run(-1, vec![6, 4]),
+            Err(VmError::NegativeShift { ip: 0, shift: -1 })
+        );
+        // huge shifts are fine, leaving only the sign
+        let mut computer = Computer::new(1 << 40, 0, 0, asm("adv A; out A"));
+        computer.run_program().unwrap();
+        assert_eq!(computer.format_output(), "0");
+
+        // the error ends the stream, after the output so far
+        let mut computer = Computer::new(0, 0, 0, vec![5, 0, 2, 7, 5, 0]);
+        let outputs: Vec<_> = computer.outputs(100).collect();
+        assert_eq!(
+            outputs,
+            [
+                Ok(0),
+                Err(VmError::InvalidComboOperand { ip: 2, operand: 7 })
+            ]
+        );
+
+        let err = part1(Computer::new(0, 0, 0, vec![0, 7])).unwrap_err();
+        assert_eq!(err.to_string(), "invalid combo operand 7 at 0");
+    }
+
+    #[test]
+    fn assemble_and_disassemble() -> Result<()> {
+        let input = parse_input(INPUT)?;
+        let listing = Program::to_asm(input.program())?;
+        assert_eq!(
+            listing,
+            indoc! {"
+                bst A
+                bxl 2
+                cdv B
+                bxc 1
+                bxl 3
+                out B
+                adv 3
+                jnz 0"}
+        );
+        assert_eq!(Program::from_asm(&listing)?, input.program());
+        assert_eq!(
+            Program::from_asm("bst a; BXL 2;cdv B;bxc 1;bxl 3;out b;adv 3;jnz 0")?,
+            input.program()
+        );
+        assert_eq!(asm("jnz 6; bxl 7; bxc"), [3, 6, 1, 7, 4, 0]);
+        assert_eq!(Program::to_asm(&[4, 0])?, "bxc");
+        Ok(())
+    }
+
+    #[test]
+    fn bad_asm_rejected() {
+        for bad in [
+            "foo 1", "adv", "adv 4", "adv D", "jnz A", "jnz 8", "out 1 2",
+        ] {
+            assert!(Program::from_asm(bad).is_err(), "{bad}");
+        }
+        assert!(Program::to_asm(&[0, 7]).is_err());
+        assert!(Program::to_asm(&[8, 0]).is_err());
+        assert!(Program::to_asm(&[0]).is_err());
+    }
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn quine_prefix_prune_correct() -> Result<()> {
+        let computer = parse_input(indoc! {"
+            Register A: 2024
+            Register B: 0
+            Register C: 0
+
+            Program: 0,3,5,4,3,0
+        "})?;
+        assert_eq!(find_quine_prefix_prune(&computer), Some(117440));
+        assert_eq!(find_quine_full_run(&computer), Some(117440));
+        Ok(())
+    }
+
+    #[test]
+    fn quine_search_gives_up() -> Result<()> {
+        // outputs a single value, so it can never reproduce its two
+        let computer = parse_input(indoc! {"
+            Register A: 0
+            Register B: 0
+            Register C: 0
+
+            Program: 5,4
+        "})?;
+        assert_eq!(quine_search_bound(&computer), 64);
+        assert_eq!(find_quine_prefix_prune(&computer), None);
+        assert_eq!(find_quine_full_run(&computer), None);
+        Ok(())
+    }
+
+    #[test]
+    fn run_many_matches_single_runs() -> Result<()> {
+        let computer = parse_input(EXAMPLE)?;
+        let results = run_many((0..500_i64).into_par_iter(), &computer);
+        assert_eq!(results.len(), 500);
+        for (i, (a, output)) in results.iter().enumerate() {
+            assert_eq!(*a, i as i64);
+            let mut single = Computer::new(*a, 0, 0, computer.program().to_vec());
+            single.run_program().unwrap();
+            assert_eq!(output.as_deref(), Ok(single.output()));
+        }
+        assert_eq!(results[0].1, Ok(vec![0]));
+        Ok(())
+    }
+
+    #[test]
+    fn run_many_sweep_finds_quine() -> Result<()> {
+        let computer = parse_input(indoc! {"
+            Register A: 2024
+            Register B: 0
+            Register C: 0
+
+            Program: 0,3,5,4,3,0
+        "})?;
+        let quines: Vec<_> = run_many((117_000..118_000_i64).into_par_iter(), &computer)
+            .into_iter()
+            .filter(|(_, output)| output.as_deref() == Ok(computer.program()))
+            .map(|(a, _)| a)
+            .collect();
+        // A is divided by 8 before anything is output, so the low bits don't matter
+        assert_eq!(quines, (117440..117448).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn reset_keeps_program() {
+        let mut computer = Computer::new(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        let copy = computer.clone();
+        assert!(Arc::ptr_eq(&computer.program, &copy.program));
+        computer.run_program().unwrap();
+        computer.reset(10, 0, 0);
+        assert!(computer.output().is_empty());
+        computer.run_program().unwrap();
+        assert_eq!(computer.format_output(), "5,2,1,0");
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let output = part1(problem)?;
+        assert_eq!(output, "4,6,3,5,6,3,5,2,1,0");
+        Ok(())
+    }
+}
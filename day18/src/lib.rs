@@ -0,0 +1,654 @@
+use anyhow::{bail, Result};
+use common::{
+    cartesian::{Point, ScreenDir},
+    error::AocContext,
+    graph::{bfs_grid, GridDistances, UnionFind},
+    grid, OptionAnyhow,
+};
+use nalgebra::DMatrix;
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    corrupted: Vec<Point>,
+    /// Step each byte falls at, when the input is `x,y,t` rather than `x,y`
+    times: Option<Vec<i64>>,
+}
+
+impl Problem {
+    pub fn has_timestamps(&self) -> bool {
+        self.times.is_some()
+    }
+
+    pub fn byte_count(&self) -> usize {
+        self.corrupted.len()
+    }
+
+    /// Check every byte lands within a `dim_x` by `dim_y` grid, naming the first that
+    /// doesn't by its line in the input
+    pub fn validate(&self, dim_x: usize, dim_y: usize) -> Result<()> {
+        if dim_x == 0 || dim_y == 0 {
+            bail!("the grid must be at least 1x1, not {dim_x}x{dim_y}");
+        }
+        let grid = DMatrix::from_element(dim_y, dim_x, ());
+        for (line_no, p) in self.corrupted.iter().enumerate() {
+            if !p.within_bounds(&grid) {
+                bail!(
+                    "line {}: byte {},{} is outside the {dim_x}x{dim_y} grid",
+                    line_no + 1,
+                    p.x,
+                    p.y
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bytes as `x,y` lines, or `x,y,t` lines if they fall at given steps; every line
+/// must use the same format
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut corrupted = vec![];
+    let mut times = vec![];
+    for line in input.lines() {
+        let fields: Vec<&str> = line.split(',').collect();
+        match fields[..] {
+            [x, y] => corrupted.push(Point::new(x.parse()?, y.parse()?)),
+            [x, y, t] => {
+                corrupted.push(Point::new(x.parse()?, y.parse()?));
+                times.push(t.parse()?);
+            }
+            _ => bail!("expected x,y or x,y,t: {line:?}"),
+        }
+    }
+
+    let times = match times.len() {
+        0 => None,
+        n if n == corrupted.len() => Some(times),
+        _ => bail!("some bytes have timestamps and some do not"),
+    };
+    Ok(Problem { corrupted, times })
+}
+
+/// Step after which each cell is blocked: bytes falling at `t` block the cell for
+/// every step `s > t`, and cells no byte lands on are never blocked
+fn fallen_at(
+    dim_x: usize,
+    dim_y: usize,
+    bytes: impl IntoIterator<Item = (Point, i64)>,
+) -> Result<DMatrix<i64>> {
+    let mut fallen = DMatrix::from_element(dim_y, dim_x, i64::MAX);
+    for (p, t) in bytes {
+        let Some(cell) = fallen.get_mut(p) else {
+            bail!("byte {},{} is outside the {dim_x}x{dim_y} grid", p.x, p.y);
+        };
+        *cell = (*cell).min(t);
+    }
+    Ok(fallen)
+}
+
+/// Distances from the top left, and the predecessor of each point on a shortest path
+/// to it; the first `corrupt_take` bytes have all fallen already
+fn search(
+    problem: &Problem,
+    dim_x: usize,
+    dim_y: usize,
+    corrupt_take: usize,
+) -> Result<GridDistances> {
+    let bytes = problem
+        .corrupted
+        .iter()
+        .take(corrupt_take)
+        .map(|p| (*p, -1));
+    Ok(search_fallen(&fallen_at(dim_x, dim_y, bytes)?))
+}
+
+/// Search over the time-expanded grid, where reaching a cell at step `s` requires
+/// its byte not to have fallen before `s`. Bytes only ever accumulate, so arriving
+/// anywhere later (or waiting) never helps, and the earliest arrival at each cell
+/// is the only state worth keeping. Every step costs one, so a breadth-first search
+/// finds the earliest arrivals in the order Dijkstra would.
+fn search_fallen(fallen: &DMatrix<i64>) -> GridDistances {
+    bfs_grid(Point::new(0, 0), fallen.shape(), |p, steps| {
+        fallen.get(p).is_some_and(|&t| t >= steps)
+    })
+}
+
+fn end_point(dim_x: usize, dim_y: usize) -> Point {
+    Point::new((dim_x - 1) as i64, (dim_y - 1) as i64)
+}
+
+pub fn part1(problem: &Problem, dim_x: usize, dim_y: usize, corrupt_take: usize) -> Result<i64> {
+    let found = search(problem, dim_x, dim_y, corrupt_take)?;
+    Ok(*found.dist.get(end_point(dim_x, dim_y)).unwrap())
+}
+
+/// Shortest escape when each byte falls at its own timestamp, so the path must keep
+/// ahead of bytes landing on it; `i64::MAX` if there is no way out
+pub fn part1_timed(problem: &Problem, dim_x: usize, dim_y: usize) -> Result<i64> {
+    let times = problem.times.as_ref().ok_anyhow()?;
+    let bytes = problem.corrupted.iter().copied().zip(times.iter().copied());
+    let found = search_fallen(&fallen_at(dim_x, dim_y, bytes)?);
+    Ok(*found.dist.get(end_point(dim_x, dim_y)).unwrap())
+}
+
+/// One shortest escape path from the top left to the bottom right, including both
+/// ends, or `None` if the fallen bytes block every path
+pub fn shortest_path(
+    problem: &Problem,
+    dim_x: usize,
+    dim_y: usize,
+    corrupt_take: usize,
+) -> Result<Option<Vec<Point>>> {
+    let found = search(problem, dim_x, dim_y, corrupt_take)?;
+    Ok(found.path_to(end_point(dim_x, dim_y)))
+}
+
+/// Grid with fallen bytes `#`, the path `O`, and the highlighted byte `@`
+fn render(
+    dim_x: usize,
+    dim_y: usize,
+    fallen: &[Point],
+    path: &[Point],
+    highlight: Option<Point>,
+) -> String {
+    let mut grid = DMatrix::from_element(dim_y, dim_x, '.');
+    for p in path {
+        *grid.get_mut(*p).unwrap() = 'O';
+    }
+    for p in fallen {
+        *grid.get_mut(*p).unwrap() = '#';
+    }
+    if let Some(p) = highlight {
+        *grid.get_mut(p).unwrap() = '@';
+    }
+
+    grid::render(&grid)
+}
+
+/// Show the path as bytes fall, redrawing each time a byte lands on the current
+/// path, until the final blocking byte
+pub fn visualize(problem: &Problem, dim_x: usize, dim_y: usize, init_take: usize) -> Result<()> {
+    let init_take = init_take.min(problem.corrupted.len());
+    let mut path = shortest_path(problem, dim_x, dim_y, init_take)?.unwrap_or_default();
+    // steps, as part 1 counts them, are one fewer than the cells on the path
+    match path.len() {
+        0 => println!("After {init_take} bytes, the exit is blocked"),
+        cells => println!("After {init_take} bytes, path length {}", cells - 1),
+    }
+    print!(
+        "{}",
+        render(dim_x, dim_y, &problem.corrupted[..init_take], &path, None)
+    );
+
+    for take in init_take + 1..=problem.corrupted.len() {
+        let fallen = &problem.corrupted[..take];
+        let byte = fallen[take - 1];
+        if !path.contains(&byte) {
+            continue;
+        }
+        match shortest_path(problem, dim_x, dim_y, take)? {
+            Some(p) => {
+                path = p;
+                println!();
+                println!(
+                    "After {take} bytes, {},{} fell on the path; new length {}",
+                    byte.x,
+                    byte.y,
+                    path.len() - 1
+                );
+                print!("{}", render(dim_x, dim_y, fallen, &path, None));
+            }
+            None => {
+                println!();
+                println!("After {take} bytes, {},{} blocks the exit", byte.x, byte.y);
+                print!("{}", render(dim_x, dim_y, fallen, &path, Some(byte)));
+                return Ok(());
+            }
+        }
+    }
+    Ok(())
+}
+
+// super inefficient re-creating the map starting from scratch every time, but still under 500ms
+pub fn part2(problem: &Problem, dim_x: usize, dim_y: usize, init_take: usize) -> Result<String> {
+    for corrupt_take in init_take..problem.corrupted.len() {
+        let dist = part1(problem, dim_x, dim_y, corrupt_take)?;
+        if dist == i64::MAX {
+            let final_point = problem.corrupted[corrupt_take - 1];
+            return Ok(format!("{},{}", final_point.x, final_point.y));
+        }
+    }
+    bail!("No solution")
+}
+
+/// As `part2`, but bisecting on the number of bytes fallen: once the exit is blocked it
+/// stays blocked, so only about log2(bytes) searches are needed
+pub fn part2_bisect(
+    problem: &Problem,
+    dim_x: usize,
+    dim_y: usize,
+    init_take: usize,
+) -> Result<String> {
+    let blocked = |take| Ok::<_, anyhow::Error>(part1(problem, dim_x, dim_y, take)? == i64::MAX);
+
+    // the exit is open after `lo` bytes and blocked after `hi`
+    let (mut lo, mut hi) = (init_take, problem.corrupted.len());
+    if blocked(lo)? || !blocked(hi)? {
+        bail!("No solution")
+    }
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if blocked(mid)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    let final_point = problem.corrupted[hi - 1];
+    Ok(format!("{},{}", final_point.x, final_point.y))
+}
+
+/// Number of bytes after which the exit is first cut off, or `None` if it never is.
+/// Rather than searching after each byte, this starts with every byte fallen and takes
+/// them away again in reverse, joining each freed cell to its open neighbours until
+/// the corners connect.
+pub fn bytes_to_block(problem: &Problem, dim_x: usize, dim_y: usize) -> Result<Option<usize>> {
+    let bytes = problem.corrupted.iter().enumerate();
+    let first_fall = fallen_at(dim_x, dim_y, bytes.map(|(i, p)| (*p, i as i64)))?;
+    // a cell is open after `take` bytes until its first byte falls
+    let open = |p: Point, take: usize| first_fall.get(p).is_some_and(|&i| i >= take as i64);
+    let index = |p: Point| p.y as usize * dim_x + p.x as usize;
+    let (start, end) = (Point::new(0, 0), end_point(dim_x, dim_y));
+
+    let mut sets = UnionFind::new(dim_x * dim_y);
+    let join = |sets: &mut UnionFind, p: Point, take: usize| {
+        for dir in ScreenDir::iter() {
+            let next = p + dir.into();
+            if open(next, take) {
+                sets.union(index(p), index(next));
+            }
+        }
+    };
+    let take = problem.corrupted.len();
+    for y in 0..dim_y {
+        for x in 0..dim_x {
+            let p = Point::new(x as i64, y as i64);
+            if open(p, take) {
+                join(&mut sets, p, take);
+            }
+        }
+    }
+    let escapes = |sets: &mut UnionFind, take| {
+        open(start, take) && open(end, take) && sets.connected(index(start), index(end))
+    };
+    if escapes(&mut sets, take) {
+        return Ok(None);
+    }
+
+    for take in (0..problem.corrupted.len()).rev() {
+        let p = problem.corrupted[take];
+        // only the first byte on a cell blocks it
+        if first_fall.get(p) == Some(&(take as i64)) {
+            join(&mut sets, p, take);
+        }
+        if escapes(&mut sets, take) {
+            return Ok(Some(take + 1));
+        }
+    }
+    bail!("the exit is blocked with no bytes fallen")
+}
+
+/// How a byte falling changes the shortest escape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteMetric {
+    /// Bytes fallen, including this one
+    pub bytes: usize,
+    pub byte: Point,
+    /// Shortest path length afterwards, or `None` if the exit is blocked
+    pub length: Option<i64>,
+    /// Shortest path length before this byte fell
+    pub previous: Option<i64>,
+}
+impl ByteMetric {
+    pub const CSV_HEADER: &str = "bytes,x,y,length,increase,critical";
+
+    /// Whether this byte made the escape longer, or blocked it
+    pub fn is_critical(&self) -> bool {
+        self.previous.is_some() && self.length != self.previous
+    }
+
+    /// A row to go under `CSV_HEADER`, with the length and increase empty once blocked
+    pub fn csv_row(&self) -> String {
+        let length = self.length.map_or(String::new(), |l| l.to_string());
+        let increase = match (self.length, self.previous) {
+            (Some(l), Some(p)) => (l - p).to_string(),
+            _ => String::new(),
+        };
+        format!(
+            "{},{},{},{length},{increase},{}",
+            self.bytes,
+            self.byte.x,
+            self.byte.y,
+            self.is_critical()
+        )
+    }
+}
+
+/// Shortest path length after each byte falls. A byte can only lengthen the path if
+/// it lands on the current shortest path, so only those bytes need a new search, and
+/// none are needed once `bytes_to_block` says the exit is cut off.
+pub fn path_metrics(problem: &Problem, dim_x: usize, dim_y: usize) -> Result<Vec<ByteMetric>> {
+    let blocked_at = bytes_to_block(problem, dim_x, dim_y)?.unwrap_or(usize::MAX);
+    let mut path = shortest_path(problem, dim_x, dim_y, 0)?;
+    let steps = |path: &Option<Vec<Point>>| path.as_ref().map(|p| p.len() as i64 - 1);
+
+    let mut metrics = Vec::with_capacity(problem.corrupted.len());
+    for (i, &byte) in problem.corrupted.iter().enumerate() {
+        let bytes = i + 1;
+        let previous = steps(&path);
+        if bytes >= blocked_at {
+            path = None;
+        } else if path.as_ref().is_some_and(|p| p.contains(&byte)) {
+            path = shortest_path(problem, dim_x, dim_y, bytes)?;
+        }
+        metrics.push(ByteMetric {
+            bytes,
+            byte,
+            length: steps(&path),
+            previous,
+        });
+    }
+    Ok(metrics)
+}
+
+/// The puzzle's grid size, and the bytes fallen for part 1
+pub const PUZZLE_DIMS: (usize, usize, usize) = (71, 71, 1024);
+
+/// Answers to both parts on a `(dim_x, dim_y)` grid with `take` bytes fallen for
+/// part 1, given as `(dim_x, dim_y, take)`
+pub fn solve_with(input: &str, dims: (usize, usize, usize)) -> Result<(String, String)> {
+    let (dim_x, dim_y, take) = dims;
+    let problem = parse_input(input).day_context(DAY.day)?;
+    Ok((
+        part1(&problem, dim_x, dim_y, take)
+            .part_context(DAY.day, "1")?
+            .to_string(),
+        part2(&problem, dim_x, dim_y, take).part_context(DAY.day, "2")?,
+    ))
+}
+
+/// Answers to both parts for the puzzle input `input`, on the puzzle's grid
+pub fn solve(input: &str) -> Result<(String, String)> {
+    solve_with(input, PUZZLE_DIMS)
+}
+
+/// Answers to both parts for the example, on its smaller grid
+pub fn solve_example(input: &str) -> Result<(String, Option<String>)> {
+    let (part1, part2) = solve_with(input, EXAMPLE_DIMS)?;
+    Ok((part1, Some(part2)))
+}
+
+common::aoc_day!(18, "RAM Run", example: solve_example);
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    5,4
+    4,2
+    4,5
+    3,0
+    2,1
+    6,3
+    2,4
+    1,5
+    0,6
+    3,3
+    2,6
+    5,1
+    1,2
+    5,5
+    2,5
+    6,5
+    1,4
+    0,4
+    6,4
+    1,1
+    6,1
+    1,0
+    0,5
+    1,6
+    2,0
+"};
+/// The example's grid size and bytes fallen for part 1, rather than the puzzle's
+/// `PUZZLE_DIMS`
+pub const EXAMPLE_DIMS: (usize, usize, usize) = (7, 7, 12);
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("22"), Some("6,1")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem, 7, 7, 12)?;
+        assert_eq!(count, 22);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem, 7, 7, 12)?;
+        assert_eq!(count, "6,1");
+        Ok(())
+    }
+
+    #[test]
+    fn visualize_more_bytes_than_fell() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        visualize(&problem, 7, 7, 5000)?;
+        visualize(&problem, 7, 7, 0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn solve_on_example_grid() -> Result<()> {
+        let answers = solve_with(EXAMPLE, EXAMPLE_DIMS)?;
+        assert_eq!(answers, ("22".to_string(), "6,1".to_string()));
+        // on the puzzle's grid there are too few bytes to block the exit
+        assert!(solve(EXAMPLE).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn part2_bisect_matches_linear() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        for init_take in [0, 5, 12, 20] {
+            assert_eq!(
+                part2_bisect(&problem, 7, 7, init_take)?,
+                part2(&problem, 7, 7, init_take)?
+            );
+        }
+        // blocked from the start, or never blocked
+        assert!(part2_bisect(&problem, 7, 7, 25).is_err());
+        assert!(part2_bisect(&problem, 9, 9, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate_dims() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        problem.validate(7, 7)?;
+        let err = problem.validate(7, 6).unwrap_err();
+        assert_eq!(err.to_string(), "line 9: byte 0,6 is outside the 7x6 grid");
+        assert!(problem.validate(0, 7).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn shortest_path_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let path = shortest_path(&problem, 7, 7, 12)?.unwrap();
+        assert_eq!(path.len(), 23);
+        assert_eq!(path.first(), Some(&Point::new(0, 0)));
+        assert_eq!(path.last(), Some(&Point::new(6, 6)));
+        for (a, b) in path.iter().zip(&path[1..]) {
+            let d = *b - *a;
+            assert_eq!(d.x.abs() + d.y.abs(), 1);
+            assert!(!problem.corrupted[..12].contains(b));
+        }
+
+        // 6,1 is byte 21, which blocks the exit
+        assert!(shortest_path(&problem, 7, 7, 20)?.is_some());
+        assert!(shortest_path(&problem, 7, 7, 21)?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn render_marks_path_and_blocker() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let path = shortest_path(&problem, 7, 7, 12)?.unwrap();
+        let grid = render(7, 7, &problem.corrupted[..12], &path, None);
+        assert_eq!(grid.lines().count(), 7);
+        assert_eq!(grid.chars().filter(|c| *c == 'O').count(), 23);
+        assert_eq!(grid.chars().filter(|c| *c == '#').count(), 12);
+
+        let grid = render(7, 7, &problem.corrupted[..21], &[], Some(Point::new(6, 1)));
+        assert_eq!(grid.lines().nth(1).unwrap().chars().nth(6), Some('@'));
+        Ok(())
+    }
+
+    #[test]
+    fn blocking_byte_from_reverse() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        // 6,1 is byte 21
+        assert_eq!(bytes_to_block(&problem, 7, 7)?, Some(21));
+        assert_eq!(bytes_to_block(&problem, 9, 9)?, None);
+
+        // a repeated byte only blocks its cell the first time
+        let problem = parse_input("1,0\n1,1\n1,0\n1,2\n")?;
+        assert_eq!(bytes_to_block(&problem, 3, 3)?, Some(4));
+        // bytes on the corners block them too
+        let problem = parse_input("1,1\n2,2\n")?;
+        assert_eq!(bytes_to_block(&problem, 3, 3)?, Some(2));
+        assert!(bytes_to_block(&problem, 2, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn corner_bytes_agree_with_search() -> Result<()> {
+        for input in [
+            "0,0\n",
+            "2,2\n",
+            "1,1\n0,0\n",
+            "1,0\n2,2\n0,1\n",
+            "0,1\n1,0\n",
+        ] {
+            let problem = parse_input(input)?;
+            let mut blocked_at = None;
+            for take in 0..=problem.byte_count() {
+                if part1(&problem, 3, 3, take)? == i64::MAX {
+                    blocked_at = Some(take);
+                    break;
+                }
+            }
+            assert_eq!(bytes_to_block(&problem, 3, 3)?, blocked_at, "{input:?}");
+            let blocker = problem.corrupted[blocked_at.unwrap() - 1];
+            assert_eq!(
+                part2_bisect(&problem, 3, 3, 0)?,
+                format!("{},{}", blocker.x, blocker.y)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn metrics_match_searching_every_prefix() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let metrics = path_metrics(&problem, 7, 7)?;
+        assert_eq!(metrics.len(), 25);
+        let mut previous = Some(12);
+        for m in &metrics {
+            let length = part1(&problem, 7, 7, m.bytes)?;
+            assert_eq!(m.length, (length != i64::MAX).then_some(length));
+            assert_eq!(m.previous, previous);
+            previous = m.length;
+        }
+
+        let blocker = metrics.iter().find(|m| m.length.is_none()).unwrap();
+        assert_eq!(blocker.byte, Point::new(6, 1));
+        assert!(blocker.is_critical());
+        assert!(metrics.iter().filter(|m| m.is_critical()).count() > 1);
+
+        let rows: Vec<_> = metrics.iter().map(ByteMetric::csv_row).collect();
+        assert_eq!(rows[0], "1,5,4,12,0,false");
+        assert_eq!(rows[20], "21,6,1,,,true");
+        assert_eq!(rows[24], "25,2,0,,,false");
+        assert!(rows
+            .iter()
+            .all(|r| r.split(',').count() == ByteMetric::CSV_HEADER.split(',').count()));
+        Ok(())
+    }
+
+    #[test]
+    fn solve_names_failing_part() {
+        // too few bytes to ever block the exit
+        let err = solve("0,1\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "day 18, part 2: No solution");
+        let err = solve("0,x\n").unwrap_err();
+        assert_eq!(err.to_string(), "day 18");
+    }
+
+    #[test]
+    fn rectangular_grid() -> Result<()> {
+        // a single byte forces the path down to the second row of a 5x2 grid
+        let problem = parse_input("1,0\n")?;
+        assert_eq!(part1(&problem, 5, 2, 1)?, 5);
+        assert!(part1(&problem, 1, 5, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_timestamps() -> Result<()> {
+        let problem = parse_input("1,0,5\n1,1,5\n")?;
+        assert!(problem.has_timestamps());
+        assert!(!parse_input(EXAMPLE)?.has_timestamps());
+        assert!(parse_input("1,0,5\n1,1\n").is_err());
+        assert!(parse_input("1,0,5,2\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn timed_bytes_fall_behind_path() -> Result<()> {
+        // a full wall, but it only falls after we have crossed it
+        let problem = parse_input("1,0,5\n1,1,5\n1,2,5\n")?;
+        assert_eq!(part1(&problem, 3, 3, 3)?, i64::MAX);
+        assert_eq!(part1_timed(&problem, 3, 3)?, 4);
+
+        // each gap closes just before we can reach it
+        let problem = parse_input("1,0,0\n1,1,1\n1,2,2\n")?;
+        assert_eq!(part1_timed(&problem, 3, 3)?, i64::MAX);
+
+        // bytes falling ahead of us force a detour
+        let problem = parse_input("1,0,0\n1,1,1\n")?;
+        assert_eq!(part1_timed(&problem, 3, 3)?, 4);
+        let problem = parse_input("1,0,0\n1,1,1\n1,2,9\n0,1,0\n")?;
+        assert_eq!(part1_timed(&problem, 3, 3)?, i64::MAX);
+        Ok(())
+    }
+
+    #[test]
+    fn timed_without_timestamps_is_error() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert!(part1_timed(&problem, 7, 7).is_err());
+        Ok(())
+    }
+}
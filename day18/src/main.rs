@@ -1,149 +1,64 @@
-use std::{time::Instant, usize};
+use anyhow::Context;
+use common::{error::AocContext, timing::TimingReport};
 
-use anyhow::{bail, Result};
-use common::{
-    cartesian::{Point, ScreenDir},
-    OptionAnyhow,
+use day18::{
+    parse_input, part1, part1_timed, part2, part2_bisect, path_metrics, visualize, ByteMetric, DAY,
+    EXAMPLE, EXAMPLE_ANSWERS, EXAMPLE_DIMS, PUZZLE_DIMS,
 };
-use nalgebra::DMatrix;
-use priority_queue::PriorityQueue;
-use strum::IntoEnumIterator;
 
-#[derive(Debug, Clone)]
-pub struct Problem {
-    corrupted: Vec<Point>,
-}
-
-fn parse_input(input: &str) -> Result<Problem> {
-    let mut corrupted = vec![];
-    for line in input.lines() {
-        let (x, y) = line.split_once(",").ok_anyhow()?;
-        corrupted.push(Point::new(x.parse()?, y.parse()?));
-    }
-
-    Ok(Problem { corrupted })
-}
-
-fn part1(problem: &Problem, dim_x: usize, dim_y: usize, corrupt_take: usize) -> Result<i64> {
-    let mut map = DMatrix::from_element(dim_y, dim_x, false);
-    let mut dist = DMatrix::from_element(dim_y, dim_x, i64::MAX);
-    for p in problem.corrupted.iter().take(corrupt_take) {
-        *map.get_mut(*p).unwrap() = true;
-    }
-
-    let mut q = PriorityQueue::new();
-
-    let start = Point::new(0, 0);
-    let end = Point::new((dim_x - 1) as i64, (dim_y - 1) as i64);
-    *dist.get_mut(start).unwrap() = 0;
-    q.push(start, 0);
-
-    while let Some((cur_p, _)) = q.pop() {
-        // get distance for this state
-        let cur_dist = dist.get(cur_p).cloned().unwrap();
-
-        // update all reachable nodes
-        for dir in ScreenDir::iter() {
-            let next_p = cur_p + dir.into();
-            if let Some(next_cor) = map.get(next_p).copied() {
-                if !next_cor {
-                    // this distance is current cost + cost
-                    let cost = 1;
-                    let alt = cur_dist + cost;
-
-                    if alt < *dist.get(next_p).unwrap() {
-                        *dist.get_mut(next_p).unwrap() = alt;
-                        q.push(next_p, -alt);
-                    }
-                }
-            }
+fn main() -> anyhow::Result<()> {
+    let text = common::read_input("input1.txt", EXAMPLE).input_context(DAY.day, "input1.txt")?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
+
+    // `--dims X,Y` and `--take N` change the grid size and the bytes fallen for part 1
+    // from the puzzle's `PUZZLE_DIMS`, or the example's with `--example`
+    let (default_x, default_y, default_take) = match common::example_requested() {
+        true => EXAMPLE_DIMS,
+        false => PUZZLE_DIMS,
+    };
+    let dims = common::arg_value("--dims");
+    let take = common::arg_value("--take");
+    let (dim_x, dim_y) = match &dims {
+        Some(dims) => {
+            let (x, y) = dims.split_once(',').context("expected --dims X,Y")?;
+            (x.parse()?, y.parse()?)
         }
+        None => (default_x, default_y),
+    };
+    let take = take.as_deref().map_or(Ok(default_take), str::parse)?;
+
+    let problem = timing.parse(|| parse_input(&text))?;
+    problem.validate(dim_x, dim_y)?;
+    if take > problem.byte_count() {
+        anyhow::bail!("can't take {take} of {} bytes", problem.byte_count());
     }
 
-    let end_dist = *dist.get(end).unwrap();
-    Ok(end_dist)
-}
-
-// super inefficient re-creating the map starting from scratch every time, but still under 500ms
-fn part2(problem: &Problem, dim_x: usize, dim_y: usize, init_take: usize) -> Result<String> {
-    for corrupt_take in init_take..problem.corrupted.len() {
-        let dist = part1(problem, dim_x, dim_y, corrupt_take)?;
-        if dist == i64::MAX {
-            let final_point = problem.corrupted[corrupt_take - 1];
-            return Ok(format!("{},{}", final_point.x, final_point.y));
+    // `--metrics` prints the path length after each byte instead, as CSV for plotting
+    if std::env::args().any(|a| a == "--metrics") {
+        println!("{}", ByteMetric::CSV_HEADER);
+        for metric in path_metrics(&problem, dim_x, dim_y)? {
+            println!("{}", metric.csv_row());
         }
+        return Ok(());
     }
-    bail!("No solution")
-}
-
-fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
-    let problem = parse_input(&text)?;
 
-    let t1 = Instant::now();
-    let count_part1 = part1(&problem, 71, 71, 1024)?;
-    println!("Part 1 result is {count_part1} (took {:?})", t1.elapsed());
-
-    let t2 = Instant::now();
-    let count_part2 = part2(&problem, 71, 71, 1024)?;
-    println!("Part 2 result is {count_part2} (took {:?})", t2.elapsed());
-
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use indoc::indoc;
-
-    const EXAMPLE: &str = indoc! {"
-        5,4
-        4,2
-        4,5
-        3,0
-        2,1
-        6,3
-        2,4
-        1,5
-        0,6
-        3,3
-        2,6
-        5,1
-        1,2
-        5,5
-        2,5
-        6,5
-        1,4
-        0,4
-        6,4
-        1,1
-        6,1
-        1,0
-        0,5
-        1,6
-        2,0
-    "};
-
-    #[test]
-    fn test_parse_input() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        println!("{:?}", problem);
-        Ok(())
+    if problem.has_timestamps() {
+        timing.part("1 (timed)", || part1_timed(&problem, dim_x, dim_y))?;
+    } else if std::env::args().any(|a| a == "--find-blocker") {
+        // just part 2, comparing a search per byte with bisection
+        timing.part("2", || part2(&problem, dim_x, dim_y, take))?;
+        timing.part("2 (bisect)", || part2_bisect(&problem, dim_x, dim_y, take))?;
+    } else if dims.is_some() || take != default_take {
+        timing.part("1", || part1(&problem, dim_x, dim_y, take))?;
+        timing.part("2", || part2_bisect(&problem, dim_x, dim_y, take))?;
+    } else {
+        timing.part("1", || part1(&problem, dim_x, dim_y, take))?;
+        timing.part("2", || part2(&problem, dim_x, dim_y, take))?;
     }
 
-    #[test]
-    fn part1_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part1(&problem, 7, 7, 12)?;
-        assert_eq!(count, 22);
-        Ok(())
+    if std::env::args().any(|a| a == "--visualize") {
+        visualize(&problem, dim_x, dim_y, take)?;
     }
 
-    #[test]
-    fn part2_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part2(&problem, 7, 7, 12)?;
-        assert_eq!(count, "6,1");
-        Ok(())
-    }
+    Ok(())
 }
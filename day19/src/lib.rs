@@ -0,0 +1,456 @@
+use std::{fmt::Display, iter};
+
+use anyhow::{anyhow, Result};
+use common::{memo::Memoized, OptionAnyhow, ResultAnyhow};
+use itertools::Itertools;
+use rayon::prelude::*;
+
+/// A stripe colour, stored as its lowercase ASCII letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Color(u8);
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0 as char)
+    }
+}
+
+type Towel = Vec<Color>;
+type Pattern = Vec<Color>;
+
+/// The colours that appear on the towels; patterns may only use these
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Alphabet(Vec<Color>);
+impl Alphabet {
+    fn color(&self, ch: char) -> Option<Color> {
+        let ch = ch.to_ascii_lowercase();
+        self.0.iter().copied().find(|c| c.0 as char == ch)
+    }
+
+    /// Position of `color` in the alphabet
+    fn index(&self, color: Color) -> usize {
+        self.0.binary_search(&color).unwrap()
+    }
+
+    fn indices(&self, pattern: &[Color]) -> Vec<usize> {
+        pattern.iter().map(|c| self.index(*c)).collect()
+    }
+}
+
+/// The towels as a trie over the alphabet, so every towel matching at some point of
+/// a pattern is found in one walk down from the root
+#[derive(Debug, Clone)]
+pub struct TowelTrie {
+    /// `width` children for each node, by colour index; 0 for none, as the root (node
+    /// 0) is never a child
+    children: Vec<u32>,
+    /// whether the path to each node spells a whole towel
+    is_towel: Vec<bool>,
+    width: usize,
+}
+impl TowelTrie {
+    fn new(alphabet: &Alphabet, towels: &[Towel]) -> Self {
+        let width = alphabet.0.len();
+        let mut trie = TowelTrie {
+            children: vec![0; width],
+            is_towel: vec![false],
+            width,
+        };
+        for towel in towels {
+            let mut node = 0;
+            for &color in towel {
+                let slot = node * trie.width + alphabet.index(color);
+                if trie.children[slot] == 0 {
+                    trie.children[slot] = trie.is_towel.len() as u32;
+                    trie.children.extend(iter::repeat_n(0, width));
+                    trie.is_towel.push(false);
+                }
+                node = trie.children[slot] as usize;
+            }
+            trie.is_towel[node] = true;
+        }
+        trie
+    }
+
+    /// Ways of making `pattern`, given as colour indices, from the towels; counted
+    /// from the end, as the ways of making each suffix
+    fn count_ways(&self, pattern: &[usize]) -> usize {
+        let mut ways = vec![0; pattern.len() + 1];
+        ways[pattern.len()] = 1;
+        for start in (0..pattern.len()).rev() {
+            let mut node = 0;
+            for (end, &color) in pattern.iter().enumerate().skip(start) {
+                node = self.children[node * self.width + color] as usize;
+                if node == 0 {
+                    break;
+                }
+                if self.is_towel[node] {
+                    ways[start] += ways[end + 1];
+                }
+            }
+        }
+        ways[0]
+    }
+
+    /// Whether `pattern`, given as colour indices, can be made at all. Greedy, trying
+    /// the longest towel first at each point, and backtracking to shorter ones; each
+    /// position is only tried once, so it's never worse than counting the ways, and
+    /// stops at the first arrangement found.
+    fn can_make(&self, pattern: &[usize]) -> bool {
+        let mut tried = vec![false; pattern.len() + 1];
+        let mut stack = vec![0];
+        while let Some(start) = stack.pop() {
+            if start == pattern.len() {
+                return true;
+            }
+            // shortest pushed first, so the longest is popped first
+            let mut node = 0;
+            for (end, &color) in pattern.iter().enumerate().skip(start) {
+                node = self.children[node * self.width + color] as usize;
+                if node == 0 {
+                    break;
+                }
+                if self.is_towel[node] && !tried[end + 1] {
+                    tried[end + 1] = true;
+                    stack.push(end + 1);
+                }
+            }
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    alphabet: Alphabet,
+    towels: Vec<Towel>,
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone)]
+struct PrintPat<'a>(&'a [Color]);
+impl Display for PrintPat<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for v in self.0 {
+            write!(f, "{v}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Towels are comma-separated runs of letters, in either case; the alphabet is
+/// every letter that appears
+fn parse_towels(line: &str) -> Result<(Alphabet, Vec<Towel>)> {
+    let mut colors = vec![];
+    let mut towels = vec![];
+    let mut column = 1;
+    for towel in line.split(',') {
+        let leading = towel.len() - towel.trim_start().len();
+        let mut parsed = vec![];
+        for (i, ch) in towel.trim().chars().enumerate() {
+            if !ch.is_ascii_alphabetic() {
+                return Err(anyhow!("unexpected character {ch:?}")).at(1, column + leading + i);
+            }
+            let color = Color(ch.to_ascii_lowercase() as u8);
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+            parsed.push(color);
+        }
+        if parsed.is_empty() {
+            return Err(anyhow!("empty towel")).at(1, column);
+        }
+        towels.push(parsed);
+        column += towel.len() + 1;
+    }
+    colors.sort();
+    Ok((Alphabet(colors), towels))
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut lines = input.lines().map(|l| l.trim_end());
+
+    let first = lines
+        .next()
+        .with_context_anyhow(|| "expected towels")
+        .at_line(1)?;
+    let (alphabet, towels) = parse_towels(first)?;
+
+    // skip blank
+    let blank = lines.next().filter(|l| l.is_empty());
+    blank
+        .with_context_anyhow(|| "expected a blank line after the towels")
+        .at_line(2)?;
+
+    let mut patterns = vec![];
+    for (line_no, line) in lines.enumerate().filter(|(_, l)| !l.is_empty()) {
+        let pattern = line
+            .chars()
+            .enumerate()
+            .map(|(col, ch)| {
+                alphabet
+                    .color(ch)
+                    .with_context_anyhow(|| format!("colour {ch:?} is not on any towel"))
+                    .at(line_no + 3, col + 1)
+            })
+            .collect::<Result<_>>()?;
+        patterns.push(pattern);
+    }
+
+    Ok(Problem {
+        alphabet,
+        towels,
+        patterns,
+    })
+}
+
+impl Problem {
+    /// The towels as a trie, to build once and share between the parts
+    pub fn towel_trie(&self) -> TowelTrie {
+        TowelTrie::new(&self.alphabet, &self.towels)
+    }
+
+    /// Whether `pattern`, written in the input's letters, can be made from the towels;
+    /// never for a pattern using a colour no towel has
+    pub fn can_make(&self, trie: &TowelTrie, pattern: &str) -> bool {
+        let pattern: Option<Vec<_>> = pattern.chars().map(|ch| self.alphabet.color(ch)).collect();
+        pattern.is_some_and(|p| trie.can_make(&self.alphabet.indices(&p)))
+    }
+
+    fn count_solutions_for(
+        &self,
+        pattern: &[Color],
+        known: &mut Memoized<Vec<Color>, usize>,
+    ) -> usize {
+        assert!(!pattern.is_empty());
+
+        if let Some(k) = known.get(pattern) {
+            return k;
+        }
+
+        let mut found_count = 0;
+        for t in &self.towels {
+            if pattern[..].starts_with(t) {
+                let rem = &pattern[t.len()..];
+                if rem.is_empty() {
+                    found_count += 1;
+                } else {
+                    found_count += self.count_solutions_for(rem, known);
+                }
+            }
+        }
+
+        known.insert(pattern.to_vec(), found_count);
+        found_count
+    }
+}
+
+pub fn count_solutions(problem: &Problem) -> Result<(usize, usize)> {
+    let mut problem = problem.clone();
+    problem.towels.sort_by_key(|t| -(t.len() as i64));
+    let mut known = Memoized::new();
+    let mut count_solved = 0;
+    let mut total_solutions = 0;
+    for pattern in &problem.patterns {
+        let solutions = problem.count_solutions_for(pattern, &mut known);
+        if solutions > 0 {
+            count_solved += 1;
+        }
+        total_solutions += solutions;
+    }
+    Ok((count_solved, total_solutions))
+}
+
+/// Number of patterns that can be made, and the total number of ways of making them.
+/// Patterns are counted in parallel, all sharing one read-only towel trie.
+pub fn solve_both(problem: &Problem) -> (usize, usize) {
+    let trie = TowelTrie::new(&problem.alphabet, &problem.towels);
+    problem
+        .patterns
+        .par_iter()
+        .map(|pattern| {
+            let ways = trie.count_ways(&problem.alphabet.indices(pattern));
+            (usize::from(ways > 0), ways)
+        })
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1))
+}
+
+/// Number of patterns that can be made, stopping at the first arrangement of each
+pub fn part1(problem: &Problem, trie: &TowelTrie) -> usize {
+    problem
+        .patterns
+        .par_iter()
+        .filter(|pattern| trie.can_make(&problem.alphabet.indices(pattern)))
+        .count()
+}
+
+/// Total number of ways of making all the patterns
+pub fn part2(problem: &Problem, trie: &TowelTrie) -> usize {
+    problem
+        .patterns
+        .par_iter()
+        .map(|pattern| trie.count_ways(&problem.alphabet.indices(pattern)))
+        .sum()
+}
+
+/// Print the towels, colours and the number of solutions for each pattern
+pub fn print_solutions(problem: &Problem) {
+    let mut problem = problem.clone();
+    problem.towels.sort_by_key(|t| -(t.len() as i64));
+    println!("{}", problem.towels.iter().map(|p| PrintPat(p)).join("; "));
+    println!("colours: {}", PrintPat(&problem.alphabet.0));
+
+    let mut known = Memoized::new();
+    for pattern in &problem.patterns {
+        let solutions = problem.count_solutions_for(pattern, &mut known);
+        println!("{} => {} solutions", PrintPat(pattern), solutions);
+    }
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    let trie = problem.towel_trie();
+    Ok((
+        part1(&problem, &trie).to_string(),
+        part2(&problem, &trie).to_string(),
+    ))
+}
+
+common::aoc_day!(19, "Linen Layout");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    r, wr, b, g, bwu, rb, gb, br
+
+    brwrr
+    bggr
+    gbbr
+    rrbgbr
+    ubwu
+    bwurrg
+    brgr
+    bbrgwb
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("6"), Some("16")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let (count, _) = count_solutions(&problem)?;
+        assert_eq!(count, 6);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let (_, count) = count_solutions(&problem)?;
+        assert_eq!(count, 16);
+        Ok(())
+    }
+
+    #[test]
+    fn trie_matches_memoized() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(solve_both(&problem), (6, 16));
+
+        // towels that are prefixes of each other, and a pattern no towel starts
+        let problem = parse_input("a, aa, aaa, b\n\naaaa\nbaab\naab\nab\n")?;
+        assert_eq!(solve_both(&problem), count_solutions(&problem)?);
+        assert_eq!(solve_both(&problem), (4, 7 + 2 + 2 + 1));
+        let problem = parse_input("ab, c\n\nba\n")?;
+        assert_eq!(solve_both(&problem), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn can_make_matches_counting() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let trie = problem.towel_trie();
+        assert_eq!((part1(&problem, &trie), part2(&problem, &trie)), (6, 16));
+        assert!(problem.can_make(&trie, "brwrr"));
+        assert!(!problem.can_make(&trie, "ubwu"));
+        assert!(!problem.can_make(&trie, "brxr"));
+        assert!(problem.can_make(&trie, ""));
+
+        // the longest towel first leads to a dead end, and backtracking finds the way
+        let problem = parse_input("a, aa, aaa, ab\n\naaab\n")?;
+        let trie = problem.towel_trie();
+        assert!(problem.can_make(&trie, "aaab"));
+        assert!(problem.can_make(&trie, "AAAAB"));
+        assert!(!problem.can_make(&trie, "aaac"));
+
+        let mut rng = common::rng::seeded(19);
+        for _ in 0..200 {
+            let len = rng.range(1, 30);
+            let pattern: String = (0..len).map(|_| *rng.choose(&['a', 'b'])).collect();
+            let towels = ["aab", "ba", "bbb", "abab"];
+            let text = format!("{}\n\n{pattern}\n", towels.join(", "));
+            let problem = parse_input(&text)?;
+            let trie = problem.towel_trie();
+            let (made, _) = solve_both(&problem);
+            assert_eq!(problem.can_make(&trie, &pattern), made == 1, "{pattern}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn alphabet_from_towels() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(PrintPat(&problem.alphabet.0).to_string(), "bgruw");
+        assert_eq!(PrintPat(&problem.towels[4]).to_string(), "bwu");
+
+        // a different alphabet entirely, with mixed case
+        let problem = parse_input("xY, zz,Q\n\nXYZQ\nqq\n")?;
+        assert_eq!(PrintPat(&problem.alphabet.0).to_string(), "qxyz");
+        assert_eq!(PrintPat(&problem.patterns[0]).to_string(), "xyzq");
+        let (count, _) = count_solutions(&problem)?;
+        assert_eq!(count, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn windows_newlines_and_trailing_whitespace() -> Result<()> {
+        let crlf = EXAMPLE.replace('\n', "  \r\n");
+        let problem = parse_input(&crlf)?;
+        assert_eq!(problem.patterns.len(), 8);
+        assert_eq!(count_solutions(&problem)?, (6, 16));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_characters_rejected() {
+        let err = parse_input("r, wr\n\nrw\nrxw\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 4, column 2: colour 'x' is not on any towel"
+        );
+        let err = parse_input("r, w1\n\nrw\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 1, column 5: unexpected character '1'"
+        );
+        let err = parse_input("r, , w\n\nrw\n").unwrap_err();
+        assert_eq!(format!("{err:#}"), "line 1, column 3: empty towel");
+        let err = parse_input("r, w\nrw\n").unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 2: expected a blank line after the towels"
+        );
+        assert_eq!(
+            format!("{:#}", parse_input("").unwrap_err()),
+            "line 1: expected towels"
+        );
+    }
+}
@@ -0,0 +1,63 @@
+/// One report per line, as whitespace-separated levels
+pub fn parse_input(text: &str) -> anyhow::Result<Vec<Vec<i32>>> {
+    let reports: Result<Vec<_>, _> = text
+        .lines()
+        .map(|l| {
+            l.split_whitespace()
+                .map(|n| n.parse())
+                .collect::<Result<Vec<i32>, _>>()
+        })
+        .collect();
+    Ok(reports?)
+}
+
+pub fn safe_part_1(report: &[i32]) -> bool {
+    let diffs = || report.windows(2).map(|v| v[1] - v[0]);
+
+    let monotonic = diffs().all(|d| d < 0) || diffs().all(|d| d > 0);
+    let magnitudes = diffs().all(|d| (1..=3).contains(&d.abs()));
+
+    monotonic && magnitudes
+}
+
+pub fn safe_part_2(report: &[i32]) -> bool {
+    // original case
+    if safe_part_1(report) {
+        return true;
+    }
+
+    // brute force removing one element at a time
+    for idx_removed in 0..report.len() {
+        let mut dampened = report.to_vec();
+        dampened.remove(idx_removed);
+
+        if safe_part_1(&dampened) {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    let reports = parse_input(input)?;
+    let count = |safe: fn(&[i32]) -> bool| reports.iter().filter(|r| safe(r)).count();
+    Ok((
+        count(safe_part_1).to_string(),
+        count(safe_part_2).to_string(),
+    ))
+}
+
+common::aoc_day!(2, "Red-Nosed Reports");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    7 6 4 2 1
+    1 2 7 8 9
+    9 7 6 2 1
+    1 3 2 4 5
+    8 6 4 4 1
+    1 3 6 7 9
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("2"), Some("4")];
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use common::{
+    cartesian::{Point, ScreenDir},
+    maze::{parse_maze, Block, Maze},
+};
+use fxhash::FxHashMap;
+use rayon::prelude::*;
+use strum::IntoEnumIterator;
+
+pub type Problem = Maze;
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    parse_maze(input)
+}
+
+/// The racetrack from start to end in order, so a cell's index is its distance from
+/// the start. Errors unless the track is a single corridor without branches.
+pub fn track_path(problem: &Problem) -> Result<Vec<Point>> {
+    let mut path = vec![problem.start];
+    let mut prev = None;
+    let mut pos = problem.start;
+    while pos != problem.end {
+        let mut steps = ScreenDir::iter().map(|sd| pos + sd.into()).filter(|&p| {
+            Some(p) != prev && matches!(problem.map.get(p), Some(Block::Open | Block::End))
+        });
+        let Some(next) = steps.next() else {
+            bail!("track reaches a dead end at {pos:?}");
+        };
+        if steps.next().is_some() {
+            bail!("track branches at {pos:?}");
+        }
+        prev = Some(pos);
+        pos = next;
+        path.push(next);
+    }
+    Ok(path)
+}
+
+/// End point and saving of every cheat starting at `path[from]` and lasting up to
+/// `cheat_len`. Only cells further along the track can be worth cheating to, and the
+/// saving is the distance along the track less the manhattan distance cut across.
+fn cheats_from(
+    path: &[Point],
+    from: usize,
+    cheat_len: i64,
+) -> impl Iterator<Item = (Point, i64)> + '_ {
+    let start = path[from];
+    let mut to = from + 1;
+    std::iter::from_fn(move || {
+        while let Some(&end) = path.get(to) {
+            let delta = end - start;
+            let across = delta.x.abs() + delta.y.abs();
+            if across > cheat_len {
+                // each step along the track moves one cell, so the next
+                // `across - cheat_len` cells are all still out of reach
+                to += (across - cheat_len) as usize;
+                continue;
+            }
+            let along = (to - from) as i64;
+            to += 1;
+            if across < along {
+                return Some((end, along - across));
+            }
+        }
+        None
+    })
+}
+
+/// Number of distinct cheats (by start and end) for each saving, for cheats
+/// lasting up to `cheat_len` picoseconds. Start points are scanned in parallel,
+/// each thread filling its own histogram; these are merged at the end.
+pub fn savings_histogram(problem: &Problem, cheat_len: i64) -> Result<BTreeMap<i64, usize>> {
+    let path = track_path(problem)?;
+    Ok((0..path.len())
+        .into_par_iter()
+        .fold(BTreeMap::new, |mut counts, from| {
+            for (_, saving) in cheats_from(&path, from, cheat_len) {
+                *counts.entry(saving).or_default() += 1;
+            }
+            counts
+        })
+        .reduce(BTreeMap::new, |mut merged, counts| {
+            for (saving, count) in counts {
+                *merged.entry(saving).or_default() += count;
+            }
+            merged
+        }))
+}
+
+pub type CheatMap = FxHashMap<(Point, Point), i64>;
+
+/// Record the saving of the cheat from `start` to `end`. Seeing a cheat again is
+/// allowed only with the same saving; a different one means the distances are
+/// inconsistent, so it's an error rather than overwriting the first.
+fn record_cheat(cheats: &mut CheatMap, start: Point, end: Point, saving: i64) -> Result<()> {
+    match cheats.insert((start, end), saving) {
+        Some(previous) if previous != saving => {
+            bail!("cheat {start:?} -> {end:?} saves both {previous} and {saving}")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Every cheat lasting up to `cheat_len`, keyed by start and end, checking that no
+/// key is produced with two different savings
+pub fn cheats_by_endpoints(problem: &Problem, cheat_len: i64) -> Result<CheatMap> {
+    let path = track_path(problem)?;
+    let mut cheats = CheatMap::default();
+    for (from, &start) in path.iter().enumerate() {
+        for (end, saving) in cheats_from(&path, from, cheat_len) {
+            record_cheat(&mut cheats, start, end, saving)?;
+        }
+    }
+    Ok(cheats)
+}
+
+/// Check the parallel histogram against one built from the distinct cheats, failing
+/// if any cheat was counted twice or with conflicting savings
+pub fn audit_histogram(problem: &Problem, cheat_len: i64) -> Result<usize> {
+    let cheats = cheats_by_endpoints(problem, cheat_len)?;
+    let mut expected = BTreeMap::new();
+    for saving in cheats.values() {
+        *expected.entry(*saving).or_default() += 1;
+    }
+    let histogram = savings_histogram(problem, cheat_len)?;
+    if histogram != expected {
+        bail!(
+            "histogram counts {} cheats, but there are {} distinct",
+            histogram.values().sum::<usize>(),
+            cheats.len()
+        );
+    }
+    Ok(cheats.len())
+}
+
+fn count_saving_at_least(histogram: &BTreeMap<i64, usize>, threshold: i64) -> usize {
+    histogram.range(threshold..).map(|(_, count)| count).sum()
+}
+
+pub fn print_histogram(cheat_len: i64, histogram: &BTreeMap<i64, usize>, threshold: i64) {
+    println!("Cheats up to {cheat_len} saving at least {threshold}:");
+    for (saving, count) in histogram.range(threshold..) {
+        println!("{count} cheats that save {saving}");
+    }
+}
+
+/// Number of cheats lasting up to `cheat_len` that save at least `threshold`
+pub fn count_cheats(problem: &Problem, cheat_len: i64, threshold: i64) -> Result<usize> {
+    Ok(count_saving_at_least(
+        &savings_histogram(problem, cheat_len)?,
+        threshold,
+    ))
+}
+
+pub fn part1(problem: &Problem, threshold: i64) -> Result<usize> {
+    count_cheats(problem, 2, threshold)
+}
+
+pub fn part2(problem: &Problem, threshold: i64) -> Result<usize> {
+    count_cheats(problem, 20, threshold)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((
+        part1(&problem, 100)?.to_string(),
+        part2(&problem, 100)?.to_string(),
+    ))
+}
+
+common::aoc_day!(20, "Race Condition");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    ###############
+    #...#...#.....#
+    #.#.#.#.#.###.#
+    #S#...#.#.#...#
+    #######.#.#.###
+    #######.#.#...#
+    #######.#.###.#
+    ###..E#...#...#
+    ###.#######.###
+    #...###...#...#
+    #.#####.#.###.#
+    #.#...#.#.#...#
+    #.#.#.#.#.#.###
+    #...#...#...###
+    ###############
+"};
+// the example has no cheats saving the 100 picoseconds `solve` counts
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [None, None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let counts = savings_histogram(&problem, 2)?;
+        assert_eq!(counts.get(&64).copied(), Some(1));
+        assert_eq!(counts.get(&20).copied(), Some(1));
+        assert_eq!(counts.get(&2).copied(), Some(14));
+        assert_eq!(counts.get(&8).copied(), Some(4));
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_cheat_len_2() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let expected = BTreeMap::from([
+            (2, 14),
+            (4, 14),
+            (6, 2),
+            (8, 4),
+            (10, 2),
+            (12, 3),
+            (20, 1),
+            (36, 1),
+            (38, 1),
+            (40, 1),
+            (64, 1),
+        ]);
+        assert_eq!(savings_histogram(&problem, 2)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn histogram_cheat_len_20() -> Result<()> {
+        // the puzzle only lists cheats saving at least 50
+        let problem = parse_input(EXAMPLE)?;
+        let expected = BTreeMap::from([
+            (50, 32),
+            (52, 31),
+            (54, 29),
+            (56, 39),
+            (58, 25),
+            (60, 23),
+            (62, 20),
+            (64, 19),
+            (66, 12),
+            (68, 14),
+            (70, 12),
+            (72, 22),
+            (74, 4),
+            (76, 3),
+        ]);
+        let histogram = savings_histogram(&problem, 20)?;
+        let at_least_50: BTreeMap<_, _> = histogram
+            .range(50..)
+            .map(|(saving, count)| (*saving, *count))
+            .collect();
+        assert_eq!(at_least_50, expected);
+        assert_eq!(count_saving_at_least(&histogram, 50), 285);
+        Ok(())
+    }
+
+    #[test]
+    fn duplicate_cheats() -> Result<()> {
+        let (a, b) = (Point::new(1, 1), Point::new(3, 1));
+        let mut cheats = CheatMap::default();
+        record_cheat(&mut cheats, a, b, 4)?;
+        record_cheat(&mut cheats, a, b, 4)?;
+        // the reverse direction is a different cheat
+        record_cheat(&mut cheats, b, a, 6)?;
+        assert_eq!(cheats.len(), 2);
+
+        let err = record_cheat(&mut cheats, a, b, 2).unwrap_err();
+        assert!(err.to_string().contains("saves both 4 and 2"));
+        Ok(())
+    }
+
+    #[test]
+    fn track_is_ordered_by_distance() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let path = track_path(&problem)?;
+        assert_eq!(path.len(), 85);
+        assert_eq!((path[0], path[84]), (problem.start, problem.end));
+        for pair in path.windows(2) {
+            let step = pair[1] - pair[0];
+            assert_eq!(step.x.abs() + step.y.abs(), 1);
+        }
+
+        // opening a wall next to the track gives it a branch
+        let branched = EXAMPLE.replacen("#S#...", "#S....", 1);
+        let err = track_path(&parse_input(&branched)?).unwrap_err();
+        assert!(err.to_string().contains("branches"));
+        Ok(())
+    }
+
+    #[test]
+    fn audit_agrees() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(audit_histogram(&problem, 2)?, 44);
+        let cheats = cheats_by_endpoints(&problem, 20)?;
+        assert_eq!(cheats.values().filter(|s| **s >= 50).count(), 285);
+        assert_eq!(audit_histogram(&problem, 20)?, cheats.len());
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem, 50)?;
+        assert_eq!(count, 285);
+        Ok(())
+    }
+}
@@ -0,0 +1,1259 @@
+use anyhow::{anyhow, bail, Result};
+use arrayvec::ArrayVec;
+use common::cartesian::{Point, ScreenDir};
+use fxhash::FxHashMap;
+use indoc::indoc;
+use itertools::Itertools;
+use nalgebra::{matrix, Matrix2x3, Matrix4x3};
+use priority_queue::PriorityQueue;
+use rayon::prelude::*;
+
+pub const INPUT: &str = indoc! {"
+    805A
+    983A
+    149A
+    413A
+    582A
+"};
+
+#[derive(Debug, Copy, Clone, Default, Hash, Eq, PartialEq)]
+enum NumKey {
+    #[default]
+    Blank,
+    Activate,
+    Val(u8),
+}
+
+impl NumKey {
+    fn to_char(self) -> Option<char> {
+        match self {
+            NumKey::Blank => None,
+            NumKey::Activate => Some('A'),
+            NumKey::Val(v) => char::from_digit(v as u32, 10),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Hash, Eq, PartialEq)]
+enum DirKey {
+    #[default]
+    Blank,
+    Activate,
+    Dir(ScreenDir),
+}
+impl DirKey {
+    const fn inputs() -> [DirKey; 5] {
+        [
+            DirKey::Activate,
+            DirKey::Dir(ScreenDir::U),
+            DirKey::Dir(ScreenDir::L),
+            DirKey::Dir(ScreenDir::D),
+            DirKey::Dir(ScreenDir::R),
+        ]
+    }
+
+    /// index of this key in `inputs()`
+    fn index(&self) -> usize {
+        match self {
+            DirKey::Blank => panic!("blank key has no index"),
+            DirKey::Activate => 0,
+            DirKey::Dir(ScreenDir::U) => 1,
+            DirKey::Dir(ScreenDir::L) => 2,
+            DirKey::Dir(ScreenDir::D) => 3,
+            DirKey::Dir(ScreenDir::R) => 4,
+        }
+    }
+}
+
+/// Cost of moving from one dirpad key to another and pressing it, indexed by `DirKey::index`
+type TransitionCosts = [[i64; 5]; 5];
+
+/// Cost of typing a sequence, starting from Activate
+fn sequence_cost(costs: &TransitionCosts, seq: &[DirKey]) -> i64 {
+    let mut prev = DirKey::Activate;
+    let mut total = 0_i64;
+    for key in seq {
+        total = total.saturating_add(costs[prev.index()][key.index()]);
+        prev = *key;
+    }
+    total
+}
+
+const NUMPAD: NumPad = NumPad {
+    map: matrix![
+        NumKey::Val(7), NumKey::Val(8), NumKey::Val(9);
+        NumKey::Val(4), NumKey::Val(5), NumKey::Val(6);
+        NumKey::Val(1), NumKey::Val(2), NumKey::Val(3);
+        NumKey::Blank, NumKey::Val(0), NumKey::Activate;
+    ],
+};
+
+#[derive(Debug, Clone)]
+struct NumPad {
+    map: Matrix4x3<NumKey>,
+}
+impl NumPad {
+    fn get(&self, p: Point) -> Option<NumKey> {
+        self.map.get(p).copied()
+    }
+
+    fn initial_pos() -> Point {
+        Point::new(2, 3)
+    }
+}
+
+const DIRPAD: DirPad = DirPad {
+    map: matrix![
+        DirKey::Blank, DirKey::Dir(ScreenDir::U), DirKey::Activate;
+        DirKey::Dir(ScreenDir::L), DirKey::Dir(ScreenDir::D), DirKey::Dir(ScreenDir::R)
+    ],
+};
+#[derive(Debug, Clone)]
+struct DirPad {
+    map: Matrix2x3<DirKey>,
+}
+impl DirPad {
+    fn get(&self, p: Point) -> Option<DirKey> {
+        self.map.get(p).copied()
+    }
+
+    fn initial_pos() -> Point {
+        Point::new(2, 0)
+    }
+
+    fn position_for(key: DirKey) -> Point {
+        match key {
+            DirKey::Blank => Point::new(0, 0),
+            DirKey::Activate => Point::new(2, 0),
+            DirKey::Dir(screen_dir) => match screen_dir {
+                ScreenDir::U => Point::new(1, 0),
+                ScreenDir::L => Point::new(0, 1),
+                ScreenDir::D => Point::new(1, 1),
+                ScreenDir::R => Point::new(2, 1),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Code {
+    key_codes: Vec<NumKey>,
+    numeric_part: i32,
+}
+
+impl Code {
+    /// Presses needed times the numeric part; an error if either is too large to count
+    fn complexity(&self, moves: i64) -> Result<i64> {
+        if moves == i64::MAX {
+            bail!("{:?} needs too many presses to count", self.key_codes);
+        }
+        moves
+            .checked_mul(self.numeric_part as i64)
+            .ok_or_else(|| anyhow!("complexity of {:?} overflows i64", self.key_codes))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    door_codes: Vec<Code>,
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut door_codes = vec![];
+    for l in input.lines() {
+        match l.strip_suffix('A') {
+            Some(digits) if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {}
+            _ => bail!("expected digits followed by A: {l:?}"),
+        }
+        let mut key_codes = vec![];
+        for ch in l.chars() {
+            key_codes.push(match ch {
+                '0'..='9' => NumKey::Val(format!("{ch}").parse()?),
+                'A' => NumKey::Activate,
+                _ => bail!("unexpected digit {ch}"),
+            });
+        }
+
+        let numeric_part = l.trim_start_matches('0').trim_end_matches('A').parse()?;
+        door_codes.push(Code {
+            key_codes,
+            numeric_part,
+        });
+    }
+    Ok(Problem { door_codes })
+}
+
+#[derive(Copy, Debug, Clone, Hash, Eq, PartialEq)]
+struct State {
+    num_completed: usize,
+    pos: Point,
+}
+
+#[derive(Copy, Debug, Clone, Hash, Eq, PartialEq)]
+struct StateAction {
+    state: State,
+    action: DirKey,
+}
+
+#[derive(Clone, Debug)]
+struct Dist {
+    cost: i32,
+    origins: ArrayVec<StateAction, 4>,
+}
+impl Dist {
+    fn new(cost: i32) -> Self {
+        Self {
+            cost,
+            origins: ArrayVec::default(),
+        }
+    }
+}
+
+fn min_moves_path_numpad(codes: &[NumKey]) -> FxHashMap<State, Dist> {
+    let init_state = State {
+        num_completed: 0,
+        pos: NumPad::initial_pos(),
+    };
+    let mut q = PriorityQueue::new();
+    let mut dist = FxHashMap::<State, Dist>::default();
+
+    q.push(init_state, 0);
+    dist.insert(init_state, Dist::new(0));
+
+    while let Some((st, prio)) = q.pop() {
+        let cur_dist = -prio;
+        for k in DirKey::inputs() {
+            match k {
+                DirKey::Blank => {}
+                DirKey::Dir(d) => {
+                    let next_pos = st.pos + d.into();
+                    if let Some(NumKey::Activate) | Some(NumKey::Val(..)) = NUMPAD.get(next_pos) {
+                        let alt = cur_dist + 1;
+                        let next_state = State {
+                            pos: next_pos,
+                            ..st
+                        };
+
+                        // prior state and action that went from it to here
+                        let state_action = StateAction {
+                            state: st,
+                            action: DirKey::Dir(d),
+                        };
+
+                        let existing = dist.entry(next_state).or_insert(Dist::new(i32::MAX));
+                        match alt.cmp(&existing.cost) {
+                            std::cmp::Ordering::Less => {
+                                *existing = Dist::new(alt);
+                                existing.origins.push(state_action);
+                                q.push(next_state, -alt);
+                            }
+                            std::cmp::Ordering::Equal => {
+                                existing.origins.push(state_action);
+                            }
+                            std::cmp::Ordering::Greater => { // ignore
+                            }
+                        }
+                    }
+                }
+                DirKey::Activate => {
+                    // check matches expected, or ignore
+                    let expected = codes[st.num_completed];
+                    if NUMPAD.get(st.pos) == Some(expected) {
+                        //println!("Got {expected:?} for {} in {:?}", st.num_completed, codes);
+
+                        let alt = cur_dist + 1;
+                        let next_state = State {
+                            num_completed: st.num_completed + 1,
+                            ..st
+                        };
+
+                        // prior state and action that went from it to here
+                        let state_action = StateAction {
+                            state: st,
+                            action: DirKey::Activate,
+                        };
+
+                        let existing = dist.entry(next_state).or_insert(Dist::new(i32::MAX));
+
+                        // advance to new state if we're not complete
+                        if alt < existing.cost {
+                            if let NumKey::Val(..) = expected {
+                                // advance to next digit and queue it for exploration
+                                q.push(next_state, -alt);
+                            }
+                        }
+
+                        // update cost
+                        match alt.cmp(&existing.cost) {
+                            std::cmp::Ordering::Less => {
+                                *existing = Dist::new(alt);
+                                existing.origins.push(state_action);
+                            }
+                            std::cmp::Ordering::Equal => {
+                                existing.origins.push(state_action);
+                            }
+                            std::cmp::Ordering::Greater => {
+                                //ignore
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    dist
+}
+
+fn trace_paths_rev(
+    prior: &[DirKey],
+    dist: &FxHashMap<State, Dist>,
+    end: State,
+    paths: &mut Vec<Vec<DirKey>>,
+    best_len: &mut usize,
+) {
+    let init = dist.get(&end).unwrap();
+
+    // start position -- record path; and if we find a new shorter path,
+    // throw away all the longer ones recorded previously.
+    if init.origins.is_empty() && prior.len() <= *best_len {
+        let path = prior.iter().copied().rev().collect();
+        if prior.len() < *best_len {
+            paths.clear();
+            *best_len = prior.len();
+        }
+        paths.push(path);
+    }
+
+    for origin in &init.origins {
+        //println!("at {init:?} with origin {origin:?}");
+        let mut new_prior: Vec<_> = prior.to_vec();
+        new_prior.push(origin.action);
+        trace_paths_rev(&new_prior, dist, origin.state, paths, best_len);
+    }
+}
+
+fn dirkey_move_sequences(from: Point, to: Point) -> Vec<Vec<DirKey>> {
+    let Point { x, y } = to - from;
+
+    let mut moves = vec![];
+    let xm = if x > 0 {
+        DirKey::Dir(ScreenDir::R)
+    } else {
+        DirKey::Dir(ScreenDir::L)
+    };
+    for _ in 0..x.abs() {
+        moves.push(xm);
+    }
+
+    let ym = if y > 0 {
+        DirKey::Dir(ScreenDir::D)
+    } else {
+        DirKey::Dir(ScreenDir::U)
+    };
+    for _ in 0..y.abs() {
+        moves.push(ym);
+    }
+
+    let mut sequences = vec![];
+    let k = moves.len();
+    for perm in moves.into_iter().permutations(k) {
+        let mut pos = from;
+        let mut legal = true;
+        for mv in perm.iter().copied() {
+            if let DirKey::Dir(d) = mv {
+                pos = pos + d.into();
+                if DIRPAD.get(pos).unwrap() == DirKey::Blank {
+                    legal = false;
+                    break;
+                }
+            } else {
+                panic!("not a direction")
+            }
+        }
+
+        if legal {
+            debug_assert_eq!(pos, to);
+            sequences.push(perm);
+        }
+    }
+
+    sequences
+}
+
+/// A dirpad sequence packed three bits to a key under a leading 1, so sequences of up
+/// to 21 keys make cache keys without allocating
+fn seq_key(seq: &[DirKey]) -> Option<u64> {
+    (seq.len() <= 21).then(|| {
+        seq.iter()
+            .fold(1, |packed, key| packed << 3 | key.index() as u64)
+    })
+}
+
+#[derive(Clone)]
+pub struct Solver {
+    max_level: usize,
+    /// Cost of each sequence seen at each level, by `seq_key`
+    levels_cache: Vec<FxHashMap<u64, i64>>,
+    levels_matrices: Vec<TransitionCosts>,
+    cache_hits: u64,
+    cache_misses: u64,
+}
+impl Solver {
+    pub fn new(max_level: usize) -> Self {
+        Solver {
+            max_level,
+            levels_cache: vec![FxHashMap::default(); max_level + 1],
+            levels_matrices: vec![],
+            cache_hits: 0,
+            cache_misses: 0,
+        }
+    }
+
+    /// Sequences cached over all levels, and the lookups that found or missed one so
+    /// far; all zero for a solver using matrices, which has no cache
+    pub fn cache_stats(&self) -> (usize, u64, u64) {
+        let entries = self.levels_cache.iter().map(|c| c.len()).sum();
+        (entries, self.cache_hits, self.cache_misses)
+    }
+
+    /// A copy of this solver's cache and matrices with its counts reset, for scoring
+    /// on another thread before merging back in
+    fn fork(&self) -> Self {
+        Solver {
+            cache_hits: 0,
+            cache_misses: 0,
+            ..self.clone()
+        }
+    }
+
+    /// Take in the sequences cached by another solver for the same depth, and add its
+    /// counts to ours
+    pub fn merge(&mut self, other: Solver) {
+        debug_assert_eq!(self.max_level, other.max_level);
+        for (ours, theirs) in self.levels_cache.iter_mut().zip(other.levels_cache) {
+            ours.extend(theirs);
+        }
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+    }
+
+    /// Precompute pairwise transition costs for every level, working down from the
+    /// final level where each key press costs 1. Level N costs follow directly from
+    /// the level N+1 costs, so this is linear in depth and needs no sequence cache.
+    /// Costs saturate at `i64::MAX` for very deep chains.
+    pub fn with_matrices(max_level: usize) -> Self {
+        let mut levels_matrices = vec![[[1; 5]; 5]; max_level + 1];
+        for level in (0..max_level).rev() {
+            let next = levels_matrices[level + 1];
+            for from in DirKey::inputs() {
+                for to in DirKey::inputs() {
+                    let from_pos = DirPad::position_for(from);
+                    let to_pos = DirPad::position_for(to);
+                    levels_matrices[level][from.index()][to.index()] =
+                        dirkey_move_sequences(from_pos, to_pos)
+                            .into_iter()
+                            .map(|mut sub_seq| {
+                                sub_seq.push(DirKey::Activate);
+                                sequence_cost(&next, &sub_seq)
+                            })
+                            .min()
+                            .unwrap();
+                }
+            }
+        }
+
+        Solver {
+            max_level,
+            levels_cache: vec![],
+            levels_matrices,
+            cache_hits: 0,
+            cache_misses: 0,
+        }
+    }
+
+    fn min_moves_for_seq(&mut self, seq: &[DirKey], level: usize) -> i64 {
+        if !self.levels_matrices.is_empty() {
+            return sequence_cost(&self.levels_matrices[level], seq);
+        }
+
+        // final level - work out number of inputs required since we're going to
+        // input keys directly on the final keypad - it's just a count.
+        if level == self.max_level {
+            // every sequence for this level should return to "Activate"
+            debug_assert_eq!(seq[seq.len() - 1], DirKey::Activate);
+            return seq.len() as i64;
+        }
+
+        let key = seq_key(seq);
+        if let Some(total) = key.and_then(|key| self.levels_cache[level].get(&key)) {
+            self.cache_hits += 1;
+            return *total;
+        }
+        self.cache_misses += 1;
+
+        // intermediate levels - split the sequence up into sub sequences that return
+        // to Activate, and recursively calculate distance on those.
+        let mut pos = DirPad::initial_pos();
+        let mut total_distance = 0;
+        for key in seq {
+            let next_pos = DirPad::position_for(*key);
+
+            // test all legal permutations for dir keypad, picking the smallest
+            let mut min_moves = i64::MAX;
+            for mut sub_seq in dirkey_move_sequences(pos, next_pos) {
+                // activate required after moves
+                sub_seq.push(DirKey::Activate);
+                let moves_required = self.min_moves_for_seq(sub_seq.as_slice(), level + 1);
+                min_moves = min_moves.min(moves_required);
+            }
+
+            total_distance = min_moves.saturating_add(total_distance);
+            pos = next_pos;
+        }
+
+        if let Some(key) = key {
+            self.levels_cache[level].insert(key, total_distance);
+        }
+        total_distance
+    }
+
+    /// One concrete sequence of final-level presses that types `seq` at `level`,
+    /// choosing a cheapest way of reaching each key
+    fn expand(&mut self, seq: &[DirKey], level: usize) -> Vec<DirKey> {
+        if level == self.max_level {
+            return seq.to_vec();
+        }
+
+        let mut pos = DirPad::initial_pos();
+        let mut expanded = vec![];
+        for key in seq {
+            let next_pos = DirPad::position_for(*key);
+            let best = dirkey_move_sequences(pos, next_pos)
+                .into_iter()
+                .map(|mut sub_seq| {
+                    sub_seq.push(DirKey::Activate);
+                    sub_seq
+                })
+                .min_by_key(|sub_seq| self.min_moves_for_seq(sub_seq, level + 1))
+                .expect("always at least one legal sequence");
+            expanded.extend(self.expand(&best, level + 1));
+            pos = next_pos;
+        }
+        expanded
+    }
+}
+
+pub fn score(
+    problem: &Problem,
+    dirpad_depth: usize,
+    make_solver: fn(usize) -> Solver,
+) -> Result<i64> {
+    score_with(problem, &mut make_solver(dirpad_depth))
+}
+
+/// Score every code with the one solver, so sequences cached for one code are reused
+/// for the rest
+pub fn score_with(problem: &Problem, solver: &mut Solver) -> Result<i64> {
+    let mut total = 0;
+
+    for codes in &problem.door_codes {
+        let moves = moves_required(&codes.key_codes, solver)?;
+        let value = codes.complexity(moves)?;
+        //println!("{codes:?} -> {moves} moves -> {value}");
+        total = value
+            .checked_add(total)
+            .ok_or_else(|| anyhow!("total complexity overflows i64"))?;
+    }
+
+    Ok(total)
+}
+
+/// As `score_with`, but scoring the codes in parallel. Each rayon job works on a fork
+/// of `solver`, and their caches are merged back into it at the end, so entries
+/// found by one code are only reused within the same job. Some sequences end up
+/// cached (and counted as misses) more than once.
+pub fn score_parallel(problem: &Problem, solver: &mut Solver) -> Result<i64> {
+    let jobs: Vec<Result<(i64, Solver)>> = problem
+        .door_codes
+        .par_iter()
+        .fold(
+            || Ok((0, solver.fork())),
+            |job, codes| {
+                let (total, mut forked) = job?;
+                let moves = moves_required(&codes.key_codes, &mut forked)?;
+                let total = codes
+                    .complexity(moves)?
+                    .checked_add(total)
+                    .ok_or_else(|| anyhow!("total complexity overflows i64"))?;
+                Ok((total, forked))
+            },
+        )
+        .collect();
+
+    let mut total = 0;
+    for job in jobs {
+        let (job_total, forked) = job?;
+        total = job_total
+            .checked_add(total)
+            .ok_or_else(|| anyhow!("total complexity overflows i64"))?;
+        solver.merge(forked);
+    }
+    Ok(total)
+}
+
+/// As `score` with `Solver::new`, but starting each code with an empty cache; for
+/// comparison with sharing the cache
+pub fn score_cache_per_code(problem: &Problem, dirpad_depth: usize) -> Result<i64> {
+    let mut total = 0;
+    for codes in &problem.door_codes {
+        let moves = moves_required(&codes.key_codes, &mut Solver::new(dirpad_depth))?;
+        total = codes
+            .complexity(moves)?
+            .checked_add(total)
+            .ok_or_else(|| anyhow!("total complexity overflows i64"))?;
+    }
+    Ok(total)
+}
+
+fn moves_required(door_codes: &[NumKey], solver: &mut Solver) -> Result<i64> {
+    let paths = numpad_paths(door_codes);
+
+    let mut min_cost = i64::MAX;
+    for path in &paths {
+        let mut total_cost = 0;
+        for seq in path.split_inclusive(|k| *k == DirKey::Activate) {
+            let dir_key_cost = solver.min_moves_for_seq(seq, 1);
+            total_cost = dir_key_cost.saturating_add(total_cost);
+        }
+        //println!("{path:?} cost {total_cost}");
+
+        min_cost = min_cost.min(total_cost);
+    }
+    Ok(min_cost)
+}
+
+/// All the shortest dirpad sequences that type the code on the numpad
+fn numpad_paths(door_codes: &[NumKey]) -> Vec<Vec<DirKey>> {
+    let min_paths_numpad = min_moves_path_numpad(door_codes);
+
+    let mut paths = vec![];
+    let mut best_len1 = usize::MAX;
+    trace_paths_rev(
+        &[],
+        &min_paths_numpad,
+        // every code ends by pressing A
+        State {
+            num_completed: door_codes.len(),
+            pos: Point::new(2, 3),
+        },
+        &mut paths,
+        &mut best_len1,
+    );
+    paths
+}
+
+/// One optimal sequence of presses on the human's dirpad that types the code
+fn expand_solution(door_codes: &[NumKey], dirpad_depth: usize) -> Vec<DirKey> {
+    let mut solver = Solver::new(dirpad_depth);
+    let best_path = numpad_paths(door_codes)
+        .into_iter()
+        .min_by_key(|path| solver.min_moves_for_seq(path, 1))
+        .expect("every code can be typed");
+    solver.expand(&best_path, 1)
+}
+
+/// Replay presses on the human's dirpad through the chain of robots, returning the
+/// keys pressed on the numpad; fails if any robot aims at a gap or off the keypad
+fn simulate(presses: &[DirKey], dirpad_depth: usize) -> Result<Vec<NumKey>> {
+    let mut seq = presses.to_vec();
+    for _ in 1..dirpad_depth {
+        let mut pos = DirPad::initial_pos();
+        let mut output = vec![];
+        for key in &seq {
+            match key {
+                DirKey::Dir(d) => {
+                    pos = pos + (*d).into();
+                    if let Some(DirKey::Blank) | None = DIRPAD.get(pos) {
+                        bail!("dirpad robot moved off the keys at {pos:?}");
+                    }
+                }
+                DirKey::Activate => output.push(DIRPAD.get(pos).unwrap()),
+                DirKey::Blank => bail!("blank key pressed"),
+            }
+        }
+        seq = output;
+    }
+
+    let mut pos = NumPad::initial_pos();
+    let mut output = vec![];
+    for key in &seq {
+        match key {
+            DirKey::Dir(d) => {
+                pos = pos + (*d).into();
+                if let Some(NumKey::Blank) | None = NUMPAD.get(pos) {
+                    bail!("numpad robot moved off the keys at {pos:?}");
+                }
+            }
+            DirKey::Activate => output.push(NUMPAD.get(pos).unwrap()),
+            DirKey::Blank => bail!("blank key pressed"),
+        }
+    }
+    Ok(output)
+}
+
+/// Expand each code into concrete presses and check they type the code
+pub fn verify_solutions(problem: &Problem, dirpad_depth: usize) -> Result<()> {
+    for code in &problem.door_codes {
+        let presses = expand_solution(&code.key_codes, dirpad_depth);
+        let typed = simulate(&presses, dirpad_depth)?;
+        if typed != code.key_codes {
+            bail!("{presses:?} typed {typed:?}, expected {:?}", code.key_codes);
+        }
+        println!(
+            "{:?}: {} presses verified at depth {dirpad_depth}",
+            code.key_codes,
+            presses.len()
+        );
+    }
+    Ok(())
+}
+
+/// Layout of one keypad in a chain; gaps are `None`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PadSpec {
+    rows: Vec<Vec<Option<char>>>,
+}
+impl PadSpec {
+    /// One row per line, with spaces for gaps; every pad needs an `A` key, where
+    /// its arm starts
+    pub fn from_layout(layout: &str) -> Result<Self> {
+        let rows: Vec<Vec<_>> = layout
+            .lines()
+            .map(|l| l.chars().map(|ch| (ch != ' ').then_some(ch)).collect())
+            .collect();
+        let keys: Vec<char> = rows.iter().flatten().flatten().copied().collect();
+        if let Some(key) = keys.iter().duplicates().next() {
+            bail!("key {key:?} appears more than once");
+        }
+        if !keys.contains(&'A') {
+            bail!("pad has no A key");
+        }
+        Ok(PadSpec { rows })
+    }
+
+    pub fn numpad() -> Self {
+        Self::from_layout("789\n456\n123\n 0A").expect("valid layout")
+    }
+
+    pub fn dirpad() -> Self {
+        Self::from_layout(" ^A\n<v>").expect("valid layout")
+    }
+
+    /// The puzzle's chain: the door's numpad, then `dirpads` directional pads, the
+    /// last being the one the human presses
+    pub fn standard_chain(dirpads: usize) -> Vec<Self> {
+        let mut chain = vec![Self::numpad()];
+        chain.extend(std::iter::repeat_n(Self::dirpad(), dirpads));
+        chain
+    }
+
+    fn keys(&self) -> impl Iterator<Item = char> + '_ {
+        self.rows.iter().flatten().flatten().copied()
+    }
+
+    fn get(&self, p: Point) -> Option<char> {
+        let (r, c) = p.to_coord()?;
+        self.rows.get(r)?.get(c).copied().flatten()
+    }
+
+    fn position(&self, key: char) -> Option<Point> {
+        self.rows.iter().enumerate().find_map(|(r, row)| {
+            let c = row.iter().position(|k| *k == Some(key))?;
+            Some(Point::from((r, c)))
+        })
+    }
+
+    /// Whether this pad can drive the arm over another pad
+    fn is_directional(&self) -> bool {
+        "^<v>A".chars().all(|k| self.position(k).is_some())
+    }
+}
+
+fn dir_for(key: char) -> ScreenDir {
+    match key {
+        '^' => ScreenDir::U,
+        'v' => ScreenDir::D,
+        '<' => ScreenDir::L,
+        _ => ScreenDir::R,
+    }
+}
+
+/// Cost in human presses of moving an arm from one key to another and pressing it
+type PadCosts = FxHashMap<(char, char), i64>;
+
+/// Cheapest way of moving an arm over `pad` from `from` to each key and pressing it,
+/// given the costs of presses on the pad driving it. Searches over (arm position, key
+/// last pressed on the driving pad), so detours around gaps are found as well.
+fn arm_costs(pad: &PadSpec, from: Point, driver: &PadCosts) -> FxHashMap<char, i64> {
+    let driver_cost = |a, b| driver.get(&(a, b)).copied().unwrap_or(i64::MAX);
+
+    let mut dist = FxHashMap::default();
+    let mut q = PriorityQueue::new();
+    dist.insert((from, 'A'), 0);
+    q.push((from, 'A'), 0);
+    while let Some(((pos, last), prio)) = q.pop() {
+        let cost: i64 = -prio;
+        for key in ['^', '<', 'v', '>'] {
+            let next = pos + dir_for(key).into();
+            if pad.get(next).is_none() {
+                continue;
+            }
+            let alt = cost.saturating_add(driver_cost(last, key));
+            if alt < *dist.get(&(next, key)).unwrap_or(&i64::MAX) {
+                dist.insert((next, key), alt);
+                q.push((next, key), -alt);
+            }
+        }
+    }
+
+    let mut best: FxHashMap<char, i64> = FxHashMap::default();
+    for ((pos, last), cost) in dist {
+        let key = pad.get(pos).expect("only keys are visited");
+        let cost = cost.saturating_add(driver_cost(last, 'A'));
+        let entry = best.entry(key).or_insert(i64::MAX);
+        *entry = (*entry).min(cost);
+    }
+    best
+}
+
+/// Cost of typing `keys` on a pad, starting from `A`; saturates at `i64::MAX`, which
+/// is also the cost of an unreachable key
+fn press_cost(costs: &PadCosts, keys: &[char]) -> i64 {
+    let mut prev = 'A';
+    let mut total = 0_i64;
+    for &key in keys {
+        let cost = costs.get(&(prev, key)).copied().unwrap_or(i64::MAX);
+        total = total.saturating_add(cost);
+        prev = key;
+    }
+    total
+}
+
+/// Solver for an arbitrary chain of keypads, described from the door upwards. The
+/// human presses the last pad directly, and each pad above the first drives the arm
+/// over the pad below it, so must have the direction keys and `A`. Costs are
+/// worked out for each level in turn from the top, keyed on that level's own pad.
+#[derive(Debug, Clone)]
+pub struct ChainSolver {
+    pads: Vec<PadSpec>,
+    costs: Vec<PadCosts>,
+}
+impl ChainSolver {
+    pub fn new(pads: Vec<PadSpec>) -> Result<Self> {
+        let Some(top) = pads.len().checked_sub(1) else {
+            bail!("no pads in the chain");
+        };
+        if let Some(level) = (1..pads.len()).find(|&l| !pads[l].is_directional()) {
+            bail!("pad {level} can't drive the pad below: it needs ^, <, v, > and A keys");
+        }
+
+        let mut costs = vec![PadCosts::default(); pads.len()];
+        costs[top] = pads[top]
+            .keys()
+            .cartesian_product(pads[top].keys().collect_vec())
+            .map(|pair| (pair, 1))
+            .collect();
+        for level in (0..top).rev() {
+            let pad = &pads[level];
+            for from in pad.keys() {
+                let from_pos = pad.position(from).expect("key is on the pad");
+                // keys cut off by gaps have no cost at all
+                for (to, cost) in arm_costs(pad, from_pos, &costs[level + 1]) {
+                    costs[level].insert((from, to), cost);
+                }
+            }
+        }
+        Ok(ChainSolver { pads, costs })
+    }
+
+    /// Human presses needed to type `keys` on the first pad
+    pub fn presses(&self, keys: &str) -> Result<i64> {
+        let keys: Vec<char> = keys.chars().collect();
+        if let Some(key) = keys.iter().find(|k| self.pads[0].position(**k).is_none()) {
+            bail!("key {key:?} is not on the first pad");
+        }
+        match press_cost(&self.costs[0], &keys) {
+            i64::MAX => bail!("{keys:?} can't be typed, or needs too many presses to count"),
+            cost => Ok(cost),
+        }
+    }
+}
+
+/// Sum of complexities, typing the codes through an arbitrary chain of pads
+pub fn score_chain(problem: &Problem, pads: Vec<PadSpec>) -> Result<i64> {
+    let solver = ChainSolver::new(pads)?;
+    let mut total = 0;
+    for code in &problem.door_codes {
+        let keys: String = code.key_codes.iter().filter_map(|k| k.to_char()).collect();
+        total = code
+            .complexity(solver.presses(&keys)?)?
+            .checked_add(total)
+            .ok_or_else(|| anyhow!("total complexity overflows i64"))?;
+    }
+    Ok(total)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((
+        score(&problem, 3, Solver::new)?.to_string(),
+        score(&problem, 26, Solver::new)?.to_string(),
+    ))
+}
+
+common::aoc_day!(21, "Keypad Conundrum");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    029A
+    980A
+    179A
+    456A
+    379A
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("126384"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fxhash::FxHashSet;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn codes_end_in_activate() -> Result<()> {
+        let err = parse_input("1").unwrap_err();
+        assert_eq!(err.to_string(), r#"expected digits followed by A: "1""#);
+        assert!(parse_input("A").is_err());
+        assert!(parse_input("1A2A").is_err());
+
+        // codes needn't be three digits long
+        let problem = parse_input("1A\n02A\n")?;
+        verify_solutions(&problem, 3)?;
+        let presses: Vec<_> = problem
+            .door_codes
+            .iter()
+            .map(|code| expand_solution(&code.key_codes, 3).len() as i64)
+            .collect();
+        assert_eq!(
+            score(&problem, 3, Solver::new)?,
+            presses[0] + 2 * presses[1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = score(&problem, 3, Solver::new)?;
+        assert_eq!(count, 126384);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_matrices_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = score(&problem, 3, Solver::with_matrices)?;
+        assert_eq!(count, 126384);
+        Ok(())
+    }
+
+    #[test]
+    fn matrices_match_memoized() {
+        let seq = [
+            DirKey::Dir(ScreenDir::L),
+            DirKey::Dir(ScreenDir::U),
+            DirKey::Activate,
+            DirKey::Dir(ScreenDir::D),
+            DirKey::Dir(ScreenDir::R),
+            DirKey::Activate,
+        ];
+        for depth in 1..=26 {
+            let mut memoized = Solver::new(depth);
+            let mut matrices = Solver::with_matrices(depth);
+            for level in 0..depth {
+                assert_eq!(
+                    memoized.min_moves_for_seq(&seq, level),
+                    matrices.min_moves_for_seq(&seq, level)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn cache_shared_across_codes() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let mut solver = Solver::new(26);
+        let total = score_with(&problem, &mut solver)?;
+        assert_eq!(total, score_cache_per_code(&problem, 26)?);
+        assert_eq!(total, score(&problem, 26, Solver::with_matrices)?);
+
+        // one miss per entry, and more hits than misses once warmed up
+        let (entries, hits, misses) = solver.cache_stats();
+        assert_eq!(entries as u64, misses);
+        assert!(hits > misses);
+
+        // a second pass is all hits
+        score_with(&problem, &mut solver)?;
+        assert_eq!(solver.cache_stats().2, misses);
+        assert_eq!(Solver::with_matrices(26).cache_stats(), (0, 0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_matches_serial() -> Result<()> {
+        for input in [EXAMPLE, INPUT] {
+            let problem = parse_input(input)?;
+            let serial = score_with(&problem, &mut Solver::new(26))?;
+            let mut solver = Solver::new(26);
+            assert_eq!(score_parallel(&problem, &mut solver)?, serial);
+            let parallel_misses = solver.cache_stats().2;
+            assert!(parallel_misses > 0);
+
+            // the merged cache answers everything on another pass
+            assert_eq!(score_with(&problem, &mut solver)?, serial);
+            assert_eq!(solver.cache_stats().2, parallel_misses);
+            assert_eq!(
+                score_parallel(&problem, &mut Solver::with_matrices(26))?,
+                serial
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn matrices_deep_chain() {
+        let mut solver = Solver::with_matrices(1000);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 980);
+        assert_eq!(moves, 94569958);
+        // far too many moves to represent
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, i64::MAX);
+
+        // which scoring reports rather than overflowing
+        let problem = parse_input(INPUT).unwrap();
+        let err = score(&problem, 1000, Solver::with_matrices).unwrap_err();
+        assert!(err.to_string().contains("too many presses"), "{err}");
+    }
+
+    #[test]
+    fn packed_cache_keys() {
+        let keys = |seq: &[DirKey]| seq_key(seq).unwrap();
+        let (up, a) = (DirKey::Dir(ScreenDir::U), DirKey::Activate);
+        // leading activates still make distinct keys
+        assert_ne!(keys(&[a, up]), keys(&[up]));
+        assert_ne!(keys(&[a]), keys(&[]));
+        assert_eq!(keys(&[up; 21]).leading_zeros(), 0);
+        assert_eq!(seq_key(&[up; 22]), None);
+    }
+
+    #[test]
+    fn part1_alternate_moves_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let level = 3;
+        assert_eq!(
+            68,
+            moves_required(&problem.door_codes[0].key_codes, &mut Solver::new(level))?
+        );
+        assert_eq!(
+            60,
+            moves_required(&problem.door_codes[1].key_codes, &mut Solver::new(level))?
+        );
+        assert_eq!(
+            68,
+            moves_required(&problem.door_codes[2].key_codes, &mut Solver::new(level))?
+        );
+        assert_eq!(
+            64,
+            moves_required(&problem.door_codes[3].key_codes, &mut Solver::new(level))?
+        );
+        assert_eq!(
+            64,
+            moves_required(&problem.door_codes[4].key_codes, &mut Solver::new(level))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn expanded_solution_types_code() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        for depth in 1..=6 {
+            for code in &problem.door_codes {
+                let presses = expand_solution(&code.key_codes, depth);
+                let min_moves = moves_required(&code.key_codes, &mut Solver::new(depth))?;
+                assert_eq!(presses.len() as i64, min_moves);
+                assert_eq!(simulate(&presses, depth)?, code.key_codes);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn simulate_puzzle_example() -> Result<()> {
+        // the example's sequence for 029A, as typed by the human
+        let presses: Vec<_> =
+            "<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A"
+                .chars()
+                .map(|ch| match ch {
+                    '<' => DirKey::Dir(ScreenDir::L),
+                    '>' => DirKey::Dir(ScreenDir::R),
+                    '^' => DirKey::Dir(ScreenDir::U),
+                    'v' => DirKey::Dir(ScreenDir::D),
+                    _ => DirKey::Activate,
+                })
+                .collect();
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(simulate(&presses, 3)?, problem.door_codes[0].key_codes);
+
+        // moving left from the numpad's A to the gap fails
+        let gap = [DirKey::Dir(ScreenDir::L), DirKey::Dir(ScreenDir::L)];
+        assert!(simulate(&gap, 1).is_err());
+        Ok(())
+    }
+
+    /// Fewest human presses by breadth-first search over the positions of every arm
+    fn brute_force_presses(pads: &[PadSpec], keys: &str) -> Option<i64> {
+        let keys: Vec<char> = keys.chars().collect();
+        let top = pads.len() - 1;
+        let arms: Vec<Point> = pads[..top]
+            .iter()
+            .map(|p| p.position('A').unwrap())
+            .collect();
+
+        let mut seen = FxHashSet::default();
+        let mut q = VecDeque::from([((arms, 0), 0)]);
+        while let Some(((arms, typed), presses)) = q.pop_front() {
+            if typed == keys.len() {
+                return Some(presses);
+            }
+            if !seen.insert((arms.clone(), typed)) {
+                continue;
+            }
+            'press: for human_key in pads[top].keys() {
+                let mut arms = arms.clone();
+                let mut typed = typed;
+                let mut key = human_key;
+                // the key pressed on each level, working down
+                for level in (0..top).rev() {
+                    if key != 'A' {
+                        arms[level] = arms[level] + Point::from(dir_for(key));
+                        if pads[level].get(arms[level]).is_none() {
+                            continue 'press;
+                        }
+                        break;
+                    }
+                    key = pads[level].get(arms[level]).unwrap();
+                    if level == 0 {
+                        if key != keys[typed] {
+                            continue 'press;
+                        }
+                        typed += 1;
+                    }
+                }
+                if top == 0 {
+                    if key != keys[typed] {
+                        continue 'press;
+                    }
+                    typed += 1;
+                }
+                q.push_back(((arms, typed), presses + 1));
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn standard_chain_matches_solver() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        for dirpads in [1, 2, 3, 26] {
+            assert_eq!(
+                score_chain(&problem, PadSpec::standard_chain(dirpads))?,
+                score(&problem, dirpads, Solver::new)?
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_chains_match_brute_force() -> Result<()> {
+        let line = PadSpec::from_layout("<^v>A")?;
+        let wide = PadSpec::from_layout("^ A\n<v>")?;
+        let chains = [
+            vec![PadSpec::numpad()],
+            vec![PadSpec::numpad(), line.clone()],
+            vec![PadSpec::numpad(), line.clone(), PadSpec::dirpad()],
+            vec![PadSpec::numpad(), PadSpec::dirpad(), line.clone()],
+            vec![PadSpec::numpad(), wide.clone(), line.clone()],
+            vec![PadSpec::dirpad(), wide.clone(), PadSpec::dirpad()],
+        ];
+        for chain in chains {
+            let solver = ChainSolver::new(chain.clone())?;
+            let codes: &[&str] = if chain[0] == PadSpec::numpad() {
+                &["029A", "980A", "456A"]
+            } else {
+                &["<v>A", "^^A"]
+            };
+            for code in codes {
+                assert_eq!(
+                    Some(solver.presses(code)?),
+                    brute_force_presses(&chain, code),
+                    "{code} on {chain:?}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_chains_are_errors() -> Result<()> {
+        assert!(ChainSolver::new(vec![]).is_err());
+        assert!(ChainSolver::new(vec![PadSpec::dirpad(), PadSpec::numpad()]).is_err());
+        assert!(PadSpec::from_layout("12\n34").is_err());
+        assert!(PadSpec::from_layout("1A\n1A").is_err());
+
+        let solver = ChainSolver::new(PadSpec::standard_chain(2))?;
+        assert!(solver.presses("12X").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dirkey_moves_correct() {
+        let mut solver = Solver::new(1);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, 2);
+
+        let mut solver = Solver::new(2);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, 8);
+
+        let mut solver = Solver::new(3);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, 18);
+
+        let mut solver = Solver::new(4);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, 46);
+
+        let mut solver = Solver::new(20);
+        let moves = solver.min_moves_for_seq(&[DirKey::Dir(ScreenDir::U)], 0);
+        assert_eq!(moves, 94569958);
+    }
+}
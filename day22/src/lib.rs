@@ -0,0 +1,428 @@
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+use common::{iterutil::windowed_deltas, OptionAnyhow};
+
+/// The secret number generator: each step multiplies by `mul1`, divides by `div` and
+/// multiplies by `mul2`, mixing (xor) each result into the secret and pruning it to
+/// below `modulus`. Each buyer generates `steps` new secrets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrngParams {
+    pub mul1: i64,
+    pub div: i64,
+    pub mul2: i64,
+    pub modulus: i64,
+    pub steps: usize,
+}
+impl Default for PrngParams {
+    /// The puzzle's generator
+    fn default() -> Self {
+        Self {
+            mul1: 64,
+            div: 32,
+            mul2: 2048,
+            modulus: 16777216,
+            steps: 2000,
+        }
+    }
+}
+impl PrngParams {
+    /// Check the generator can run without dividing by zero or overflowing
+    pub fn validate(&self) -> Result<()> {
+        if self.mul1 < 1 || self.div < 1 || self.mul2 < 1 || self.modulus < 1 {
+            bail!("generator constants must be positive: {self:?}");
+        }
+        // secrets stay below the modulus, so this is the largest product
+        if self.modulus.checked_mul(self.mul1.max(self.mul2)).is_none() {
+            bail!("secrets overflow with {self:?}");
+        }
+        Ok(())
+    }
+
+    fn next(&self, n: i64) -> i64 {
+        let n = ((n * self.mul1) ^ n) % self.modulus;
+        let n = ((n / self.div) ^ n) % self.modulus;
+        ((n * self.mul2) ^ n) % self.modulus
+    }
+
+    fn iterate(self, init: i64) -> impl Iterator<Item = i64> {
+        std::iter::successors(Some(init), move |n| Some(self.next(*n)))
+    }
+
+    /// Whether the mixing is the puzzle's, ignoring the number of steps
+    fn standard_mixing(&self) -> bool {
+        let standard = PrngParams::default();
+        *self
+            == PrngParams {
+                steps: self.steps,
+                ..standard
+            }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    initial_numbers: Vec<i64>,
+    params: PrngParams,
+}
+impl Problem {
+    /// The same buyers with a different generator
+    pub fn with_params(&self, params: PrngParams) -> Result<Problem> {
+        params.validate()?;
+        Ok(Problem {
+            initial_numbers: self.initial_numbers.clone(),
+            params,
+        })
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let initial_numbers = input
+        .lines()
+        .map(str::parse)
+        .collect::<Result<Vec<i64>, _>>()?;
+    Ok(Problem {
+        initial_numbers,
+        params: PrngParams::default(),
+    })
+}
+
+/// The puzzle's generator
+fn next(n: i64) -> i64 {
+    PrngParams::default().next(n)
+}
+
+const SECRET_BITS: usize = 24;
+
+/// A linear map on 24-bit values over GF(2), stored as the image of each bit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitMatrix([u32; SECRET_BITS]);
+impl BitMatrix {
+    fn apply(&self, v: u32) -> u32 {
+        let mut out = 0;
+        for (bit, column) in self.0.iter().enumerate() {
+            if v & (1 << bit) != 0 {
+                out ^= column;
+            }
+        }
+        out
+    }
+
+    /// `self` after `other`
+    fn compose(&self, other: &BitMatrix) -> BitMatrix {
+        BitMatrix(other.0.map(|column| self.apply(column)))
+    }
+}
+
+/// `next` to the power of 2^k for each k: every shift and xor in `next` is linear
+/// over the bits, and the modulus just drops the high bits
+fn step_powers() -> &'static [BitMatrix; 64] {
+    static POWERS: OnceLock<[BitMatrix; 64]> = OnceLock::new();
+    POWERS.get_or_init(|| {
+        let step = BitMatrix(std::array::from_fn(|bit| next(1 << bit) as u32));
+        let mut powers = [step; 64];
+        for k in 1..powers.len() {
+            powers[k] = powers[k - 1].compose(&powers[k - 1]);
+        }
+        powers
+    })
+}
+
+/// The same sequence as `iterate`, but `nth` jumps ahead in O(log n) steps
+#[derive(Debug, Clone)]
+struct SecretSequence {
+    next: i64,
+}
+impl SecretSequence {
+    fn new(init: i64) -> Self {
+        Self { next: init }
+    }
+}
+impl Iterator for SecretSequence {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        let current = self.next;
+        self.next = next(current);
+        Some(current)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<i64> {
+        let mut v = self.next as u32;
+        for (k, power) in step_powers().iter().enumerate() {
+            if n & (1 << k) != 0 {
+                v = power.apply(v);
+            }
+        }
+        self.next = v as i64;
+        self.next()
+    }
+}
+
+fn part1_with<I: Iterator<Item = i64>>(
+    problem: &Problem,
+    sequence: impl Fn(i64) -> I,
+) -> Result<i64> {
+    let mut total = 0;
+    for init in &problem.initial_numbers {
+        let nth = sequence(*init).nth(problem.params.steps).ok_anyhow()?;
+        total += nth;
+    }
+
+    Ok(total)
+}
+
+pub fn part1(problem: &Problem) -> Result<i64> {
+    part1_with(problem, |init| problem.params.iterate(init))
+}
+
+/// Only for the puzzle's mixing, which the jump tables are built from; any number of
+/// steps is fine
+pub fn part1_skip_ahead(problem: &Problem) -> Result<i64> {
+    if !problem.params.standard_mixing() {
+        bail!("skipping ahead needs the standard generator");
+    }
+    part1_with(problem, SecretSequence::new)
+}
+
+/// A monkey's prices: the last digit of the initial number and of each step after it
+fn prices(params: PrngParams, init: i64) -> impl Iterator<Item = i8> {
+    params
+        .iterate(init)
+        .take(params.steps + 1)
+        .map(|n| (n % 10) as i8)
+}
+
+/// Position of a sequence of price changes, each in -9..=9, among all of them
+fn sequence_index(seq: [i8; 4]) -> usize {
+    seq.iter().fold(0, |acc, d| acc * 19 + (d + 9) as usize)
+}
+
+/// The sequence of four price changes that sells for the most bananas, and that total.
+/// Each monkey's prices are scanned once, adding the price to the total for the four
+/// changes leading up to it, the first time the monkey sees them.
+pub fn best_sequence(problem: &Problem) -> ([i8; 4], i64) {
+    const SEQUENCES: usize = 19 * 19 * 19 * 19;
+    let mut totals = vec![0; SEQUENCES];
+    let mut last_seller = vec![usize::MAX; SEQUENCES];
+    for (monkey, init) in problem.initial_numbers.iter().enumerate() {
+        for (price, seq) in windowed_deltas::<4, _>(prices(problem.params, *init)) {
+            let i = sequence_index(seq);
+            if last_seller[i] != monkey {
+                last_seller[i] = monkey;
+                totals[i] += price as i64;
+            }
+        }
+    }
+
+    // the first of any equally good, as sequences count up from [-9, -9, -9, -9]
+    let (best, total) =
+        totals.iter().enumerate().fold(
+            (0, 0),
+            |best, (i, &t)| if t > best.1 { (i, t) } else { best },
+        );
+    let mut seq = [0; 4];
+    for (k, d) in seq.iter_mut().enumerate() {
+        *d = (best / 19_usize.pow(3 - k as u32) % 19) as i8 - 9;
+    }
+    (seq, total)
+}
+
+pub fn part2(problem: &Problem) -> Result<i64> {
+    Ok(best_sequence(problem).1)
+}
+
+/// What each monkey, by index in the input, sells for when the buyer waits for `seq`;
+/// `None` if the sequence never occurs for that monkey
+pub fn contributions(problem: &Problem, seq: [i8; 4]) -> Vec<(usize, Option<i64>)> {
+    problem
+        .initial_numbers
+        .iter()
+        .map(|init| {
+            windowed_deltas::<4, _>(prices(problem.params, *init))
+                .find(|(_, changes)| *changes == seq)
+                .map(|(price, _)| price as i64)
+        })
+        .enumerate()
+        .collect()
+}
+
+/// Number of monkeys that sell, and the total bananas, from `contributions`
+pub fn contribution_totals(contributions: &[(usize, Option<i64>)]) -> (usize, i64) {
+    let prices = contributions.iter().filter_map(|(_, price)| *price);
+    (prices.clone().count(), prices.sum())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(22, "Monkey Market");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    1
+    10
+    100
+    2024
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("37327623"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn iterate(init: i64) -> impl Iterator<Item = i64> {
+        PrngParams::default().iterate(init)
+    }
+
+    const EXAMPLE2: &str = indoc! {"
+        1
+        2
+        3
+        2024
+    "};
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn iterate_correct() {
+        let init = 123;
+        let first10: Vec<_> = iterate(init).skip(1).take(10).collect();
+        assert_eq!(
+            first10,
+            [
+                15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+                5908254
+            ]
+        );
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 37327623);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_skip_ahead_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(part1_skip_ahead(&problem)?, 37327623);
+        Ok(())
+    }
+
+    #[test]
+    fn skip_ahead_matches_simple_iterator() {
+        for init in [1, 10, 100, 2024, 123, 0, 16777215] {
+            let simple: Vec<_> = iterate(init).take(2100).collect();
+            for n in [0, 1, 2, 7, 64, 1000, 2000, 2099] {
+                assert_eq!(
+                    SecretSequence::new(init).nth(n),
+                    Some(simple[n]),
+                    "{init} {n}"
+                );
+            }
+
+            // carries on normally after a jump
+            let mut sequence = SecretSequence::new(init);
+            assert_eq!(sequence.nth(1000), Some(simple[1000]));
+            assert_eq!(sequence.next(), Some(simple[1001]));
+            assert_eq!(sequence.nth(997), Some(simple[1999]));
+        }
+    }
+
+    #[test]
+    fn custom_params() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let params = PrngParams {
+            steps: 10,
+            ..Default::default()
+        };
+        let short = problem.with_params(params)?;
+        let expected: i64 = [1, 10, 100, 2024]
+            .iter()
+            .map(|init| iterate(*init).nth(10).unwrap())
+            .sum();
+        assert_eq!(part1(&short)?, expected);
+        assert_eq!(part1_skip_ahead(&short)?, expected);
+
+        // a smaller generator, mixing by 8, 4 and 16 modulo 2^16
+        let small = PrngParams {
+            mul1: 8,
+            div: 4,
+            mul2: 16,
+            modulus: 1 << 16,
+            steps: 3,
+        };
+        assert_eq!(
+            small.iterate(123).take(4).collect::<Vec<_>>(),
+            [123, 14331, 2203, 43619]
+        );
+        let problem = problem.with_params(small)?;
+        assert!(part1(&problem)? > 0);
+        assert!(part1_skip_ahead(&problem).is_err());
+        assert_eq!(prices(small, 123).collect::<Vec<_>>(), [3, 1, 3, 9]);
+
+        for bad in [
+            PrngParams { div: 0, ..small },
+            PrngParams {
+                modulus: 1 << 60,
+                ..small
+            },
+        ] {
+            assert!(bad.validate().is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn contributions_for_example_sequence() -> Result<()> {
+        let problem = parse_input(EXAMPLE2)?;
+        let found = contributions(&problem, [-2, 1, -1, 3]);
+        assert_eq!(found, [(0, Some(7)), (1, Some(7)), (2, None), (3, Some(9))]);
+        assert_eq!(contribution_totals(&found), (3, 23));
+        Ok(())
+    }
+
+    #[test]
+    fn best_sequence_matches_contributions() -> Result<()> {
+        let problem = parse_input(EXAMPLE2)?;
+        let (seq, total) = best_sequence(&problem);
+        assert_eq!((seq, total), ([-2, 1, -1, 3], 23));
+        assert_eq!(sequence_index([-9; 4]), 0);
+        assert_eq!(sequence_index([9; 4]), 19 * 19 * 19 * 19 - 1);
+
+        // no sequence beats it, with fewer steps so they can all be checked
+        let params = PrngParams {
+            steps: 30,
+            ..PrngParams::default()
+        };
+        let problem = problem.with_params(params)?;
+        let (seq, total) = best_sequence(&problem);
+        assert_eq!(contribution_totals(&contributions(&problem, seq)).1, total);
+        let r = -9..10_i8;
+        for seq in itertools::iproduct!(r.clone(), r.clone(), r.clone(), r) {
+            let seq = [seq.0, seq.1, seq.2, seq.3];
+            assert!(contribution_totals(&contributions(&problem, seq)).1 <= total);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE2)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 23);
+        Ok(())
+    }
+}
@@ -1,153 +1,79 @@
-use std::{iter, time::Instant};
+use anyhow::Context;
+use common::timing::TimingReport;
 
-use anyhow::Result;
-use common::OptionAnyhow;
+use day22::{
+    best_sequence, contribution_totals, contributions, parse_input, part1, part1_skip_ahead, part2,
+    PrngParams, DAY, EXAMPLE, EXAMPLE_ANSWERS,
+};
 
-#[derive(Debug, Clone)]
-pub struct Problem {
-    initial_numbers: Vec<i64>,
-}
-
-fn parse_input(input: &str) -> Result<Problem> {
-    let initial_numbers = input
-        .lines()
-        .map(str::parse)
-        .collect::<Result<Vec<i64>, _>>()?;
-    Ok(Problem { initial_numbers })
-}
-
-fn next(n: i64) -> i64 {
-    let n = ((n * 64) ^ n) % 16777216;
-    let n = ((n / 32) ^ n) % 16777216;
-    let n = ((n * 2048) ^ n) % 16777216;
-    n
-}
-
-fn iterate(init: i64) -> impl Iterator<Item = i64> {
-    std::iter::successors(Some(init), |n| Some(next(*n)))
-}
-
-fn part1(problem: &Problem) -> Result<i64> {
-    let mut total = 0;
-    for init in &problem.initial_numbers {
-        let nth = iterate(*init).nth(2000).ok_anyhow()?;
-        println!("{init} {nth}");
-        total += nth;
+fn main() -> anyhow::Result<()> {
+    let text = common::read_input("input1.txt", EXAMPLE)?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
+
+    // `--steps N` sets how many secrets each buyer generates, `--modulus M` what
+    // they're pruned to, and `--mix a,b,c` the multiplier, divisor and multiplier
+    let mut params = PrngParams::default();
+    let mut custom = false;
+    if let Some(steps) = common::arg_value("--steps") {
+        params.steps = steps.parse()?;
+        custom = true;
     }
-
-    Ok(total)
-}
-
-// simple brute force is fast enough -- come back later and improve for fun
-fn part2(problem: &Problem) -> Result<i64> {
-    let mut nums = vec![];
-    let mut diffs = vec![];
-    for init in &problem.initial_numbers {
-        // 2000 price CHANGES after initial; sequence includes initial; take 2001
-        let nn: Vec<i8> = iterate(*init).take(2001).map(|n| (n % 10) as i8).collect();
-        let dd: Vec<i8> = nn.windows(2).map(|w| w[1] - w[0]).collect();
-        nums.push(nn);
-        diffs.push(dd);
+    if let Some(modulus) = common::arg_value("--modulus") {
+        params.modulus = modulus.parse()?;
+        custom = true;
     }
-
-    let mut best_tot = 0;
-
-    let r = -9..10_i8;
-    for a in r.clone() {
-        for b in r.clone() {
-            println!("Checking [{a},{b},..]");
-            for c in r.clone() {
-                for d in r.clone() {
-                    let seq = [a, b, c, d];
-                    let mut tot = 0;
-
-                    // find sale prices for each monkey
-                    for (nn, dd) in iter::zip(&nums, &diffs) {
-                        let found_loc = dd.windows(4).position(|w| w == seq);
-                        if let Some(loc) = found_loc {
-                            let price = nn[loc + 4] as i64;
-                            tot += price;
-                        }
-                    }
-
-                    if tot > best_tot {
-                        best_tot = tot;
-                        println!("new best {seq:?} for total {best_tot}");
-                    }
-                }
-            }
-        }
+    if let Some(mix) = common::arg_value("--mix") {
+        let mix: [i64; 3] = mix
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<i64>, _>>()?
+            .try_into()
+            .ok()
+            .context("expected three constants, e.g. 64,32,2048")?;
+        [params.mul1, params.div, params.mul2] = mix;
+        custom = true;
     }
-
-    Ok(best_tot)
-}
-
-fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
-    let problem = parse_input(&text)?;
-
-    let t1 = Instant::now();
-    let count_part1 = part1(&problem)?;
-    println!("Part 1 result is {count_part1} (took {:?})", t1.elapsed());
-
-    let t2 = Instant::now();
-    let count_part2 = part2(&problem)?;
-    println!("Part 2 result is {count_part2} (took {:?})", t2.elapsed());
-
-    Ok(())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use indoc::indoc;
-
-    const EXAMPLE: &str = indoc! {"
-        1
-        10
-        100
-        2024
-    "};
-    const EXAMPLE2: &str = indoc! {"
-        1
-        2
-        3
-        2024
-    "};
-
-    #[test]
-    fn test_parse_input() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        println!("{:?}", problem);
-        Ok(())
+    if custom {
+        let problem = timing.parse(|| parse_input(&text)?.with_params(params))?;
+        timing.part("1", || part1(&problem))?;
+        timing.part("2", || part2(&problem))?;
+        return Ok(());
     }
 
-    #[test]
-    fn iterate_correct() {
-        let init = 123;
-        let first10: Vec<_> = iterate(init).skip(1).take(10).collect();
-        assert_eq!(
-            first10,
-            [
-                15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
-                5908254
-            ]
+    let problem = timing.parse(|| parse_input(&text))?;
+
+    // `--contributions a,b,c,d` shows what each monkey sells for with that sequence of
+    // price changes; `--contributions best` searches for the best sequence first
+    if let Some(seq) = common::arg_value("--contributions") {
+        let seq = match seq.as_str() {
+            "best" => best_sequence(&problem).0,
+            _ => seq
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<i8>, _>>()?
+                .try_into()
+                .ok()
+                .context("expected four price changes, e.g. -2,1,-1,3")?,
+        };
+
+        let found = contributions(&problem, seq);
+        for (monkey, price) in &found {
+            match price {
+                Some(price) => println!("monkey {monkey}: sells for {price}"),
+                None => println!("monkey {monkey}: never sees the sequence"),
+            }
+        }
+        let (sellers, total) = contribution_totals(&found);
+        println!(
+            "{seq:?}: {sellers} of {} monkeys sell, for {total} bananas",
+            found.len()
         );
+        return Ok(());
     }
 
-    #[test]
-    fn part1_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part1(&problem)?;
-        assert_eq!(count, 37327623);
-        Ok(())
-    }
+    timing.part("1", || part1(&problem))?;
+    timing.part("1 (skip ahead)", || part1_skip_ahead(&problem))?;
+    timing.part("2", || part2(&problem))?;
 
-    #[test]
-    fn part2_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE2)?;
-        let count = part2(&problem)?;
-        assert_eq!(count, 23);
-        Ok(())
-    }
+    Ok(())
 }
@@ -0,0 +1,797 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    io::BufRead,
+};
+
+use anyhow::{bail, Result};
+use common::OptionAnyhow;
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+
+/// Interned node name; see `NodeNames`
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+struct NodeId(u32);
+
+/// String table for the node names. Ids are assigned in name order once parsing is
+/// done, so comparing ids compares names.
+#[derive(Clone, Debug, Default)]
+struct NodeNames(Vec<String>);
+impl NodeNames {
+    fn name(&self, id: NodeId) -> &str {
+        &self.0[id.0 as usize]
+    }
+
+    fn show<'a, T: ShowNames>(&'a self, value: &'a T) -> Shown<'a, T> {
+        Shown(self, value)
+    }
+}
+
+/// Display for types holding node ids, which need the string table
+trait ShowNames {
+    fn fmt_names(&self, names: &NodeNames, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+}
+
+struct Shown<'a, T>(&'a NodeNames, &'a T);
+impl<T: ShowNames> Display for Shown<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.1.fmt_names(self.0, f)
+    }
+}
+
+impl ShowNames for NodeId {
+    fn fmt_names(&self, names: &NodeNames, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(names.name(*self))
+    }
+}
+
+#[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+struct Link(NodeId, NodeId);
+impl Link {
+    /// create new link with canonical ordering, since it is bidirectional
+    fn new(n1: NodeId, n2: NodeId) -> Self {
+        match n1.cmp(&n2) {
+            Ordering::Less => Self(n1, n2),
+            Ordering::Greater => Self(n2, n1),
+            Ordering::Equal => Self(n1, n1),
+        }
+    }
+}
+impl ShowNames for Link {
+    fn fmt_names(&self, names: &NodeNames, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", names.name(self.0), names.name(self.1))
+    }
+}
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
+struct NetworkSet(BTreeSet<NodeId>);
+impl NetworkSet {
+    fn new(links: &[NodeId]) -> Self {
+        Self(links.iter().copied().collect())
+    }
+}
+impl ShowNames for NetworkSet {
+    fn fmt_names(&self, names: &NodeNames, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().map(|n| names.name(*n)).join(","))
+    }
+}
+impl From<BTreeSet<NodeId>> for NetworkSet {
+    fn from(value: BTreeSet<NodeId>) -> Self {
+        Self(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    names: NodeNames,
+    links: Vec<Link>,
+}
+
+/// Read `a-b` links a line at a time, interning the (case-insensitive) node names
+pub fn parse_reader(reader: impl BufRead) -> Result<Problem> {
+    let mut ids: HashMap<String, u32> = HashMap::new();
+    let mut names = vec![];
+    let mut intern = |name: &str| -> Result<u32> {
+        let name = node_name(name)?;
+        let next_id = ids.len() as u32;
+        Ok(*ids.entry(name).or_insert_with_key(|name| {
+            names.push(name.clone());
+            next_id
+        }))
+    };
+
+    let mut raw_links = vec![];
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (s1, s2) = line
+            .split_once("-")
+            .expect_anyhow(&format!("line {}: missing '-'", line_no + 1))?;
+        raw_links.push((intern(s1)?, intern(s2)?));
+    }
+
+    // renumber so that ids are in name order
+    let mut order: Vec<u32> = (0..names.len() as u32).collect();
+    order.sort_by(|a, b| names[*a as usize].cmp(&names[*b as usize]));
+    let mut renumber = vec![0; order.len()];
+    for (new, old) in order.iter().enumerate() {
+        renumber[*old as usize] = new as u32;
+    }
+    let names = order
+        .iter()
+        .map(|old| names[*old as usize].clone())
+        .collect();
+    let links = raw_links
+        .into_iter()
+        .map(|(a, b)| Link::new(NodeId(renumber[a as usize]), NodeId(renumber[b as usize])))
+        .collect();
+
+    Ok(Problem {
+        names: NodeNames(names),
+        links,
+    })
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    parse_reader(input.as_bytes())
+}
+
+impl Problem {
+    /// Each node's neighbours, indexed by id
+    fn adjacency(&self) -> Vec<FxHashSet<NodeId>> {
+        let mut adjacent = vec![FxHashSet::default(); self.names.0.len()];
+        for &Link(a, b) in &self.links {
+            if a != b {
+                adjacent[a.0 as usize].insert(b);
+                adjacent[b.0 as usize].insert(a);
+            }
+        }
+        adjacent
+    }
+}
+
+fn binomial(n: usize, r: usize) -> usize {
+    if r > n {
+        return 0;
+    }
+    (0..r).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+/// Counts `k`-cliques with a pivoting clique tree, so large cliques aren't enumerated
+/// subset by subset. Every clique among `candidates` extends `held` (all of which it
+/// contains) with any choice of `pivots` (which it may or may not contain); branching
+/// on a pivot's non-neighbours finds each clique exactly once.
+struct CliqueCounter<'a> {
+    adjacent: &'a [FxHashSet<NodeId>],
+    k: usize,
+}
+impl CliqueCounter<'_> {
+    fn count(&self, candidates: &[NodeId], held: usize, pivots: usize) -> usize {
+        if held > self.k || held + pivots + candidates.len() < self.k {
+            return 0;
+        }
+        let Some(&pivot) = candidates
+            .iter()
+            .max_by_key(|c| self.neighbours_in(**c, candidates).count())
+        else {
+            return binomial(pivots, self.k - held);
+        };
+
+        let within = |node: NodeId, among: &[NodeId]| -> Vec<NodeId> {
+            self.neighbours_in(node, among).collect()
+        };
+        let mut total = self.count(&within(pivot, candidates), held, pivots + 1);
+        let mut remaining = candidates.to_vec();
+        for node in candidates {
+            if *node != pivot && !self.adjacent[pivot.0 as usize].contains(node) {
+                remaining.retain(|n| n != node);
+                total += self.count(&within(*node, &remaining), held + 1, pivots);
+            }
+        }
+        total
+    }
+
+    fn neighbours_in<'b>(
+        &'b self,
+        node: NodeId,
+        among: &'b [NodeId],
+    ) -> impl Iterator<Item = NodeId> + 'b {
+        let adjacent = &self.adjacent[node.0 as usize];
+        among.iter().copied().filter(|n| adjacent.contains(n))
+    }
+}
+
+/// Number of `k`-node fully-connected sets; with a `prefix`, only those with at least
+/// one node whose name starts with it
+pub fn count_cliques(problem: &Problem, k: usize, prefix: Option<char>) -> usize {
+    let adjacent = problem.adjacency();
+    let counter = CliqueCounter {
+        adjacent: &adjacent,
+        k,
+    };
+    let count_among = |allowed: &dyn Fn(NodeId) -> bool| {
+        let nodes: Vec<NodeId> = (0..adjacent.len() as u32)
+            .map(NodeId)
+            .filter(|n| allowed(*n))
+            .collect();
+        // each clique is found from its lowest node
+        nodes
+            .iter()
+            .map(|node| {
+                let later: Vec<NodeId> = counter
+                    .neighbours_in(*node, &nodes)
+                    .filter(|n| n > node)
+                    .collect();
+                counter.count(&later, 1, 0)
+            })
+            .sum::<usize>()
+    };
+
+    let all = count_among(&|_| true);
+    match prefix {
+        // the cliques with a matching node are the ones left after removing them
+        Some(prefix) => all - count_among(&|n: NodeId| !problem.names.name(n).starts_with(prefix)),
+        None => all,
+    }
+}
+
+/// Triangles with at least one node starting with 't'
+pub fn part1(problem: &Problem) -> Result<usize> {
+    Ok(count_cliques(problem, 3, Some('t')))
+}
+
+fn grow_larger_sets(
+    links: &FxHashSet<Link>,
+    cur_size: usize,
+    cur_sets: &FxHashSet<NetworkSet>,
+) -> FxHashSet<NetworkSet> {
+    let mut larger: FxHashSet<NetworkSet> = FxHashSet::default();
+
+    for (i1, s1) in cur_sets.iter().enumerate() {
+        for s2 in cur_sets.iter().skip(i1 + 1) {
+            assert_eq!(s1.0.len(), cur_size);
+            assert_eq!(s2.0.len(), cur_size);
+            assert_ne!(s1, s2);
+
+            let mut diff1 = s1.0.difference(&s2.0);
+            let mut diff2 = s2.0.difference(&s1.0);
+
+            if let (Some(d1), None) = (diff1.next(), diff1.next()) {
+                if let (Some(d2), None) = (diff2.next(), diff2.next()) {
+                    let required_link = Link::new(*d1, *d2);
+                    if links.contains(&required_link) {
+                        let mut merged = s1.0.clone();
+                        merged.insert(*d1);
+                        merged.insert(*d2);
+                        larger.insert(merged.into());
+                    }
+                }
+            }
+        }
+    }
+    larger
+}
+
+/// All the largest fully-connected sets, sorted lexicographically
+fn maximum_cliques(problem: &Problem) -> Vec<NetworkSet> {
+    let links: FxHashSet<Link> = problem.links.iter().copied().collect();
+
+    let mut cur_sets = problem
+        .links
+        .iter()
+        .map(|link| NetworkSet::new(&[link.0, link.1]))
+        .collect();
+    let mut cur_size = 2;
+    loop {
+        let larger = grow_larger_sets(&links, cur_size, &cur_sets);
+        if larger.is_empty() {
+            break;
+        } else {
+            cur_sets = larger;
+            cur_size += 1;
+        }
+    }
+
+    let mut largest: Vec<_> = cur_sets.into_iter().collect();
+    largest.sort();
+    largest
+}
+
+/// Password for the largest set; if there's a tie, the lexicographically smallest wins
+pub fn part2(problem: &Problem) -> Result<String> {
+    let largest = maximum_cliques(problem);
+    let first = largest.first().ok_anyhow()?;
+    Ok(problem.names.show(first).to_string())
+}
+
+/// Node names are case-insensitive, and kept in lower case
+fn node_name(name: &str) -> Result<String> {
+    let name = name.trim().to_ascii_lowercase();
+    if name.is_empty() {
+        bail!("empty node name");
+    }
+    Ok(name)
+}
+
+/// A network that links are added to and removed from one at a time, keeping the
+/// number of triangles, and the largest fully-connected set, up to date as it goes.
+/// Node ids are handed out as names first appear, so unlike `Problem`'s they aren't
+/// in name order.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    ids: HashMap<String, NodeId>,
+    names: NodeNames,
+    adjacent: Vec<FxHashSet<NodeId>>,
+    /// Triangles each link is part of
+    link_triangles: FxHashMap<Link, usize>,
+    triangles: usize,
+    /// The largest set, with ties going to the smallest password; `None` once a
+    /// removal has broken it up, until it's next asked for
+    largest: Option<Vec<NodeId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Graph {
+            largest: Some(vec![]),
+            ..Default::default()
+        }
+    }
+
+    /// A graph with all of `problem`'s links
+    pub fn from_problem(problem: &Problem) -> Self {
+        let mut graph = Graph::new();
+        for link in &problem.links {
+            let a = graph.intern(problem.names.name(link.0));
+            let b = graph.intern(problem.names.name(link.1));
+            graph.link_ids(a, b);
+        }
+        graph
+    }
+
+    fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = NodeId(self.names.0.len() as u32);
+        self.ids.insert(name.to_string(), id);
+        self.names.0.push(name.to_string());
+        self.adjacent.push(FxHashSet::default());
+        id
+    }
+
+    /// The id for `name`, written as in the input; `None` if it's never been seen
+    fn id(&self, name: &str) -> Result<Option<NodeId>> {
+        Ok(self.ids.get(&node_name(name)?).copied())
+    }
+
+    /// Link `a` and `b`, returning whether they weren't already; linking a node to
+    /// itself does nothing, as when parsing
+    pub fn add_link(&mut self, a: &str, b: &str) -> Result<bool> {
+        let a = self.intern(&node_name(a)?);
+        let b = self.intern(&node_name(b)?);
+        Ok(self.link_ids(a, b))
+    }
+
+    fn link_ids(&mut self, a: NodeId, b: NodeId) -> bool {
+        if a == b || !self.adjacent[a.0 as usize].insert(b) {
+            return false;
+        }
+        self.adjacent[b.0 as usize].insert(a);
+
+        let common = self.common_neighbours(a, b);
+        for &c in &common {
+            *self.link_triangles.entry(Link::new(a, c)).or_default() += 1;
+            *self.link_triangles.entry(Link::new(b, c)).or_default() += 1;
+        }
+        self.triangles += common.len();
+        self.link_triangles.insert(Link::new(a, b), common.len());
+
+        // any set larger than before must include the new link
+        if let Some(largest) = &self.largest {
+            let mut found = self.best_clique(vec![a, b], common);
+            if self.better(&found, largest) {
+                found.sort_by(|x, y| self.names.name(*x).cmp(self.names.name(*y)));
+                self.largest = Some(found);
+            }
+        }
+        true
+    }
+
+    /// Unlink `a` and `b`, returning whether they were linked
+    pub fn remove_link(&mut self, a: &str, b: &str) -> Result<bool> {
+        let (Some(a), Some(b)) = (self.id(a)?, self.id(b)?) else {
+            return Ok(false);
+        };
+        if !self.adjacent[a.0 as usize].remove(&b) {
+            return Ok(false);
+        }
+        self.adjacent[b.0 as usize].remove(&a);
+
+        let common = self.common_neighbours(a, b);
+        for &c in &common {
+            *self.link_triangles.get_mut(&Link::new(a, c)).unwrap() -= 1;
+            *self.link_triangles.get_mut(&Link::new(b, c)).unwrap() -= 1;
+        }
+        self.triangles -= common.len();
+        self.link_triangles.remove(&Link::new(a, b));
+
+        if self
+            .largest
+            .as_ref()
+            .is_some_and(|largest| largest.contains(&a) && largest.contains(&b))
+        {
+            self.largest = None;
+        }
+        Ok(true)
+    }
+
+    fn common_neighbours(&self, a: NodeId, b: NodeId) -> Vec<NodeId> {
+        let (a, b) = (&self.adjacent[a.0 as usize], &self.adjacent[b.0 as usize]);
+        let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+        small
+            .iter()
+            .copied()
+            .filter(|n| large.contains(n))
+            .collect()
+    }
+
+    /// Whether `a` is larger than `b`, or the same size with a smaller password
+    fn better(&self, a: &[NodeId], b: &[NodeId]) -> bool {
+        let password = |set: &[NodeId]| -> Vec<&str> {
+            let mut names: Vec<_> = set.iter().map(|n| self.names.name(*n)).collect();
+            names.sort();
+            names
+        };
+        match a.len().cmp(&b.len()) {
+            Ordering::Equal => password(a) < password(b),
+            order => order == Ordering::Greater,
+        }
+    }
+
+    /// The best set (as for `better`) that holds all of `held`, the rest drawn from
+    /// `candidates`, all of which are linked to everything held. Bron-Kerbosch with
+    /// pivoting, which visits every maximal set.
+    fn best_clique(&self, held: Vec<NodeId>, candidates: Vec<NodeId>) -> Vec<NodeId> {
+        let mut best = held.clone();
+        self.extend_clique(&mut held.clone(), candidates, vec![], &mut best);
+        best
+    }
+
+    fn extend_clique(
+        &self,
+        held: &mut Vec<NodeId>,
+        mut candidates: Vec<NodeId>,
+        mut excluded: Vec<NodeId>,
+        best: &mut Vec<NodeId>,
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() && self.better(held, best) {
+                *best = held.clone();
+            }
+            return;
+        }
+        if held.len() + candidates.len() < best.len() {
+            return;
+        }
+        let adjacent = |n: NodeId| &self.adjacent[n.0 as usize];
+        let pivot = *candidates
+            .iter()
+            .chain(&excluded)
+            .max_by_key(|p| {
+                candidates
+                    .iter()
+                    .filter(|c| adjacent(**p).contains(c))
+                    .count()
+            })
+            .unwrap();
+        let branches: Vec<NodeId> = candidates
+            .iter()
+            .copied()
+            .filter(|c| !adjacent(pivot).contains(c))
+            .collect();
+        for node in branches {
+            let within = |set: &[NodeId]| -> Vec<NodeId> {
+                set.iter()
+                    .copied()
+                    .filter(|n| adjacent(node).contains(n))
+                    .collect()
+            };
+            held.push(node);
+            self.extend_clique(held, within(&candidates), within(&excluded), best);
+            held.pop();
+            candidates.retain(|c| *c != node);
+            excluded.push(node);
+        }
+    }
+
+    /// Number of fully-connected sets of three
+    pub fn triangles(&self) -> usize {
+        self.triangles
+    }
+
+    /// Number of triangles the link between `a` and `b` is part of; `None` if they
+    /// aren't linked
+    pub fn link_triangles(&self, a: &str, b: &str) -> Result<Option<usize>> {
+        Ok(match (self.id(a)?, self.id(b)?) {
+            (Some(a), Some(b)) => self.link_triangles.get(&Link::new(a, b)).copied(),
+            _ => None,
+        })
+    }
+
+    /// The largest fully-connected set, by name; if there's a tie, the one with the
+    /// smallest password. Found again from scratch if a removal has broken it up.
+    pub fn largest_set(&mut self) -> Vec<&str> {
+        let largest = match self.largest.take() {
+            Some(largest) => largest,
+            None => {
+                let nodes = (0..self.names.0.len() as u32).map(NodeId).collect();
+                let mut found = self.best_clique(vec![], nodes);
+                found.sort_by(|x, y| self.names.name(*x).cmp(self.names.name(*y)));
+                found
+            }
+        };
+        self.largest
+            .insert(largest)
+            .iter()
+            .map(|n| self.names.name(*n))
+            .collect()
+    }
+
+    /// Every link, in the input format and in no particular order
+    pub fn links(&self) -> impl Iterator<Item = String> + '_ {
+        self.link_triangles
+            .keys()
+            .map(|link| self.names.show(link).to_string())
+    }
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?))
+}
+
+common::aoc_day!(23, "LAN Party");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    kh-tc
+    qp-kh
+    de-cg
+    ka-co
+    yn-aq
+    qp-ub
+    cg-tb
+    vc-aq
+    tb-ka
+    wh-tc
+    yn-cg
+    kh-ub
+    ta-co
+    de-co
+    tc-td
+    tb-wq
+    wh-td
+    ta-ka
+    td-qp
+    aq-cg
+    wq-ub
+    ub-vc
+    de-ta
+    wq-aq
+    wq-vc
+    wh-yn
+    ka-de
+    kh-ta
+    co-tc
+    wh-qp
+    tb-vc
+    td-yn
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("7"), Some("co,de,ka,ta")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 7);
+        Ok(())
+    }
+
+    /// Count by checking every `k`-subset
+    fn brute_force_cliques(problem: &Problem, k: usize, prefix: Option<char>) -> usize {
+        let links: FxHashSet<Link> = problem.links.iter().copied().collect();
+        (0..problem.names.0.len() as u32)
+            .map(NodeId)
+            .combinations(k)
+            .filter(|nodes| {
+                nodes
+                    .iter()
+                    .tuple_combinations()
+                    .all(|(a, b)| links.contains(&Link::new(*a, *b)))
+            })
+            .filter(|nodes| {
+                prefix.is_none_or(|p| nodes.iter().any(|n| problem.names.name(*n).starts_with(p)))
+            })
+            .count()
+    }
+
+    #[test]
+    fn count_cliques_matches_brute_force() -> Result<()> {
+        let example = parse_input(EXAMPLE)?;
+        assert_eq!(count_cliques(&example, 3, None), 12);
+        assert_eq!(count_cliques(&example, 4, None), 1);
+        assert_eq!(count_cliques(&example, 5, None), 0);
+
+        // dense random graphs have plenty of large cliques
+        let mut rng = common::rng::seeded(23);
+        let names: Vec<String> = (0..18)
+            .map(|i| format!("{}{}", ['t', 'a', 'b'][i % 3], (b'a' + i as u8) as char))
+            .collect();
+        for _ in 0..5 {
+            let mut input = String::new();
+            for (a, b) in names.iter().tuple_combinations() {
+                if !rng.one_in(4) {
+                    input += &format!("{a}-{b}\n");
+                }
+            }
+            let problem = parse_input(&input)?;
+            for k in 1..=7 {
+                for prefix in [None, Some('t'), Some('b')] {
+                    assert_eq!(
+                        count_cliques(&problem, k, prefix),
+                        brute_force_cliques(&problem, k, prefix),
+                        "k={k} prefix={prefix:?}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let code = part2(&problem)?;
+        assert_eq!(code, "co,de,ka,ta");
+        Ok(())
+    }
+
+    #[test]
+    fn part2_tie_picks_smallest() -> Result<()> {
+        // two 4-cliques joined only by ae-xa, listed with the larger password first
+        let input = indoc! {"
+            xa-xb
+            xa-xc
+            xa-xd
+            xb-xc
+            xb-xd
+            xc-xd
+            ab-ac
+            ab-ad
+            ab-ae
+            ac-ad
+            ac-ae
+            ad-ae
+            ae-xa
+        "};
+        let problem = parse_input(input)?;
+        let cliques: Vec<_> = maximum_cliques(&problem)
+            .iter()
+            .map(|set| problem.names.show(set).to_string())
+            .collect();
+        assert_eq!(cliques, ["ab,ac,ad,ae", "xa,xb,xc,xd"]);
+        assert_eq!(part2(&problem)?, "ab,ac,ad,ae");
+        Ok(())
+    }
+
+    #[test]
+    fn graph_streams_example() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let mut graph = Graph::new();
+        for line in EXAMPLE.lines() {
+            let (a, b) = line.split_once('-').unwrap();
+            assert!(graph.add_link(a, b)?);
+        }
+        assert!(!graph.add_link("TC", "kh")?);
+        assert!(!graph.add_link("kh", "kh")?);
+        assert_eq!(graph.triangles(), count_cliques(&problem, 3, None));
+        assert_eq!(graph.largest_set(), ["co", "de", "ka", "ta"]);
+        assert_eq!(Graph::from_problem(&problem).triangles(), 12);
+
+        // co,de,ka,ta and co,de,ta are the triangles through co-de
+        assert_eq!(graph.link_triangles("co", "de")?, Some(2));
+        assert!(graph.remove_link("de", "co")?);
+        assert!(!graph.remove_link("de", "co")?);
+        assert!(!graph.remove_link("de", "zz")?);
+        assert_eq!(graph.link_triangles("co", "de")?, None);
+        assert_eq!(graph.triangles(), 10);
+        assert_eq!(graph.largest_set(), ["aq", "cg", "yn"]);
+        assert!(graph.add_link("", "co").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn graph_updates_match_recounts() -> Result<()> {
+        let mut rng = common::rng::seeded(17);
+        let names: Vec<String> = (0..12)
+            .map(|i| format!("n{}", (b'z' - i) as char))
+            .collect();
+        let mut graph = Graph::new();
+        for step in 0..600 {
+            let a = &names[rng.range(0, 11) as usize];
+            let b = &names[rng.range(0, 11) as usize];
+            // mostly adding, so the graph fills up before thinning out again
+            match step < 300 && !rng.one_in(3) || rng.one_in(3) {
+                true => graph.add_link(a, b)?,
+                false => graph.remove_link(a, b)?,
+            };
+
+            let problem = parse_input(&graph.links().join("\n"))?;
+            assert_eq!(
+                graph.triangles(),
+                count_cliques(&problem, 3, None),
+                "{step}"
+            );
+            // leave the largest set stale now and then
+            if step % 5 != 0 && graph.links().next().is_some() {
+                assert_eq!(graph.largest_set().join(","), part2(&problem)?, "{step}");
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn long_names_interned_in_name_order() -> Result<()> {
+        let input = indoc! {"
+            web01-DB
+            db-teapot
+            teapot-web01
+            db-WEB01
+            cache-db
+        "};
+        let problem = parse_input(input)?;
+        assert_eq!(problem.names.0, ["cache", "db", "teapot", "web01"]);
+        assert_eq!(problem.links[0], Link(NodeId(1), NodeId(3)));
+        assert_eq!(problem.links[3], problem.links[0]);
+        assert_eq!(
+            problem.names.show(&problem.links[0]).to_string(),
+            "db-web01"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn streaming_hostnames() -> Result<()> {
+        let input = indoc! {"
+            alpha-tango
+            tango-bravo
+            bravo-alpha
+
+            charlie-tango
+        "};
+        let problem = parse_reader(std::io::BufReader::with_capacity(4, input.as_bytes()))?;
+        assert_eq!(problem.links.len(), 4);
+        assert_eq!(part1(&problem)?, 1);
+        assert_eq!(part2(&problem)?, "alpha,bravo,tango");
+        assert!(parse_input("alpha\n").is_err());
+        assert!(parse_input("alpha-\n").is_err());
+        Ok(())
+    }
+}
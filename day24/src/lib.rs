@@ -0,0 +1,1482 @@
+use anyhow::{bail, Result};
+use common::{json, timing::json_string, OptionAnyhow, ResultAnyhow};
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+use std::{borrow::Cow, cmp::Reverse, fmt::Display};
+
+type Value = Option<bool>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Operation {
+    And,
+    Or,
+    Xor,
+}
+impl Operation {
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::And => a && b,
+            Operation::Or => a || b,
+            Operation::Xor => a ^ b,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::And => "AND",
+            Operation::Or => "OR",
+            Operation::Xor => "XOR",
+        }
+    }
+}
+
+type Calculation<'a> = (Operation, &'a str, &'a str);
+
+#[derive(Debug, Clone)]
+pub struct Problem<'a> {
+    initial_values: FxHashMap<&'a str, Value>,
+    calculated: FxHashMap<&'a str, Calculation<'a>>,
+}
+
+fn parse_operation(op: &str) -> Result<Operation> {
+    match op {
+        "AND" => Ok(Operation::And),
+        "OR" => Ok(Operation::Or),
+        "XOR" => Ok(Operation::Xor),
+        _ => bail!("Unrecognized operation {op}"),
+    }
+}
+
+/// `a OP b -> out`
+fn parse_gate(line: &str) -> Result<(&str, Calculation<'_>)> {
+    let expected = || "expected `a OP b -> out`";
+    let mut fields = line.split_whitespace();
+    let ida = fields.next().with_context_anyhow(expected)?;
+    let op = fields.next().with_context_anyhow(expected)?;
+    let idb = fields.next().with_context_anyhow(expected)?;
+    if fields.next() != Some("->") {
+        bail!(expected());
+    }
+    let id = fields.next().with_context_anyhow(expected)?;
+    Ok((id, (parse_operation(op)?, ida, idb)))
+}
+
+pub fn parse_input(input: &str) -> Result<Problem<'_>> {
+    let mut initial_values = FxHashMap::default();
+
+    let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l));
+    for (line_no, line) in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+
+        let (id, val) = line
+            .split_once(": ")
+            .with_context_anyhow(|| "expected `wire: value`")
+            .at_line(line_no)?;
+        let val = match val {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => Err(anyhow::anyhow!("Unexpected value {val}")).at(line_no, id.len() + 3)?,
+        };
+        initial_values.insert(id, val);
+    }
+
+    let mut calculated = FxHashMap::default();
+    for (line_no, line) in lines.by_ref() {
+        let (id, calculation) = parse_gate(line).at_line(line_no)?;
+        calculated.insert(id, calculation);
+    }
+
+    Ok(Problem {
+        initial_values,
+        calculated,
+    })
+}
+
+fn calculate<'a>(
+    mut registers: FxHashMap<&'a str, Value>,
+    mut remaining_calculations: FxHashMap<&'a str, Calculation>,
+) -> Result<(u64, FxHashMap<&'a str, Value>)> {
+    while !remaining_calculations.is_empty() {
+        let before = remaining_calculations.len();
+        remaining_calculations.retain(|id, calc| {
+            let (op, ida, idb) = calc;
+            let va = registers.get(ida).copied().flatten();
+            let vb = registers.get(idb).copied().flatten();
+            match (va, vb) {
+                (Some(a), Some(b)) => {
+                    let c = op.apply(a, b);
+                    registers.insert(id, Some(c));
+
+                    // completed this calculation - do not retain
+                    false
+                }
+                _ => true, // retain for next iteration
+            }
+        });
+        if remaining_calculations.len() == before {
+            bail!("{before} gates can't be evaluated: cycle or missing inputs");
+        }
+    }
+
+    // collect z values
+    let mut total = 0;
+    for i in 0.. {
+        let id = format!("z{i:02}");
+        let v = registers.get(id.as_str()).copied().flatten();
+
+        if v.is_none() {
+            break;
+        }
+        let v = v.unwrap();
+        let v = v as u64;
+        total += v << i;
+    }
+
+    Ok((total, registers))
+}
+
+pub fn part1(problem: &Problem) -> Result<u64> {
+    let registers = problem.initial_values.clone();
+    let remaining_calculations = problem.calculated.clone();
+    let (result, _) = calculate(registers, remaining_calculations)?;
+    Ok(result)
+}
+
+/// Statistics from `Circuit::simplify`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimplifyStats {
+    pub constants_folded: usize,
+    pub dead_removed: usize,
+    pub duplicates_merged: usize,
+}
+
+/// What a gate does in a ripple-carry adder, for bit `n`:
+/// `xor1 = x XOR y`, `and1 = x AND y`, `sum = xor1 XOR carry_in` (the z output),
+/// `and2 = xor1 AND carry_in`, and `carry = and1 OR and2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Xor1(u32),
+    And1(u32),
+    Sum(u32),
+    And2(u32),
+    Carry(u32),
+}
+impl Role {
+    pub fn bit(&self) -> u32 {
+        match *self {
+            Role::Xor1(n) | Role::And1(n) | Role::Sum(n) | Role::And2(n) | Role::Carry(n) => n,
+        }
+    }
+}
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Role::Xor1(_) => "xor1",
+            Role::And1(_) => "and1",
+            Role::Sum(_) => "sum",
+            Role::And2(_) => "and2",
+            Role::Carry(_) => "carry",
+        };
+        write!(f, "{name}_{:02}", self.bit())
+    }
+}
+
+/// Version of the JSON written by `Circuit::to_json`
+pub const CIRCUIT_JSON_VERSION: u64 = 1;
+
+/// The gate network, with some wires optionally fixed to constant values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit<'a> {
+    gates: FxHashMap<&'a str, Calculation<'a>>,
+    constants: FxHashMap<&'a str, bool>,
+    /// Initial values of the input wires, from the puzzle input
+    inputs: FxHashMap<&'a str, bool>,
+}
+impl<'a> Circuit<'a> {
+    pub fn new(problem: &Problem<'a>) -> Self {
+        Self {
+            gates: problem.calculated.clone(),
+            constants: FxHashMap::default(),
+            inputs: problem
+                .initial_values
+                .iter()
+                .filter_map(|(id, v)| Some((*id, (*v)?)))
+                .collect(),
+        }
+    }
+
+    /// The circuit as JSON, with its gates, fixed wires and initial values, and a
+    /// `"version"`. Members and gates are sorted by wire, one input or gate per line,
+    /// so the same circuit always gives the same text and changes diff cleanly.
+    pub fn to_json(&self) -> String {
+        let bits = |values: &FxHashMap<&str, bool>| {
+            let lines = values
+                .iter()
+                .sorted()
+                .map(|(id, v)| format!("    {}: {}", json_string(id), *v as u8));
+            block("{", lines, "}")
+        };
+        let gates = self
+            .gates
+            .iter()
+            .sorted_by_key(|(out, _)| **out)
+            .map(|(out, (op, a, b))| {
+                format!(
+                    r#"    {{"out": {}, "op": "{}", "a": {}, "b": {}}}"#,
+                    json_string(out),
+                    op.name(),
+                    json_string(a),
+                    json_string(b)
+                )
+            });
+        format!(
+            "{{\n  \"version\": {CIRCUIT_JSON_VERSION},\n  \"inputs\": {},\n  \"constants\": {},\n  \"gates\": {}\n}}\n",
+            bits(&self.inputs),
+            bits(&self.constants),
+            block("[", gates, "]")
+        )
+    }
+
+    /// Read a circuit written by `to_json`, borrowing the wire names from `json`. Wire
+    /// names must be letters and digits, as in the puzzle input, and each wire driven
+    /// by at most one gate.
+    pub fn from_json(json: &'a str) -> Result<Self> {
+        let doc = json::parse(json)?;
+        let version = doc.get("version").and_then(json::Json::as_u64);
+        if version != Some(CIRCUIT_JSON_VERSION) {
+            bail!("expected circuit version {CIRCUIT_JSON_VERSION}, not {version:?}");
+        }
+        let member = |key: &str| doc.get(key).with_context_anyhow(|| format!("no {key}"));
+
+        let bits = |key: &str| -> Result<FxHashMap<&'a str, bool>> {
+            let values = member(key)?.as_object().with_context_anyhow(|| key)?;
+            let mut bits = FxHashMap::default();
+            for (id, v) in values {
+                let bit = match v.as_u64() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => bail!("{key}: {id} should be 0 or 1"),
+                };
+                if bits.insert(wire_name(id)?, bit).is_some() {
+                    bail!("{key}: {id} given twice");
+                }
+            }
+            Ok(bits)
+        };
+        let inputs = bits("inputs")?;
+        let constants = bits("constants")?;
+
+        let mut gates = FxHashMap::default();
+        let list = member("gates")?.as_array().expect_anyhow("gates")?;
+        for (i, gate) in list.iter().enumerate() {
+            let field = |key: &str| -> Result<&'a str> {
+                match gate.get(key) {
+                    Some(json::Json::String(id)) => wire_name(id),
+                    _ => bail!("gate {i}: no {key}"),
+                }
+            };
+            let op = match gate.get("op").and_then(json::Json::as_str) {
+                Some(op) => parse_operation(op)?,
+                None => bail!("gate {i}: no op"),
+            };
+            let out = field("out")?;
+            if gates.insert(out, (op, field("a")?, field("b")?)).is_some() {
+                bail!("gate {i}: {out} is driven by another gate");
+            }
+        }
+
+        Ok(Circuit {
+            gates,
+            constants,
+            inputs,
+        })
+    }
+
+    /// Evaluate the z output for the initial values
+    pub fn evaluate_initial(&self) -> Result<u64> {
+        let inputs = self.inputs.iter().map(|(id, v)| (*id, Some(*v))).collect();
+        self.evaluate(&inputs)
+    }
+
+    /// Treat `wire` as always having `value`
+    pub fn fix(&mut self, wire: &'a str, value: bool) {
+        self.constants.insert(wire, value);
+    }
+
+    pub fn gate_count(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Fold constants, merge duplicate gates, then remove gates that don't feed any z output
+    pub fn simplify(&mut self) -> SimplifyStats {
+        let mut stats = SimplifyStats::default();
+        loop {
+            let folded = self.fold_constants();
+            let merged = self.merge_duplicates();
+            stats.constants_folded += folded;
+            stats.duplicates_merged += merged;
+            if folded == 0 && merged == 0 {
+                break;
+            }
+        }
+        stats.dead_removed = self.remove_dead();
+        stats
+    }
+
+    fn fold_constants(&mut self) -> usize {
+        let mut folded = 0;
+        loop {
+            let mut found = vec![];
+            for (&id, (op, a, b)) in &self.gates {
+                let va = self.constants.get(a).copied();
+                let vb = self.constants.get(b).copied();
+                let value = match (op, va, vb) {
+                    (_, Some(a), Some(b)) => Some(op.apply(a, b)),
+                    (Operation::And, Some(false), _) | (Operation::And, _, Some(false)) => {
+                        Some(false)
+                    }
+                    (Operation::Or, Some(true), _) | (Operation::Or, _, Some(true)) => Some(true),
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    found.push((id, value));
+                }
+            }
+            if found.is_empty() {
+                return folded;
+            }
+            for (id, value) in found {
+                self.gates.remove(id);
+                self.constants.insert(id, value);
+                folded += 1;
+            }
+        }
+    }
+
+    fn merge_duplicates(&mut self) -> usize {
+        // visit z outputs first so they're the ones kept, since they can't be renamed
+        let mut ids: Vec<_> = self.gates.keys().copied().collect();
+        ids.sort_by_key(|id| (!id.starts_with('z'), *id));
+
+        let mut seen: FxHashMap<(Operation, &str, &str), &str> = FxHashMap::default();
+        let mut renames: FxHashMap<&str, &str> = FxHashMap::default();
+        for id in ids {
+            let (op, a, b) = self.gates[id];
+            // inputs in canonical order so `a AND b` matches `b AND a`
+            let key = (op, a.min(b), a.max(b));
+            match seen.get(&key) {
+                Some(&keep) if !id.starts_with('z') => {
+                    renames.insert(id, keep);
+                }
+                Some(_) => {}
+                None => {
+                    seen.insert(key, id);
+                }
+            }
+        }
+
+        for id in renames.keys() {
+            self.gates.remove(id);
+        }
+        for (_, a, b) in self.gates.values_mut() {
+            for input in [a, b] {
+                if let Some(keep) = renames.get(input) {
+                    *input = keep;
+                }
+            }
+        }
+        renames.len()
+    }
+
+    fn remove_dead(&mut self) -> usize {
+        let mut live = FxHashSet::default();
+        let mut stack: Vec<&str> = self
+            .gates
+            .keys()
+            .copied()
+            .filter(|id| id.starts_with('z'))
+            .collect();
+        while let Some(id) = stack.pop() {
+            if live.insert(id) {
+                if let Some((_, a, b)) = self.gates.get(id) {
+                    stack.extend([*a, *b]);
+                }
+            }
+        }
+
+        let before = self.gates.len();
+        self.gates.retain(|id, _| live.contains(id));
+        before - self.gates.len()
+    }
+
+    /// Exchange the output wires of gates `a` and `b`
+    pub fn swap_outputs(&mut self, a: &'a str, b: &'a str) {
+        self.gates = swap(std::mem::take(&mut self.gates), a, b);
+    }
+
+    /// Gates feeding `id`, including `id` itself if it's a gate
+    fn cone(&self, id: &str) -> FxHashSet<&'a str> {
+        let mut found = FxHashSet::default();
+        let mut stack: Vec<&'a str> = self
+            .gates
+            .get_key_value(id)
+            .map(|(k, _)| *k)
+            .into_iter()
+            .collect();
+        while let Some(id) = stack.pop() {
+            if let Some((_, a, b)) = self.gates.get(id) {
+                if found.insert(id) {
+                    stack.extend([*a, *b]);
+                }
+            }
+        }
+        found
+    }
+
+    /// Input wires `x..` or `y..` used by the gates, indexed by bit
+    fn input_wires(&self, label: char) -> Result<Vec<(&'a str, u32)>> {
+        let mut wires: Vec<_> = self
+            .gates
+            .values()
+            .flat_map(|(_, a, b)| [*a, *b])
+            .filter(|id| id.starts_with(label))
+            .map(|id| Ok((id, id[1..].parse()?)))
+            .collect::<Result<_>>()?;
+        wires.sort_unstable();
+        wires.dedup();
+        Ok(wires)
+    }
+
+    /// Evaluate the z output for the given input wire values
+    pub fn evaluate(&self, inputs: &FxHashMap<&'a str, Value>) -> Result<u64> {
+        let mut registers = inputs.clone();
+        registers.extend(self.constants.iter().map(|(id, v)| (*id, Some(*v))));
+        let (total, _) = calculate(registers, self.gates.clone())?;
+        Ok(total)
+    }
+
+    /// Wires read by the gates that no gate drives and that aren't fixed
+    fn free_inputs(&self) -> FxHashSet<&'a str> {
+        self.gates
+            .values()
+            .flat_map(|(_, a, b)| [*a, *b])
+            .filter(|id| !self.gates.contains_key(id) && !self.constants.contains_key(id))
+            .collect()
+    }
+
+    /// Value of `wire` for every combination of the `inputs`, with all other free
+    /// inputs held at 0. Entry `i` has `inputs[k]` set to bit `k` of `i`. Only the
+    /// gates feeding `wire` are evaluated, so a cycle elsewhere doesn't matter; one
+    /// feeding it is an error.
+    pub fn truth_table(&self, wire: &str, inputs: &[&str]) -> Result<Vec<bool>> {
+        const MAX_INPUTS: usize = 20;
+        if inputs.len() > MAX_INPUTS {
+            bail!("{} inputs is more than {MAX_INPUTS}", inputs.len());
+        }
+        let free = self.free_inputs();
+        let selected = inputs
+            .iter()
+            .map(|id| {
+                free.get(*id)
+                    .copied()
+                    .with_context_anyhow(|| format!("{id} is not a free input wire"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let cone: FxHashMap<_, _> = self
+            .cone(wire)
+            .into_iter()
+            .map(|id| (id, self.gates[id]))
+            .collect();
+        let mut registers: FxHashMap<&'a str, Value> =
+            free.iter().map(|id| (*id, Some(false))).collect();
+        registers.extend(self.constants.iter().map(|(id, v)| (*id, Some(*v))));
+        if cone.is_empty() && !registers.contains_key(wire) {
+            bail!("no wire {wire}");
+        }
+
+        (0..1_usize << selected.len())
+            .map(|row| {
+                for (k, id) in selected.iter().enumerate() {
+                    registers.insert(id, Some(row >> k & 1 == 1));
+                }
+                let (_, values) = calculate(registers.clone(), cone.clone())?;
+                values[wire].expect_anyhow("wire not evaluated")
+            })
+            .collect()
+    }
+
+    /// Roles of the gates' output wires, found from the structure of the circuit rather
+    /// than its wire names, so a swapped output wire gets the role of the gate that now
+    /// drives it. Works up from the inputs one bit at a time, each stage found from the
+    /// previous carry; gates that don't fit the adder are left out.
+    pub fn annotate_roles(&self) -> Result<FxHashMap<String, Role>> {
+        let mut users: FxHashMap<&str, Vec<&'a str>> = FxHashMap::default();
+        for (&id, (_, a, b)) in &self.gates {
+            users.entry(a).or_default().push(id);
+            users.entry(b).or_default().push(id);
+        }
+        for list in users.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+        let op_users = |wires: &[Option<&str>], op: Operation| -> Vec<&'a str> {
+            wires
+                .iter()
+                .flatten()
+                .flat_map(|w| users.get(w).into_iter().flatten().copied())
+                .filter(|id| self.gates[id].0 == op)
+                .unique()
+                .collect()
+        };
+
+        let mut roles: FxHashMap<&'a str, Role> = FxHashMap::default();
+        let mut assign = |ids: Vec<&'a str>, role: Role| {
+            for id in &ids {
+                roles.entry(id).or_insert(role);
+            }
+            ids.first().copied()
+        };
+
+        let bits = self.input_wires('x')?.iter().map(|(_, i)| i + 1).max();
+        let mut carry = None;
+        for bit in 0..bits.unwrap_or(0) {
+            let (x, y) = (get_idx(bit as i32), get_idy(bit as i32));
+            let inputs = [Some(x.as_str()), Some(y.as_str())];
+            let xor1 = assign(op_users(&inputs, Operation::Xor), Role::Xor1(bit));
+            let and1 = assign(op_users(&inputs, Operation::And), Role::And1(bit));
+
+            let mut and2 = None;
+            if carry.is_some() {
+                let stage = [xor1, carry];
+                assign(op_users(&stage, Operation::Xor), Role::Sum(bit));
+                and2 = assign(op_users(&stage, Operation::And), Role::And2(bit));
+            }
+            // the first bit is a half adder, where `and1` is the carry itself
+            carry = match assign(op_users(&[and1, and2], Operation::Or), Role::Carry(bit)) {
+                None if bit == 0 => and1,
+                found => found,
+            };
+        }
+
+        Ok(roles
+            .into_iter()
+            .map(|(id, role)| (id.to_string(), role))
+            .collect())
+    }
+}
+
+/// Lines between `open` and `close`, comma separated, or just the two if there are none
+fn block(open: &str, lines: impl Iterator<Item = String>, close: &str) -> String {
+    let lines: Vec<_> = lines.collect();
+    match lines.is_empty() {
+        true => format!("{open}{close}"),
+        false => format!("{open}\n{}\n  {close}", lines.join(",\n")),
+    }
+}
+
+/// A wire name from JSON, which must be borrowed from the text, so can't be written
+/// with escapes, even of characters that don't need them
+fn wire_name<'a>(id: &Cow<'a, str>) -> Result<&'a str> {
+    match id {
+        Cow::Borrowed(id) if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Ok(id)
+        }
+        Cow::Borrowed(_) => bail!("{id:?} is not a wire name"),
+        Cow::Owned(_) => bail!("{id:?}: escaped wire names are not supported"),
+    }
+}
+
+/// A random addition the circuit got wrong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mismatch {
+    pub x: u64,
+    pub y: u64,
+    pub expected: u64,
+    pub actual: u64,
+    /// lowest bit where `actual` differs from `expected`
+    pub lowest_bit: u32,
+}
+
+/// Feed `trials` random x/y pairs through the circuit and compare the z output against
+/// `x + y`; returns the failures. Errors if the circuit can't be evaluated, e.g. after
+/// a swap introduced a cycle.
+pub fn verify_adder(circuit: &Circuit, trials: usize, rng_seed: u64) -> Result<Vec<Mismatch>> {
+    let xs = circuit.input_wires('x')?;
+    let ys = circuit.input_wires('y')?;
+    let bits = xs.iter().chain(&ys).map(|(_, i)| i + 1).max().unwrap_or(0);
+    if bits > 63 {
+        bail!("{bits}-bit inputs don't fit in u64 addition");
+    }
+    let mask = (1_u64 << bits) - 1;
+
+    let mut rng = common::rng::seeded(rng_seed);
+    let mut mismatches = vec![];
+    for _ in 0..trials {
+        let x = rng.next_u64() & mask;
+        let y = rng.next_u64() & mask;
+        let inputs: FxHashMap<_, _> = xs
+            .iter()
+            .map(|&(id, i)| (id, Some(x >> i & 1 == 1)))
+            .chain(ys.iter().map(|&(id, i)| (id, Some(y >> i & 1 == 1))))
+            .collect();
+
+        let expected = x + y;
+        let actual = circuit.evaluate(&inputs)?;
+        if actual != expected {
+            mismatches.push(Mismatch {
+                x,
+                y,
+                expected,
+                actual,
+                lowest_bit: (actual ^ expected).trailing_zeros(),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// Fitness for the swap search: higher is better, and `None` for the lowest bit means
+/// there were no mismatches
+fn adder_fitness(mismatches: &[Mismatch]) -> (u32, Reverse<usize>) {
+    let lowest = mismatches.iter().map(|m| m.lowest_bit).min();
+    (lowest.unwrap_or(u32::MAX), Reverse(mismatches.len()))
+}
+
+/// Gates that first feed output bit `bit` or the one above it, so are worth swapping
+/// when `bit` is the lowest failing; sorted, so searches are repeatable
+fn swap_candidates<'a>(circuit: &Circuit<'a>, bit: u32) -> Vec<&'a str> {
+    let mut candidates = circuit.cone(&get_idz(bit as i32));
+    candidates.extend(circuit.cone(&get_idz(bit as i32 + 1)));
+    if bit > 0 {
+        for id in circuit.cone(&get_idz(bit as i32 - 1)) {
+            candidates.remove(id);
+        }
+    }
+    let mut candidates: Vec<_> = candidates.into_iter().collect();
+    candidates.sort_unstable();
+    candidates
+}
+
+/// Greedily search for up to `max_swaps` output swaps that make the circuit add
+/// correctly, using `verify_adder` as the fitness function. Each round tries swapping
+/// pairs of gates that first feed the lowest failing bit or the one above it, and keeps
+/// the swap that pushes the first failure highest.
+pub fn search_swaps<'a>(
+    problem: &Problem<'a>,
+    max_swaps: usize,
+    trials: usize,
+    rng_seed: u64,
+) -> Result<Vec<(&'a str, &'a str)>> {
+    let mut circuit = Circuit::new(problem);
+    let mut swaps = vec![];
+    let mut fitness = adder_fitness(&verify_adder(&circuit, trials, rng_seed)?);
+
+    while fitness.0 != u32::MAX {
+        let bit = fitness.0;
+        if swaps.len() == max_swaps {
+            bail!("still failing at bit {bit} after {max_swaps} swaps: {swaps:?}");
+        }
+
+        let mut best = None;
+        for (a, b) in swap_candidates(&circuit, bit)
+            .into_iter()
+            .tuple_combinations()
+        {
+            let mut trial = circuit.clone();
+            trial.swap_outputs(a, b);
+            // swaps that create cycles are simply not candidates
+            let Ok(mismatches) = verify_adder(&trial, trials, rng_seed) else {
+                continue;
+            };
+            let trial_fitness = adder_fitness(&mismatches);
+            if trial_fitness > best.map_or(fitness, |(f, _)| f) {
+                best = Some((trial_fitness, (a, b)));
+            }
+        }
+
+        let Some((best_fitness, (a, b))) = best else {
+            bail!("no swap improves on failing bit {bit}");
+        };
+        circuit.swap_outputs(a, b);
+        swaps.push((a, b));
+        fitness = best_fitness;
+    }
+    Ok(swaps)
+}
+
+/// Settings for `find_swaps`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeamSearch {
+    /// circuits kept after each round
+    pub width: usize,
+    /// random additions used to score each circuit
+    pub trials: usize,
+    pub rng_seed: u64,
+}
+impl Default for BeamSearch {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            trials: 64,
+            rng_seed: 24,
+        }
+    }
+}
+
+/// Best set of swaps found by `find_swaps`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapSolution<'a> {
+    pub swaps: Vec<(&'a str, &'a str)>,
+    /// lowest output bit still wrong, if any
+    pub failing_bit: Option<u32>,
+    /// trials the circuit still gets wrong
+    pub mismatches: usize,
+}
+
+/// Beam search for up to `k` output swaps that leave the fewest mismatches, for when
+/// the greedy `search_swaps` commits to a wrong swap early. Each round extends every
+/// circuit in the beam by one swap among the gates around its lowest failing bit, then
+/// keeps the `width` best, ranked by how high the first failure is and then by the
+/// number of mismatches. Nothing assumes a particular adder design; the candidates come
+/// from which gates feed which output bits. Stops as soon as a circuit adds correctly,
+/// and otherwise returns the best seen.
+pub fn find_swaps<'a>(
+    problem: &Problem<'a>,
+    k: usize,
+    beam: BeamSearch,
+) -> Result<SwapSolution<'a>> {
+    let fitness_of = |circuit: &Circuit| {
+        verify_adder(circuit, beam.trials, beam.rng_seed).map(|m| adder_fitness(&m))
+    };
+
+    let circuit = Circuit::new(problem);
+    let start = fitness_of(&circuit)?;
+    let mut best = (start, vec![]);
+    let mut frontier = vec![(start, vec![], circuit)];
+    let mut seen = FxHashSet::default();
+    for _ in 0..k {
+        if best.0 .0 == u32::MAX {
+            break;
+        }
+        let mut next = vec![];
+        for (fitness, swaps, circuit) in &frontier {
+            for (a, b) in swap_candidates(circuit, fitness.0)
+                .into_iter()
+                .tuple_combinations()
+            {
+                let mut trial_swaps: Vec<(&str, &str)> = swaps.clone();
+                trial_swaps.push((a, b));
+                trial_swaps.sort_unstable();
+                if !seen.insert(trial_swaps.clone()) {
+                    continue;
+                }
+                let mut trial = circuit.clone();
+                trial.swap_outputs(a, b);
+                // swaps that create cycles are simply not candidates
+                let Ok(trial_fitness) = fitness_of(&trial) else {
+                    continue;
+                };
+                next.push((trial_fitness, trial_swaps, trial));
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        next.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        next.truncate(beam.width);
+        if next[0].0 > best.0 {
+            best = (next[0].0, next[0].1.clone());
+        }
+        frontier = next;
+    }
+
+    let ((bit, Reverse(mismatches)), swaps) = best;
+    Ok(SwapSolution {
+        swaps,
+        failing_bit: (bit != u32::MAX).then_some(bit),
+        mismatches,
+    })
+}
+
+/// Part 2 answer format: the wires of every swap, sorted and joined with commas
+pub fn swapped_wires(swaps: &[(&str, &str)]) -> String {
+    let mut wires: Vec<_> = swaps.iter().flat_map(|s| [s.0, s.1]).collect();
+    wires.sort();
+    wires.join(",")
+}
+
+fn precendents_for<'a>(problem: &'a Problem, id: &'a str, found_ids: &mut FxHashSet<&'a str>) {
+    if let Some(calc) = problem.calculated.get(id) {
+        let (_, a, b) = *calc;
+        if found_ids.insert(a) {
+            precendents_for(problem, a, found_ids);
+        }
+        if found_ids.insert(b) {
+            precendents_for(problem, b, found_ids);
+        }
+    }
+}
+
+fn get_id(label: char, index: i32) -> String {
+    format!("{label}{index:02}")
+}
+
+fn get_idz(index: i32) -> String {
+    get_id('z', index)
+}
+
+fn get_idx(index: i32) -> String {
+    get_id('x', index)
+}
+
+fn get_idy(index: i32) -> String {
+    get_id('y', index)
+}
+
+fn swap<'a>(
+    mut calcs: FxHashMap<&'a str, Calculation<'a>>,
+    a: &'a str,
+    b: &'a str,
+) -> FxHashMap<&'a str, Calculation<'a>> {
+    let temp = (Operation::And, "", "");
+    let calc_a = *calcs.entry(a).or_insert(temp);
+    let calc_b = *calcs.entry(b).or_insert(temp);
+
+    *calcs.entry(a).or_insert(temp) = calc_b;
+    *calcs.entry(b).or_insert(temp) = calc_a;
+
+    calcs
+}
+
+/// Part 2 answer from `search_swaps` rather than the hand-found swaps
+pub fn part2_search(problem: &Problem) -> Result<String> {
+    let swaps = search_swaps(problem, 4, 64, 24)?;
+    Ok(swapped_wires(&swaps))
+}
+
+pub fn part2(problem: &Problem) -> Result<String> {
+    let Problem {
+        mut calculated,
+        initial_values,
+    } = problem.clone();
+
+    // these were found by inspection and running the tests to find
+    // where each first bit went wrong; could probably automate this
+    // process by trying to swap all the recently-added dependencies that
+    // we're printing out for each new bit.
+    let swaps = [
+        ("z17", "cmv"), // swap 1 - this fixes bit 17
+        ("z23", "rmj"), // swap 2 - this fixes bit 22
+        ("z30", "rdg"), // swap 3 - this fixes bit 30
+        ("btb", "mwp"), // swap 4 - this fixes bit 38
+    ];
+
+    for (a, b) in swaps {
+        calculated = swap(calculated, a, b);
+    }
+
+    let problem = Problem {
+        calculated,
+        initial_values,
+    };
+    let errors = tests(&problem)?;
+    println!("remaining errors: {errors}");
+
+    let mismatches = verify_adder(&Circuit::new(&problem), 1000, 24)?;
+    if let Some(m) = mismatches.first() {
+        bail!(
+            "swapped circuit still fails {} random additions, e.g. {m:?}",
+            mismatches.len()
+        );
+    }
+
+    let mut swaps_flat: Vec<_> = swaps.iter().flat_map(|s| [s.0, s.1]).collect();
+    swaps_flat.sort();
+
+    Ok(swaps_flat.join(","))
+}
+
+fn tests(problem: &Problem) -> Result<usize> {
+    let mut error_count = 0;
+
+    // find largest bit
+    let msb = (0..63)
+        .rfind(|b| problem.calculated.contains_key(get_idz(*b).as_str()))
+        .unwrap();
+    println!("msb {msb}");
+
+    // trace precendents for each bit
+    let idzs: Vec<String> = (0..=msb).map(get_idz).collect();
+    let mut prev_preceding = FxHashSet::default();
+    for i in 0..=msb {
+        let id = &idzs[i as usize];
+
+        let mut preceding = FxHashSet::default();
+        precendents_for(problem, id.as_str(), &mut preceding);
+
+        let added = preceding.difference(&prev_preceding);
+        println!("{id} depends on added {added:?}");
+
+        // checks
+        for u in 0..=i {
+            if i == msb {
+                continue;
+            }
+            let idx = get_idx(u);
+            let idy = get_idy(u);
+            if !preceding.contains(idx.as_str()) {
+                println!("{id} missing dependence on {idx}");
+                error_count += 1;
+            }
+            if !preceding.contains(idy.as_str()) {
+                println!("{id} missing dependence on {idy}");
+                error_count += 1;
+            }
+        }
+
+        for u in i + 1..=msb {
+            let idx = get_idx(u);
+            let idy = get_idy(u);
+            if preceding.contains(idx.as_str()) {
+                println!("{id} should not depend {idx}");
+                error_count += 1;
+            }
+            if preceding.contains(idy.as_str()) {
+                println!("{id} should not depend {idy}");
+                error_count += 1;
+            }
+        }
+
+        prev_preceding = preceding;
+    }
+
+    // bit tests
+    let inputs = [(false, false), (false, true), (true, false), (true, true)];
+    for i in 0..=(msb - 1) {
+        for (x, y) in inputs {
+            // setup registers
+            let mut registers = problem.initial_values.clone();
+            for j in 0..=(msb - 1) {
+                *registers.get_mut(get_idx(j).as_str()).unwrap() = Some(false);
+                *registers.get_mut(get_idy(j).as_str()).unwrap() = Some(false);
+            }
+            *registers.get_mut(get_idx(i).as_str()).unwrap() = Some(x);
+            *registers.get_mut(get_idy(i).as_str()).unwrap() = Some(y);
+
+            // expected z and carry bit
+            let expected_z = (x ^ y) as u64;
+            let expected_carry = (x && y) as u64;
+            let expected_res = (expected_z << i) | (expected_carry << (i + 1));
+
+            // run calculation and check the results
+            let remaining_calculations = problem.calculated.clone();
+            let (result, registers_post) = calculate(registers, remaining_calculations).unwrap();
+
+            let expected_z = registers_post
+                .get(get_idx(i).as_str())
+                .copied()
+                .flatten()
+                .unwrap() as u64;
+            let expected_carry = registers_post
+                .get(get_idx(i).as_str())
+                .copied()
+                .flatten()
+                .unwrap() as u64;
+
+            if expected_res != result {
+                let x = x as u64;
+                let y = y as u64;
+                println!("Unexpected result for bit {i} - input {x},{y} got {expected_z} carry {expected_carry}; totals got {result} expected {expected_res}");
+                error_count += 1;
+            }
+        }
+    }
+    Ok(error_count)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2_search(&problem)?))
+}
+
+/// Part 1 for the example, which isn't an adder, so has no part 2
+pub fn solve_example(input: &str) -> Result<(String, Option<String>)> {
+    Ok((part1(&parse_input(input)?)?.to_string(), None))
+}
+
+common::aoc_day!(24, "Crossed Wires", example: solve_example);
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    x00: 1
+    x01: 0
+    x02: 1
+    x03: 1
+    x04: 0
+    y00: 1
+    y01: 1
+    y02: 1
+    y03: 1
+    y04: 1
+
+    ntg XOR fgs -> mjb
+    y02 OR x01 -> tnw
+    kwq OR kpj -> z05
+    x00 OR x03 -> fst
+    tgd XOR rvg -> z01
+    vdt OR tnw -> bfw
+    bfw AND frj -> z10
+    ffh OR nrd -> bqk
+    y00 AND y03 -> djm
+    y03 OR y00 -> psh
+    bqk OR frj -> z08
+    tnw OR fst -> frj
+    gnj AND tgd -> z11
+    bfw XOR mjb -> z00
+    x03 OR x00 -> vdt
+    gnj AND wpb -> z02
+    x04 AND y00 -> kjc
+    djm OR pbm -> qhw
+    nrd AND vdt -> hwm
+    kjc AND fst -> rvg
+    y04 OR y02 -> fgs
+    y01 AND x02 -> pbm
+    ntg OR kjc -> kwq
+    psh XOR fgs -> tgd
+    qhw XOR tgd -> z09
+    pbm OR djm -> kpj
+    x03 XOR y03 -> ffh
+    x00 XOR y04 -> ntg
+    bfw OR bqk -> z06
+    nrd XOR fgs -> wpb
+    frj XOR qhw -> z04
+    bqk OR frj -> z07
+    y03 OR x01 -> nrd
+    hwm AND bqk -> z03
+    tgd XOR rvg -> z12
+    tnw OR pbm -> gnj
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("2024"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors_have_positions() {
+        let message = |input: &str| format!("{:#}", parse_input(input).unwrap_err());
+        assert_eq!(message("x00: 1\nx01 1\n"), "line 2: expected `wire: value`");
+        assert_eq!(
+            message("x00: 1\nx01: 2\n"),
+            "line 2, column 6: Unexpected value 2"
+        );
+        assert_eq!(
+            message("x00: 1\n\nx00 AND x00 -> z00\nx00 NAND x00 -> z01\n"),
+            "line 4: Unrecognized operation NAND"
+        );
+        assert_eq!(
+            message("x00: 1\n\nx00 AND x00 z00\n"),
+            "line 3: expected `a OP b -> out`"
+        );
+    }
+
+    /// Random circuit over short wire names, some fixed, with random initial values
+    fn random_circuit(rng: &mut common::rng::Rng) -> Circuit<'static> {
+        let names: Vec<&'static str> = (0..rng.range(1, 30))
+            .map(|_| {
+                let len = rng.range(1, 4) as usize;
+                let name: String = (0..len)
+                    .map(|_| *rng.choose(&['a', 'x', 'y', 'z', '0', '7', 'Q']))
+                    .collect();
+                &*name.leak()
+            })
+            .collect();
+        let ops = [Operation::And, Operation::Or, Operation::Xor];
+        let mut circuit = Circuit {
+            gates: FxHashMap::default(),
+            constants: FxHashMap::default(),
+            inputs: FxHashMap::default(),
+        };
+        for name in &names {
+            match rng.index(4) {
+                0 => {
+                    let gate = (*rng.choose(&ops), *rng.choose(&names), *rng.choose(&names));
+                    circuit.gates.insert(name, gate);
+                }
+                1 => _ = circuit.constants.insert(name, rng.one_in(2)),
+                2 => _ = circuit.inputs.insert(name, rng.one_in(2)),
+                _ => {}
+            }
+        }
+        circuit
+    }
+
+    #[test]
+    fn json_round_trip() -> Result<()> {
+        let mut rng = common::rng::seeded(24);
+        for _ in 0..200 {
+            let circuit = random_circuit(&mut rng);
+            let json = circuit.to_json();
+            let loaded = Circuit::from_json(&json)?;
+            assert_eq!(loaded, circuit);
+            assert_eq!(loaded.to_json(), json);
+        }
+
+        let problem = parse_input(EXAMPLE)?;
+        let json = Circuit::new(&problem).to_json();
+        assert!(json.starts_with("{\n  \"version\": 1,\n  \"inputs\": {\n    \"x00\": 1,\n"));
+        assert!(json.contains(
+            "\n    {\"out\": \"bfw\", \"op\": \"OR\", \"a\": \"vdt\", \"b\": \"tnw\"},\n"
+        ));
+        assert!(json.contains("\"constants\": {},\n"));
+        assert_eq!(Circuit::from_json(&json)?.evaluate_initial()?, 2024);
+        Ok(())
+    }
+
+    #[test]
+    fn json_errors() {
+        let message = |json: &str| Circuit::from_json(json).unwrap_err().to_string();
+        let empty = r#"{"version": 1, "inputs": {}, "constants": {}, "gates": []}"#;
+        assert!(Circuit::from_json(empty).is_ok());
+        assert_eq!(
+            message(&empty.replace("1,", "2,")),
+            "expected circuit version 1, not Some(2)"
+        );
+        assert_eq!(
+            message(&empty.replace(r#""constants": {}, "#, "")),
+            "no constants"
+        );
+        assert_eq!(
+            message(&empty.replace(r#""inputs": {}"#, r#""inputs": {"x00": 2}"#)),
+            "inputs: x00 should be 0 or 1"
+        );
+        assert_eq!(
+            message(&empty.replace(r#""inputs": {}"#, r#""inputs": {"x0\u0030": 1}"#)),
+            r#""x00": escaped wire names are not supported"#
+        );
+        assert_eq!(
+            message(&empty.replace(r#""inputs": {}"#, r#""inputs": {"x-0": 1}"#)),
+            r#""x-0" is not a wire name"#
+        );
+        let gate = r#"{"out": "z00", "op": "AND", "a": "x00", "b": "y00"}"#;
+        let gates = |gates: &str| empty.replace("[]", &format!("[{gates}]"));
+        assert!(Circuit::from_json(&gates(gate)).is_ok());
+        assert_eq!(
+            message(&gates(&format!("{gate}, {gate}"))),
+            "gate 1: z00 is driven by another gate"
+        );
+        assert_eq!(
+            message(&gates(&gate.replace("AND", "NAND"))),
+            "Unrecognized operation NAND"
+        );
+        assert_eq!(
+            message(&gates(&gate.replace("\"b\"", "\"c\""))),
+            "gate 0: no b"
+        );
+        assert!(message("{").starts_with("byte 1"));
+    }
+
+    #[test]
+    fn part1_small_correct() -> Result<()> {
+        let problem = parse_input(SMALL_EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 2024);
+        Ok(())
+    }
+
+    #[test]
+    fn simplify_preserves_result() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let mut circuit = Circuit::new(&problem);
+        circuit.simplify();
+        assert_eq!(circuit.evaluate(&problem.initial_values)?, 2024);
+        Ok(())
+    }
+
+    #[test]
+    fn simplify_merges_and_removes_dead() -> Result<()> {
+        let problem = parse_input(REDUNDANT_EXAMPLE)?;
+        let mut circuit = Circuit::new(&problem);
+        let stats = circuit.simplify();
+        assert_eq!(
+            stats,
+            SimplifyStats {
+                constants_folded: 0,
+                dead_removed: 1,
+                duplicates_merged: 1,
+            }
+        );
+        assert_eq!(circuit.gate_count(), 2);
+        assert_eq!(circuit.evaluate(&problem.initial_values)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn simplify_folds_constants() -> Result<()> {
+        let problem = parse_input(REDUNDANT_EXAMPLE)?;
+        let mut circuit = Circuit::new(&problem);
+        circuit.fix("y00", false);
+        let stats = circuit.simplify();
+        // both ANDs and then the OR fold; the XOR is only half known, and is dead
+        assert_eq!(
+            stats,
+            SimplifyStats {
+                constants_folded: 3,
+                dead_removed: 1,
+                duplicates_merged: 0,
+            }
+        );
+        assert_eq!(circuit.gate_count(), 0);
+        assert_eq!(circuit.evaluate(&problem.initial_values)?, 0);
+        Ok(())
+    }
+
+    /// Ripple-carry adder over `bits` bits, with all inputs zero
+    fn ripple_adder(bits: usize) -> String {
+        let mut text = String::new();
+        for label in ['x', 'y'] {
+            for i in 0..bits {
+                text += &format!("{label}{i:02}: 0\n");
+            }
+        }
+        text += "\n";
+        for i in 0..bits {
+            text += &format!("x{i:02} XOR y{i:02} -> s{i:02}\n");
+            text += &format!("x{i:02} AND y{i:02} -> a{i:02}\n");
+        }
+        text += "s00 OR s00 -> z00\n";
+        text += "a00 OR a00 -> c00\n";
+        for i in 1..bits {
+            let carry_out = if i + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            };
+            text += &format!("s{i:02} XOR c{:02} -> z{i:02}\n", i - 1);
+            text += &format!("s{i:02} AND c{:02} -> t{i:02}\n", i - 1);
+            text += &format!("a{i:02} OR t{i:02} -> {carry_out}\n");
+        }
+        text
+    }
+
+    #[test]
+    fn verify_correct_adder() -> Result<()> {
+        let text = ripple_adder(12);
+        let problem = parse_input(&text)?;
+        assert_eq!(verify_adder(&Circuit::new(&problem), 200, 1)?, vec![]);
+        Ok(())
+    }
+
+    #[test]
+    fn verify_swapped_adder() -> Result<()> {
+        let text = ripple_adder(12);
+        let problem = parse_input(&text)?;
+        let mut circuit = Circuit::new(&problem);
+        circuit.swap_outputs("z05", "t05");
+        let mismatches = verify_adder(&circuit, 200, 1)?;
+        assert!(!mismatches.is_empty());
+        assert!(mismatches.iter().all(|m| m.lowest_bit >= 5));
+        assert!(mismatches.iter().any(|m| m.lowest_bit == 5));
+
+        // a swap creating a cycle can't be evaluated at all
+        circuit.swap_outputs("s03", "c03");
+        assert!(verify_adder(&circuit, 10, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn search_finds_swaps() -> Result<()> {
+        let text = ripple_adder(16)
+            .replace("-> z05", "-> tmp")
+            .replace("-> t05", "-> z05")
+            .replace("-> tmp", "-> t05")
+            .replace("-> s10", "-> tmp")
+            .replace("-> a10", "-> s10")
+            .replace("-> tmp", "-> a10");
+        let problem = parse_input(&text)?;
+        let swaps = search_swaps(&problem, 4, 64, 2)?;
+        assert_eq!(swaps, vec![("t05", "z05"), ("a10", "s10")]);
+        assert!(search_swaps(&problem, 1, 64, 2).is_err());
+        Ok(())
+    }
+
+    /// Adder with the carry as the majority of x, y and the carry in, rather than the
+    /// usual ripple-carry gates
+    fn majority_adder(bits: usize) -> String {
+        let mut text = String::new();
+        for label in ['x', 'y'] {
+            for i in 0..bits {
+                text += &format!("{label}{i:02}: 0\n");
+            }
+        }
+        text += "\n";
+        text += "x00 XOR y00 -> z00\n";
+        text += "x00 AND y00 -> c00\n";
+        for i in 1..bits {
+            let c = format!("c{:02}", i - 1);
+            let carry_out = if i + 1 == bits {
+                format!("z{bits:02}")
+            } else {
+                format!("c{i:02}")
+            };
+            text += &format!("x{i:02} XOR y{i:02} -> p{i:02}\n");
+            text += &format!("p{i:02} XOR {c} -> z{i:02}\n");
+            text += &format!("x{i:02} AND y{i:02} -> g{i:02}\n");
+            text += &format!("x{i:02} AND {c} -> h{i:02}\n");
+            text += &format!("y{i:02} AND {c} -> k{i:02}\n");
+            text += &format!("g{i:02} OR h{i:02} -> m{i:02}\n");
+            text += &format!("m{i:02} OR k{i:02} -> {carry_out}\n");
+        }
+        text
+    }
+
+    fn with_swaps(text: &str, swaps: &[(&str, &str)]) -> String {
+        let mut text = text.to_string();
+        for (a, b) in swaps {
+            text = text
+                .replace(&format!("-> {a}\n"), "-> tmp\n")
+                .replace(&format!("-> {b}\n"), &format!("-> {a}\n"))
+                .replace("-> tmp\n", &format!("-> {b}\n"));
+        }
+        text
+    }
+
+    #[test]
+    fn beam_search_finds_swaps() -> Result<()> {
+        let text = with_swaps(&ripple_adder(16), &[("z05", "t05"), ("s10", "a10")]);
+        let problem = parse_input(&text)?;
+        let found = find_swaps(&problem, 4, BeamSearch::default())?;
+        assert_eq!(found.swaps, vec![("a10", "s10"), ("t05", "z05")]);
+        assert_eq!((found.failing_bit, found.mismatches), (None, 0));
+        assert_eq!(swapped_wires(&found.swaps), "a10,s10,t05,z05");
+
+        // one swap short: the best partial fix, still failing higher up
+        let partial = find_swaps(&problem, 1, BeamSearch::default())?;
+        assert_eq!(partial.swaps, vec![("t05", "z05")]);
+        assert_eq!(partial.failing_bit, Some(10));
+        assert!(partial.mismatches > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn beam_search_on_other_adders() -> Result<()> {
+        let adder = majority_adder(10);
+        let problem = parse_input(&adder)?;
+        assert_eq!(verify_adder(&Circuit::new(&problem), 200, 1)?, vec![]);
+
+        let swapped = [("z03", "m03"), ("h07", "p07"), ("z09", "k09")];
+        let text = with_swaps(&adder, &swapped);
+        let problem = parse_input(&text)?;
+        let found = find_swaps(&problem, 3, BeamSearch::default())?;
+        assert_eq!(found.mismatches, 0);
+        let mut expected: Vec<_> = swapped.iter().map(|&(a, b)| (a.min(b), a.max(b))).collect();
+        expected.sort();
+        assert_eq!(found.swaps, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn truth_tables() -> Result<()> {
+        let text = ripple_adder(4);
+        let problem = parse_input(&text)?;
+        let mut circuit = Circuit::new(&problem);
+        assert_eq!(
+            circuit.truth_table("s01", &["x01", "y01"])?,
+            [false, true, true, false]
+        );
+        // with the other inputs at 0, z02 = x02 ^ y02 ^ (x01 & y01)
+        let table = circuit.truth_table("z02", &["x01", "y01", "x02"])?;
+        let expected: Vec<_> = (0..8)
+            .map(|i: usize| (i & 1 == 1 && i & 2 == 2) ^ (i & 4 == 4))
+            .collect();
+        assert_eq!(table, expected);
+        assert_eq!(circuit.truth_table("z04", &[])?, [false]);
+        assert_eq!(circuit.truth_table("x03", &["x03"])?, [false, true]);
+
+        assert!(circuit.truth_table("nope", &["x01"]).is_err());
+        assert!(circuit.truth_table("z01", &["s01"]).is_err());
+        assert!(circuit.truth_table("z01", &["q01"]).is_err());
+
+        // a cycle only matters to wires it feeds
+        circuit.swap_outputs("c01", "t02");
+        assert!(circuit.truth_table("z02", &["x02"]).is_err());
+        assert_eq!(
+            circuit.truth_table("z01", &["x01", "x00", "y00"])?,
+            [false, true, false, true, false, true, true, false]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn roles_from_structure() -> Result<()> {
+        let text = ripple_adder(12);
+        let problem = parse_input(&text)?;
+        let roles = Circuit::new(&problem).annotate_roles()?;
+        assert_eq!(roles["s03"], Role::Xor1(3));
+        assert_eq!(roles["a00"], Role::And1(0));
+        assert_eq!(roles["z07"], Role::Sum(7));
+        assert_eq!(roles["t07"], Role::And2(7));
+        assert_eq!(roles["c00"], Role::Carry(0));
+        assert_eq!(roles["z12"], Role::Carry(11));
+        assert_eq!(roles["c10"].to_string(), "carry_10");
+        // z00 just passes s00 on, so has no role in the adder
+        assert_eq!(roles.len(), 12 * 5 - 2);
+        assert!(!roles.contains_key("z00"));
+
+        // swapped outputs take the role of the gate now driving them
+        let mut circuit = Circuit::new(&problem);
+        circuit.swap_outputs("z05", "t05");
+        circuit.swap_outputs("s09", "a09");
+        let roles = circuit.annotate_roles()?;
+        assert_eq!(roles["t05"], Role::Sum(5));
+        assert_eq!(roles["z05"], Role::And2(5));
+        assert_eq!(roles["a09"], Role::Xor1(9));
+        assert_eq!(roles["s09"], Role::And1(9));
+        assert_eq!(roles["z09"], Role::Sum(9));
+        assert_eq!(roles["z12"], Role::Carry(11));
+        Ok(())
+    }
+
+    const REDUNDANT_EXAMPLE: &str = indoc! {"
+        x00: 1
+        y00: 1
+
+        x00 AND y00 -> aaa
+        y00 AND x00 -> bbb
+        aaa OR bbb -> z00
+        x00 XOR y00 -> ccc
+    "};
+
+    const SMALL_EXAMPLE: &str = indoc! {"
+        x00: 1
+        x01: 1
+        x02: 1
+        y00: 0
+        y01: 1
+        y02: 0
+
+        x00 AND y00 -> z00
+        x01 XOR y01 -> z01
+        x02 OR y02 -> z02
+    "};
+}
@@ -0,0 +1,405 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use common::{
+    answers::AnswerLog,
+    cartesian::matrix_from_lines,
+    grid::{flip_v, rotate_cw},
+};
+use nalgebra::DMatrix;
+use rayon::prelude::*;
+
+/// Pin heights of a lock, or of a key flipped over to face the same way as a lock
+type Heights = Vec<usize>;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    locks: Vec<Heights>,
+    keys: Vec<Heights>,
+    /// room for pins between the top and bottom rows
+    space: usize,
+}
+
+/// Heights of the pins hanging down from the top row
+fn heights(schematic: &DMatrix<char>) -> Heights {
+    // rotated, each pin is a row ending in the top row
+    rotate_cw(schematic)
+        .row_iter()
+        .map(|row| row.iter().filter(|ch| **ch == '#').count() - 1)
+        .collect()
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut locks = vec![];
+    let mut keys = vec![];
+    let mut space = None;
+
+    let lines: Vec<_> = input.lines().collect();
+    for schematic in lines.split(|l| l.is_empty()) {
+        let map = matrix_from_lines(schematic, |ch| match ch {
+            '#' | '.' => Ok(ch),
+            _ => bail!("Unexpected character {ch}"),
+        })?;
+        let Some(pin_space) = map.nrows().checked_sub(2) else {
+            bail!("schematic too short for a lock or key");
+        };
+        if *space.get_or_insert(pin_space) != pin_space {
+            bail!("schematics differ in height");
+        }
+
+        // locks are filled along the top row, keys along the bottom
+        if map.row(0).iter().all(|ch| *ch == '#') {
+            locks.push(heights(&map));
+        } else if map.row(map.nrows() - 1).iter().all(|ch| *ch == '#') {
+            keys.push(heights(&flip_v(&map)));
+        } else {
+            bail!("schematic is neither a lock nor a key");
+        }
+    }
+
+    Ok(Problem {
+        locks,
+        keys,
+        space: space.unwrap_or_default(),
+    })
+}
+
+fn fits(lock: &Heights, key: &Heights, space: usize) -> bool {
+    lock.len() == key.len() && lock.iter().zip(key).all(|(l, k)| l + k <= space)
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    let mut count = 0;
+    for lock in &problem.locks {
+        for key in &problem.keys {
+            if fits(lock, key, problem.space) {
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+/// Guard bit of each nibble lane
+const GUARD: u64 = 0x8888_8888_8888_8888;
+
+/// Heights packed a nibble per pin, first pin lowest
+fn pack(heights: &Heights) -> u64 {
+    heights
+        .iter()
+        .rev()
+        .fold(0, |packed, h| (packed << 4) | *h as u64)
+}
+
+/// Every lock and key as one `u64` each, with the space left above each lock pin, so
+/// a key fits when no lane of it is higher than the lock's. `None` unless the
+/// schematics all have the same width, at most 16 pins, and space for at most 7, so
+/// each lane has a spare top bit.
+fn packed(problem: &Problem) -> Option<(Vec<u64>, Vec<u64>, u64)> {
+    let pins = problem.locks.first().or(problem.keys.first())?.len();
+    let same_width = problem
+        .locks
+        .iter()
+        .chain(&problem.keys)
+        .all(|h| h.len() == pins);
+    if !same_width || pins > 16 || problem.space > 7 {
+        return None;
+    }
+
+    let room = problem.locks.iter().map(|lock| {
+        let room = lock.iter().map(|l| problem.space - l).collect();
+        pack(&room)
+    });
+    let guard = GUARD & (u64::MAX >> (64 - 4 * pins.max(1)));
+    Some((
+        room.collect(),
+        problem.keys.iter().map(pack).collect(),
+        guard,
+    ))
+}
+
+/// As `part1`, comparing all of a key's pins with a lock's at once. Setting each lane's
+/// guard bit before subtracting means no lane borrows from the next, and the guard
+/// survives only where the lock has room. Keys are checked in parallel, and schematics
+/// that won't pack are compared pin by pin instead.
+pub fn part1_packed(problem: &Problem) -> Result<usize> {
+    let Some((room, keys, guard)) = packed(problem) else {
+        let count = |key| {
+            let fitting = problem
+                .locks
+                .iter()
+                .filter(|lock| fits(lock, key, problem.space));
+            fitting.count()
+        };
+        return Ok(problem.keys.par_iter().map(count).sum());
+    };
+    let count = |&key: &u64| {
+        let fitting = room
+            .iter()
+            .filter(|&&r| ((r | guard) - key) & guard == guard);
+        fitting.count()
+    };
+    Ok(keys.par_iter().map(count).sum())
+}
+
+/// As `part1`, with a bitset over the locks for each pin and key height, of the locks
+/// with room for it; the locks a key fits are the intersection of its pins' sets
+pub fn part1_bitset(problem: &Problem) -> Result<usize> {
+    let Some(pins) = problem.locks.first().map(Vec::len) else {
+        return Ok(0);
+    };
+    let words = problem.locks.len().div_ceil(64);
+    let mut room = vec![vec![vec![0_u64; words]; problem.space + 1]; pins];
+    for (i, lock) in problem.locks.iter().enumerate() {
+        if lock.len() != pins {
+            continue;
+        }
+        for (pin, l) in lock.iter().enumerate() {
+            for set in &mut room[pin][..=problem.space - l] {
+                set[i / 64] |= 1 << (i % 64);
+            }
+        }
+    }
+
+    let count = |key: &Heights| {
+        if key.len() != pins {
+            return 0;
+        }
+        let mut fit = room[0][key[0]].clone();
+        for (pin, k) in key.iter().enumerate().skip(1) {
+            for (f, r) in fit.iter_mut().zip(&room[pin][*k]) {
+                *f &= r;
+            }
+        }
+        fit.iter().map(|f| f.count_ones() as usize).sum()
+    };
+    Ok(problem.keys.par_iter().map(count).sum())
+}
+
+/// Random schematics in the input format, `locks` locks then `keys` keys, each `pins`
+/// wide with heights up to `space`. Reproducible from `seed`.
+pub fn generate_schematics(
+    locks: usize,
+    keys: usize,
+    pins: usize,
+    space: usize,
+    seed: u64,
+) -> String {
+    let mut rng = common::rng::seeded(seed);
+    let mut schematics = vec![];
+    for is_lock in std::iter::repeat_n(true, locks).chain(std::iter::repeat_n(false, keys)) {
+        let heights: Vec<_> = (0..pins).map(|_| rng.range(0, space as i64)).collect();
+        // rows counted down from the top for locks, up from the bottom for keys
+        let mut rows: Vec<String> = (0..space + 2)
+            .map(|row| {
+                let row = row as i64;
+                heights
+                    .iter()
+                    .map(|h| if row <= *h { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        if !is_lock {
+            rows.reverse();
+        }
+        schematics.push(rows.join("\n") + "\n");
+    }
+    schematics.join("\n")
+}
+
+/// Every part but the last one, which is awarded once all of these are done
+pub fn other_parts() -> impl Iterator<Item = (u8, u8)> {
+    (1..=24)
+        .flat_map(|day| [(day, 1), (day, 2)])
+        .chain(std::iter::once((25, 1)))
+}
+
+/// How far through the calendar the answer log gets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    missing: Vec<(u8, u8)>,
+}
+impl Completion {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Parts without a recorded answer, as `(day, part)`
+    pub fn missing(&self) -> &[(u8, u8)] {
+        &self.missing
+    }
+
+    pub fn stars(&self) -> usize {
+        let recorded = other_parts().count() - self.missing.len();
+        recorded + usize::from(self.is_complete())
+    }
+}
+impl Display for Completion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} stars", self.stars())?;
+        if !self.is_complete() {
+            let missing: Vec<_> = self
+                .missing
+                .iter()
+                .map(|(day, part)| format!("{day}.{part}"))
+                .collect();
+            write!(f, "; missing {}", missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+// there's no part 2 puzzle on the last day: the star comes for free once the
+// other 49 parts are done, so check the log for those
+pub fn part2(log: &AnswerLog) -> Result<Completion> {
+    Ok(Completion {
+        missing: log.missing(other_parts()),
+    })
+}
+
+/// Answers to both parts for the puzzle input `input`, checking completion against
+/// `log`
+pub fn solve_with(input: &str, log: &AnswerLog) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(log)?.to_string()))
+}
+
+/// Answers to both parts for the puzzle input `input`; with no answer log, part 2
+/// lists every other part as missing
+pub fn solve(input: &str) -> Result<(String, String)> {
+    solve_with(input, &AnswerLog::default())
+}
+
+common::aoc_day!(25, "Code Chronicle");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    #####
+    .####
+    .####
+    .####
+    .#.#.
+    .#...
+    .....
+
+    #####
+    ##.##
+    .#.##
+    ...##
+    ...#.
+    ...#.
+    .....
+
+    .....
+    #....
+    #....
+    #...#
+    #.#.#
+    #.###
+    #####
+
+    .....
+    .....
+    #.#..
+    ###..
+    ###.#
+    ###.#
+    #####
+
+    .....
+    .....
+    .....
+    #....
+    #.#..
+    #.#.#
+    #####
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("3"), None];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(problem.locks, [vec![0, 5, 3, 4, 3], vec![1, 2, 0, 5, 3]]);
+        assert_eq!(
+            problem.keys,
+            [
+                vec![5, 0, 2, 1, 3],
+                vec![4, 3, 4, 0, 2],
+                vec![3, 0, 2, 0, 1]
+            ]
+        );
+        assert_eq!(problem.space, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn fast_counts_match() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(part1_packed(&problem)?, 3);
+        assert_eq!(part1_bitset(&problem)?, 3);
+
+        // packed, with a full 16 pins, and compared pin by pin with too much space
+        for (pins, space) in [(5, 5), (16, 7), (3, 9)] {
+            let input = generate_schematics(150, 90, pins, space, pins as u64);
+            let problem = parse_input(&input)?;
+            assert_eq!((problem.locks.len(), problem.keys.len()), (150, 90));
+            assert_eq!(problem.space, space);
+            assert_eq!(packed(&problem).is_some(), space <= 7);
+
+            let expected = part1(&problem)?;
+            assert!(expected > 0);
+            assert_eq!(part1_packed(&problem)?, expected);
+            assert_eq!(part1_bitset(&problem)?, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn generated_schematics_parse() -> Result<()> {
+        let input = generate_schematics(1, 1, 4, 3, 0);
+        assert_eq!(input.lines().count(), 11);
+        let problem = parse_input(&input)?;
+        assert!(problem.locks[0]
+            .iter()
+            .chain(&problem.keys[0])
+            .all(|h| *h <= 3));
+        assert_eq!(pack(&vec![1, 2, 3]), 0x321);
+        Ok(())
+    }
+
+    #[test]
+    fn completion_reports_missing() -> Result<()> {
+        let mut log = AnswerLog::default();
+        for (day, part) in other_parts() {
+            log.insert(day, part, 1);
+        }
+        let done = part2(&log)?;
+        assert!(done.is_complete());
+        assert_eq!(done.stars(), 50);
+        assert_eq!(done.to_string(), "50 stars");
+
+        let log = AnswerLog::parse(
+            &log.to_string()
+                .replace("22 2 1\n", "")
+                .replace("25 1 1\n", ""),
+        )?;
+        let partial = part2(&log)?;
+        assert_eq!(partial.missing(), [(22, 2), (25, 1)]);
+        assert_eq!(partial.to_string(), "47 stars; missing 22.2, 25.1");
+
+        assert_eq!(part2(&AnswerLog::default())?.missing().len(), 49);
+        Ok(())
+    }
+}
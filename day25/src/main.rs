@@ -1,3 +1,28 @@
-fn main() {
-    println!("Hello, world!");
+use common::{answers::AnswerLog, timing::TimingReport};
+
+use day25::{
+    generate_schematics, parse_input, part1, part1_bitset, part1_packed, part2, DAY, EXAMPLE,
+    EXAMPLE_ANSWERS,
+};
+
+fn main() -> anyhow::Result<()> {
+    let text = common::read_input("input1.txt", EXAMPLE)?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
+
+    let problem = timing.parse(|| parse_input(&text))?;
+    timing.part("1", || part1(&problem))?;
+
+    // part 2 checks the other days' recorded answers, `--answers FILE` or answers.txt
+    let answers = common::arg_value("--answers").unwrap_or_else(|| "answers.txt".to_string());
+    let log = AnswerLog::load(&answers)?;
+    timing.part("2", || part2(&log))?;
+
+    if std::env::args().any(|a| a == "--bench") {
+        let large = parse_input(&generate_schematics(20_000, 20_000, 5, 5, 25))?;
+        timing.part("1 (naive, 20k each)", || part1(&large))?;
+        timing.part("1 (packed, 20k each)", || part1_packed(&large))?;
+        timing.part("1 (bitset, 20k each)", || part1_bitset(&large))?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,272 @@
+use std::io::BufRead;
+
+use anyhow::bail;
+use common::error::AocContext;
+use regex::Regex;
+
+/// Arguments are 1-3 digit numbers
+const MAX_ARG_DIGITS: usize = 3;
+
+/// Add the product to the running sum, failing rather than overflowing
+fn accumulate(sum: i64, l: i64, r: i64) -> anyhow::Result<i64> {
+    match l
+        .checked_mul(r)
+        .and_then(|product| sum.checked_add(product))
+    {
+        Some(sum) => Ok(sum),
+        None => bail!("overflow adding mul({l},{r}) to {sum}"),
+    }
+}
+
+pub fn part1(input: &str) -> anyhow::Result<i64> {
+    let re = Regex::new(r#"mul\(([0-9]{1,3}),([0-9]{1,3})\)"#).unwrap();
+
+    let mut sum = 0;
+    for cap in re.captures_iter(input) {
+        let (_, [l, r]) = cap.extract();
+        sum = accumulate(sum, l.parse()?, r.parse()?)?;
+    }
+
+    Ok(sum)
+}
+
+pub fn part2(input: &str) -> anyhow::Result<i64> {
+    let re = Regex::new(r#"mul\(([0-9]{1,3}),([0-9]{1,3})\)|don't|do"#).unwrap();
+
+    let mut enabled = true;
+    let mut sum = 0;
+    for cap in re.captures_iter(input) {
+        match &cap[0] {
+            "do" => enabled = true,
+            "don't" => enabled = false,
+            _ => {
+                if enabled {
+                    sum = accumulate(sum, cap[1].parse()?, cap[2].parse()?)?;
+                }
+            }
+        }
+    }
+
+    Ok(sum)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+/// Progress through a possible instruction; digit states hold the value and digit count so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum ScanState {
+    #[default]
+    Start,
+    M,
+    Mu,
+    Mul,
+    Left(i64, usize),
+    Right(i64, i64, usize),
+    D,
+    Do,
+    Don,
+    DonQuote,
+}
+
+/// Byte-at-a-time scanner matching the same instructions as the regex in `part2`,
+/// so input can be fed in chunks of any size with instructions straddling chunks.
+#[derive(Debug, Default)]
+struct Scanner {
+    state: ScanState,
+}
+impl Scanner {
+    fn start_state(b: u8) -> ScanState {
+        match b {
+            b'm' => ScanState::M,
+            b'd' => ScanState::D,
+            _ => ScanState::Start,
+        }
+    }
+
+    fn push_digit(value: i64, b: u8) -> i64 {
+        value * 10 + (b - b'0') as i64
+    }
+
+    fn feed(&mut self, b: u8) -> Option<Instruction> {
+        use ScanState::*;
+        let (state, instruction) = match (self.state, b) {
+            (M, b'u') => (Mu, None),
+            (Mu, b'l') => (Mul, None),
+            (Mul, b'(') => (Left(0, 0), None),
+            (Left(v, n), b'0'..=b'9') if n < MAX_ARG_DIGITS => {
+                (Left(Self::push_digit(v, b), n + 1), None)
+            }
+            (Left(v, n), b',') if n > 0 => (Right(v, 0, 0), None),
+            (Right(l, v, n), b'0'..=b'9') if n < MAX_ARG_DIGITS => {
+                (Right(l, Self::push_digit(v, b), n + 1), None)
+            }
+            (Right(l, v, n), b')') if n > 0 => (Start, Some(Instruction::Mul(l, v))),
+            (D, b'o') => (Do, None),
+            (Do, b'n') => (Don, None),
+            (Don, b'\'') => (DonQuote, None),
+            (DonQuote, b't') => (Start, Some(Instruction::Dont)),
+            // "don't" didn't match, but "do" did; the current byte may start a new instruction
+            (Do | Don | DonQuote, _) => (Self::start_state(b), Some(Instruction::Do)),
+            _ => (Self::start_state(b), None),
+        };
+        self.state = state;
+        instruction
+    }
+
+    /// End of input: complete a pending "do"
+    fn finish(&mut self) -> Option<Instruction> {
+        let state = std::mem::take(&mut self.state);
+        match state {
+            ScanState::Do | ScanState::Don | ScanState::DonQuote => Some(Instruction::Do),
+            _ => None,
+        }
+    }
+}
+
+fn scan_stream(
+    mut reader: impl BufRead,
+    mut handle: impl FnMut(Instruction) -> anyhow::Result<()>,
+) -> anyhow::Result<()> {
+    let mut scanner = Scanner::default();
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for b in buf {
+            if let Some(instruction) = scanner.feed(*b) {
+                handle(instruction)?;
+            }
+        }
+        let len = buf.len();
+        reader.consume(len);
+    }
+    if let Some(instruction) = scanner.finish() {
+        handle(instruction)?;
+    }
+    Ok(())
+}
+
+/// Part 1 reading the memory dump in chunks, without holding it all in memory
+pub fn part1_streaming(reader: impl BufRead) -> anyhow::Result<i64> {
+    let mut sum = 0;
+    scan_stream(reader, |instruction| {
+        if let Instruction::Mul(l, r) = instruction {
+            sum = accumulate(sum, l, r)?;
+        }
+        Ok(())
+    })?;
+    Ok(sum)
+}
+
+/// Part 2 reading the memory dump in chunks, without holding it all in memory
+pub fn part2_streaming(reader: impl BufRead) -> anyhow::Result<i64> {
+    let mut enabled = true;
+    let mut sum = 0;
+    scan_stream(reader, |instruction| {
+        match instruction {
+            Instruction::Do => enabled = true,
+            Instruction::Dont => enabled = false,
+            Instruction::Mul(l, r) => {
+                if enabled {
+                    sum = accumulate(sum, l, r)?;
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(sum)
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    Ok((
+        part1(input).part_context(DAY.day, "1")?.to_string(),
+        part2(input).part_context(DAY.day, "2")?.to_string(),
+    ))
+}
+
+common::aoc_day!(3, "Mull It Over");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("161"), Some("48")];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn part1_correct() {
+        let sum = part1(EXAMPLE).unwrap();
+        assert_eq!(sum, 161);
+    }
+
+    #[test]
+    fn part2_correct() {
+        let sum = part2(EXAMPLE).unwrap();
+        assert_eq!(sum, 48);
+    }
+
+    #[test]
+    fn streaming_straddles_buffer_edges() {
+        // buffer sizes smaller than a single instruction force every
+        // instruction to straddle at least one chunk boundary
+        for capacity in 1..=16 {
+            let reader = BufReader::with_capacity(capacity, EXAMPLE.as_bytes());
+            assert_eq!(part1_streaming(reader).unwrap(), 161, "capacity {capacity}");
+            let reader = BufReader::with_capacity(capacity, EXAMPLE.as_bytes());
+            assert_eq!(part2_streaming(reader).unwrap(), 48, "capacity {capacity}");
+        }
+    }
+
+    #[test]
+    fn four_digit_arguments_rejected() {
+        let input = "mul(1000,2)mul(2,1000)mul(999,999)mul(12345,1)";
+        assert_eq!(part1(input).unwrap(), 999 * 999);
+        assert_eq!(part2(input).unwrap(), 999 * 999);
+        assert_eq!(part1_streaming(input.as_bytes()).unwrap(), 999 * 999);
+        assert_eq!(part2_streaming(input.as_bytes()).unwrap(), 999 * 999);
+    }
+
+    #[test]
+    fn accumulate_overflow_is_error() {
+        assert_eq!(accumulate(1, 999, 999).unwrap(), 998002);
+        assert!(accumulate(i64::MAX - 10, 999, 999).is_err());
+    }
+
+    #[test]
+    fn streaming_matches_regex() {
+        let inputs = [
+            "mul(mul(2,3)",
+            "mul(2,3)mul(,4)mul(5,)mul (6,7)",
+            "don't()mul(2,3)do()mul(4,5)",
+            "don'tmul(2,3)undomul(4,5)",
+            "dondon'don't()mul(1,1)ddomul(2,2)",
+            "mul(2,3)don",
+            "mul(2,3)do",
+            "mul(1234,5)mul(5,1234)mul(123,4)",
+            "mul(0001,2)mul(001,2)",
+        ];
+        for input in inputs {
+            assert_eq!(
+                part1_streaming(input.as_bytes()).unwrap(),
+                part1(input).unwrap(),
+                "{input}"
+            );
+            assert_eq!(
+                part2_streaming(input.as_bytes()).unwrap(),
+                part2(input).unwrap(),
+                "{input}"
+            );
+        }
+    }
+}
@@ -1,70 +1,20 @@
-use regex::Regex;
+use common::{error::AocContext, timing::TimingReport};
+
+use day3::{part1, part1_streaming, part2, part2_streaming, DAY, EXAMPLE, EXAMPLE_ANSWERS};
 
 fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
+    let text = common::read_input("input1.txt", EXAMPLE).input_context(DAY.day, "input1.txt")?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
 
-    let sum_part1 = part1(&text)?;
-    println!("part 1 sum is {sum_part1}");
+    timing.part("1", || part1(&text))?;
+    timing.part("2", || part2(&text))?;
 
-    let sum_part2 = part2(&text)?;
-    println!("part 2 sum is {sum_part2}");
+    timing.part("1 (streaming)", || {
+        part1_streaming(common::open_input("input1.txt", EXAMPLE)?)
+    })?;
+    timing.part("2 (streaming)", || {
+        part2_streaming(common::open_input("input1.txt", EXAMPLE)?)
+    })?;
 
     Ok(())
 }
-
-fn part1(input: &str) -> anyhow::Result<i32> {
-    let re = Regex::new(r#"mul\((\d+),(\d+)\)"#).unwrap();
-
-    let mut sum = 0;
-    for cap in re.captures_iter(input) {
-        let (_, [l, r]) = cap.extract();
-        let l: i32 = l.parse()?;
-        let r: i32 = r.parse()?;
-        sum += l * r;
-    }
-
-    Ok(sum)
-}
-
-fn part2(input: &str) -> anyhow::Result<i32> {
-    let re = Regex::new(r#"mul\((\d+),(\d+)\)|don't|do"#).unwrap();
-
-    let mut enabled = true;
-    let mut sum = 0;
-    for cap in re.captures_iter(input) {
-        match &cap[0] {
-            "do" => enabled = true,
-            "don't" => enabled = false,
-            _ => {
-                if enabled {
-                    let l: i32 = cap[1].parse()?;
-                    let r: i32 = cap[2].parse()?;
-                    sum += l * r;
-                }
-            }
-        }
-    }
-
-    Ok(sum)
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::*;
-
-    const EXAMPLE: &str = indoc::indoc! {"
-        xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))
-    "};
-
-    #[test]
-    fn part1_correct() {
-        let sum = part1(EXAMPLE).unwrap();
-        assert_eq!(sum, 161);
-    }
-
-    #[test]
-    fn part2_correct() {
-        let sum = part2(EXAMPLE).unwrap();
-        assert_eq!(sum, 48);
-    }
-}
@@ -0,0 +1,302 @@
+use common::grid::{grid_from_str, rotate_cw};
+use nalgebra::DMatrix;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub matrix: DMatrix<char>,
+}
+
+/// Count `word` reading forwards along rows and down-right diagonals; over the four
+/// rotations of the grid, this covers all eight directions
+fn count_forwards(grid: &DMatrix<char>, word: &[char]) -> usize {
+    let (rows, cols) = grid.shape();
+    let n = word.len();
+    let mut count = 0;
+    for r in 0..rows {
+        for c in 0..cols {
+            let along_row = c + n <= cols && (0..n).all(|i| grid[(r, c + i)] == word[i]);
+            let along_diagonal =
+                r + n <= rows && c + n <= cols && (0..n).all(|i| grid[(r + i, c + i)] == word[i]);
+            count += along_row as usize + along_diagonal as usize;
+        }
+    }
+    count
+}
+
+/// One bit per cell, set where the grid holds a given letter, with each row packed
+/// into u64 lanes; bits past the end of a row are always clear
+struct LetterMask {
+    lanes: usize,
+    bits: Vec<u64>,
+}
+impl LetterMask {
+    fn new(grid: &DMatrix<char>, letter: char) -> Self {
+        let (rows, cols) = grid.shape();
+        let lanes = cols.div_ceil(64);
+        let mut bits = vec![0; rows * lanes];
+        // column-major, like the matrix storage
+        for c in 0..cols {
+            for r in 0..rows {
+                if grid[(r, c)] == letter {
+                    bits[r * lanes + c / 64] |= 1 << (c % 64);
+                }
+            }
+        }
+        Self { lanes, bits }
+    }
+
+    fn row(&self, r: usize) -> &[u64] {
+        &self.bits[r * self.lanes..(r + 1) * self.lanes]
+    }
+}
+
+/// Write `row` into `out` so that bit `c` of the output is bit `c + k` of the input,
+/// with zeros shifted in from either end
+fn shift_into(row: &[u64], k: isize, out: &mut [u64]) {
+    let lane = |i: isize| {
+        if i >= 0 && (i as usize) < row.len() {
+            row[i as usize]
+        } else {
+            0
+        }
+    };
+    let lane_offset = k.div_euclid(64);
+    let bit_offset = k.rem_euclid(64) as u32;
+    for (i, o) in out.iter_mut().enumerate() {
+        let i = i as isize + lane_offset;
+        *o = if bit_offset == 0 {
+            lane(i)
+        } else {
+            (lane(i) >> bit_offset) | (lane(i + 1) << (64 - bit_offset))
+        };
+    }
+}
+
+/// Count `word` in all eight directions at once per row: for a direction (dr, dc), the
+/// word starts at (r, c) when letter i's mask has bit c + i * dc set in row r + i * dr,
+/// so each row is the AND of suitably shifted mask rows
+fn count_bitset(grid: &DMatrix<char>, word: &[char]) -> usize {
+    let rows = grid.nrows() as isize;
+    let masks: Vec<LetterMask> = word.iter().map(|l| LetterMask::new(grid, *l)).collect();
+    let lanes = masks[0].lanes;
+    let mut acc = vec![0; lanes];
+    let mut shifted = vec![0; lanes];
+
+    let mut count = 0;
+    for dr in -1..=1 {
+        for dc in -1..=1 {
+            if dr == 0 && dc == 0 {
+                continue;
+            }
+            let end_offset = dr * (word.len() as isize - 1);
+            for r in 0..rows {
+                if !(0..rows).contains(&(r + end_offset)) {
+                    continue;
+                }
+                acc.copy_from_slice(masks[0].row(r as usize));
+                for (i, mask) in masks.iter().enumerate().skip(1) {
+                    let i = i as isize;
+                    shift_into(mask.row((r + i * dr) as usize), i * dc, &mut shifted);
+                    for (a, s) in acc.iter_mut().zip(&shifted) {
+                        *a &= s;
+                    }
+                }
+                count += acc.iter().map(|a| a.count_ones() as usize).sum::<usize>();
+            }
+        }
+    }
+    count
+}
+
+/// Square grid of letters from the word, so matches are reasonably common
+pub fn generate_grid(size: usize, seed: u64) -> DMatrix<char> {
+    common::rng::seeded(seed).grid(size, size, &['X', 'M', 'A', 'S'])
+}
+
+pub fn parse(input: &str) -> anyhow::Result<Problem> {
+    let matrix = grid_from_str(input, Ok)?;
+    Ok(Problem { matrix })
+}
+
+pub fn part1(problem: &Problem) -> usize {
+    let word: Vec<char> = "XMAS".chars().collect();
+
+    let mut grid = problem.matrix.clone();
+    let mut count = 0;
+    for _ in 0..4 {
+        count += count_forwards(&grid, &word);
+        grid = rotate_cw(&grid);
+    }
+    count
+}
+
+pub fn part1_bitset(problem: &Problem) -> usize {
+    let word: Vec<char> = "XMAS".chars().collect();
+    count_bitset(&problem.matrix, &word)
+}
+
+/// Count X shapes of `word` crossing at its middle letter: both diagonals through a
+/// cell read the word, forwards or backwards. The word must have an odd length, and
+/// crosses too close to the edge for their arms are skipped.
+fn count_crosses_in(grid: &DMatrix<char>, word: &[char]) -> usize {
+    debug_assert!(word.len() % 2 == 1);
+    let arm = word.len() / 2;
+    let (rows, cols) = grid.shape();
+    if rows < word.len() || cols < word.len() {
+        return 0;
+    }
+
+    // reads the word along a diagonal through (r, c), stepping `dr` rows per column
+    let reads = |r: usize, c: usize, dr: isize| {
+        let cell = |k: usize| {
+            let row = (r as isize + dr * (k as isize - arm as isize)) as usize;
+            grid[(row, c + k - arm)]
+        };
+        (0..word.len()).all(|k| cell(k) == word[k])
+            || (0..word.len()).all(|k| cell(k) == word[word.len() - 1 - k])
+    };
+
+    let mut count = 0;
+    for c in arm..cols - arm {
+        for r in arm..rows - arm {
+            if grid[(r, c)] == word[arm] && reads(r, c, 1) && reads(r, c, -1) {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// X shapes of `word` in the grid, as in part 2 but for any word of odd length
+pub fn count_crosses(problem: &Problem, word: &str) -> anyhow::Result<usize> {
+    let word: Vec<char> = word.chars().collect();
+    if word.len().is_multiple_of(2) {
+        anyhow::bail!("{} letters has no middle letter to cross at", word.len());
+    }
+    Ok(count_crosses_in(&problem.matrix, &word))
+}
+
+pub fn part2(problem: &Problem) -> usize {
+    count_crosses_in(&problem.matrix, &['M', 'A', 'S'])
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    let problem = parse(input)?;
+    Ok((
+        part1_bitset(&problem).to_string(),
+        part2(&problem).to_string(),
+    ))
+}
+
+common::aoc_day!(4, "Ceres Search");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    MMMSXXMASM
+    MSAMXMSMSA
+    AMXSXMAAMM
+    MSAMASMSMX
+    XMASAMXAMM
+    XXAMMXXAMA
+    SMSMSASXSS
+    SAXAMASAAA
+    MAMMMXMMMM
+    MXMXAXMASX
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("18"), Some("9")];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const EXAMPLE_SIMPLE: &str = indoc::indoc! {"
+    ..X...
+    .SAMX.
+    .A..A.
+    XMAS.S
+    .X....
+    "};
+
+    #[test]
+    fn part1_basic_correct() {
+        let problem = parse(EXAMPLE_SIMPLE).unwrap();
+        let count = part1(&problem);
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn part1_correct() {
+        let problem = parse(EXAMPLE).unwrap();
+        let count = part1(&problem);
+        assert_eq!(count, 18);
+    }
+
+    #[test]
+    fn part1_bitset_correct() {
+        assert_eq!(part1_bitset(&parse(EXAMPLE_SIMPLE).unwrap()), 4);
+        assert_eq!(part1_bitset(&parse(EXAMPLE).unwrap()), 18);
+    }
+
+    #[test]
+    fn bitset_matches_naive_across_lanes() {
+        // widths either side of the u64 lane boundaries, plus non-square grids
+        for (seed, (rows, cols)) in [(1, 1), (3, 64), (64, 3), (65, 65), (40, 129), (130, 70)]
+            .into_iter()
+            .enumerate()
+        {
+            let grid = generate_grid(rows.max(cols), seed as u64);
+            let grid = grid.view((0, 0), (rows, cols)).into_owned();
+            let problem = Problem { matrix: grid };
+            assert_eq!(part1_bitset(&problem), part1(&problem), "{rows}x{cols}");
+        }
+    }
+
+    #[test]
+    fn shift_into_moves_bits_across_lanes() {
+        let row = [1 << 63, 1];
+        let mut out = [0; 2];
+        shift_into(&row, 1, &mut out);
+        assert_eq!(out, [3 << 62, 0]);
+        shift_into(&row, 63, &mut out);
+        assert_eq!(out, [3, 0]);
+        shift_into(&row, -1, &mut out);
+        assert_eq!(out, [0, 3]);
+        shift_into(&row, -64, &mut out);
+        assert_eq!(out, [0, 1 << 63]);
+        shift_into(&row, 64, &mut out);
+        assert_eq!(out, [1, 0]);
+    }
+
+    #[test]
+    fn part2_correct() {
+        let problem = parse(EXAMPLE).unwrap();
+        let count = part2(&problem);
+        assert_eq!(count, 9);
+    }
+
+    #[test]
+    fn longer_crosses() {
+        // TIGER up one diagonal and backwards down the other, plus a G on the
+        // edge with no room for its arms
+        let problem = parse(indoc::indoc! {"
+            R...R.
+            .E.E..
+            ..G..G
+            .I.I..
+            T...T.
+        "})
+        .unwrap();
+        assert_eq!(count_crosses(&problem, "TIGER").unwrap(), 1);
+        assert_eq!(count_crosses(&problem, "REGIT").unwrap(), 1);
+        assert_eq!(count_crosses(&problem, "TIGRE").unwrap(), 0);
+        assert_eq!(count_crosses(&problem, "G").unwrap(), 2);
+        assert_eq!(count_crosses(&problem, "LONGERWORD!").unwrap(), 0);
+        assert!(count_crosses(&problem, "TIGERS").is_err());
+        assert!(count_crosses(&problem, "").is_err());
+
+        let problem = parse(EXAMPLE).unwrap();
+        assert_eq!(count_crosses(&problem, "MAS").unwrap(), 9);
+        assert_eq!(count_crosses(&problem, "SAM").unwrap(), 9);
+    }
+}
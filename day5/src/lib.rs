@@ -0,0 +1,524 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    num::ParseIntError,
+    str::FromStr,
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail};
+
+// unique key that ignores order of a,b by canonicalizing so that
+// the first `Key` field is the smaller of the in the `Rule`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct Key(usize, usize);
+impl From<&Rule> for Key {
+    fn from(value: &Rule) -> Self {
+        let Rule(a, b) = value;
+        match a.cmp(b) {
+            Ordering::Less => Key(*a, *b),
+            Ordering::Equal => Key(*a, *b),
+            Ordering::Greater => Key(*b, *a),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Rule(usize, usize);
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut it = s.split('|');
+        let a = it.next().ok_or(anyhow!("missing first"))?.parse()?;
+        let b = it.next().ok_or(anyhow!("missing second"))?.parse()?;
+        Ok(Rule(a, b))
+    }
+}
+impl Rule {
+    fn key(&self) -> Key {
+        Key::from(self)
+    }
+
+    fn rev(&self) -> Rule {
+        Rule(self.1, self.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PageUpdates(Vec<usize>);
+impl FromStr for PageUpdates {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let res: Result<Vec<usize>, _> = s.split(',').map(|s| s.parse()).collect();
+        res.map(PageUpdates)
+    }
+}
+
+pub struct Problem {
+    rules: Vec<Rule>,
+    updates: Vec<PageUpdates>,
+}
+
+pub struct Solver {
+    rules: HashMap<Key, Rule>,
+    // every rule as an edge, including any that contradict each other directly
+    successors: BTreeMap<usize, BTreeSet<usize>>,
+}
+impl Solver {
+    fn new(rules: &[Rule]) -> Self {
+        let mut successors: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for Rule(a, b) in rules {
+            successors.entry(*a).or_default().insert(*b);
+            successors.entry(*b).or_default();
+        }
+        let rules = rules.iter().map(|r| (r.key(), *r)).collect();
+        Self { rules, successors }
+    }
+
+    pub fn from_problem(problem: &Problem) -> Self {
+        Self::new(&problem.rules)
+    }
+
+    fn compare(&self, a: usize, b: usize) -> Ordering {
+        let rule = Rule(a, b);
+        let key = rule.key();
+        let ordering_rule = self.rules.get(&key).expect("missing rule");
+
+        if rule == *ordering_rule {
+            Ordering::Less
+        } else if rule == ordering_rule.rev() {
+            Ordering::Greater
+        } else {
+            panic!("retrieved rule mismatch")
+        }
+    }
+
+    /// Middle page of `pages` once put in order, found by quickselect with the rule
+    /// comparator rather than a full sort; expected linear in the update length
+    pub fn middle_page_after_fix(&self, pages: &[usize]) -> usize {
+        let mut pages = pages.to_vec();
+        let middle = pages.len() / 2;
+        *pages
+            .select_nth_unstable_by(middle, |a, b| self.compare(*a, *b))
+            .1
+    }
+
+    // check in order
+    fn update_correct(&self, pages: &[usize]) -> bool {
+        for i in 1..pages.len() {
+            let a = pages[i - 1];
+            let b = pages[i];
+            if self.compare(a, b) != Ordering::Less {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Topologically sort a subset of pages using only the rules between them, failing
+    /// if those rules contain a cycle. Ties go to the smallest page, so this is stable.
+    fn global_order(&self, pages: &HashSet<usize>) -> anyhow::Result<Vec<usize>> {
+        let mut in_degree: BTreeMap<usize, usize> = pages.iter().map(|p| (*p, 0)).collect();
+        for p in pages {
+            for next in self.successors.get(p).into_iter().flatten() {
+                if let Some(d) = in_degree.get_mut(next) {
+                    *d += 1;
+                }
+            }
+        }
+
+        let mut ready: BTreeSet<usize> = in_degree
+            .iter()
+            .filter(|(_, d)| **d == 0)
+            .map(|(p, _)| *p)
+            .collect();
+        let mut order = Vec::with_capacity(pages.len());
+        while let Some(p) = ready.pop_first() {
+            order.push(p);
+            for next in self.successors.get(&p).into_iter().flatten() {
+                if let Some(d) = in_degree.get_mut(next) {
+                    *d -= 1;
+                    if *d == 0 {
+                        ready.insert(*next);
+                    }
+                }
+            }
+        }
+
+        if order.len() != pages.len() {
+            let stuck: BTreeSet<_> = in_degree
+                .iter()
+                .filter(|(_, d)| **d > 0)
+                .map(|(p, _)| *p)
+                .collect();
+            bail!("rules between pages {stuck:?} contain a cycle");
+        }
+        Ok(order)
+    }
+
+    /// Cycles in the full rule set: one shortest cycle for each group of pages whose
+    /// rules contradict each other (each strongly connected component)
+    fn find_cycles(&self) -> Vec<Vec<usize>> {
+        let mut cycles = vec![];
+        for component in self.strongly_connected() {
+            let start = component[0];
+            let members: HashSet<usize> = component.iter().copied().collect();
+
+            // breadth first back round to the start, staying within the component
+            let mut prev: HashMap<usize, usize> = HashMap::new();
+            let mut q = VecDeque::from([start]);
+            'search: while let Some(p) = q.pop_front() {
+                for &next in &self.successors[&p] {
+                    if !members.contains(&next) {
+                        continue;
+                    }
+                    if next == start {
+                        let mut cycle = vec![p];
+                        while let Some(&before) = prev.get(cycle.last().unwrap()) {
+                            cycle.push(before);
+                        }
+                        cycle.reverse();
+                        cycles.push(cycle);
+                        break 'search;
+                    }
+                    if next != start && !prev.contains_key(&next) {
+                        prev.insert(next, p);
+                        q.push_back(next);
+                    }
+                }
+            }
+        }
+        cycles
+    }
+
+    /// Tarjan's algorithm; only components that contain a cycle are returned, each
+    /// sorted, starting from the smallest page
+    fn strongly_connected(&self) -> Vec<Vec<usize>> {
+        struct Tarjan<'a> {
+            successors: &'a BTreeMap<usize, BTreeSet<usize>>,
+            index: HashMap<usize, usize>,
+            low_link: HashMap<usize, usize>,
+            stack: Vec<usize>,
+            on_stack: HashSet<usize>,
+            components: Vec<Vec<usize>>,
+        }
+        impl Tarjan<'_> {
+            fn visit(&mut self, v: usize) {
+                let i = self.index.len();
+                self.index.insert(v, i);
+                self.low_link.insert(v, i);
+                self.stack.push(v);
+                self.on_stack.insert(v);
+
+                for &w in &self.successors[&v] {
+                    if !self.index.contains_key(&w) {
+                        self.visit(w);
+                        let low = self.low_link[&v].min(self.low_link[&w]);
+                        self.low_link.insert(v, low);
+                    } else if self.on_stack.contains(&w) {
+                        let low = self.low_link[&v].min(self.index[&w]);
+                        self.low_link.insert(v, low);
+                    }
+                }
+
+                if self.low_link[&v] == self.index[&v] {
+                    let mut component = vec![];
+                    loop {
+                        let w = self.stack.pop().unwrap();
+                        self.on_stack.remove(&w);
+                        component.push(w);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    let self_loop = self.successors[&v].contains(&v);
+                    if component.len() > 1 || self_loop {
+                        component.sort();
+                        self.components.push(component);
+                    }
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            successors: &self.successors,
+            index: HashMap::new(),
+            low_link: HashMap::new(),
+            stack: vec![],
+            on_stack: HashSet::new(),
+            components: vec![],
+        };
+        for &v in self.successors.keys() {
+            if !tarjan.index.contains_key(&v) {
+                tarjan.visit(v);
+            }
+        }
+        tarjan.components.sort();
+        tarjan.components
+    }
+}
+
+/// Check the claim that the full rule set has cycles, but no single update does
+pub fn analyse_rules(problem: &Problem) {
+    let solver = Solver::new(&problem.rules);
+    let cycles = solver.find_cycles();
+    println!("{} cycles in the full rule set", cycles.len());
+    for cycle in &cycles {
+        println!("  cycle of {} pages: {cycle:?}", cycle.len());
+    }
+
+    let mut acyclic = 0;
+    for (i, PageUpdates(pages)) in problem.updates.iter().enumerate() {
+        match solver.global_order(&pages.iter().copied().collect()) {
+            Ok(_) => acyclic += 1,
+            Err(e) => println!("  update {i}: {e}"),
+        }
+    }
+    println!(
+        "{acyclic} of {} updates have a global ordering",
+        problem.updates.len()
+    );
+}
+
+pub fn parse(input: &str) -> anyhow::Result<Problem> {
+    let mut lines = input.lines();
+
+    let mut rules = vec![];
+    for l in lines.by_ref() {
+        if l.is_empty() {
+            break;
+        }
+        rules.push(l.parse::<Rule>()?);
+    }
+
+    let mut updates = vec![];
+    for l in lines.by_ref() {
+        updates.push(l.parse::<PageUpdates>()?);
+    }
+
+    Ok(Problem { rules, updates })
+}
+
+pub fn part1(problem: &Problem) -> usize {
+    let solver = Solver::new(&problem.rules);
+
+    let mut count = 0;
+    for PageUpdates(pages) in &problem.updates {
+        if solver.update_correct(pages) {
+            let middle = pages.len() / 2;
+            count += pages[middle];
+        }
+    }
+
+    count
+}
+
+pub fn part2(problem: &Problem) -> usize {
+    let solver = Solver::new(&problem.rules);
+
+    let mut count = 0;
+    for PageUpdates(pages) in &problem.updates {
+        if !solver.update_correct(pages) {
+            count += solver.middle_page_after_fix(pages);
+        }
+    }
+
+    count
+}
+
+/// Original part 2, fully sorting each update to fix its ordering
+pub fn part2_sort(problem: &Problem) -> usize {
+    let solver = Solver::new(&problem.rules);
+
+    let mut count = 0;
+    for PageUpdates(pages) in &problem.updates {
+        if !solver.update_correct(pages) {
+            // fix ordering
+            let mut pages = pages.clone();
+            pages.sort_by(|a, b| solver.compare(*a, *b));
+
+            let middle = pages.len() / 2;
+            count += pages[middle];
+        }
+    }
+
+    count
+}
+
+/// Rules putting `pages` pages in a random total order, with `updates` shuffled
+/// updates of every page; reproducible from `seed`
+pub fn generate_long_updates(pages: usize, updates: usize, seed: u64) -> Problem {
+    let mut rng = common::rng::seeded(seed);
+    let mut shuffled = |mut v: Vec<usize>| {
+        rng.shuffle(&mut v);
+        v
+    };
+
+    let order = shuffled((10..10 + pages).collect());
+    let mut rules = Vec::with_capacity(pages * pages.saturating_sub(1) / 2);
+    for (i, a) in order.iter().enumerate() {
+        for b in &order[i + 1..] {
+            rules.push(Rule(*a, *b));
+        }
+    }
+    let updates = (0..updates)
+        .map(|_| PageUpdates(shuffled(order.clone())))
+        .collect();
+    Problem { rules, updates }
+}
+
+/// Compare sorting against quickselect for the middle page of long updates; the
+/// solver is built up front, since with every pair of pages ruled it dominates
+pub fn benchmark_middle_page() {
+    for pages in [101, 1001, 3001] {
+        let problem = generate_long_updates(pages, 50, pages as u64);
+        let solver = Solver::from_problem(&problem);
+
+        let t = Instant::now();
+        let mut sorted = 0;
+        for PageUpdates(pages) in &problem.updates {
+            let mut pages = pages.clone();
+            pages.sort_by(|a, b| solver.compare(*a, *b));
+            sorted += pages[pages.len() / 2];
+        }
+        let sort_time = t.elapsed();
+
+        let t = Instant::now();
+        let selected: usize = problem
+            .updates
+            .iter()
+            .map(|PageUpdates(pages)| solver.middle_page_after_fix(pages))
+            .sum();
+        let select_time = t.elapsed();
+
+        println!(
+            "{pages} pages: sort {sorted} (took {sort_time:?}), select {selected} (took {select_time:?})"
+        );
+    }
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    let problem = parse(input)?;
+    Ok((part1(&problem).to_string(), part2(&problem).to_string()))
+}
+
+common::aoc_day!(5, "Print Queue");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    47|53
+    97|13
+    97|61
+    97|47
+    75|29
+    61|13
+    75|53
+    29|13
+    97|29
+    53|29
+    61|53
+    97|53
+    61|29
+    47|13
+    75|47
+    97|75
+    47|61
+    75|61
+    47|29
+    75|13
+    53|13
+
+    75,47,61,53,29
+    97,61,53,29,13
+    75,29,13
+    75,97,47,61,53
+    61,13,29
+    97,13,75,29,47
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("143"), Some("123")];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn parse_ok() {
+        parse(EXAMPLE).expect("parse failed");
+    }
+
+    #[test]
+    fn part1_correct() {
+        let problem = parse(EXAMPLE).expect("parse failed");
+        let count = part1(&problem);
+        assert_eq!(count, 143);
+    }
+
+    #[test]
+    fn part2_correct() {
+        let problem = parse(EXAMPLE).expect("parse failed");
+        let count = part2(&problem);
+        assert_eq!(count, 123);
+    }
+
+    #[test]
+    fn part2_sort_correct() {
+        let problem = parse(EXAMPLE).expect("parse failed");
+        assert_eq!(part2_sort(&problem), 123);
+    }
+
+    #[test]
+    fn middle_page_matches_sort() {
+        let problem = parse(EXAMPLE).expect("parse failed");
+        let solver = Solver::from_problem(&problem);
+        assert_eq!(solver.middle_page_after_fix(&[75, 97, 47, 61, 53]), 47);
+        assert_eq!(solver.middle_page_after_fix(&[61, 13, 29]), 29);
+        assert_eq!(solver.middle_page_after_fix(&[97, 13, 75, 29, 47]), 47);
+
+        for seed in 1..=5 {
+            let problem = generate_long_updates(301 + seed as usize, 10, seed);
+            assert_eq!(part2(&problem), part2_sort(&problem), "seed {seed}");
+            let solver = Solver::from_problem(&problem);
+            for PageUpdates(pages) in &problem.updates {
+                let mut sorted = pages.clone();
+                sorted.sort_by(|a, b| solver.compare(*a, *b));
+                assert_eq!(
+                    solver.middle_page_after_fix(pages),
+                    sorted[sorted.len() / 2]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn example_global_order() {
+        let problem = parse(EXAMPLE).expect("parse failed");
+        let solver = Solver::new(&problem.rules);
+        assert!(solver.find_cycles().is_empty());
+
+        let all_pages = solver.successors.keys().copied().collect();
+        let order = solver.global_order(&all_pages).unwrap();
+        assert_eq!(order, [97, 75, 47, 61, 53, 29, 13]);
+
+        let subset = [13, 61, 29].into_iter().collect();
+        assert_eq!(solver.global_order(&subset).unwrap(), [61, 29, 13]);
+    }
+
+    #[test]
+    fn cycles_found() {
+        // 1 < 2 < 3 < 1, plus a direct contradiction between 5 and 6
+        let rules: Vec<Rule> = ["1|2", "2|3", "3|1", "3|4", "5|6", "6|5"]
+            .iter()
+            .map(|r| r.parse().unwrap())
+            .collect();
+        let solver = Solver::new(&rules);
+        assert_eq!(solver.find_cycles(), [vec![1, 2, 3], vec![5, 6]]);
+
+        let cyclic = [1, 2, 3, 4].into_iter().collect();
+        assert!(solver.global_order(&cyclic).is_err());
+        let acyclic = [1, 2, 4].into_iter().collect();
+        assert_eq!(solver.global_order(&acyclic).unwrap(), [1, 2, 4]);
+    }
+}
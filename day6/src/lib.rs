@@ -0,0 +1,412 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::bail;
+use common::{
+    cartesian::{DirMap, Point, ScreenDir},
+    grid::{self, grid_from_str},
+    OptionAnyhow,
+};
+use nalgebra::DMatrix;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+enum Block {
+    #[default]
+    Empty,
+    Wall,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Guard(Point, ScreenDir);
+impl Guard {
+    pub fn position(&self) -> Point {
+        self.0
+    }
+
+    pub fn dir(&self) -> ScreenDir {
+        self.1
+    }
+}
+
+type Map = DMatrix<Block>;
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    map: Map,
+    guard: Guard,
+    obstruction: Option<Point>,
+}
+impl Problem {
+    /// The same lab with an extra obstruction at `pos`, if that's an empty cell other
+    /// than the guard's starting position
+    pub fn with_obstruction(&self, pos: Point) -> Option<Problem> {
+        if pos == self.guard.0 || self.map.get(pos) != Some(&Block::Empty) {
+            return None;
+        }
+        let mut problem = self.clone();
+        problem.map[pos.to_coord()?] = Block::Wall;
+        problem.obstruction = Some(pos);
+        Some(problem)
+    }
+
+    /// The map with the loop's cells drawn over it: `|` and `-` for the direction of
+    /// travel, `+` where the guard turns or crosses its own path, and `O` for the
+    /// added obstruction
+    pub fn render_loop(&self, info: &LoopInfo) -> String {
+        let mut grid = self.grid();
+        draw_route(
+            &mut grid,
+            walk(self)
+                .skip_while(|g| *g != info.entry)
+                .take(info.cycle_len),
+        );
+        if let Some(pos) = self.obstruction {
+            grid[pos.to_coord().unwrap()] = 'O';
+        }
+        grid::render(&grid)
+    }
+
+    /// The map with the guard's patrol drawn over it as for `render_loop`, `^` where
+    /// the guard starts, and `O` at each of `obstructions`, such as the ones that
+    /// would trap the guard in a loop
+    pub fn render_patrol(&self, obstructions: &[Point]) -> String {
+        let mut grid = self.grid();
+        // stop at the first repeated state, in case the guard is already looping
+        let mut seen = HashSet::new();
+        draw_route(&mut grid, walk(self).take_while(|g| seen.insert(*g)));
+        grid[self.guard.0.to_coord().unwrap()] = '^';
+        for pos in obstructions {
+            if let Some(cell) = grid.get_mut(*pos) {
+                *cell = 'O';
+            }
+        }
+        grid::render(&grid)
+    }
+
+    fn grid(&self) -> DMatrix<char> {
+        self.map.map(|b| match b {
+            Block::Empty => '.',
+            Block::Wall => '#',
+        })
+    }
+}
+
+/// Mark each guard state's cell with `|` or `-` for its direction, or `+` where
+/// directions cross
+fn draw_route(grid: &mut DMatrix<char>, route: impl Iterator<Item = Guard>) {
+    for Guard(pos, dir) in route {
+        let line = match dir {
+            ScreenDir::U | ScreenDir::D => '|',
+            ScreenDir::L | ScreenDir::R => '-',
+        };
+        let cell = &mut grid[pos.to_coord().unwrap()];
+        *cell = match *cell {
+            '.' => line,
+            c if c == line => line,
+            _ => '+',
+        };
+    }
+}
+
+/// The cycle a guard ends up walking forever
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopInfo {
+    /// First state of the walk that's part of the cycle
+    pub entry: Guard,
+    /// Number of states in the cycle, counting turns on the spot
+    pub cycle_len: usize,
+    /// Distinct cells on the cycle, in the order they're first reached from `entry`
+    pub cells: Vec<Point>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Termination {
+    Exited,
+    Loop,
+}
+
+pub fn parse_input(input: &str) -> anyhow::Result<Problem> {
+    // load map
+    let mut guard = None;
+    let map = grid_from_str(input, |ch| match ch {
+        '.' => Ok(Block::Empty),
+        '^' => Ok(Block::Empty),
+        '#' => Ok(Block::Wall),
+        _ => bail!("unexpected map character: {}", ch),
+    })?;
+
+    // locate guard - planning on refactoring above later, so keeping this separate
+    for (row, line) in input.lines().enumerate() {
+        for (col, ch) in line.chars().enumerate() {
+            if ch == '^' {
+                let (y, x) = (row as i64, col as i64);
+                guard = Some(Guard(Point::new(x, y), ScreenDir::U));
+            }
+        }
+    }
+
+    Ok(Problem {
+        map,
+        guard: guard.ok_anyhow()?,
+        obstruction: None,
+    })
+}
+
+/// Successive guard states, starting where the guard stands; turning on the spot is a
+/// state of its own. Ends when the guard leaves the map, and never ends if the guard is
+/// stuck in a loop.
+struct Walk<'a> {
+    map: &'a Map,
+    next: Option<Guard>,
+}
+impl Iterator for Walk<'_> {
+    type Item = Guard;
+
+    fn next(&mut self) -> Option<Guard> {
+        let guard = self.next?;
+        self.next = guard.0.step_within(guard.1, self.map).map(|ahead| {
+            if self.map.get(ahead) == Some(&Block::Wall) {
+                Guard(guard.0, guard.1.right())
+            } else {
+                Guard(ahead, guard.1)
+            }
+        });
+        Some(guard)
+    }
+}
+
+fn walk(problem: &Problem) -> impl Iterator<Item = Guard> + '_ {
+    Walk {
+        map: &problem.map,
+        next: Some(problem.guard),
+    }
+}
+
+pub fn part1(problem: &Problem) -> usize {
+    let visited: HashSet<Point> = walk(problem).map(|Guard(pos, _)| pos).collect();
+    visited.len()
+}
+
+pub fn part2(problem: &Problem) -> usize {
+    let mut loop_termination_count = 0;
+    let mut problem_temp = problem.clone();
+    let mut visited =
+        DMatrix::from_element(problem.map.nrows(), problem.map.ncols(), DirMap::default());
+    for c in 0..problem.map.ncols() {
+        for r in 0..problem.map.nrows() {
+            if problem.map[(r, c)] == Block::Empty {
+                // insert temporary block
+                problem_temp.map.copy_from(&problem.map);
+                problem_temp.map[(r, c)] = Block::Wall;
+
+                // iterate and check for infinite loop
+                if let Termination::Loop = iterate(&problem_temp, &mut visited) {
+                    loop_termination_count += 1
+                }
+            }
+        }
+    }
+    loop_termination_count
+}
+
+/// Walk until the guard leaves or repeats a state; `visited` holds whether each cell
+/// has been left in each direction
+fn iterate(problem: &Problem, visited: &mut DMatrix<DirMap<bool>>) -> Termination {
+    visited.fill(DirMap::default());
+    for Guard(pos, dir) in walk(problem) {
+        // same location, same direction - we're in a loop
+        let seen = &mut visited[pos.to_coord().unwrap()][dir];
+        if *seen {
+            return Termination::Loop;
+        }
+        *seen = true;
+    }
+    Termination::Exited
+}
+
+/// The cycle the guard is stuck in, or `None` if the guard leaves the map
+pub fn find_loop(problem: &Problem) -> Option<LoopInfo> {
+    let mut first_seen = HashMap::new();
+    let mut states = vec![];
+    for guard in walk(problem) {
+        if let Some(&start) = first_seen.get(&guard) {
+            let cycle = &states[start..];
+            let mut seen = HashSet::new();
+            let cells = cycle
+                .iter()
+                .map(|Guard(pos, _)| *pos)
+                .filter(|pos| seen.insert(*pos))
+                .collect();
+            return Some(LoopInfo {
+                entry: guard,
+                cycle_len: cycle.len(),
+                cells,
+            });
+        }
+        first_seen.insert(guard, states.len());
+        states.push(guard);
+    }
+    None
+}
+
+/// Positions where a single added obstruction traps the guard in a loop, with the loop
+/// each one causes, in column-major order
+pub fn loop_obstructions(problem: &Problem) -> Vec<(Point, LoopInfo)> {
+    let mut found = vec![];
+    for c in 0..problem.map.ncols() {
+        for r in 0..problem.map.nrows() {
+            let pos = Point::new(c as i64, r as i64);
+            let Some(candidate) = problem.with_obstruction(pos) else {
+                continue;
+            };
+            if let Some(info) = find_loop(&candidate) {
+                found.push((pos, info));
+            }
+        }
+    }
+    found
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> anyhow::Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem).to_string(), part2(&problem).to_string()))
+}
+
+common::aoc_day!(6, "Guard Gallivant");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    ....#.....
+    .........#
+    ..........
+    ..#.......
+    .......#..
+    ..........
+    .#..^.....
+    ........#.
+    #.........
+    ......#...
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("41"), Some("6")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        println!("{:?}", problem);
+    }
+
+    #[test]
+    fn part1_correct() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = part1(&problem);
+        assert_eq!(count, 41);
+    }
+
+    #[test]
+    fn part2_correct() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = part2(&problem);
+        assert_eq!(count, 6);
+    }
+
+    #[test]
+    fn walk_states() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let states: Vec<Guard> = walk(&problem).collect();
+        assert_eq!(states[0], problem.guard);
+        assert_eq!(states[4], Guard(Point::new(4, 2), ScreenDir::U));
+        assert_eq!(states[5], Guard(Point::new(4, 1), ScreenDir::U));
+        assert_eq!(states[6], Guard(Point::new(4, 1), ScreenDir::R));
+        assert_eq!(states.last(), Some(&Guard(Point::new(7, 9), ScreenDir::D)));
+
+        let turns = states.windows(2).filter(|w| w[0].0 == w[1].0).count();
+        assert_eq!(turns, 10);
+    }
+
+    #[test]
+    fn loops_agree_with_simulation() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let found = loop_obstructions(&problem);
+        assert_eq!(found.len(), part2(&problem));
+
+        let mut visited = DMatrix::from_element(10, 10, DirMap::default());
+        for c in 0..10 {
+            for r in 0..10 {
+                let Some(candidate) = problem.with_obstruction(Point::new(c, r)) else {
+                    continue;
+                };
+                let looped = matches!(iterate(&candidate, &mut visited), Termination::Loop);
+                assert_eq!(find_loop(&candidate).is_some(), looped);
+            }
+        }
+        assert_eq!(find_loop(&problem), None);
+    }
+
+    #[test]
+    fn first_example_loop() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let candidate = problem.with_obstruction(Point::new(3, 6)).unwrap();
+        let info = find_loop(&candidate).unwrap();
+        // a rectangle of 18 cells, plus a turn on the spot at each corner
+        assert_eq!(info.entry, Guard(Point::new(4, 6), ScreenDir::U));
+        assert_eq!(info.cells.len(), 18);
+        assert_eq!(info.cycle_len, 22);
+        assert!(info.cells.contains(&Point::new(4, 1)));
+
+        let rendered = candidate.render_loop(&info);
+        assert_eq!(
+            rendered.lines().take(8).collect::<Vec<_>>(),
+            [
+                "....#.....",
+                "....+---+#",
+                "....|...|.",
+                "..#.|...|.",
+                "....|..#|.",
+                "....|...|.",
+                ".#.O+---+.",
+                "........#.",
+            ]
+        );
+    }
+
+    #[test]
+    fn patrol_with_obstructions() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let obstructions: Vec<Point> = loop_obstructions(&problem)
+            .into_iter()
+            .map(|(pos, _)| pos)
+            .collect();
+        // the six positions from the puzzle text
+        assert_eq!(
+            problem
+                .render_patrol(&obstructions)
+                .lines()
+                .collect::<Vec<_>>(),
+            [
+                "....#.....",
+                "....+---+#",
+                "....|...|.",
+                "..#.|...|.",
+                "..+-+-+#|.",
+                "..|.|.|.|.",
+                ".#+O^-+-+.",
+                ".+----OO#.",
+                "#O-O--+|..",
+                "......#O..",
+            ]
+        );
+    }
+
+    #[test]
+    fn boxed_in_guard_loops() {
+        let problem = parse_input(".#.\n#^#\n.#.\n").unwrap();
+        let mut visited = DMatrix::from_element(3, 3, DirMap::default());
+        assert!(matches!(iterate(&problem, &mut visited), Termination::Loop));
+        let info = find_loop(&problem).unwrap();
+        assert_eq!((info.cycle_len, info.cells), (4, vec![Point::new(1, 1)]));
+    }
+}
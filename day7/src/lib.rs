@@ -0,0 +1,499 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+use arrayvec::ArrayVec;
+use common::{error::AocContext, OptionAnyhow};
+use itertools::Itertools;
+
+#[derive(Debug, Clone)]
+pub struct Equation {
+    test_value: i64,
+    numbers: Vec<i64>,
+}
+
+impl Equation {
+    pub fn new(test_value: i64, numbers: Vec<i64>) -> Self {
+        Self {
+            test_value,
+            numbers,
+        }
+    }
+
+    pub fn test_value(&self) -> i64 {
+        self.test_value
+    }
+
+    pub fn numbers(&self) -> &[i64] {
+        &self.numbers
+    }
+
+    /// The equation with `ops` written between its numbers, e.g. `190 = 10 * 19`
+    pub fn annotate(&self, ops: &[Op]) -> String {
+        let mut text = format!("{} =", self.test_value);
+        for (i, n) in self.numbers.iter().enumerate() {
+            if i > 0 {
+                text += &format!(" {}", ops[i - 1]);
+            }
+            text += &format!(" {n}");
+        }
+        text
+    }
+}
+
+/// In the input format, `190: 10 19`
+impl Display for Equation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.test_value)?;
+        for n in &self.numbers {
+            write!(f, " {n}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    equations: Vec<Equation>,
+}
+
+impl Problem {
+    pub fn equations(&self) -> &[Equation] {
+        &self.equations
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add,
+    Multiply,
+    Concatenate,
+}
+impl Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Op::Add => "+",
+            Op::Multiply => "*",
+            Op::Concatenate => "||",
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+/// An operator that can be inserted between two numbers, evaluated left to right
+pub trait BinOp {
+    /// `None` if the result overflows or is otherwise undefined
+    fn apply(&self, a: i64, b: i64) -> Option<i64>;
+
+    /// Whether the result is never less than `a` for positive operands; when every
+    /// operator in the set is, the search can give up once it passes the target
+    fn is_increasing(&self) -> bool {
+        false
+    }
+}
+
+impl BinOp for Op {
+    fn apply(&self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            Op::Add => a.checked_add(b),
+            Op::Multiply => a.checked_mul(b),
+            Op::Concatenate => concatenate(a, b),
+        }
+    }
+
+    fn is_increasing(&self) -> bool {
+        true
+    }
+}
+
+/// A user-defined operator from a closure, so that operator sets can be extended
+/// without touching `Op`; built-in operators convert with `OpFn::from`
+pub struct OpFn {
+    f: Box<dyn Fn(i64, i64) -> Option<i64> + Send + Sync>,
+    increasing: bool,
+}
+
+impl OpFn {
+    pub fn new(f: impl Fn(i64, i64) -> Option<i64> + Send + Sync + 'static) -> Self {
+        Self {
+            f: Box::new(f),
+            increasing: false,
+        }
+    }
+
+    /// As `new`, promising that `f(a, b) >= a` for positive operands
+    pub fn increasing(f: impl Fn(i64, i64) -> Option<i64> + Send + Sync + 'static) -> Self {
+        Self {
+            f: Box::new(f),
+            increasing: true,
+        }
+    }
+}
+
+impl From<Op> for OpFn {
+    fn from(op: Op) -> Self {
+        Self::increasing(move |a, b| op.apply(a, b))
+    }
+}
+
+impl BinOp for OpFn {
+    fn apply(&self, a: i64, b: i64) -> Option<i64> {
+        (self.f)(a, b)
+    }
+
+    fn is_increasing(&self) -> bool {
+        self.increasing
+    }
+}
+
+/// Whether some choice of `ops` between the numbers makes the test value
+pub fn solve_with_ops<O: BinOp>(equation: &Equation, ops: &[O]) -> bool {
+    let Some((init, remaining)) = equation.numbers.split_first() else {
+        return false;
+    };
+    let prune = ops.iter().all(BinOp::is_increasing);
+    smart::solve_pruned(equation.test_value, *init, remaining, ops, prune)
+}
+
+/// The operators that solve the equation, if any do. Searched in the order of `ops`,
+/// so this is the lexicographically smallest solution in that order.
+pub fn find_solution(equation: &Equation, ops: &[Op]) -> Option<Vec<Op>> {
+    fn search(
+        target: i64,
+        current: i64,
+        remaining: &[i64],
+        ops: &[Op],
+        found: &mut Vec<Op>,
+    ) -> bool {
+        let Some((&a, rest)) = remaining.split_first() else {
+            return current == target;
+        };
+        // every `Op` is increasing, so there's no coming back from overshooting
+        if current > target {
+            return false;
+        }
+        for &op in ops {
+            let Some(next) = op.apply(current, a) else {
+                continue;
+            };
+            found.push(op);
+            if search(target, next, rest, ops, found) {
+                return true;
+            }
+            found.pop();
+        }
+        false
+    }
+
+    let (&init, remaining) = equation.numbers.split_first()?;
+    let mut found = Vec::with_capacity(remaining.len());
+    search(equation.test_value, init, remaining, ops, &mut found).then_some(found)
+}
+
+/// Sum of the test values of the equations that `ops` can solve
+pub fn sum_solvable<O: BinOp>(problem: &Problem, ops: &[O]) -> Result<i64> {
+    let mut sum = 0;
+    for eq in problem.equations.iter() {
+        if solve_with_ops(eq, ops) {
+            sum += eq.test_value;
+        }
+    }
+    Ok(sum)
+}
+
+type OpsVec = ArrayVec<Op, 16>;
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let mut equations = Vec::new();
+    for l in input.lines() {
+        let (test, rest) = l.split_once(':').ok_anyhow()?;
+        let test_value = test.parse()?;
+        let numbers = rest.split_whitespace().map(|n| n.parse()).try_collect()?;
+        equations.push(Equation {
+            test_value,
+            numbers,
+        });
+    }
+    Ok(Problem { equations })
+}
+
+fn concatenate(a: i64, b: i64) -> Option<i64> {
+    let mut btemp = b;
+    let mut a = a * 10;
+    while btemp.abs() >= 10 {
+        btemp /= 10;
+        a = a.checked_mul(10)?;
+    }
+    a.checked_add(b)
+}
+
+pub mod brute {
+    use crate::{BinOp, Equation, Op, OpsVec, Problem};
+    use anyhow::Result;
+
+    fn evaluate_left_right(values: &[i64], operators: &[Op]) -> Option<i64> {
+        let mut vit = values.iter();
+        let mut v = *vit.next().unwrap();
+
+        for (a, op) in std::iter::zip(vit, operators.iter()) {
+            v = op.apply(v, *a)?;
+        }
+
+        Some(v)
+    }
+
+    fn part1_solve(equation: &Equation, operators: &[Op], available_ops: &[Op]) -> bool {
+        // terminal case
+        if operators.len() == equation.numbers.len() - 1 {
+            return match evaluate_left_right(equation.numbers.as_slice(), operators) {
+                Some(v) => v == equation.test_value,
+                None => false,
+            };
+        }
+
+        // DFS
+        for op in available_ops.iter().copied() {
+            let mut ops: OpsVec = operators.iter().copied().collect();
+            ops.push(op);
+
+            if part1_solve(equation, ops.as_slice(), available_ops) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn part1(problem: &Problem) -> Result<i64> {
+        let available_ops = [Op::Add, Op::Multiply];
+        let mut sum = 0;
+        for eq in problem.equations.iter() {
+            if part1_solve(eq, &[], &available_ops) {
+                sum += eq.test_value;
+            }
+        }
+        Ok(sum)
+    }
+
+    pub fn part2(problem: &Problem) -> Result<i64> {
+        let available_ops = [Op::Add, Op::Multiply, Op::Concatenate];
+        let mut sum = 0;
+        for eq in problem.equations.iter() {
+            if part1_solve(eq, &[], &available_ops) {
+                sum += eq.test_value;
+            }
+        }
+        Ok(sum)
+    }
+}
+
+pub mod smart {
+    use crate::{sum_solvable, BinOp, Op, Problem};
+    use anyhow::Result;
+
+    pub fn solve<O: BinOp>(
+        test_case: i64,
+        current_val: i64,
+        remaining_numbers: &[i64],
+        available_ops: &[O],
+    ) -> bool {
+        let prune = available_ops.iter().all(BinOp::is_increasing);
+        solve_pruned(
+            test_case,
+            current_val,
+            remaining_numbers,
+            available_ops,
+            prune,
+        )
+    }
+
+    /// `prune` is only sound when every operator is increasing
+    pub(crate) fn solve_pruned<O: BinOp>(
+        test_case: i64,
+        current_val: i64,
+        remaining_numbers: &[i64],
+        available_ops: &[O],
+        prune: bool,
+    ) -> bool {
+        // terminal case
+        if remaining_numbers.is_empty() {
+            return test_case == current_val;
+        }
+
+        // early break - numbers only increase
+        if prune && current_val > test_case {
+            return false;
+        }
+
+        // DFS
+        for op in available_ops {
+            let (&a, next_remaining) = remaining_numbers.split_first().unwrap();
+            match op.apply(current_val, a) {
+                Some(v) if solve_pruned(test_case, v, next_remaining, available_ops, prune) => {
+                    return true
+                }
+                Some(_) => {}
+                None if prune => return false,
+                None => {}
+            }
+        }
+        false
+    }
+
+    pub fn part1(problem: &Problem) -> Result<i64> {
+        sum_solvable(problem, &[Op::Add, Op::Multiply])
+    }
+
+    pub fn part2(problem: &Problem) -> Result<i64> {
+        sum_solvable(problem, &[Op::Add, Op::Multiply, Op::Concatenate])
+    }
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input).day_context(DAY.day)?;
+    Ok((
+        smart::part1(&problem)
+            .part_context(DAY.day, "1")?
+            .to_string(),
+        smart::part2(&problem)
+            .part_context(DAY.day, "2")?
+            .to_string(),
+    ))
+}
+
+common::aoc_day!(7, "Bridge Repair");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    190: 10 19
+    3267: 81 40 27
+    83: 17 5
+    156: 15 6
+    7290: 6 8 6 15
+    161011: 16 10 13
+    192: 17 8 14
+    21037: 9 7 18 13
+    292: 11 6 16 20
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("3749"), Some("11387")];
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_parse_input() {
+        let _problem = parse_input(EXAMPLE).unwrap();
+    }
+
+    #[test]
+    fn part1_correct_brute() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = brute::part1(&problem).unwrap();
+        assert_eq!(count, 3749);
+    }
+
+    #[test]
+    fn part2_correct_brute() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = brute::part2(&problem).unwrap();
+        assert_eq!(count, 11387);
+    }
+
+    #[test]
+    fn part1_correct_smart() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = smart::part1(&problem).unwrap();
+        assert_eq!(count, 3749);
+    }
+
+    #[test]
+    fn part2_correct_smart() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let count = smart::part2(&problem).unwrap();
+        assert_eq!(count, 11387);
+    }
+
+    #[test]
+    fn concatenate_correct() {
+        assert_eq!(concatenate(1, 1).unwrap(), 11);
+        assert_eq!(concatenate(1, 0).unwrap(), 10);
+        assert_eq!(concatenate(0, 1).unwrap(), 1);
+        assert_eq!(concatenate(15, 6).unwrap(), 156);
+    }
+
+    #[test]
+    fn solutions_found() {
+        use Op::*;
+        let problem = parse_input(EXAMPLE).unwrap();
+        let eqs = problem.equations();
+        let part1 = [Add, Multiply];
+        let part2 = [Add, Multiply, Concatenate];
+
+        assert_eq!(find_solution(&eqs[0], &part1), Some(vec![Multiply]));
+        // 81 + 40 * 27 and 81 * 40 + 27 both work; addition comes first
+        assert_eq!(find_solution(&eqs[1], &part1), Some(vec![Add, Multiply]));
+        assert_eq!(
+            find_solution(&eqs[1], &[Multiply, Add]),
+            Some(vec![Multiply, Add])
+        );
+        assert_eq!(find_solution(&eqs[2], &part2), None);
+        assert_eq!(find_solution(&eqs[4], &part1), None);
+        let ops = find_solution(&eqs[4], &part2).unwrap();
+        assert_eq!(eqs[4].annotate(&ops), "7290 = 6 * 8 || 6 * 15");
+        assert_eq!(eqs[0].annotate(&[Multiply]), "190 = 10 * 19");
+        assert_eq!(eqs[0].to_string(), "190: 10 19");
+
+        // found exactly for the equations that count towards each part
+        for ops in [&part1[..], &part2[..]] {
+            let sum: i64 = eqs
+                .iter()
+                .filter(|eq| find_solution(eq, ops).is_some())
+                .map(|eq| eq.test_value())
+                .sum();
+            assert_eq!(sum, sum_solvable(&problem, ops).unwrap());
+        }
+        assert_eq!(
+            find_solution(&Equation::new(5, vec![5]), &part1),
+            Some(vec![])
+        );
+        assert_eq!(find_solution(&Equation::new(5, vec![]), &part1), None);
+    }
+
+    #[test]
+    fn custom_ops_match_builtin() {
+        let problem = parse_input(EXAMPLE).unwrap();
+        let ops = [
+            OpFn::new(|a, b| a.checked_add(b)),
+            OpFn::new(|a, b| a.checked_mul(b)),
+        ];
+        assert_eq!(sum_solvable(&problem, &ops).unwrap(), 3749);
+
+        let ops: Vec<OpFn> = vec![Op::Add.into(), Op::Multiply.into(), Op::Concatenate.into()];
+        assert_eq!(sum_solvable(&problem, &ops).unwrap(), 11387);
+    }
+
+    #[test]
+    fn custom_ops_extend_search() {
+        let subtract = || OpFn::new(|a, b| a.checked_sub(b));
+        let power = || OpFn::increasing(|a, b| a.checked_pow(b.try_into().ok()?));
+
+        // 10 - 5 - 3; not reachable by adding or multiplying
+        let eq = Equation::new(2, vec![10, 5, 3]);
+        assert!(!solve_with_ops(&eq, &[Op::Add, Op::Multiply]));
+        assert!(solve_with_ops(&eq, &[Op::Add.into(), subtract()]));
+
+        // subtracting below the target has to keep searching: 20 - 15 * 3
+        let eq = Equation::new(15, vec![20, 15, 3]);
+        assert!(solve_with_ops(
+            &eq,
+            &[Op::Add.into(), Op::Multiply.into(), subtract()]
+        ));
+
+        // 2 ^ 3 + 2, and overflowing powers are skipped
+        let eq = Equation::new(10, vec![2, 3, 2]);
+        assert!(solve_with_ops(&eq, &[Op::Add.into(), power()]));
+        let eq = Equation::new(3, vec![2, 64, 1]);
+        assert!(!solve_with_ops(&eq, &[power()]));
+    }
+}
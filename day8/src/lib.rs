@@ -0,0 +1,302 @@
+use anyhow::Result;
+use common::{cartesian::Point, grid::grid_from_str};
+use itertools::Itertools;
+use nalgebra::DMatrix;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    iter::successors,
+};
+
+type AntennaMap = DMatrix<AntennaElement>;
+
+/// Antinode locations, grouped by the frequency that produced them
+type Antinodes = BTreeMap<char, BTreeSet<Point>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum AntennaElement {
+    #[default]
+    None,
+    Antenna(char),
+}
+impl std::fmt::Display for AntennaElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AntennaElement::None => write!(f, "."),
+            AntennaElement::Antenna(ch) => write!(f, "{}", ch),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    map: AntennaMap,
+}
+impl Problem {
+    /// Enough harmonics to reach across the whole map
+    pub fn max_harmonics(&self) -> usize {
+        self.map.nrows().max(self.map.ncols())
+    }
+
+    /// Antenna positions grouped by frequency
+    pub fn antennae(&self) -> BTreeMap<char, Vec<Point>> {
+        antennae(&self.map)
+    }
+
+    pub fn frequencies(&self) -> Vec<char> {
+        self.antennae().into_keys().collect()
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    let map = grid_from_str(input, |ch| match ch {
+        '.' => Ok(AntennaElement::None),
+        ch => Ok(AntennaElement::Antenna(ch)),
+    })?;
+    Ok(Problem { map })
+}
+
+fn antennae(map: &AntennaMap) -> BTreeMap<char, Vec<Point>> {
+    let mut antennae = BTreeMap::new();
+    for r in 0..map.nrows() {
+        for c in 0..map.ncols() {
+            if let AntennaElement::Antenna(freq) = map[(r, c)] {
+                let entry = antennae.entry(freq).or_insert(Vec::new());
+                let point = Point::from((r, c));
+                entry.push(point);
+            }
+        }
+    }
+    antennae
+}
+
+pub fn find_antinodes(problem: &Problem, exclude_antenna: bool, harmonics: usize) -> Antinodes {
+    find_antinodes_filtered(problem, exclude_antenna, harmonics, |_| true)
+}
+
+/// As `find_antinodes`, only for the frequencies `include` accepts
+pub fn find_antinodes_filtered(
+    problem: &Problem,
+    exclude_antenna: bool,
+    harmonics: usize,
+    include: impl Fn(char) -> bool,
+) -> Antinodes {
+    let map = &problem.map;
+    let mut antinodes = Antinodes::new();
+
+    // iterate through all pairs
+    for (freq, list) in antennae(map).into_iter().filter(|(f, _)| include(*f)) {
+        let found = antinodes.entry(freq).or_default();
+        for pair in list.iter().copied().combinations(2) {
+            let a = pair[0];
+            let b = pair[1];
+            let delta = a - b;
+
+            let init_offset = match exclude_antenna {
+                true => delta,
+                false => Point::default(),
+            };
+
+            // iterate through harmonics until we run off the map or number required
+            for (start, step) in [
+                (a + init_offset, delta),
+                (b - init_offset, Point::default() - delta),
+            ] {
+                let harmonic = successors(Some(start), |p| Some(*p + step)).take(harmonics);
+                found.extend(harmonic.take_while(|pt| pt.within_bounds(map)));
+            }
+        }
+    }
+    antinodes
+}
+
+/// Distinct antinode locations across all frequencies
+fn distinct_antinodes(antinodes: &Antinodes) -> BTreeSet<Point> {
+    antinodes.values().flatten().copied().collect()
+}
+
+/// Number of antinodes each frequency produces, counting a location once per
+/// frequency; the total can exceed the distinct count where frequencies share a location
+pub fn contributions(antinodes: &Antinodes) -> Vec<(char, usize)> {
+    antinodes
+        .iter()
+        .map(|(freq, found)| (*freq, found.len()))
+        .collect()
+}
+
+/// Distinct antinode locations for a set of frequencies
+pub fn count_distinct(antinodes: &Antinodes) -> usize {
+    distinct_antinodes(antinodes).len()
+}
+
+fn count_antinodes(problem: &Problem, exclude_antenna: bool, harmonics: usize) -> Result<usize> {
+    let antinodes = find_antinodes(problem, exclude_antenna, harmonics);
+    Ok(distinct_antinodes(&antinodes).len())
+}
+
+/// Map with two characters per cell: the antenna frequency (or `#` for an antinode
+/// alone), then `*` where an antenna is also an antinode; followed by counts per
+/// frequency.
+pub fn render_overlay(problem: &Problem, antinodes: &Antinodes) -> String {
+    let map = &problem.map;
+    let all_antinodes = distinct_antinodes(antinodes);
+
+    let mut out = String::new();
+    for r in 0..map.nrows() {
+        for c in 0..map.ncols() {
+            let is_antinode = all_antinodes.contains(&Point::from((r, c)));
+            let cell = match (map[(r, c)], is_antinode) {
+                (AntennaElement::Antenna(ch), true) => [ch, '*'],
+                (AntennaElement::Antenna(ch), false) => [ch, ' '],
+                (AntennaElement::None, true) => ['#', ' '],
+                (AntennaElement::None, false) => ['.', ' '],
+            };
+            out.extend(cell);
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+    }
+
+    out.push_str("\nlegend: a = antenna, # = antinode, a* = antenna on an antinode\n");
+    for (freq, list) in antennae(map) {
+        let count = antinodes.get(&freq).map(|a| a.len()).unwrap_or_default();
+        writeln!(out, "{freq}: {} antennas, {count} antinodes", list.len()).unwrap();
+    }
+    writeln!(out, "total: {} distinct antinodes", all_antinodes.len()).unwrap();
+    out
+}
+
+pub fn part1(problem: &Problem) -> Result<usize> {
+    count_antinodes(problem, true, 1)
+}
+
+pub fn part2(problem: &Problem) -> Result<usize> {
+    count_antinodes(problem, false, problem.max_harmonics())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input)?;
+    Ok((part1(&problem)?.to_string(), part2(&problem)?.to_string()))
+}
+
+common::aoc_day!(8, "Resonant Collinearity");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = indoc::indoc! {"
+    ............
+    ........0...
+    .....0......
+    .......0....
+    ....0.......
+    ......A.....
+    ............
+    ............
+    ........A...
+    .........A..
+    ............
+    ............
+"};
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("14"), Some("34")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{}", problem.map);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 14);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2(&problem)?;
+        assert_eq!(count, 34);
+        Ok(())
+    }
+
+    #[test]
+    fn frequency_filter_and_contributions() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        assert_eq!(problem.frequencies(), ['0', 'A']);
+        assert_eq!(problem.antennae()[&'A'].len(), 3);
+
+        let all = find_antinodes(&problem, true, 1);
+        assert_eq!(contributions(&all), [('0', 10), ('A', 5)]);
+        assert_eq!(count_distinct(&all), 14);
+
+        let only_a = find_antinodes_filtered(&problem, true, 1, |f| f == 'A');
+        assert_eq!(contributions(&only_a), [('A', 5)]);
+        assert_eq!(count_distinct(&only_a), 5);
+
+        let none = find_antinodes_filtered(&problem, true, 1, |f| f == 'z');
+        assert_eq!(count_distinct(&none), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn overlay_rendered() -> Result<()> {
+        let problem = parse_input(
+            indoc! {"
+            ..........
+            ...#......
+            ..........
+            ....a.....
+            ..........
+            .....a....
+            ..........
+            ......#...
+            ..........
+            ..........
+        "}
+            .replace('#', ".")
+            .as_str(),
+        )?;
+        let antinodes = find_antinodes(&problem, true, 1);
+        let rendered = render_overlay(&problem, &antinodes);
+        let expected = indoc! {"
+            . . . . . . . . . .
+            . . . # . . . . . .
+            . . . . . . . . . .
+            . . . . a . . . . .
+            . . . . . . . . . .
+            . . . . . a . . . .
+            . . . . . . . . . .
+            . . . . . . # . . .
+            . . . . . . . . . .
+            . . . . . . . . . .
+
+            legend: a = antenna, # = antinode, a* = antenna on an antinode
+            a: 2 antennas, 2 antinodes
+            total: 2 distinct antinodes
+        "};
+        assert_eq!(rendered, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn overlay_marks_antenna_on_antinode() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let antinodes = find_antinodes(&problem, true, 1);
+        let rendered = render_overlay(&problem, &antinodes);
+
+        // the top-most A antenna coincides with an antinode from the 0s
+        assert!(rendered.lines().nth(5).unwrap().contains("A*"));
+        assert!(rendered.contains("0: 4 antennas, 10 antinodes"));
+        assert!(rendered.contains("A: 3 antennas, 5 antinodes"));
+        assert!(rendered.contains("total: 14 distinct antinodes"));
+        Ok(())
+    }
+}
@@ -1,175 +1,54 @@
-use anyhow::Result;
-use common::cartesian::{matrix_from_lines, Point};
-use itertools::Itertools;
-use nalgebra::DMatrix;
-use std::{collections::HashMap, iter::successors, time::Instant};
+use common::timing::TimingReport;
 
-type AntennaMap = DMatrix<AntennaElement>;
-type AntinodeMap = DMatrix<AntinodeElement>;
+use day8::{
+    contributions, count_distinct, find_antinodes_filtered, parse_input, part1, part2,
+    render_overlay, DAY, EXAMPLE, EXAMPLE_ANSWERS,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct AntinodeElement(bool);
-impl std::fmt::Display for AntinodeElement {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0 {
-            true => write!(f, "#"),
-            false => write!(f, "."),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum AntennaElement {
-    None,
-    Antenna(char),
-}
-impl Default for AntennaElement {
-    fn default() -> Self {
-        Self::None
-    }
-}
-impl std::fmt::Display for AntennaElement {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AntennaElement::None => write!(f, "."),
-            AntennaElement::Antenna(ch) => write!(f, "{}", ch),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct Problem {
-    map: AntennaMap,
-}
-
-fn parse_input(input: &str) -> Result<Problem> {
-    let lines: Vec<_> = input.lines().collect();
-
-    let map = matrix_from_lines(&lines, |ch| match ch {
-        '.' => Ok(AntennaElement::None),
-        ch => Ok(AntennaElement::Antenna(ch)),
-    })?;
-    Ok(Problem { map })
-}
-
-fn count_antinodes(problem: &Problem, exclude_antenna: bool, harmonics: usize) -> Result<usize> {
-    let map = &problem.map;
-    let mut antinodes = AntinodeMap::from_element(map.nrows(), map.ncols(), AntinodeElement(false));
-
-    // group all antenna types
-    let mut antennae = HashMap::new();
-    for r in 0..map.nrows() {
-        for c in 0..map.ncols() {
-            if let AntennaElement::Antenna(freq) = map[(r, c)] {
-                let entry = antennae.entry(freq).or_insert(Vec::new());
-                let point = Point::from((r, c));
-                entry.push(point);
+fn main() -> anyhow::Result<()> {
+    let text = common::read_input("input1.txt", EXAMPLE)?;
+    let timing = TimingReport::for_day(&DAY).with_example_answers(EXAMPLE_ANSWERS);
+
+    let problem = timing.parse(|| parse_input(&text))?;
+    timing.part("1", || part1(&problem))?;
+    timing.part("2", || part2(&problem))?;
+
+    // `--freq a,B,3` restricts the counts and renders below to those frequencies
+    let freqs: Option<Vec<char>> = common::arg_value("--freq").map(|f| {
+        f.split(',')
+            .filter_map(|s| s.trim().chars().next())
+            .collect()
+    });
+    let include = |freq: char| freqs.as_ref().is_none_or(|f| f.contains(&freq));
+    if freqs.is_some() {
+        for (part, exclude, harmonics) in [(1, true, 1), (2, false, problem.max_harmonics())] {
+            let antinodes = find_antinodes_filtered(&problem, exclude, harmonics, include);
+            println!(
+                "Part {part}: {} distinct antinodes",
+                count_distinct(&antinodes)
+            );
+            for (freq, count) in contributions(&antinodes) {
+                println!("  {freq}: {count}");
             }
         }
     }
 
-    // iterate through all pairs
-    for (_, list) in antennae {
-        for pair in list.iter().copied().combinations(2) {
-            let a = pair[0];
-            let b = pair[1];
-            let delta = a - b;
-
-            let init_offset = match exclude_antenna {
-                true => delta,
-                false => Point::default(),
-            };
-
-            // iterate through harmonics until we run off the map or number required
-            for pt in successors(Some(a + init_offset), |p| Some(*p + delta)).take(harmonics) {
-                match antinodes.get_mut(pt) {
-                    Some(v) => *v = AntinodeElement(true),
-                    None => break,
-                }
-            }
-
-            for pt in successors(Some(b - init_offset), |p| Some(*p - delta)).take(harmonics) {
-                match antinodes.get_mut(pt) {
-                    Some(v) => *v = AntinodeElement(true),
-                    None => break,
-                }
-            }
-        }
+    if std::env::args().any(|a| a == "--render") {
+        println!(
+            "Part 1\n{}",
+            render_overlay(
+                &problem,
+                &find_antinodes_filtered(&problem, true, 1, include)
+            )
+        );
+        println!(
+            "Part 2\n{}",
+            render_overlay(
+                &problem,
+                &find_antinodes_filtered(&problem, false, problem.max_harmonics(), include)
+            )
+        );
     }
 
-    // count antinodes on map
-    let num_antinodes = antinodes.iter().filter(|n| n.0).count();
-
-    // println!("{}", map);
-    // println!("{}", antinodes);
-
-    Ok(num_antinodes)
-}
-
-fn part1(problem: &Problem) -> Result<usize> {
-    count_antinodes(problem, true, 1)
-}
-
-fn part2(problem: &Problem) -> Result<usize> {
-    let max_harmonics = problem.map.nrows().max(problem.map.ncols());
-    count_antinodes(problem, false, max_harmonics)
-}
-
-fn main() -> anyhow::Result<()> {
-    let text = common::read_file("input1.txt")?;
-    let problem = parse_input(&text)?;
-
-    let t1 = Instant::now();
-    let count_part1 = part1(&problem)?;
-    println!("Part 1 result is {count_part1} (took {:?})", t1.elapsed());
-
-    let t2 = Instant::now();
-    let count_part2 = part2(&problem)?;
-    println!("Part 2 result is {count_part2} (took {:?})", t2.elapsed());
-
     Ok(())
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use indoc::indoc;
-
-    const EXAMPLE: &str = indoc! {"
-        ............
-        ........0...
-        .....0......
-        .......0....
-        ....0.......
-        ......A.....
-        ............
-        ............
-        ........A...
-        .........A..
-        ............
-        ............
-    "};
-
-    #[test]
-    fn test_parse_input() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        println!("{}", problem.map);
-        Ok(())
-    }
-
-    #[test]
-    fn part1_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part1(&problem)?;
-        assert_eq!(count, 14);
-        Ok(())
-    }
-
-    #[test]
-    fn part2_correct() -> Result<()> {
-        let problem = parse_input(EXAMPLE)?;
-        let count = part2(&problem)?;
-        assert_eq!(count, 34);
-        Ok(())
-    }
-}
@@ -0,0 +1,587 @@
+use std::{
+    fmt::Display,
+    io::{BufRead, Write},
+    ops::Range,
+};
+
+use anyhow::{bail, Result};
+use common::{error::AocContext, intervals::IntervalSet, math::CheckedAccumulator, OptionAnyhow};
+
+#[derive(Debug, Clone)]
+struct Record {
+    id: u64,
+    len: u64,
+    free_after: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Problem {
+    files: Vec<Record>,
+}
+impl Problem {
+    fn total_length(&self) -> u64 {
+        self.files.iter().map(|r| r.len + r.free_after).sum()
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Problem> {
+    parse_reader(input.as_bytes())
+}
+
+/// Parse the disk map a chunk at a time, so the input never needs to be held in memory
+pub fn parse_reader(mut reader: impl BufRead) -> Result<Problem> {
+    let mut files = Vec::new();
+    let mut pending_len = None;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        for &b in buf {
+            let digit = match b {
+                b'0'..=b'9' => (b - b'0') as u64,
+                _ if b.is_ascii_whitespace() => continue,
+                _ => bail!("unexpected character {:?} in disk map", b as char),
+            };
+            match pending_len.take() {
+                None => pending_len = Some(digit),
+                Some(len) => files.push(Record {
+                    id: files.len() as u64,
+                    len,
+                    free_after: digit,
+                }),
+            }
+        }
+        let len = buf.len();
+        reader.consume(len);
+    }
+    if let Some(len) = pending_len {
+        files.push(Record {
+            id: files.len() as u64,
+            len,
+            free_after: 0,
+        });
+    }
+    Ok(Problem { files })
+}
+
+fn create_disk(files: &[Record]) -> Vec<Option<u64>> {
+    let mut disk: Vec<Option<u64>> = Vec::new();
+    for record in files.iter() {
+        for _ in 0..record.len {
+            disk.push(Some(record.id));
+        }
+        for _ in 0..record.free_after {
+            disk.push(None);
+        }
+    }
+    disk
+}
+
+fn disk_map(disk: &[Option<u64>]) -> String {
+    let mut disk_map = String::new();
+    for x in disk.iter() {
+        match x {
+            Some(v) => {
+                let print_num = v % 10;
+                disk_map.push_str(&print_num.to_string());
+            }
+            None => disk_map.push('.'),
+        }
+    }
+    disk_map
+}
+
+/// What a block-level defragmentation did, and how fragmented it left the disk
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DefragStats {
+    /// single blocks in part 1, whole files in part 2
+    pub moves: u64,
+    pub blocks_shifted: u64,
+    /// share of the free blocks still stranded before the last file block; 0 once
+    /// all the free space is at the end
+    pub fragmentation: f64,
+}
+impl Display for DefragStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} moves, {} blocks shifted, fragmentation {:.3}",
+            self.moves, self.blocks_shifted, self.fragmentation
+        )
+    }
+}
+
+fn fragmentation(disk: &[Option<u64>]) -> f64 {
+    let free = disk.iter().filter(|b| b.is_none()).count();
+    if free == 0 {
+        return 0.0;
+    }
+    let end = disk.iter().rposition(|b| b.is_some()).map_or(0, |i| i + 1);
+    let stranded = disk[..end].iter().filter(|b| b.is_none()).count();
+    stranded as f64 / free as f64
+}
+
+/// Draw the disk, prefixed by the move count, before the first move and then every
+/// `every` moves; `every == 0` draws nothing
+fn draw_frame(
+    out: &mut impl Write,
+    every: u64,
+    stats: &DefragStats,
+    disk: &[Option<u64>],
+) -> Result<()> {
+    if every > 0 && stats.moves.is_multiple_of(every) {
+        writeln!(out, "{:>8} {}", stats.moves, disk_map(disk))?;
+    }
+    Ok(())
+}
+
+/// The final frame, unless the last move already drew it
+fn draw_final(
+    out: &mut impl Write,
+    every: u64,
+    stats: &DefragStats,
+    disk: &[Option<u64>],
+) -> Result<()> {
+    if every > 0 && !stats.moves.is_multiple_of(every) {
+        writeln!(out, "{:>8} {}", stats.moves, disk_map(disk))?;
+    }
+    Ok(())
+}
+
+fn checksum_disk(disk: &[Option<u64>]) -> Result<u128> {
+    let mut sum = CheckedAccumulator::sum();
+    for (i, id) in disk.iter().enumerate() {
+        if let Some(id) = id {
+            sum.add_product(i as u128, *id as u128)?;
+        }
+    }
+    Ok(sum.value())
+}
+
+/// Checksum accumulated one extent at a time, without materializing the disk
+#[derive(Debug, Default)]
+struct ExtentChecksum {
+    pos: u64,
+    sum: CheckedAccumulator<u128>,
+}
+impl ExtentChecksum {
+    /// `len` blocks of file `id` at the current position
+    fn file(&mut self, id: u64, len: u64) -> Result<()> {
+        self.sum.add(extent_checksum(id, self.pos, len))?;
+        self.pos += len;
+        Ok(())
+    }
+}
+
+/// sum of `position * id` over `len` blocks starting at `pos`
+fn extent_checksum(id: u64, pos: u64, len: u64) -> u128 {
+    let (id, pos, len) = (id as u128, pos as u128, len as u128);
+    id * (len * pos + len * len.saturating_sub(1) / 2)
+}
+
+/// Part 1 over extents: the gap after each file on the left is filled with blocks taken
+/// from files on the right, which are consumed from the end towards the start.
+pub fn part1(problem: &Problem) -> Result<u128> {
+    let files = &problem.files;
+    let mut checksum = ExtentChecksum::default();
+    if files.is_empty() {
+        return Ok(0);
+    }
+
+    let mut right = files.len() - 1;
+    let mut right_remaining = files[right].len;
+    for left in 0..files.len() {
+        if left > right {
+            break;
+        }
+        if left == right {
+            // only what hasn't already been moved is left of this file
+            checksum.file(files[left].id, right_remaining)?;
+            break;
+        }
+        checksum.file(files[left].id, files[left].len)?;
+
+        let mut free = files[left].free_after;
+        while free > 0 && right > left {
+            let take = free.min(right_remaining);
+            checksum.file(files[right].id, take)?;
+            free -= take;
+            right_remaining -= take;
+            if right_remaining == 0 {
+                right -= 1;
+                right_remaining = files[right].len;
+            }
+        }
+    }
+
+    Ok(checksum.sum.value())
+}
+
+/// Original block-swapping approach to part 1, which materializes the disk
+pub fn part1_blocks(problem: &Problem) -> Result<u128> {
+    println!("total length {}", problem.total_length());
+    Ok(defrag_blocks(problem, 0, &mut std::io::sink())?.0)
+}
+
+/// Block-swapping part 1, drawing the disk to `out` every `every` swaps; returns the
+/// checksum and what the compaction did
+pub fn defrag_blocks(
+    problem: &Problem,
+    every: u64,
+    out: &mut impl Write,
+) -> Result<(u128, DefragStats)> {
+    let mut disk = create_disk(&problem.files);
+    let mut stats = DefragStats::default();
+    draw_frame(out, every, &stats, &disk)?;
+
+    // each swap fills the first free block and frees the last file block, so neither
+    // needs looking for again from the ends of the disk
+    let (mut left, mut right) = (0, disk.len());
+    loop {
+        while left < disk.len() && disk[left].is_some() {
+            left += 1;
+        }
+        while right > 0 && disk[right - 1].is_none() {
+            right -= 1;
+        }
+        if left + 1 < right {
+            disk.swap(left, right - 1);
+            stats.moves += 1;
+            stats.blocks_shifted += 1;
+            draw_frame(out, every, &stats, &disk)?;
+        } else {
+            break;
+        }
+    }
+
+    draw_final(out, every, &stats, &disk)?;
+    stats.fragmentation = fragmentation(&disk);
+    Ok((checksum_disk(&disk)?, stats))
+}
+
+/// Brute-force, copy-stuff-around approach that works
+pub fn part2_brute(problem: &Problem) -> Result<u128> {
+    Ok(defrag_files(problem, 0, &mut std::io::sink())?.0)
+}
+
+/// Brute-force part 2, drawing the disk to `out` every `every` file moves; returns
+/// the checksum and what the compaction did
+pub fn defrag_files(
+    problem: &Problem,
+    every: u64,
+    out: &mut impl Write,
+) -> Result<(u128, DefragStats)> {
+    fn find_id(disk: &[Option<u64>], id: u64) -> Option<Range<usize>> {
+        if let Some(start) = disk.iter().position(|x| x == &Some(id)) {
+            let end = disk[start..].iter().take_while(|x| *x == &Some(id)).count();
+            let end = end + start;
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    fn find_space(disk: &[Option<u64>], required_len: usize) -> Option<usize> {
+        disk.windows(required_len)
+            .position(|w| w.iter().all(|x| x.is_none()))
+    }
+
+    let mut disk = create_disk(&problem.files);
+    let mut stats = DefragStats::default();
+    draw_frame(out, every, &stats, &disk)?;
+
+    let max_id = problem.files.last().ok_anyhow()?.id;
+    for id in (1..=max_id).rev() {
+        // find the file we are considering moving
+        let range_id = find_id(&disk, id).ok_anyhow()?;
+
+        // find a potential location to the left of it
+        let search_space = &disk[0..range_id.start];
+        let required_len = range_id.clone().count();
+        assert_eq!(required_len, problem.files[id as usize].len as usize);
+        if let Some(dest) = find_space(search_space, required_len) {
+            // move elements
+            disk.copy_within(range_id.clone(), dest);
+            // "delete" old
+            disk[range_id.clone()].fill(None);
+
+            stats.moves += 1;
+            stats.blocks_shifted += required_len as u64;
+            draw_frame(out, every, &stats, &disk)?;
+        }
+    }
+
+    draw_final(out, every, &stats, &disk)?;
+    stats.fragmentation = fragmentation(&disk);
+    Ok((checksum_disk(&disk)?, stats))
+}
+
+/// This works, and is much more efficient, but required me to do the brute force
+/// approach first in order to debug it. It passed the tests fine. Although a more
+/// extensive set of my own unit tests would have revealed the problem.
+pub fn part2_smarter(problem: &Problem) -> Result<u128> {
+    let mut files = problem.files.clone();
+    let initial_disk = create_disk(&files);
+
+    let max_id = files.last().ok_anyhow()?.id;
+    for id in (2..=max_id).rev() {
+        let cur = files.iter().position(|f| f.id == id).ok_anyhow()?;
+        let cur_prior = cur - 1;
+        let required_len = files[cur].len;
+        let dest_prior = files
+            .iter()
+            .enumerate()
+            .find(|(_, r)| r.free_after >= required_len)
+            .map(|(i, _)| i);
+
+        match dest_prior {
+            // we can move the file left into any space where it fits, including
+            // the free space after the node immediately to the left of it.
+            Some(dest_prior) if dest_prior <= cur_prior => {
+                // existing location - give the space taken and space free to the prior node
+                files[cur_prior].free_after =
+                    files[cur_prior].free_after + files[cur].len + files[cur].free_after;
+
+                // for the node we're moving, the free space to the right is the remaning space from dest_right
+                files[cur].free_after = files[dest_prior].free_after - files[cur].len;
+
+                // destination location - remove the space on the right of the destination node completely,
+                // since we're placing the node directly to the right of it
+                files[dest_prior].free_after = 0;
+
+                // finally, move the file to the destination location,
+                // inserting it to the right of the destination node
+                let file = files.remove(cur);
+                files.insert(dest_prior + 1, file);
+            }
+            _ => {}
+        }
+    }
+
+    let disk = create_disk(&files);
+    let sum = checksum_disk(&disk)?;
+    assert_eq!(disk.len(), initial_disk.len());
+
+    Ok(sum)
+}
+
+/// Part 2 over extents, scaling to very large disk maps. Free space is an
+/// `IntervalSet`, which finds the leftmost span that fits by checking one candidate
+/// per span length. Space vacated by a move is never needed, since the remaining
+/// files are all to the left of it.
+pub fn part2_extents(problem: &Problem) -> Result<u128> {
+    let files = &problem.files;
+
+    let mut starts = Vec::with_capacity(files.len());
+    let mut free = IntervalSet::new();
+    let mut pos = 0;
+    for file in files {
+        starts.push(pos);
+        pos += file.len;
+        free.insert(pos as i64..(pos + file.free_after) as i64);
+        pos += file.free_after;
+    }
+
+    let mut sum = CheckedAccumulator::sum();
+    for (file, &start) in files.iter().zip(&starts).rev() {
+        let start = match free.first_fit(file.len as i64) {
+            Some(span) if span.start < start as i64 => {
+                free.remove(span.start..span.start + file.len as i64);
+                span.start as u64
+            }
+            _ => start,
+        };
+        sum.add(extent_checksum(file.id, start, file.len))?;
+    }
+
+    Ok(sum.value())
+}
+
+/// Answers to both parts for the puzzle input `input`
+pub fn solve(input: &str) -> Result<(String, String)> {
+    let problem = parse_input(input).day_context(DAY.day)?;
+    Ok((
+        part1(&problem).part_context(DAY.day, "1")?.to_string(),
+        part2_extents(&problem)
+            .part_context(DAY.day, "2")?
+            .to_string(),
+    ))
+}
+
+common::aoc_day!(9, "Disk Fragmenter");
+
+/// The example from the puzzle text, for `--example`
+pub const EXAMPLE: &str = "2333133121414131402";
+pub const EXAMPLE_ANSWERS: [Option<&str>; 2] = [Some("1928"), Some("2858")];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_parse_input() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        println!("{:?}", problem);
+        println!("total length {}", problem.total_length());
+        Ok(())
+    }
+
+    #[test]
+    fn part1_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1(&problem)?;
+        assert_eq!(count, 1928);
+        Ok(())
+    }
+
+    #[test]
+    fn part1_blocks_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part1_blocks(&problem)?;
+        assert_eq!(count, 1928);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_extents_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2_extents(&problem)?;
+        assert_eq!(count, 2858);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_non_digits() {
+        assert!(parse_input("12a4").is_err());
+        assert!(parse_input("12-4").is_err());
+        assert_eq!(parse_input("1234\n").unwrap().files.len(), 2);
+    }
+
+    /// Random disk map digits produced on demand, so large maps never exist as a string;
+    /// files are 1-9 blocks long as in the puzzle input, with 0-9 free blocks after
+    struct GeneratedDiskMap {
+        rng: common::rng::Rng,
+        remaining: usize,
+        file_next: bool,
+    }
+    impl std::io::Read for GeneratedDiskMap {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.remaining);
+            for b in &mut buf[..n] {
+                *b = match self.file_next {
+                    true => b'1' + self.rng.below(9) as u8,
+                    false => b'0' + self.rng.below(10) as u8,
+                };
+                self.file_next = !self.file_next;
+            }
+            self.remaining -= n;
+            Ok(n)
+        }
+    }
+
+    fn generated(digits: usize, seed: u64) -> Result<Problem> {
+        let map = GeneratedDiskMap {
+            rng: common::rng::seeded(seed),
+            remaining: digits,
+            file_next: true,
+        };
+        parse_reader(BufReader::new(map))
+    }
+
+    #[test]
+    fn extents_match_blocks_on_generated_maps() -> Result<()> {
+        for seed in 1..=20 {
+            let problem = generated(301, seed)?;
+            assert_eq!(part1(&problem)?, part1_blocks(&problem)?, "seed {seed}");
+            let expected = part2_brute(&problem)?;
+            assert_eq!(part2_smarter(&problem)?, expected, "seed {seed}");
+            assert_eq!(part2_extents(&problem)?, expected, "seed {seed}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn blocks_match_extents_on_larger_maps() -> Result<()> {
+        for seed in 1..=3 {
+            let problem = generated(20_001, seed)?;
+            assert_eq!(part1(&problem)?, part1_blocks(&problem)?, "seed {seed}");
+        }
+        // the puzzle's small example, no free space at all, and free space only at the end
+        for map in ["12345", "1020304", "19"] {
+            let problem = parse_input(map)?;
+            assert_eq!(part1(&problem)?, part1_blocks(&problem)?, "{map}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn very_large_generated_map() -> Result<()> {
+        // ~4.5e7 blocks and 5e6 file ids; the checksum doesn't fit in a u64
+        let problem = generated(10_000_000, 9)?;
+        assert_eq!(problem.files.len(), 5_000_000);
+        let part1 = part1(&problem)?;
+        let part2 = part2_extents(&problem)?;
+        assert!(part1 > u64::MAX as u128);
+        assert!(part2 > part1);
+        Ok(())
+    }
+
+    #[test]
+    fn part2_smarter_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2_smarter(&problem)?;
+        assert_eq!(count, 2858);
+        Ok(())
+    }
+
+    #[test]
+    fn defrag_frames_and_stats() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+
+        let mut out = Vec::new();
+        let (checksum, stats) = defrag_blocks(&problem, 1, &mut out)?;
+        assert_eq!(checksum, 1928);
+        let frames = String::from_utf8(out)?;
+        assert_eq!(frames.lines().count() as u64, stats.moves + 1);
+        assert_eq!(
+            frames.lines().next(),
+            Some("       0 00...111...2...333.44.5555.6666.777.888899")
+        );
+        assert!(frames
+            .lines()
+            .last()
+            .unwrap()
+            .ends_with(" 0099811188827773336446555566.............."));
+        assert_eq!(stats.blocks_shifted, stats.moves);
+        assert_eq!(stats.fragmentation, 0.0);
+
+        // the four files that move, with a final frame after the last one
+        let mut out = Vec::new();
+        let (checksum, stats) = defrag_files(&problem, 3, &mut out)?;
+        assert_eq!(checksum, 2858);
+        let frames = String::from_utf8(out)?;
+        assert_eq!(frames.lines().count(), 3);
+        assert_eq!(
+            frames.lines().last(),
+            Some("       4 00992111777.44.333....5555.6666.....8888..")
+        );
+        assert_eq!((stats.moves, stats.blocks_shifted), (4, 8));
+        assert_eq!(stats.fragmentation, 12.0 / 14.0);
+
+        // nothing drawn when not asked for
+        let mut out = Vec::new();
+        defrag_files(&problem, 0, &mut out)?;
+        assert!(out.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn part2_brute_correct() -> Result<()> {
+        let problem = parse_input(EXAMPLE)?;
+        let count = part2_brute(&problem)?;
+        assert_eq!(count, 2858);
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day12::parse_input(data);
+    // solving is slower, so only small inputs are solved
+    if data.len() <= 64 {
+        let _ = day12::solve(data);
+    }
+});
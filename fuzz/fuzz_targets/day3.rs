@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    let _ = day3::part1(data);
+    let _ = day3::part2(data);
+    let _ = day3::part1_streaming(data.as_bytes());
+    let _ = day3::part2_streaming(data.as_bytes());
+    // solving is slower, so only small inputs are solved
+    if data.len() <= 64 {
+        let _ = day3::solve(data);
+    }
+});
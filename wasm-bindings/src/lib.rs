@@ -0,0 +1,67 @@
+//! Day solvers compiled to WebAssembly for a browser playground. Only days whose
+//! `solve` is quick and self-contained are included: day 17 part 2 is specific to my
+//! program, and days 22 and 23 take too long to run in a browser.
+
+use anyhow::Context;
+use common::registry::AocDay;
+use wasm_bindgen::prelude::*;
+
+/// Days in `aoc::DAYS` left out of the playground
+pub const EXCLUDED: &[u8] = &[17, 22, 23];
+
+/// Days available in the playground
+pub fn days() -> impl Iterator<Item = &'static AocDay> {
+    aoc::DAYS.iter().filter(|d| !EXCLUDED.contains(&d.day))
+}
+
+/// Both answers for `day`, as returned by that day's `solve`
+pub fn solve(day: u8, input: &str) -> anyhow::Result<(String, String)> {
+    let found = days()
+        .find(|d| d.day == day)
+        .with_context(|| format!("day {day} is not available in the playground"))?;
+    (found.solve)(input)
+}
+
+/// Solve `day` for `input`, returning the two answers as a `[part1, part2]` array
+#[wasm_bindgen]
+pub fn solve_day(day: u8, input: &str) -> Result<JsValue, JsError> {
+    let (part1, part2) = solve(day, input).map_err(|e| JsError::new(&format!("{e:#}")))?;
+    Ok(js_sys::Array::of2(&part1.into(), &part2.into()).into())
+}
+
+/// Days available in the playground
+#[wasm_bindgen]
+pub fn available_days() -> Vec<u8> {
+    days().map(|d| d.day).collect()
+}
+
+/// Puzzle title for `day`, if it's in the playground
+#[wasm_bindgen]
+pub fn day_title(day: u8) -> Option<String> {
+    days().find(|d| d.day == day).map(|d| d.title.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatches_to_day() -> anyhow::Result<()> {
+        let input = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+        assert_eq!(solve(1, input)?, ("11".to_string(), "31".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn unavailable_day_is_error() {
+        assert!(solve(22, "").is_err());
+        assert!(solve(26, "").is_err());
+        assert_eq!(day_title(22), None);
+    }
+
+    #[test]
+    fn titles_from_days() {
+        assert_eq!(day_title(24).as_deref(), Some("Crossed Wires"));
+        assert_eq!(available_days().len(), 22);
+    }
+}